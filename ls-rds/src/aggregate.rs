@@ -0,0 +1,190 @@
+//! Per-account aggregate for multi-account ls-rds scans
+//!
+//! Leadership reports want totals, not rows: instances, Aurora clusters,
+//! engine mix and unencrypted instances per account, plus an overall total.
+//! `--stats` breaks the instances down by engine and major version instead,
+//! the question after every upgrade campaign.
+
+use crate::RdsInstance;
+use crate::engines::major_version;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Totals for one account
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountAggregate {
+    pub instances: usize,
+    pub clusters: BTreeSet<String>,
+    /// Engine → instance count
+    pub engines: BTreeMap<String, usize>,
+    pub unencrypted: usize,
+}
+
+impl AccountAggregate {
+    fn add(&mut self, inst: &RdsInstance) {
+        self.instances += 1;
+        if let Some(cluster) = &inst.cluster_id {
+            self.clusters.insert(format!("{}/{}", inst.region, cluster));
+        }
+        *self.engines.entry(inst.engine.clone()).or_default() += 1;
+        if !inst.storage_encrypted {
+            self.unencrypted += 1;
+        }
+    }
+
+    /// Engine mix, e.g. `mysql:1,postgres:3`
+    pub fn engine_breakdown(&self) -> String {
+        let parts: Vec<String> = self.engines.iter().map(|(e, n)| format!("{}:{}", e, n)).collect();
+        if parts.is_empty() { "-".to_owned() } else { parts.join(",") }
+    }
+}
+
+/// Aggregate instances per account ID
+pub fn aggregate_by_account(instances: &[RdsInstance]) -> BTreeMap<String, AccountAggregate> {
+    let mut out: BTreeMap<String, AccountAggregate> = BTreeMap::new();
+    for inst in instances {
+        out.entry(inst.account_id.clone()).or_default().add(inst);
+    }
+    out
+}
+
+/// Format the per-account aggregate as tab-separated lines with a total.
+pub fn format_account_summary(instances: &[RdsInstance]) -> String {
+    let mut out = String::from("ACCOUNT\tINSTANCES\tCLUSTERS\tENGINES\tUNENCRYPTED\n");
+    let mut total = AccountAggregate::default();
+    for (account, agg) in aggregate_by_account(instances) {
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            account,
+            agg.instances,
+            agg.clusters.len(),
+            agg.engine_breakdown(),
+            agg.unencrypted
+        ));
+        total.instances += agg.instances;
+        total.clusters.extend(agg.clusters.into_iter().map(|c| format!("{}/{}", account, c)));
+        for (engine, n) in agg.engines {
+            *total.engines.entry(engine).or_default() += n;
+        }
+        total.unencrypted += agg.unencrypted;
+    }
+    out.push_str(&format!(
+        "TOTAL\t{}\t{}\t{}\t{}\n",
+        total.instances,
+        total.clusters.len(),
+        total.engine_breakdown(),
+        total.unencrypted
+    ));
+    out
+}
+
+/// Instances of one engine major version
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VersionCount {
+    pub instances: usize,
+    /// Accounts running it
+    pub accounts: BTreeSet<String>,
+}
+
+/// Count instances per (engine, major version)
+pub fn count_by_engine_version(instances: &[RdsInstance]) -> BTreeMap<(String, String), VersionCount> {
+    let mut out: BTreeMap<(String, String), VersionCount> = BTreeMap::new();
+    for inst in instances {
+        let key = (inst.engine.clone(), major_version(&inst.engine, &inst.engine_version));
+        let count = out.entry(key).or_default();
+        count.instances += 1;
+        count.accounts.insert(inst.account_id.clone());
+    }
+    out
+}
+
+/// Format the engine/major version breakdown as tab-separated lines with a
+/// total; versions of an engine sort oldest first.
+pub fn format_engine_stats(instances: &[RdsInstance]) -> String {
+    let mut counts: Vec<_> = count_by_engine_version(instances).into_iter().collect();
+    counts.sort_by_cached_key(|((engine, major), _)| {
+        let numeric: Vec<u32> = major.split('.').map(|p| p.parse().unwrap_or(u32::MAX)).collect();
+        (engine.clone(), numeric)
+    });
+    let mut out = String::from("ENGINE\tMAJOR-VERSION\tINSTANCES\tACCOUNTS\n");
+    let mut accounts = BTreeSet::new();
+    for ((engine, major), count) in counts {
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\n",
+            engine,
+            if major.is_empty() { "-" } else { &major },
+            count.instances,
+            count.accounts.len()
+        ));
+        accounts.extend(count.accounts);
+    }
+    out.push_str(&format!("TOTAL\t-\t{}\t{}\n", instances.len(), accounts.len()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inst(account: &str, engine: &str, cluster: Option<&str>, encrypted: bool) -> RdsInstance {
+        RdsInstance {
+            account_id: account.to_string(),
+            region: "us-east-1".to_string(),
+            engine: engine.to_string(),
+            cluster_id: cluster.map(str::to_owned),
+            storage_encrypted: encrypted,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn aggregate_counts_clusters_once() {
+        let instances = vec![
+            inst("111", "aurora-postgresql", Some("orders"), true),
+            inst("111", "aurora-postgresql", Some("orders"), true),
+            inst("111", "mysql", None, false),
+        ];
+        let agg = &aggregate_by_account(&instances)["111"];
+        assert_eq!(agg.instances, 3);
+        assert_eq!(agg.clusters.len(), 1);
+        assert_eq!(agg.engine_breakdown(), "aurora-postgresql:2,mysql:1");
+        assert_eq!(agg.unencrypted, 1);
+    }
+
+    #[test]
+    fn format_account_summary_totals_accounts() {
+        let instances = vec![
+            inst("111", "postgres", None, false),
+            inst("222", "aurora-mysql", Some("orders"), true),
+            inst("222", "postgres", None, true),
+        ];
+        let out = format_account_summary(&instances);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "ACCOUNT\tINSTANCES\tCLUSTERS\tENGINES\tUNENCRYPTED");
+        assert_eq!(lines[1], "111\t1\t0\tpostgres:1\t1");
+        assert_eq!(lines[2], "222\t2\t1\taurora-mysql:1,postgres:1\t0");
+        assert_eq!(lines[3], "TOTAL\t3\t1\taurora-mysql:1,postgres:2\t1");
+    }
+
+    #[test]
+    fn format_engine_stats_counts_major_versions() {
+        let version = |account: &str, engine: &str, version: &str| RdsInstance {
+            engine_version: version.to_string(),
+            ..inst(account, engine, None, true)
+        };
+        let instances = vec![
+            version("111", "postgres", "14.9"),
+            version("222", "postgres", "14.11"),
+            version("222", "postgres", "9.6.24"),
+            version("111", "postgres", "16.1"),
+            version("111", "aurora-mysql", "8.0.mysql_aurora.3.05.2"),
+        ];
+        let out = format_engine_stats(&instances);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "ENGINE\tMAJOR-VERSION\tINSTANCES\tACCOUNTS");
+        assert_eq!(lines[1], "aurora-mysql\t8.0\t1\t1");
+        assert_eq!(lines[2], "postgres\t9.6\t1\t1");
+        assert_eq!(lines[3], "postgres\t14\t2\t2");
+        assert_eq!(lines[4], "postgres\t16\t1\t1");
+        assert_eq!(lines[5], "TOTAL\t-\t5\t2");
+    }
+}
@@ -0,0 +1,54 @@
+//! AWS API call accounting for ls-rds
+//!
+//! Every SDK call site records itself in the scan's `ApiStats` (shared with
+//! ls-vpc) so `--api-stats` can report how many calls of each type a run
+//! made, broken down by region and account.
+
+pub use ls_vpc::api_stats::{ApiCall, ApiStats, CallCounter};
+
+use std::collections::BTreeMap;
+
+/// Format call counts as tab-separated lines with a header and total.
+pub fn format_api_stats(stats: &BTreeMap<ApiCall, u64>) -> String {
+    let mut out = String::from("SERVICE\tOPERATION\tREGION\tACCOUNT\tCALLS\n");
+    for (call, count) in stats {
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            call.service, call.operation, call.region, call.account, count
+        ));
+    }
+    out.push_str(&format!("TOTAL\t\t\t\t{}\n", stats.values().sum::<u64>()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_api_stats_includes_total() {
+        let mut stats = BTreeMap::new();
+        stats.insert(
+            ApiCall {
+                service: "sts",
+                operation: "GetCallerIdentity",
+                region: "us-east-1".to_string(),
+                account: "123456789012".to_string(),
+            },
+            1,
+        );
+        stats.insert(
+            ApiCall {
+                service: "rds",
+                operation: "DescribeDBInstances",
+                region: "us-west-2".to_string(),
+                account: "123456789012".to_string(),
+            },
+            3,
+        );
+        let out = format_api_stats(&stats);
+        assert!(out.starts_with("SERVICE\tOPERATION"));
+        assert!(out.contains("rds\tDescribeDBInstances\tus-west-2\t123456789012\t3"));
+        assert!(out.ends_with("TOTAL\t\t\t\t4\n"));
+    }
+}
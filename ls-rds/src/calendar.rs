@@ -0,0 +1,176 @@
+//! Maintenance window calendar for ls-rds
+//!
+//! Resolves the weekly `ddd:hh24:mi-ddd:hh24:mi` (UTC) maintenance windows RDS
+//! reports into concrete upcoming timestamps, and renders them as a sorted
+//! list or an iCalendar file.
+
+use crate::{RdsInstance, format_instance};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Timelike, Utc, Weekday};
+
+const MINUTES_PER_WEEK: i64 = 7 * 24 * 60;
+
+fn parse_point(s: &str) -> Option<(Weekday, NaiveTime)> {
+    let (day, time) = s.split_once(':')?;
+    Some((day.parse().ok()?, NaiveTime::parse_from_str(time, "%H:%M").ok()?))
+}
+
+fn minute_of_week(day: Weekday, time: NaiveTime) -> i64 {
+    i64::from(day.num_days_from_monday()) * 24 * 60 + i64::from(time.hour() * 60 + time.minute())
+}
+
+/// Next (or currently running) occurrence of a weekly maintenance window.
+pub fn next_window(window: &str, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let (start, end) = window.split_once('-')?;
+    let (start_day, start_time) = parse_point(start)?;
+    let (end_day, end_time) = parse_point(end)?;
+    let length = Duration::minutes(
+        (minute_of_week(end_day, end_time) - minute_of_week(start_day, start_time)).rem_euclid(MINUTES_PER_WEEK),
+    );
+
+    let days_ahead = (i64::from(start_day.num_days_from_monday())
+        - i64::from(now.weekday().num_days_from_monday()))
+    .rem_euclid(7);
+    let mut begin = (now.date_naive() + Duration::days(days_ahead))
+        .and_time(start_time)
+        .and_utc();
+    if begin + length <= now {
+        begin += Duration::days(7);
+    }
+    // A window that started last week may still be running (e.g. sat 23:00 – sun 01:00).
+    let previous = begin - Duration::days(7);
+    if previous + length > now {
+        begin = previous;
+    }
+    Some((begin, begin + length))
+}
+
+/// Instances paired with their next window, soonest first (instances
+/// without a window are left out).
+pub fn upcoming(instances: &[RdsInstance], now: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>, &RdsInstance)> {
+    let mut rows: Vec<_> = instances
+        .iter()
+        .filter_map(|inst| {
+            let (start, end) = next_window(inst.maintenance_window.as_deref()?, now)?;
+            Some((start, end, inst))
+        })
+        .collect();
+    rows.sort_by(|a, b| (a.0, &a.2.instance_id).cmp(&(b.0, &b.2.instance_id)));
+    rows
+}
+
+/// Format instances sorted by their next maintenance window.
+pub fn format_calendar(instances: &[RdsInstance], now: DateTime<Utc>) -> String {
+    upcoming(instances, now)
+        .into_iter()
+        .map(|(start, _, inst)| {
+            format!(
+                "{}\t{}\t{}\n",
+                start.format("%Y-%m-%d %H:%M UTC"),
+                format_instance(inst),
+                inst.maintenance_window.as_deref().unwrap_or_default()
+            )
+        })
+        .collect()
+}
+
+/// Render the upcoming windows as an iCalendar (RFC 5545) document.
+pub fn to_ical(instances: &[RdsInstance], now: DateTime<Utc>) -> String {
+    const STAMP: &str = "%Y%m%dT%H%M%SZ";
+    let mut out = String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//aws-tools//ls-rds//EN\r\n");
+    for (start, end, inst) in upcoming(instances, now) {
+        out.push_str("BEGIN:VEVENT\r\n");
+        out.push_str(&format!(
+            "UID:{}-{}-{}-{}@ls-rds\r\n",
+            inst.account_id,
+            inst.region,
+            inst.instance_id,
+            start.format(STAMP)
+        ));
+        out.push_str(&format!("DTSTAMP:{}\r\n", now.format(STAMP)));
+        out.push_str(&format!("DTSTART:{}\r\n", start.format(STAMP)));
+        out.push_str(&format!("DTEND:{}\r\n", end.format(STAMP)));
+        out.push_str(&format!("SUMMARY:RDS maintenance: {} ({})\r\n", inst.instance_id, inst.region));
+        out.push_str("END:VEVENT\r\n");
+    }
+    out.push_str("END:VCALENDAR\r\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // Wednesday 2025-03-05 12:00 UTC
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 5, 12, 0, 0).unwrap()
+    }
+
+    fn inst(id: &str, window: Option<&str>) -> RdsInstance {
+        RdsInstance {
+            account_id: "123456789012".to_string(),
+            region: "us-west-2".to_string(),
+            instance_id: id.to_string(),
+            maintenance_window: window.map(str::to_owned),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn next_window_later_this_week() {
+        let (start, end) = next_window("sun:05:00-sun:05:30", now()).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 3, 9, 5, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 3, 9, 5, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn next_window_rolls_to_next_week_when_passed() {
+        let (start, _) = next_window("wed:03:00-wed:03:30", now()).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 3, 12, 3, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn next_window_keeps_running_window() {
+        let (start, end) = next_window("wed:11:30-wed:12:30", now()).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 3, 5, 11, 30, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 3, 5, 12, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn next_window_wraps_across_week_boundary() {
+        let (start, end) = next_window("sun:23:30-mon:00:30", now()).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 3, 9, 23, 30, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 3, 10, 0, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn next_window_rejects_garbage() {
+        assert!(next_window("whenever", now()).is_none());
+        assert!(next_window("xyz:05:00-sun:06:00", now()).is_none());
+    }
+
+    #[test]
+    fn format_calendar_sorts_and_skips_missing() {
+        let instances = vec![
+            inst("late", Some("sat:05:00-sat:05:30")),
+            inst("none", None),
+            inst("early", Some("thu:01:00-thu:01:30")),
+        ];
+        let out = format_calendar(&instances, now());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("2025-03-06 01:00 UTC\tus-west-2\tearly"));
+        assert!(lines[1].contains("late"));
+    }
+
+    #[test]
+    fn to_ical_emits_one_event_per_window() {
+        let out = to_ical(&[inst("orders", Some("sun:05:00-sun:05:30"))], now());
+        assert!(out.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert_eq!(out.matches("BEGIN:VEVENT").count(), 1);
+        assert!(out.contains("DTSTART:20250309T050000Z\r\n"));
+        assert!(out.contains("DTEND:20250309T053000Z\r\n"));
+        assert!(out.contains("UID:123456789012-us-west-2-orders-20250309T050000Z@ls-rds\r\n"));
+        assert!(out.ends_with("END:VCALENDAR\r\n"));
+    }
+}
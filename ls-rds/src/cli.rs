@@ -3,20 +3,94 @@
 //! This module contains only the clap derive structs.
 //! Validation happens in config.rs.
 
-use clap::Parser;
+use clap::{ArgGroup, Parser, ValueEnum};
+use std::path::PathBuf;
+
+/// Licensing family of a DB engine
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineFamily {
+    /// Oracle, SQL Server and Db2
+    Commercial,
+    /// MySQL, PostgreSQL, MariaDB and their Aurora variants
+    OpenSource,
+}
+
+/// Output format of the scan
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// ASCII table of instances
+    Table,
+    /// Tab-separated lines (and the per-mode listings)
+    #[default]
+    Text,
+    /// One JSON document following the versioned schema (same as --json)
+    Json,
+    /// Comma-separated instances with a header row, for spreadsheets
+    Csv,
+    /// Like csv, tab-separated
+    Tsv,
+}
+
+/// Grouping for the instance listing
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Aurora clusters with their writer and readers nested beneath
+    Cluster,
+}
 
 #[derive(Parser, Debug, Clone)]
 #[command(name = "ls-rds", author, version = env!("GIT_DESCRIBE"), about)]
+#[command(group(ArgGroup::new("org_role").multiple(true).args(["use_org", "account_ids", "accounts_file"])))]
 pub struct Cli {
     /// Enumerate *all* accounts via AWS Organizations
     #[clap(long)]
     pub use_org: bool,
 
+    /// Scan only these account IDs (e.g. `--account-ids 111111111111,222222222222`),
+    /// assuming the org role in each
+    #[clap(long, value_name = "IDS", value_delimiter = ',', conflicts_with_all = ["use_org", "role_arns"])]
+    pub account_ids: Vec<String>,
+
+    /// Scan the account IDs listed in this file (one per line, `#` comments),
+    /// assuming the org role in each
+    #[clap(long, value_name = "FILE", conflicts_with_all = ["use_org", "role_arns"])]
+    pub accounts_file: Option<PathBuf>,
+
+    /// YAML file mapping account IDs to the role ARN to assume in them, plus
+    /// a default role name (org mode and explicit account lists only)
+    #[clap(long, value_name = "FILE", requires = "org_role")]
+    pub role_map: Option<PathBuf>,
+
+    /// Add an ACCOUNT-NAME column: the Organizations account name with
+    /// --use-org, else the IAM account alias of each scanned account
+    #[clap(long)]
+    pub account_names: bool,
+
+    /// Add ACCOUNT-EMAIL and ACCOUNT-STATUS columns from Organizations
+    #[clap(long, requires = "use_org")]
+    pub account_details: bool,
+
+    /// Only scan the accounts under this Organizational Unit (e.g.
+    /// `ou-ab12-cd34ef56`), including its nested OUs
+    #[clap(long, value_name = "OU_ID", requires = "use_org")]
+    pub ou: Option<String>,
+
+    /// Only scan these organization accounts, by ID or name, where `*`
+    /// matches any run of characters (e.g. `--accounts 111111111111,prod-*`)
+    #[clap(long, value_name = "ACCOUNTS", value_delimiter = ',', requires = "use_org")]
+    pub accounts: Vec<String>,
+
+    /// Skip these organization accounts, by ID or name (e.g.
+    /// `--exclude-accounts 'sandbox-*'`); wins over --accounts
+    #[clap(long, value_name = "ACCOUNTS", value_delimiter = ',', requires = "use_org")]
+    pub exclude_accounts: Vec<String>,
+
     /// One or more specific role ARNs (mutually exclusive with --use-org)
     #[clap(long, conflicts_with = "use_org")]
     pub role_arns: Vec<String>,
 
-    /// One or more AWS Regions to scan.  You may supply them as
+    /// One or more AWS Regions (or region groups from the shared config file,
+    /// `~/.config/aws-tools/config.yml`) to scan.  You may supply them as
     ///   --regions us-west-2 us-east-1
     /// or as a single comma‑separated string:
     ///   --regions us-west-2,us-east-1
@@ -27,6 +101,346 @@ pub struct Cli {
         default_values = ["us-east-1", "us-west-2"]
     )]
     pub regions: Vec<String>,
+
+    /// Scan every region each account has enabled, read from EC2
+    /// DescribeRegions per account, instead of --regions (opt-in regions
+    /// the account has not enabled are skipped)
+    #[clap(long, conflicts_with = "regions")]
+    pub all_regions: bool,
+
+    /// Named profile from the shared AWS config and credentials files to
+    /// take the base credentials from, instead of exporting AWS_PROFILE
+    #[clap(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// When the SSO session has expired, run `aws sso login` (for --profile
+    /// or AWS_PROFILE) and try once more instead of failing
+    #[clap(long)]
+    pub sso_login: bool,
+
+    /// Send STS calls (AssumeRole, GetCallerIdentity) to this region's
+    /// endpoint instead of each scanned region's (e.g. `us-east-1`)
+    #[clap(long, value_name = "REGION")]
+    pub sts_region: Option<String>,
+
+    /// External ID to pass when assuming cross-account roles whose trust
+    /// policy requires an `sts:ExternalId` condition
+    #[clap(long, value_name = "ID")]
+    pub external_id: Option<String>,
+
+    /// Session name of assumed roles, shown in the member accounts'
+    /// CloudTrail (default `ls-rds`)
+    #[clap(long, value_name = "NAME")]
+    pub session_name: Option<String>,
+
+    /// Lifetime of assumed-role credentials, between 15m and 12h and within
+    /// the role's maximum (e.g. `2h` for large scans; default 1h)
+    #[clap(long, value_name = "DURATION")]
+    pub session_duration: Option<String>,
+
+    /// Join per-account RDS spend from Cost Explorer over the given window
+    /// (e.g. `30d`, `2w`) as an extra column.
+    #[clap(long, value_name = "PERIOD")]
+    pub with_spend: Option<String>,
+
+    /// Print full DB ARNs, one per line, instead of identifiers
+    #[clap(long, conflicts_with = "topology")]
+    pub arns: bool,
+
+    /// Render instances as a primary → read-replica tree instead of a flat
+    /// list, with Aurora instances grouped under their cluster's writer and
+    /// readers
+    #[clap(long)]
+    pub topology: bool,
+
+    /// Print instance counts by engine and major version across every
+    /// scanned account instead of a listing
+    #[clap(long, conflicts_with_all = ["tui", "pick", "json", "emit_tf_imports"])]
+    pub stats: bool,
+
+    /// List instances sorted by their next maintenance window
+    #[clap(long)]
+    pub maintenance_calendar: bool,
+
+    /// Also write the upcoming maintenance windows to this iCalendar file
+    #[clap(long, value_name = "FILE", requires = "maintenance_calendar")]
+    pub ical: Option<PathBuf>,
+
+    /// List blue/green deployments (with switchover status) instead of instances
+    #[clap(long)]
+    pub blue_green: bool,
+
+    /// List DB clusters (engine, status, Serverless v2 capacity, writer and
+    /// members) instead of instances, including clusters with no instances
+    #[clap(long)]
+    pub clusters: bool,
+
+    /// Also list RDS Proxies (engine family, endpoint, status) after the
+    /// instances of each listing
+    #[clap(long)]
+    pub include_proxies: bool,
+
+    /// List Aurora global databases with their primary and secondary
+    /// regions instead of instances
+    #[clap(long)]
+    pub global_clusters: bool,
+
+    /// List Aurora cluster writer, reader and custom endpoints (with the
+    /// instances they target) instead of instances
+    #[clap(long)]
+    pub cluster_endpoints: bool,
+
+    /// List pending maintenance actions (OS patches, engine upgrades, CA
+    /// rotations) with the date each is applied, soonest first, instead of
+    /// instances
+    #[clap(long)]
+    pub maintenance: bool,
+
+    /// Audit DB snapshots (public shares, age) instead of listing instances
+    #[clap(long)]
+    pub snapshots: bool,
+
+    /// Only list instances created more than this long ago (e.g. `365d`);
+    /// with --snapshots, flag snapshots older than this as stale instead
+    #[clap(long, value_name = "PERIOD")]
+    pub older_than: Option<String>,
+
+    /// Only list instances created within this period (e.g. `7d`)
+    #[clap(long, value_name = "PERIOD", conflicts_with = "snapshots")]
+    pub newer_than: Option<String>,
+
+    /// Only list instances carrying this tag; repeat to require several
+    /// (e.g. `--tag team=payments --tag env=prod`)
+    #[clap(long = "tag", value_name = "KEY=VALUE")]
+    pub tags: Vec<String>,
+
+    /// Add an AGE column (days since the instance was created); implied by
+    /// --older-than / --newer-than
+    #[clap(long)]
+    pub with_age: bool,
+
+    /// Add ENGINE, VERSION, CLASS, STORAGE-GIB and AZ columns, for
+    /// inventory and upgrade planning
+    #[clap(long)]
+    pub with_specs: bool,
+
+    /// Add VPC-ID, VPC-NAME, VPC-CIDR and PLACEMENT (public/private subnet)
+    /// columns, resolved from each instance's subnet group
+    #[clap(long)]
+    pub with_network: bool,
+
+    /// Only list instances of this engine family; `commercial` also adds
+    /// LICENSE-MODEL, EDITION and CHARSET columns for license true-ups
+    #[clap(long, value_enum, value_name = "FAMILY")]
+    pub engine_family: Option<EngineFamily>,
+
+    /// Only list instances of these engines
+    /// (e.g. `--engine postgres,mysql,aurora-postgresql`)
+    #[clap(long, value_name = "ENGINES", value_delimiter = ',')]
+    pub engine: Vec<String>,
+
+    /// Only list instances in these states, with a STATUS column, e.g.
+    /// `--status stopped` for databases still paying for storage
+    #[clap(long, value_name = "STATUSES", value_delimiter = ',')]
+    pub status: Vec<String>,
+
+    /// Only list publicly accessible (internet-reachable) instances
+    #[clap(long)]
+    pub public_only: bool,
+
+    /// Only list instances whose storage is not encrypted at rest (the
+    /// listing counterpart of --fail-on-unencrypted)
+    #[clap(long)]
+    pub unencrypted_only: bool,
+
+    /// Only list instances with Performance Insights turned off (combine
+    /// with --tag, e.g. `--tag env=prod`, to check production)
+    #[clap(long)]
+    pub no_pi_only: bool,
+
+    /// Only list instances without a Multi-AZ standby (Aurora members are
+    /// left out; use --require-multi-az to check their clusters)
+    #[clap(long)]
+    pub single_az_only: bool,
+
+    /// Add CLUSTER and CLUSTER-ROLE (writer/reader) columns for Aurora members
+    #[clap(long)]
+    pub with_cluster: bool,
+
+    /// Group the listing, e.g. `--group-by cluster` to nest cluster members
+    /// under their cluster
+    #[clap(long, value_enum, value_name = "KEY", conflicts_with_all = ["topology", "arns"])]
+    pub group_by: Option<GroupBy>,
+
+    /// Add a column per named DB parameter, resolved from each instance's
+    /// parameter group or the engine defaults (e.g. `--with-params
+    /// max_connections,shared_buffers`)
+    #[clap(long, value_name = "NAMES", value_delimiter = ',')]
+    pub with_params: Vec<String>,
+
+    /// Add STORAGE (allocated/max) and autoscaling status columns, flagging
+    /// instances with less than this much headroom below their ceiling
+    #[clap(long, value_name = "PCT", num_args = 0..=1, default_missing_value = "20%")]
+    pub storage_headroom: Option<String>,
+
+    /// Add NEXT-MINOR and LATEST-MAJOR columns: the nearest minor and the
+    /// latest major engine version each instance can be upgraded to
+    #[clap(long)]
+    pub upgrade_targets: bool,
+
+    /// Browse instances in an interactive terminal UI (filterable table,
+    /// detail pane, copy identifier/endpoint/ARN)
+    #[clap(long)]
+    pub tui: bool,
+
+    /// Pick an instance with a fuzzy finder as the scan discovers them, then
+    /// print its details
+    #[clap(long, conflicts_with = "tui")]
+    pub pick: bool,
+
+    /// With --pick, print only the picked identifier (for shell pipelines)
+    #[clap(short, long, requires = "pick")]
+    pub quiet: bool,
+
+    /// Print Terraform (1.5+) `import` blocks for the instances and their
+    /// clusters, subnet groups and parameter groups instead of a listing
+    #[clap(long, conflicts_with_all = ["tui", "pick"])]
+    pub emit_tf_imports: bool,
+
+    /// Address template for --emit-tf-imports; placeholders are {type},
+    /// {name}, {id}, {account} and {region}
+    /// (e.g. `module.rds_{account}.{type}.{name}`)
+    #[clap(long, value_name = "TEMPLATE", requires = "emit_tf_imports")]
+    pub tf_address: Option<String>,
+
+    /// Print the scan as one JSON document following the versioned schema
+    /// (see --schema) instead of a listing
+    #[clap(long, conflicts_with_all = ["tui", "pick", "emit_tf_imports", "arns", "topology", "group_by"])]
+    pub json: bool,
+
+    /// Output format; defaults to `table` on a terminal and `text` when
+    /// stdout is piped.  `--output json` is the same as --json
+    /// (e.g. `ls-rds --output json | jq '.instances[].arn'`)
+    #[clap(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        conflicts_with_all = ["json", "tui", "pick", "emit_tf_imports", "arns", "topology", "group_by"]
+    )]
+    pub output: Option<OutputFormat>,
+
+    /// Print at most this many instances, in listing order (e.g. `--limit 50`).
+    /// Not for --topology or --group-by, which would render a partial tree
+    #[clap(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["topology", "group_by"]
+    )]
+    pub limit: Option<u32>,
+
+    /// With --limit, print this page of instances (1-based)
+    #[clap(long, value_name = "P", requires = "limit", value_parser = clap::value_parser!(u32).range(1..))]
+    pub page: Option<u32>,
+
+    /// Print the JSON Schema of --json output and exit
+    #[clap(long)]
+    pub schema: bool,
+
+    /// Replace account IDs, ARNs and identifiers with stable aliases so the
+    /// output can be shared outside the company
+    #[clap(long)]
+    pub redact: bool,
+
+    /// Exit non-zero, listing the findings on stderr, if any scanned
+    /// instance or cluster lacks storage encryption (for scheduled CI checks)
+    #[clap(long)]
+    pub fail_on_unencrypted: bool,
+
+    /// Exit non-zero, listing the findings on stderr, if any scanned
+    /// database runs in a single Availability Zone (no Multi-AZ standby, or
+    /// a cluster whose members share one AZ)
+    #[clap(long)]
+    pub require_multi_az: bool,
+
+    /// Limit --require-multi-az to instances with a tag (`KEY=VALUE`, e.g.
+    /// `env=prod`) or whose identifier matches a pattern (e.g. `prod-*`)
+    #[clap(long, value_name = "SCOPE", requires = "require_multi_az")]
+    pub multi_az_scope: Option<String>,
+
+    /// Exit non-zero, listing the findings on stderr, if any scanned
+    /// instance or cluster keeps automated backups for fewer than N days
+    /// (disabled backups count as 0)
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=35))]
+    pub min_backup_retention: Option<u32>,
+
+    /// Retry throttled or failed AWS calls up to this many times; throttled
+    /// RDS and STS calls also back off exponentially (default 3)
+    #[clap(long, value_name = "N")]
+    pub max_retries: Option<u32>,
+
+    /// Scan at most this many account/region pairs at once (default 4);
+    /// raise it for large organizations, lower it when throttled
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_concurrency: Option<u32>,
+
+    /// Abort the scan, printing the partial results, once this many AWS
+    /// calls have been made (guards shared accounts against runaway scans)
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_api_calls: Option<u64>,
+
+    /// Abort the scan, printing the partial results, once it has run this
+    /// long (e.g. `20m`)
+    #[clap(long, value_name = "DURATION")]
+    pub max_duration: Option<String>,
+
+    /// Give up connecting to an AWS endpoint after this long (e.g. `5s`)
+    #[clap(long, value_name = "DURATION")]
+    pub connect_timeout: Option<String>,
+
+    /// Give up on a single AWS call, retries included, after this long
+    /// (e.g. `2m`)
+    #[clap(long, value_name = "DURATION")]
+    pub operation_timeout: Option<String>,
+
+    /// Append each finished account/region, with its results, to this JSON
+    /// Lines file as the scan runs
+    #[clap(long, value_name = "FILE")]
+    pub manifest: Option<PathBuf>,
+
+    /// Skip the account/regions already recorded in --manifest and report
+    /// their saved results, to continue an interrupted scan (run it with the
+    /// same options as the interrupted one)
+    #[clap(long, requires = "manifest")]
+    pub resume: bool,
+
+    /// Print a per-account/region table of scan time, API calls and instances
+    /// found to stderr
+    #[clap(long)]
+    pub timings: bool,
+
+    /// Print a per-service/region/account count of AWS API calls to stderr
+    #[clap(long)]
+    pub api_stats: bool,
+
+    /// Add the arguments of a named view from the settings file
+    /// (`~/.config/aws-tools/config.yml`), e.g. `--view security-audit`
+    #[clap(long, value_name = "NAME")]
+    pub view: Option<String>,
+
+    /// Resolve the accounts, roles and regions to scan, print the plan and
+    /// an estimated API call count, and exit without scanning
+    #[clap(long)]
+    pub dry_run: bool,
+
+    /// Print the account, ARN, partition and credential source of the base
+    /// credentials and exit
+    #[clap(long)]
+    pub whoami: bool,
+
+    /// Print the --whoami identity to stderr before the scan starts
+    #[clap(short, long)]
+    pub verbose: bool,
 }
 
 #[cfg(test)]
@@ -45,6 +459,164 @@ mod tests {
         assert_eq!(cli.regions, vec!["us-east-1", "us-west-2"]);
         assert!(!cli.use_org);
         assert!(cli.role_arns.is_empty());
+        assert!(cli.with_spend.is_none());
+    }
+
+    #[test]
+    fn cli_parses_arns() {
+        let cli = Cli::parse_from(["ls-rds", "--arns"]);
+        assert!(cli.arns);
+    }
+
+    #[test]
+    fn cli_rejects_arns_with_topology() {
+        assert!(Cli::try_parse_from(["ls-rds", "--arns", "--topology"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_topology() {
+        let cli = Cli::parse_from(["ls-rds", "--topology"]);
+        assert!(cli.topology);
+    }
+
+    #[test]
+    fn cli_parses_maintenance_calendar_with_ical() {
+        let cli = Cli::parse_from(["ls-rds", "--maintenance-calendar", "--ical", "windows.ics"]);
+        assert!(cli.maintenance_calendar);
+        assert_eq!(cli.ical, Some(PathBuf::from("windows.ics")));
+    }
+
+    #[test]
+    fn cli_ical_requires_maintenance_calendar() {
+        assert!(Cli::try_parse_from(["ls-rds", "--ical", "windows.ics"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_blue_green() {
+        let cli = Cli::parse_from(["ls-rds", "--blue-green"]);
+        assert!(cli.blue_green);
+    }
+
+    #[test]
+    fn cli_parses_cluster_endpoints() {
+        let cli = Cli::parse_from(["ls-rds", "--cluster-endpoints"]);
+        assert!(cli.cluster_endpoints);
+    }
+
+    #[test]
+    fn cli_parses_snapshots_with_older_than() {
+        let cli = Cli::parse_from(["ls-rds", "--snapshots", "--older-than", "90d"]);
+        assert!(cli.snapshots);
+        assert_eq!(cli.older_than.as_deref(), Some("90d"));
+    }
+
+    #[test]
+    fn cli_parses_instance_age_filters() {
+        let cli = Cli::parse_from(["ls-rds", "--older-than", "365d", "--newer-than", "730d", "--with-age"]);
+        assert!(!cli.snapshots);
+        assert_eq!(cli.older_than.as_deref(), Some("365d"));
+        assert_eq!(cli.newer_than.as_deref(), Some("730d"));
+        assert!(cli.with_age);
+    }
+
+    #[test]
+    fn cli_parses_cluster_membership_flags() {
+        let cli = Cli::parse_from(["ls-rds", "--with-cluster", "--group-by", "cluster"]);
+        assert!(cli.with_cluster);
+        assert_eq!(cli.group_by, Some(GroupBy::Cluster));
+        assert!(Cli::try_parse_from(["ls-rds", "--group-by", "cluster", "--topology"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--group-by", "engine"]).is_err());
+    }
+
+    #[test]
+    fn cli_newer_than_conflicts_with_snapshots() {
+        assert!(Cli::try_parse_from(["ls-rds", "--snapshots", "--newer-than", "7d"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_engine_family() {
+        let cli = Cli::parse_from(["ls-rds", "--engine-family", "commercial"]);
+        assert_eq!(cli.engine_family, Some(EngineFamily::Commercial));
+        let cli = Cli::parse_from(["ls-rds", "--engine-family", "open-source"]);
+        assert_eq!(cli.engine_family, Some(EngineFamily::OpenSource));
+        assert!(Cli::try_parse_from(["ls-rds", "--engine-family", "oracle"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_with_params() {
+        let cli = Cli::parse_from(["ls-rds", "--with-params", "max_connections,shared_buffers"]);
+        assert_eq!(cli.with_params, vec!["max_connections", "shared_buffers"]);
+    }
+
+    #[test]
+    fn cli_parses_storage_headroom() {
+        let cli = Cli::parse_from(["ls-rds", "--storage-headroom"]);
+        assert_eq!(cli.storage_headroom.as_deref(), Some("20%"));
+        let cli = Cli::parse_from(["ls-rds", "--storage-headroom", "10%"]);
+        assert_eq!(cli.storage_headroom.as_deref(), Some("10%"));
+    }
+
+    #[test]
+    fn cli_parses_tui() {
+        let cli = Cli::parse_from(["ls-rds", "--tui"]);
+        assert!(cli.tui);
+    }
+
+    #[test]
+    fn cli_parses_pick_quiet() {
+        let cli = Cli::parse_from(["ls-rds", "--pick", "-q"]);
+        assert!(cli.pick);
+        assert!(cli.quiet);
+        assert!(Cli::try_parse_from(["ls-rds", "--quiet"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_emit_tf_imports() {
+        let cli = Cli::parse_from(["ls-rds", "--emit-tf-imports", "--tf-address", "module.db.{type}.{name}"]);
+        assert!(cli.emit_tf_imports);
+        assert_eq!(cli.tf_address.as_deref(), Some("module.db.{type}.{name}"));
+        assert!(Cli::try_parse_from(["ls-rds", "--tf-address", "{type}.{name}"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_redact() {
+        let cli = Cli::parse_from(["ls-rds", "--redact"]);
+        assert!(cli.redact);
+    }
+
+    #[test]
+    fn cli_parses_sdk_tuning() {
+        let cli = Cli::parse_from([
+            "ls-rds",
+            "--max-retries",
+            "0",
+            "--connect-timeout",
+            "5s",
+            "--operation-timeout",
+            "2m",
+        ]);
+        assert_eq!(cli.max_retries, Some(0));
+        assert_eq!(cli.connect_timeout.as_deref(), Some("5s"));
+        assert_eq!(cli.operation_timeout.as_deref(), Some("2m"));
+        assert!(Cli::try_parse_from(["ls-rds", "--max-retries", "-1"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_timings() {
+        let cli = Cli::parse_from(["ls-rds", "--timings"]);
+        assert!(cli.timings);
+    }
+
+    #[test]
+    fn cli_parses_api_stats() {
+        let cli = Cli::parse_from(["ls-rds", "--api-stats"]);
+        assert!(cli.api_stats);
+    }
+
+    #[test]
+    fn cli_parses_with_spend() {
+        let cli = Cli::parse_from(["ls-rds", "--with-spend", "30d"]);
+        assert_eq!(cli.with_spend.as_deref(), Some("30d"));
     }
 
     #[test]
@@ -71,6 +643,40 @@ mod tests {
         assert!(cli.use_org);
     }
 
+    #[test]
+    fn cli_parses_role_map() {
+        let cli = Cli::parse_from(["ls-rds", "--use-org", "--role-map", "roles.yml"]);
+        assert_eq!(cli.role_map, Some(PathBuf::from("roles.yml")));
+    }
+
+    #[test]
+    fn cli_role_map_requires_use_org() {
+        assert!(Cli::try_parse_from(["ls-rds", "--role-map", "roles.yml"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_account_ids_and_file() {
+        let cli = Cli::parse_from([
+            "ls-rds",
+            "--account-ids",
+            "111111111111,222222222222",
+            "--accounts-file",
+            "accounts.txt",
+            "--role-map",
+            "roles.yml",
+        ]);
+        assert_eq!(cli.account_ids, vec!["111111111111", "222222222222"]);
+        assert_eq!(cli.accounts_file, Some(PathBuf::from("accounts.txt")));
+        assert!(Cli::try_parse_from(["ls-rds", "--use-org", "--account-ids", "111111111111"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--accounts-file", "a.txt", "--role-arns", "arn:aws:iam::1:role/R"]).is_err());
+    }
+
+    #[test]
+    fn cli_account_details_requires_use_org() {
+        assert!(Cli::parse_from(["ls-rds", "--use-org", "--account-details"]).account_details);
+        assert!(Cli::try_parse_from(["ls-rds", "--account-details"]).is_err());
+    }
+
     #[test]
     fn cli_parses_role_arns() {
         let cli = Cli::parse_from([
@@ -93,4 +699,266 @@ mod tests {
         ]);
         assert_eq!(cli.role_arns.len(), 2);
     }
+
+    #[test]
+    fn cli_parses_fail_on_unencrypted() {
+        let cli = Cli::parse_from(["ls-rds", "--fail-on-unencrypted"]);
+        assert!(cli.fail_on_unencrypted);
+        assert!(!Cli::parse_from(["ls-rds"]).fail_on_unencrypted);
+    }
+
+    #[test]
+    fn cli_parses_json_and_schema() {
+        assert!(Cli::parse_from(["ls-rds", "--json"]).json);
+        assert!(Cli::parse_from(["ls-rds", "--schema"]).schema);
+        assert!(Cli::try_parse_from(["ls-rds", "--json", "--tui"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--json", "--arns"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--json", "--emit-tf-imports"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_whoami_and_verbose() {
+        assert!(Cli::parse_from(["ls-rds", "--whoami"]).whoami);
+        assert!(Cli::parse_from(["ls-rds", "-v"]).verbose);
+    }
+
+    #[test]
+    fn cli_parses_output() {
+        let cli = Cli::parse_from(["ls-rds", "--output", "json"]);
+        assert_eq!(cli.output, Some(OutputFormat::Json));
+        let cli = Cli::parse_from(["ls-rds", "--output", "table"]);
+        assert_eq!(cli.output, Some(OutputFormat::Table));
+        let cli = Cli::parse_from(["ls-rds", "--output", "csv"]);
+        assert_eq!(cli.output, Some(OutputFormat::Csv));
+        assert!(Cli::parse_from(["ls-rds"]).output.is_none());
+        assert!(Cli::try_parse_from(["ls-rds", "--output", "yaml"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--output", "json", "--json"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--output", "json", "--tui"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_limit_and_page() {
+        let cli = Cli::parse_from(["ls-rds", "--limit", "50", "--page", "2"]);
+        assert_eq!(cli.limit, Some(50));
+        assert_eq!(cli.page, Some(2));
+        assert!(Cli::try_parse_from(["ls-rds", "--limit", "0"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--limit", "10", "--page", "0"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--page", "2"]).is_err());
+    }
+
+    #[test]
+    fn cli_limit_conflicts_with_grouped_views() {
+        assert!(Cli::try_parse_from(["ls-rds", "--limit", "10", "--topology"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--limit", "10", "--group-by", "cluster"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_with_specs() {
+        let cli = Cli::parse_from(["ls-rds", "--with-specs"]);
+        assert!(cli.with_specs);
+    }
+
+    #[test]
+    fn cli_parses_upgrade_targets() {
+        let cli = Cli::parse_from(["ls-rds", "--upgrade-targets"]);
+        assert!(cli.upgrade_targets);
+    }
+
+    #[test]
+    fn cli_parses_clusters() {
+        let cli = Cli::parse_from(["ls-rds", "--clusters"]);
+        assert!(cli.clusters);
+    }
+
+    #[test]
+    fn cli_parses_global_clusters() {
+        let cli = Cli::parse_from(["ls-rds", "--global-clusters"]);
+        assert!(cli.global_clusters);
+    }
+
+    #[test]
+    fn cli_parses_require_multi_az_with_scope() {
+        let cli = Cli::parse_from(["ls-rds", "--require-multi-az", "--multi-az-scope", "env=prod"]);
+        assert!(cli.require_multi_az);
+        assert_eq!(cli.multi_az_scope.as_deref(), Some("env=prod"));
+        assert!(Cli::try_parse_from(["ls-rds", "--multi-az-scope", "prod-*"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_include_proxies() {
+        let cli = Cli::parse_from(["ls-rds", "--include-proxies"]);
+        assert!(cli.include_proxies);
+    }
+
+    #[test]
+    fn cli_parses_repeated_tags() {
+        let cli = Cli::parse_from(["ls-rds", "--tag", "team=payments", "--tag", "env=prod"]);
+        assert_eq!(cli.tags, vec!["team=payments", "env=prod"]);
+    }
+
+    #[test]
+    fn cli_parses_sts_region() {
+        let cli = Cli::parse_from(["ls-rds", "--sts-region", "eu-west-1"]);
+        assert_eq!(cli.sts_region.as_deref(), Some("eu-west-1"));
+    }
+
+    #[test]
+    fn cli_parses_manifest_and_resume() {
+        let cli = Cli::parse_from(["ls-rds", "--manifest", "scan.jsonl", "--resume"]);
+        assert_eq!(cli.manifest, Some(PathBuf::from("scan.jsonl")));
+        assert!(cli.resume);
+        assert!(Cli::try_parse_from(["ls-rds", "--resume"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_engine() {
+        let cli = Cli::parse_from(["ls-rds", "--engine", "postgres,aurora-postgresql"]);
+        assert_eq!(cli.engine, vec!["postgres", "aurora-postgresql"]);
+    }
+
+    #[test]
+    fn cli_parses_dry_run() {
+        let cli = Cli::parse_from(["ls-rds", "--dry-run"]);
+        assert!(cli.dry_run);
+    }
+
+    #[test]
+    fn cli_parses_status() {
+        let cli = Cli::parse_from(["ls-rds", "--status", "available,stopped"]);
+        assert_eq!(cli.status, vec!["available", "stopped"]);
+    }
+
+    #[test]
+    fn cli_parses_public_only() {
+        let cli = Cli::parse_from(["ls-rds", "--public-only"]);
+        assert!(cli.public_only);
+    }
+
+    #[test]
+    fn cli_parses_stats() {
+        let cli = Cli::parse_from(["ls-rds", "--stats"]);
+        assert!(cli.stats);
+        assert!(Cli::try_parse_from(["ls-rds", "--stats", "--json"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--stats", "--tui"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_single_az_only() {
+        let cli = Cli::parse_from(["ls-rds", "--single-az-only"]);
+        assert!(cli.single_az_only);
+    }
+
+    #[test]
+    fn cli_parses_external_id() {
+        let cli = Cli::parse_from(["ls-rds", "--external-id", "ls-rds-audit"]);
+        assert_eq!(cli.external_id.as_deref(), Some("ls-rds-audit"));
+    }
+
+    #[test]
+    fn cli_parses_view() {
+        let cli = Cli::parse_from(["ls-rds", "--view", "security-audit"]);
+        assert_eq!(cli.view.as_deref(), Some("security-audit"));
+    }
+
+    #[test]
+    fn cli_parses_session_name_and_duration() {
+        let cli = Cli::parse_from(["ls-rds", "--session-name", "audit-2024", "--session-duration", "4h"]);
+        assert_eq!(cli.session_name.as_deref(), Some("audit-2024"));
+        assert_eq!(cli.session_duration.as_deref(), Some("4h"));
+    }
+
+    #[test]
+    fn cli_parses_with_network() {
+        let cli = Cli::parse_from(["ls-rds", "--with-network"]);
+        assert!(cli.with_network);
+    }
+
+    #[test]
+    fn cli_parses_max_concurrency() {
+        let cli = Cli::parse_from(["ls-rds", "--max-concurrency", "16"]);
+        assert_eq!(cli.max_concurrency, Some(16));
+        assert!(Cli::try_parse_from(["ls-rds", "--max-concurrency", "0"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_scan_budgets() {
+        let cli = Cli::parse_from(["ls-rds", "--max-api-calls", "5000", "--max-duration", "20m"]);
+        assert_eq!(cli.max_api_calls, Some(5000));
+        assert_eq!(cli.max_duration.as_deref(), Some("20m"));
+        assert!(Cli::try_parse_from(["ls-rds", "--max-api-calls", "0"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_ou() {
+        let cli = Cli::parse_from(["ls-rds", "--use-org", "--ou", "ou-ab12-cd34ef56"]);
+        assert_eq!(cli.ou.as_deref(), Some("ou-ab12-cd34ef56"));
+        assert!(Cli::try_parse_from(["ls-rds", "--ou", "ou-ab12-cd34ef56"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_account_filters() {
+        let cli = Cli::parse_from([
+            "ls-rds",
+            "--use-org",
+            "--accounts",
+            "111111111111,prod-*",
+            "--exclude-accounts",
+            "sandbox-*",
+        ]);
+        assert_eq!(cli.accounts, vec!["111111111111", "prod-*"]);
+        assert_eq!(cli.exclude_accounts, vec!["sandbox-*"]);
+        assert!(Cli::try_parse_from(["ls-rds", "--accounts", "prod-*"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--exclude-accounts", "sandbox-*"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_all_regions() {
+        let cli = Cli::parse_from(["ls-rds", "--all-regions"]);
+        assert!(cli.all_regions);
+        assert!(Cli::try_parse_from(["ls-rds", "--all-regions", "--regions", "eu-west-1"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_profile() {
+        let cli = Cli::parse_from(["ls-rds", "--profile", "audit"]);
+        assert_eq!(cli.profile.as_deref(), Some("audit"));
+    }
+
+    #[test]
+    fn cli_parses_sso_login() {
+        let cli = Cli::parse_from(["ls-rds", "--sso-login"]);
+        assert!(cli.sso_login);
+    }
+
+    #[test]
+    fn cli_parses_account_names() {
+        let cli = Cli::parse_from(["ls-rds", "--account-names"]);
+        assert!(cli.account_names);
+    }
+
+    #[test]
+    fn cli_parses_unencrypted_only() {
+        let cli = Cli::parse_from(["ls-rds", "--unencrypted-only"]);
+        assert!(cli.unencrypted_only);
+    }
+
+    #[test]
+    fn cli_parses_min_backup_retention() {
+        let cli = Cli::parse_from(["ls-rds", "--min-backup-retention", "7"]);
+        assert_eq!(cli.min_backup_retention, Some(7));
+        assert!(Cli::try_parse_from(["ls-rds", "--min-backup-retention", "0"]).is_err());
+        assert!(Cli::try_parse_from(["ls-rds", "--min-backup-retention", "36"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_maintenance() {
+        let cli = Cli::parse_from(["ls-rds", "--maintenance"]);
+        assert!(cli.maintenance);
+    }
+
+    #[test]
+    fn cli_parses_no_pi_only() {
+        let cli = Cli::parse_from(["ls-rds", "--no-pi-only"]);
+        assert!(cli.no_pi_only);
+    }
 }
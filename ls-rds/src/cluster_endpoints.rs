@@ -0,0 +1,167 @@
+//! Aurora cluster endpoints for ls-rds
+//!
+//! Lists each cluster's writer and reader endpoints plus any custom
+//! endpoints with the instances they route to, so application teams can
+//! check they are pointed at the right kind of endpoint.
+
+use crate::api_stats::ApiStats;
+use crate::retry;
+use aws_sdk_rds as rds;
+use aws_types::region::Region;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+/// One endpoint of an Aurora cluster
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClusterEndpoint {
+    pub account_id: String,
+    pub region: String,
+    pub cluster_id: String,
+    /// Endpoint identifier (custom endpoints only)
+    pub identifier: Option<String>,
+    /// `WRITER`, `READER` or `CUSTOM`
+    pub endpoint_type: String,
+    /// `READER` or `ANY` for custom endpoints
+    pub custom_type: Option<String>,
+    pub address: String,
+    pub status: String,
+    /// Instances a custom endpoint is pinned to
+    pub static_members: Vec<String>,
+    /// Instances a custom endpoint excludes (it targets everything else)
+    pub excluded_members: Vec<String>,
+}
+
+impl ClusterEndpoint {
+    /// Human-readable description of which instances the endpoint reaches
+    pub fn targeting(&self) -> String {
+        let kind = if self.custom_type.as_deref() == Some("READER") { "readers" } else { "instances" };
+        match self.endpoint_type.as_str() {
+            "WRITER" => "writer instance".to_owned(),
+            "READER" => "all readers".to_owned(),
+            _ if !self.static_members.is_empty() => self.static_members.join(","),
+            _ if !self.excluded_members.is_empty() => {
+                format!("all {} except {}", kind, self.excluded_members.join(","))
+            }
+            _ => format!("all {}", kind),
+        }
+    }
+}
+
+/// List the writer, reader and custom endpoints of every cluster in one
+/// region
+pub async fn list_cluster_endpoints(
+    client: &rds::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    retries: u32,
+) -> Result<Vec<ClusterEndpoint>> {
+    let mut endpoints = Vec::new();
+    let mut marker: Option<String> = None;
+    loop {
+        let page = retry::with_retries(retries, || {
+            stats.record("rds", "DescribeDBClusterEndpoints", region.as_ref(), account_id);
+            client.describe_db_cluster_endpoints().set_marker(marker.clone()).send()
+        })
+        .await?;
+        for ep in page.db_cluster_endpoints() {
+            let endpoint_type = ep.endpoint_type().unwrap_or_default().to_uppercase();
+            endpoints.push(ClusterEndpoint {
+                account_id: account_id.to_owned(),
+                region: region.to_string(),
+                cluster_id: ep.db_cluster_identifier().unwrap_or_default().to_owned(),
+                identifier: ep
+                    .db_cluster_endpoint_identifier()
+                    .filter(|_| endpoint_type == "CUSTOM")
+                    .map(str::to_owned),
+                custom_type: ep.custom_endpoint_type().map(str::to_owned),
+                address: ep.endpoint().unwrap_or_default().to_owned(),
+                status: ep.status().unwrap_or_default().to_owned(),
+                static_members: ep.static_members().to_vec(),
+                excluded_members: ep.excluded_members().to_vec(),
+                endpoint_type,
+            });
+        }
+        match page.marker() {
+            Some(next) => marker = Some(next.to_owned()),
+            None => break,
+        }
+    }
+    endpoints.sort_by(|a, b| {
+        let rank = |e: &ClusterEndpoint| match e.endpoint_type.as_str() {
+            "WRITER" => 0,
+            "READER" => 1,
+            _ => 2,
+        };
+        (&a.cluster_id, rank(a), &a.identifier).cmp(&(&b.cluster_id, rank(b), &b.identifier))
+    });
+    Ok(endpoints)
+}
+
+/// Format an endpoint as a TSV row: region, cluster, type, address, status,
+/// targeting
+pub fn format_cluster_endpoint(ep: &ClusterEndpoint) -> String {
+    let kind = match &ep.identifier {
+        Some(id) => format!("CUSTOM:{}", id),
+        None => ep.endpoint_type.clone(),
+    };
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}",
+        ep.region,
+        ep.cluster_id,
+        kind,
+        ep.address,
+        ep.status,
+        ep.targeting()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(static_members: &[&str], excluded: &[&str]) -> ClusterEndpoint {
+        ClusterEndpoint {
+            region: "us-east-1".to_string(),
+            cluster_id: "orders".to_string(),
+            identifier: Some("reporting".to_string()),
+            endpoint_type: "CUSTOM".to_string(),
+            custom_type: Some("READER".to_string()),
+            address: "reporting.cluster-custom-abc.us-east-1.rds.amazonaws.com".to_string(),
+            status: "available".to_string(),
+            static_members: static_members.iter().map(|s| s.to_string()).collect(),
+            excluded_members: excluded.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn targeting_describes_builtin_endpoints() {
+        let writer = ClusterEndpoint {
+            endpoint_type: "WRITER".to_string(),
+            ..Default::default()
+        };
+        let reader = ClusterEndpoint {
+            endpoint_type: "READER".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(writer.targeting(), "writer instance");
+        assert_eq!(reader.targeting(), "all readers");
+    }
+
+    #[test]
+    fn targeting_describes_custom_members() {
+        assert_eq!(custom(&["orders-2", "orders-3"], &[]).targeting(), "orders-2,orders-3");
+        assert_eq!(custom(&[], &["orders-1"]).targeting(), "all readers except orders-1");
+        assert_eq!(custom(&[], &[]).targeting(), "all readers");
+    }
+
+    #[test]
+    fn format_cluster_endpoint_names_custom_endpoints() {
+        let row = format_cluster_endpoint(&custom(&["orders-2"], &[]));
+        assert_eq!(
+            row,
+            "us-east-1\torders\tCUSTOM:reporting\treporting.cluster-custom-abc.us-east-1.rds.amazonaws.com\tavailable\torders-2"
+        );
+    }
+}
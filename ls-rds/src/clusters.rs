@@ -0,0 +1,242 @@
+//! Aurora cluster membership for ls-rds
+//!
+//! Lists DB clusters for `--clusters` (Aurora Serverless v2 clusters can have
+//! no instance a DescribeDBInstances listing would show), resolves whether
+//! each clustered instance is its cluster's writer or a reader, and renders
+//! `--group-by cluster`: one line per cluster with its members indented
+//! beneath (writer first), followed by standalone instances.
+
+use crate::{Config, RdsInstance, ScanResult, format_instance_row};
+use crate::api_stats::ApiStats;
+use crate::retry;
+use aws_sdk_rds as rds;
+use aws_types::region::Region;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// One member instance of a DB cluster
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClusterMember {
+    pub instance_id: String,
+    pub writer: bool,
+}
+
+/// An Aurora or Multi-AZ DB cluster
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RdsCluster {
+    pub account_id: String,
+    pub region: String,
+    pub cluster_id: String,
+    pub arn: String,
+    pub engine: String,
+    pub engine_version: String,
+    /// `provisioned`, `serverless` (v1), `global` …
+    pub engine_mode: String,
+    pub status: String,
+    /// Serverless v2 capacity range in ACUs
+    pub serverless_capacity: Option<(f64, f64)>,
+    pub members: Vec<ClusterMember>,
+    pub storage_encrypted: bool,
+    pub multi_az: bool,
+}
+
+impl RdsCluster {
+    /// Identifier of the writer instance, if the cluster has one
+    pub fn writer(&self) -> Option<&str> {
+        self.members.iter().find(|m| m.writer).map(|m| m.instance_id.as_str())
+    }
+}
+
+/// List every DB cluster in one region
+pub async fn list_clusters(
+    client: &rds::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    retries: u32,
+) -> Result<Vec<RdsCluster>> {
+    let mut clusters = Vec::new();
+    let mut marker: Option<String> = None;
+    loop {
+        let page = retry::with_retries(retries, || {
+            stats.record("rds", "DescribeDBClusters", region.as_ref(), account_id);
+            client.describe_db_clusters().set_marker(marker.clone()).send()
+        })
+        .await?;
+        for c in page.db_clusters() {
+            clusters.push(RdsCluster {
+                account_id: account_id.to_owned(),
+                region: region.to_string(),
+                cluster_id: c.db_cluster_identifier().unwrap_or_default().to_owned(),
+                arn: c.db_cluster_arn().unwrap_or_default().to_owned(),
+                engine: c.engine().unwrap_or_default().to_owned(),
+                engine_version: c.engine_version().unwrap_or_default().to_owned(),
+                engine_mode: c.engine_mode().unwrap_or("provisioned").to_owned(),
+                status: c.status().unwrap_or_default().to_owned(),
+                serverless_capacity: c
+                    .serverless_v2_scaling_configuration()
+                    .and_then(|s| Some((s.min_capacity()?, s.max_capacity()?))),
+                members: c
+                    .db_cluster_members()
+                    .iter()
+                    .filter_map(|m| {
+                        Some(ClusterMember {
+                            instance_id: m.db_instance_identifier()?.to_owned(),
+                            writer: m.is_cluster_writer().unwrap_or(false),
+                        })
+                    })
+                    .collect(),
+                storage_encrypted: c.storage_encrypted().unwrap_or(false),
+                multi_az: c.multi_az().unwrap_or(false),
+            });
+        }
+        match page.marker() {
+            Some(next) => marker = Some(next.to_owned()),
+            None => break,
+        }
+    }
+    Ok(clusters)
+}
+
+/// Map each cluster member instance to `writer` or `reader`
+pub fn cluster_roles(clusters: &[RdsCluster]) -> HashMap<String, &'static str> {
+    clusters
+        .iter()
+        .flat_map(|c| &c.members)
+        .map(|m| (m.instance_id.clone(), if m.writer { "writer" } else { "reader" }))
+        .collect()
+}
+
+/// Format a cluster for `--clusters`: engine, status, capacity (ACU range
+/// for Serverless v2, otherwise the engine mode), writer and member count
+pub fn format_cluster(c: &RdsCluster) -> String {
+    let capacity = match c.serverless_capacity {
+        Some((min, max)) => format!("{}-{} ACU", min, max),
+        None => c.engine_mode.clone(),
+    };
+    format!(
+        "{}\t{}\t{} {}\t{}\t{}\twriter={}\t{} member(s)",
+        c.region,
+        c.cluster_id,
+        c.engine,
+        c.engine_version,
+        c.status,
+        capacity,
+        c.writer().unwrap_or("-"),
+        c.members.len()
+    )
+}
+
+/// Render instances grouped under their clusters; instances outside any
+/// cluster follow as plain rows
+pub fn format_by_cluster(instances: &[RdsInstance], config: &Config, result: &ScanResult) -> String {
+    let mut clusters: BTreeMap<(&str, &str, &str), Vec<&RdsInstance>> = BTreeMap::new();
+    let mut standalone = Vec::new();
+    for inst in instances {
+        match &inst.cluster_id {
+            Some(cluster) => clusters
+                .entry((inst.account_id.as_str(), inst.region.as_str(), cluster.as_str()))
+                .or_default()
+                .push(inst),
+            None => standalone.push(inst),
+        }
+    }
+
+    let mut out = String::new();
+    for ((_, region, cluster), mut members) in clusters {
+        members.sort_by_key(|m| m.cluster_role.as_deref() != Some("writer"));
+        match &members[0].role_arn {
+            Some(arn) => out.push_str(&format!("{}\t{}\t{} (cluster)\n", arn, region, cluster)),
+            None => out.push_str(&format!("{}\t{} (cluster)\n", region, cluster)),
+        }
+        for m in members {
+            out.push_str(&format!(
+                "  {}\t{}\n",
+                m.cluster_role.as_deref().unwrap_or("-"),
+                format_instance_row(m, config, result)
+            ));
+        }
+    }
+    for inst in standalone {
+        out.push_str(&format_instance_row(inst, config, result));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inst(id: &str, cluster: Option<&str>, role: Option<&str>) -> RdsInstance {
+        RdsInstance {
+            account_id: "111111111111".to_string(),
+            region: "us-east-1".to_string(),
+            instance_id: id.to_string(),
+            cluster_id: cluster.map(str::to_owned),
+            cluster_role: role.map(str::to_owned),
+            ..Default::default()
+        }
+    }
+
+    fn cluster(members: &[(&str, bool)], capacity: Option<(f64, f64)>) -> RdsCluster {
+        RdsCluster {
+            region: "us-east-1".to_string(),
+            cluster_id: "orders".to_string(),
+            engine: "aurora-postgresql".to_string(),
+            engine_version: "15.4".to_string(),
+            engine_mode: "provisioned".to_string(),
+            status: "available".to_string(),
+            serverless_capacity: capacity,
+            members: members
+                .iter()
+                .map(|(id, writer)| ClusterMember {
+                    instance_id: id.to_string(),
+                    writer: *writer,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cluster_roles_maps_members() {
+        let roles = cluster_roles(&[cluster(&[("orders-1", true), ("orders-2", false)], None)]);
+        assert_eq!(roles.get("orders-1"), Some(&"writer"));
+        assert_eq!(roles.get("orders-2"), Some(&"reader"));
+        assert!(cluster_roles(&[]).is_empty());
+    }
+
+    #[test]
+    fn format_cluster_shows_serverless_capacity_and_empty_clusters() {
+        assert_eq!(
+            format_cluster(&cluster(&[], Some((0.5, 16.0)))),
+            "us-east-1\torders\taurora-postgresql 15.4\tavailable\t0.5-16 ACU\twriter=-\t0 member(s)"
+        );
+        assert_eq!(
+            format_cluster(&cluster(&[("orders-1", true)], None)),
+            "us-east-1\torders\taurora-postgresql 15.4\tavailable\tprovisioned\twriter=orders-1\t1 member(s)"
+        );
+    }
+
+    #[test]
+    fn format_by_cluster_lists_writer_first_then_standalone() {
+        let instances = vec![
+            inst("legacy", None, None),
+            inst("orders-2", Some("orders"), Some("reader")),
+            inst("orders-1", Some("orders"), Some("writer")),
+        ];
+        let out = format_by_cluster(&instances, &Config::default(), &ScanResult::default());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "us-east-1\torders (cluster)",
+                "  writer\tus-east-1\torders-1",
+                "  reader\tus-east-1\torders-2",
+                "us-east-1\tlegacy",
+            ]
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Compliance gates for ls-rds
+//!
+//! `--fail-on-unencrypted` turns a scan into a CI check: every instance or
+//! Aurora cluster without storage encryption is reported and the run exits
+//! non-zero.  Cluster members share their cluster's storage, so a cluster is
+//! reported once rather than per instance.
+//!
+//! `--require-multi-az` does the same for databases that would not survive
+//! losing an Availability Zone: instances without a Multi-AZ standby, and
+//! Aurora clusters whose scanned members all sit in one AZ.  It can be
+//! scoped to production with `--multi-az-scope` (a `KEY=VALUE` tag or an
+//! identifier pattern such as `prod-*`).
+//!
+//! `--min-backup-retention N` reports databases keeping automated backups
+//! for fewer than N days (disabled backups count as 0).  Aurora retention
+//! is set on the cluster, so clusters are again reported once.
+
+use crate::RdsInstance;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Which instances `--require-multi-az` checks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum MultiAzScope {
+    #[default]
+    All,
+    /// Instances with this tag key and value
+    Tag(String, String),
+    /// Instances whose identifier matches a pattern where `*` matches any run
+    /// of characters
+    Name(String),
+}
+
+impl MultiAzScope {
+    /// `KEY=VALUE` selects by tag, anything else is an identifier pattern
+    pub fn parse(scope: &str) -> Self {
+        match scope.split_once('=') {
+            Some((key, value)) => MultiAzScope::Tag(key.to_owned(), value.to_owned()),
+            None => MultiAzScope::Name(scope.to_owned()),
+        }
+    }
+
+    pub fn matches(&self, inst: &RdsInstance) -> bool {
+        match self {
+            MultiAzScope::All => true,
+            MultiAzScope::Tag(key, value) => inst.tags.get(key) == Some(value),
+            MultiAzScope::Name(pattern) => wildcard_match(pattern, &inst.instance_id),
+        }
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters
+pub(crate) fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else { return false };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else { return rest.is_empty() };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// One non-compliant resource: `kind` is `instance` or `cluster`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Finding {
+    pub account_id: String,
+    pub region: String,
+    pub kind: &'static str,
+    pub identifier: String,
+}
+
+/// Instances and clusters lacking storage encryption, sorted and deduplicated
+pub fn unencrypted_findings(instances: &[RdsInstance]) -> Vec<Finding> {
+    let findings: BTreeSet<Finding> = instances
+        .iter()
+        .filter(|i| !i.storage_encrypted)
+        .map(database_finding)
+        .collect();
+    findings.into_iter().collect()
+}
+
+/// A finding for the database an instance stands for: its cluster when it
+/// is a member, else itself
+fn database_finding(i: &RdsInstance) -> Finding {
+    let (kind, identifier) = match &i.cluster_id {
+        Some(cluster) => ("cluster", cluster.clone()),
+        None => ("instance", i.instance_id.clone()),
+    };
+    Finding {
+        account_id: i.account_id.clone(),
+        region: i.region.clone(),
+        kind,
+        identifier,
+    }
+}
+
+/// Instances and clusters keeping automated backups for fewer than
+/// `min_days` days, sorted and deduplicated
+pub fn backup_retention_findings(instances: &[RdsInstance], min_days: u32) -> Vec<Finding> {
+    let findings: BTreeSet<Finding> = instances
+        .iter()
+        .filter(|i| i.backup_retention_days.and_then(|d| u32::try_from(d).ok()).unwrap_or(0) < min_days)
+        .map(database_finding)
+        .collect();
+    findings.into_iter().collect()
+}
+
+/// Databases in scope that run in a single Availability Zone, sorted: an
+/// instance without a Multi-AZ standby, or a cluster whose members in
+/// scope share one AZ
+pub fn single_az_findings(instances: &[RdsInstance], scope: &MultiAzScope) -> Vec<Finding> {
+    let mut findings = BTreeSet::new();
+    let mut clusters: BTreeMap<(&str, &str, &str), BTreeSet<&str>> = BTreeMap::new();
+    for i in instances.iter().filter(|i| scope.matches(i)) {
+        match &i.cluster_id {
+            Some(cluster) => {
+                let azs = clusters.entry((&i.account_id, &i.region, cluster)).or_default();
+                azs.extend(i.availability_zone.as_deref());
+            }
+            None if !i.multi_az => {
+                findings.insert(Finding {
+                    account_id: i.account_id.clone(),
+                    region: i.region.clone(),
+                    kind: "instance",
+                    identifier: i.instance_id.clone(),
+                });
+            }
+            None => {}
+        }
+    }
+    for ((account_id, region, cluster), azs) in clusters {
+        if azs.len() < 2 {
+            findings.insert(Finding {
+                account_id: account_id.to_owned(),
+                region: region.to_owned(),
+                kind: "cluster",
+                identifier: cluster.to_owned(),
+            });
+        }
+    }
+    findings.into_iter().collect()
+}
+
+/// Format findings as tab-separated lines under a header
+pub fn format_findings(findings: &[Finding]) -> String {
+    let mut out = String::from("ACCOUNT\tREGION\tKIND\tIDENTIFIER\n");
+    for f in findings {
+        out.push_str(&format!("{}\t{}\t{}\t{}\n", f.account_id, f.region, f.kind, f.identifier));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inst(id: &str, cluster: Option<&str>, encrypted: bool) -> RdsInstance {
+        RdsInstance {
+            account_id: "111111111111".to_string(),
+            region: "us-east-1".to_string(),
+            instance_id: id.to_string(),
+            cluster_id: cluster.map(str::to_owned),
+            storage_encrypted: encrypted,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unencrypted_findings_reports_clusters_once() {
+        let instances = vec![
+            inst("orders-1", Some("orders"), false),
+            inst("orders-2", Some("orders"), false),
+            inst("legacy", None, false),
+            inst("billing", None, true),
+        ];
+        let findings = unencrypted_findings(&instances);
+        let ids: Vec<(&str, &str)> = findings.iter().map(|f| (f.kind, f.identifier.as_str())).collect();
+        assert_eq!(ids, vec![("cluster", "orders"), ("instance", "legacy")]);
+    }
+
+    #[test]
+    fn backup_retention_findings_counts_disabled_backups_as_zero() {
+        let retained = |id: &str, cluster: Option<&str>, days: Option<i32>| RdsInstance {
+            backup_retention_days: days,
+            ..inst(id, cluster, true)
+        };
+        let instances = vec![
+            retained("orders-1", Some("orders"), Some(1)),
+            retained("orders-2", Some("orders"), Some(1)),
+            retained("legacy", None, Some(0)),
+            retained("unknown", None, None),
+            retained("billing", None, Some(7)),
+        ];
+        let findings = backup_retention_findings(&instances, 7);
+        let ids: Vec<(&str, &str)> = findings.iter().map(|f| (f.kind, f.identifier.as_str())).collect();
+        assert_eq!(ids, vec![("cluster", "orders"), ("instance", "legacy"), ("instance", "unknown")]);
+        assert_eq!(backup_retention_findings(&instances, 1).len(), 2);
+    }
+
+    fn placed(id: &str, cluster: Option<&str>, az: &str, multi_az: bool) -> RdsInstance {
+        RdsInstance {
+            availability_zone: Some(az.to_string()),
+            multi_az,
+            ..inst(id, cluster, true)
+        }
+    }
+
+    #[test]
+    fn single_az_findings_checks_instances_and_cluster_spread() {
+        let instances = vec![
+            placed("orders-1", Some("orders"), "us-east-1a", false),
+            placed("orders-2", Some("orders"), "us-east-1b", false),
+            placed("billing-1", Some("billing"), "us-east-1a", false),
+            placed("legacy", None, "us-east-1a", false),
+            placed("ledger", None, "us-east-1a", true),
+        ];
+        let findings = single_az_findings(&instances, &MultiAzScope::All);
+        let ids: Vec<(&str, &str)> = findings.iter().map(|f| (f.kind, f.identifier.as_str())).collect();
+        assert_eq!(ids, vec![("cluster", "billing"), ("instance", "legacy")]);
+    }
+
+    #[test]
+    fn multi_az_scope_selects_by_tag_or_name() {
+        let mut prod = inst("prod-orders", None, true);
+        prod.tags.insert("env".to_string(), "prod".to_string());
+        let dev = inst("dev-orders", None, true);
+        let tag = MultiAzScope::parse("env=prod");
+        assert_eq!(tag, MultiAzScope::Tag("env".to_string(), "prod".to_string()));
+        assert!(tag.matches(&prod) && !tag.matches(&dev));
+        let name = MultiAzScope::parse("prod-*");
+        assert!(name.matches(&prod) && !name.matches(&dev));
+        assert!(MultiAzScope::All.matches(&dev));
+    }
+
+    #[test]
+    fn wildcard_match_handles_stars() {
+        assert!(wildcard_match("prod-*", "prod-orders"));
+        assert!(wildcard_match("*-db", "orders-db"));
+        assert!(wildcard_match("*ord*", "prod-orders-1"));
+        assert!(wildcard_match("orders", "orders"));
+        assert!(!wildcard_match("orders", "orders-1"));
+        assert!(!wildcard_match("a*a", "a"));
+    }
+
+    #[test]
+    fn format_findings_is_tab_separated() {
+        let out = format_findings(&unencrypted_findings(&[inst("legacy", None, false)]));
+        assert_eq!(out, "ACCOUNT\tREGION\tKIND\tIDENTIFIER\n111111111111\tus-east-1\tinstance\tlegacy\n");
+    }
+}
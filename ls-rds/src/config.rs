@@ -2,8 +2,20 @@
 //!
 //! This module validates CLI arguments and provides defaults.
 
-use crate::cli::Cli;
-use eyre::{Result, bail};
+use crate::cli::{Cli, EngineFamily, GroupBy, OutputFormat};
+use crate::AccountInfo;
+use crate::compliance::{MultiAzScope, wildcard_match};
+use crate::engines;
+use crate::retry;
+use crate::roles::{self, RoleMap};
+use crate::terraform;
+use eyre::{Result, WrapErr, bail, eyre};
+pub use ls_vpc::config::parse_duration;
+use ls_vpc::settings::{Settings, is_region_name};
+use ls_vpc::utils::Page;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Mode of operation for ls-rds
 #[derive(Debug, Clone, PartialEq)]
@@ -14,6 +26,27 @@ pub enum ScanMode {
     Organization,
     /// Scan specific accounts via role ARNs
     RoleArns(Vec<String>),
+    /// Scan an explicit list of account IDs via the org role
+    Accounts(Vec<String>),
+}
+
+/// Which organization accounts org mode scans: entries are account IDs or
+/// name patterns (`*` matches any run of characters, case-insensitive)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountFilter {
+    /// Accounts to scan (empty = every account)
+    pub include: Vec<String>,
+    /// Accounts to skip, even when included
+    pub exclude: Vec<String>,
+}
+
+impl AccountFilter {
+    pub fn selects(&self, account: &AccountInfo) -> bool {
+        let matches = |entry: &String| {
+            entry == &account.id || wildcard_match(&entry.to_lowercase(), &account.name.to_lowercase())
+        };
+        (self.include.is_empty() || self.include.iter().any(matches)) && !self.exclude.iter().any(matches)
+    }
 }
 
 /// Validated configuration for ls-rds
@@ -21,18 +54,176 @@ pub enum ScanMode {
 pub struct Config {
     /// AWS regions to scan
     pub regions: Vec<String>,
+    /// Scan each account's enabled regions instead of `regions`
+    pub all_regions: bool,
     /// Scanning mode
     pub mode: ScanMode,
+    /// Role to assume in each member account (org mode)
+    pub role_map: RoleMap,
+    /// Add Organizations email/status columns
+    pub account_details: bool,
+    /// Show each instance's account name (Organizations) or IAM alias
+    pub account_names: bool,
+    /// Cost Explorer lookback window in days (`--with-spend`)
+    pub spend_days: Option<u32>,
+    /// Print ARNs instead of identifiers
+    pub arns: bool,
+    /// Render a read-replica tree instead of a flat list
+    pub topology: bool,
+    /// Print engine/major version counts instead of a listing
+    pub stats: bool,
+    /// List instances by upcoming maintenance window
+    pub maintenance_calendar: bool,
+    /// iCalendar file to write the maintenance windows to
+    pub ical: Option<PathBuf>,
+    /// Collect and list blue/green deployments instead of instances
+    pub blue_green: bool,
+    /// Collect and list DB clusters instead of instances
+    pub clusters: bool,
+    /// Collect and list RDS Proxies after the instances
+    pub include_proxies: bool,
+    /// Collect and list global databases instead of instances
+    pub global_clusters: bool,
+    /// List Aurora cluster endpoints instead of instances
+    pub cluster_endpoints: bool,
+    /// List pending maintenance actions instead of instances
+    pub maintenance: bool,
+    /// Audit DB snapshots instead of listing instances
+    pub snapshots: bool,
+    /// Age past which snapshots are flagged as stale (`--older-than` under
+    /// `--snapshots`)
+    pub snapshot_older_than: Option<Duration>,
+    /// Age below which instances are skipped (`--older-than` otherwise)
+    pub older_than: Option<Duration>,
+    /// Age past which instances are skipped
+    pub newer_than: Option<Duration>,
+    /// Show the AGE column
+    pub with_age: bool,
+    /// Show ENGINE, VERSION, CLASS, STORAGE-GIB and AZ columns
+    pub with_specs: bool,
+    /// Show VPC-ID, VPC-NAME, VPC-CIDR and PLACEMENT columns
+    pub with_network: bool,
+    /// Only keep instances of this engine family
+    pub engine_family: Option<EngineFamily>,
+    /// Engines to keep (empty = every engine)
+    pub engines: Vec<String>,
+    /// Instance states to keep (empty = every state)
+    pub statuses: Vec<String>,
+    /// Only keep publicly accessible instances
+    pub public_only: bool,
+    /// Only keep instances without a Multi-AZ standby
+    pub single_az_only: bool,
+    /// Only keep instances without storage encryption
+    pub unencrypted_only: bool,
+    /// Only keep instances without Performance Insights
+    pub no_pi_only: bool,
+    /// Tags (key, value) an instance must all carry to be listed
+    pub tags: Vec<(String, String)>,
+    /// Show CLUSTER and CLUSTER-ROLE columns
+    pub with_cluster: bool,
+    /// Grouping of the instance listing
+    pub group_by: Option<GroupBy>,
+    /// DB parameters to resolve and show as columns
+    pub with_params: Vec<String>,
+    /// Storage headroom percentage below which instances are flagged
+    pub storage_headroom: Option<u8>,
+    /// Resolve and show engine version upgrade targets
+    pub upgrade_targets: bool,
+    /// Browse instances in the interactive TUI
+    pub tui: bool,
+    /// Pick one instance with the fuzzy finder
+    pub pick: bool,
+    /// Print only the picked identifier
+    pub quiet: bool,
+    /// Print Terraform import blocks instead of listing instances
+    pub emit_tf_imports: bool,
+    /// Resource address template for the import blocks
+    pub tf_address: String,
+    /// Print the versioned JSON document instead of a listing
+    pub json: bool,
+    /// Format picked with `--output` or `--json`; `None` leaves it to
+    /// [`resolve_output`] once stdout is known
+    pub output: Option<OutputFormat>,
+    /// Print the instances as delimited records with this field delimiter
+    /// (`--output csv` or `tsv`)
+    pub csv: Option<u8>,
+    /// Window of instances to print
+    pub page: Page,
+    /// Print the JSON Schema instead of scanning
+    pub schema: bool,
+    /// Alias identifying values in all output
+    pub redact: bool,
+    /// Fail the run when unencrypted storage is found
+    pub fail_on_unencrypted: bool,
+    /// Fail the run when single-AZ databases in this scope are found
+    pub require_multi_az: Option<MultiAzScope>,
+    /// Fail the run when databases keep backups for fewer days than this
+    pub min_backup_retention: Option<u32>,
+    /// Organizational Unit (or root) org mode is limited to, nested OUs
+    /// included
+    pub ou: Option<String>,
+    /// Organization accounts to scan or skip
+    pub account_filter: AccountFilter,
+    /// Shared config profile the base credentials come from (the SDK's
+    /// default chain when unset)
+    pub profile: Option<String>,
+    /// Refresh an expired SSO session with `aws sso login` and retry
+    pub sso_login: bool,
+    /// Region whose STS endpoint serves AssumeRole and GetCallerIdentity
+    /// (the scanned region's when unset)
+    pub sts_region: Option<String>,
+    /// External ID passed with every AssumeRole
+    pub external_id: Option<String>,
+    /// Session name of assumed roles
+    pub session_name: String,
+    /// Lifetime of assumed-role credentials (SDK default when unset)
+    pub session_duration: Option<Duration>,
+    /// Retries per AWS call (SDK default, and `retry::DEFAULT_RETRIES` for
+    /// throttled RDS and STS calls, when unset)
+    pub max_retries: Option<u32>,
+    /// Account/region pairs scanned at once (`DEFAULT_CONCURRENCY` when unset)
+    pub max_concurrency: Option<usize>,
+    /// AWS calls after which the scan is aborted
+    pub max_api_calls: Option<u64>,
+    /// Run time after which the scan is aborted
+    pub max_duration: Option<Duration>,
+    /// Connect timeout for AWS endpoints
+    pub connect_timeout: Option<Duration>,
+    /// Timeout for a whole AWS call including retries
+    pub operation_timeout: Option<Duration>,
+    /// JSON Lines file recording each finished account/region
+    pub manifest: Option<PathBuf>,
+    /// Reuse the results already in `manifest` instead of rescanning them
+    pub resume: bool,
+    /// Report per-account/region scan timings at the end of the run
+    pub timings: bool,
+    /// Report API call counts at the end of the run
+    pub api_stats: bool,
+    /// Print the scan plan instead of scanning
+    pub dry_run: bool,
+    /// Print the caller identity instead of scanning
+    pub whoami: bool,
+    /// Print the caller identity before scanning
+    pub verbose: bool,
 }
 
 impl TryFrom<Cli> for Config {
     type Error = eyre::Error;
 
     fn try_from(cli: Cli) -> Result<Self> {
+        Config::try_from_with(cli, &Settings::load()?)
+    }
+}
+
+impl Config {
+    /// Validate the arguments, expanding region groups from `settings`
+    /// instead of reading the settings file
+    pub fn try_from_with(cli: Cli, settings: &Settings) -> Result<Self> {
         // Validate regions
         if cli.regions.is_empty() {
             bail!("At least one region must be specified");
         }
+        let regions = settings.expand_regions(&cli.regions)?;
 
         // Validate role ARN format if provided
         for arn in &cli.role_arns {
@@ -44,53 +235,402 @@ impl TryFrom<Cli> for Config {
             }
         }
 
+        if let Some(ou) = cli.ou.as_deref().filter(|ou| !is_ou_id(ou)) {
+            bail!("Invalid --ou '{}'. Expected an OU ID like ou-ab12-cd34ef56 or a root ID like r-ab12", ou);
+        }
+        let trimmed = |entries: &[String]| -> Vec<String> {
+            entries.iter().map(|e| e.trim().to_owned()).filter(|e| !e.is_empty()).collect()
+        };
+        let account_filter = AccountFilter {
+            include: trimmed(&cli.accounts),
+            exclude: trimmed(&cli.exclude_accounts),
+        };
+        if !cli.accounts.is_empty() && account_filter.include.is_empty() {
+            bail!("--accounts needs at least one account ID or name");
+        }
+        if cli.profile.as_deref().is_some_and(|p| p.trim().is_empty()) {
+            bail!("--profile needs a profile name");
+        }
+        if let Some(region) = cli.sts_region.as_deref().filter(|r| !is_region_name(r)) {
+            bail!("Invalid --sts-region '{}'. Expected a region name like us-east-1", region);
+        }
+        if let Some(id) = cli.external_id.as_deref().filter(|id| !is_external_id(id)) {
+            bail!(
+                "Invalid --external-id '{}'. Expected 2 to 1224 letters, digits, underscores or any of +=,.@:/-",
+                id
+            );
+        }
+
+        let spend_days = match cli.with_spend.as_deref() {
+            Some(period) => {
+                let days = parse_duration(period)?.as_secs() / 86_400;
+                if days == 0 {
+                    bail!("--with-spend must cover at least one day, got '{}'", period);
+                }
+                Some(u32::try_from(days).map_err(|_| eyre!("--with-spend period '{}' is too long", period))?)
+            }
+            None => None,
+        };
+
+        let older_than = cli.older_than.as_deref().map(parse_duration).transpose()?;
+        let newer_than = cli.newer_than.as_deref().map(parse_duration).transpose()?;
+        let (snapshot_older_than, older_than) = if cli.snapshots {
+            (older_than, None)
+        } else {
+            (None, older_than)
+        };
+        let with_age = cli.with_age || older_than.is_some() || newer_than.is_some();
+
+        let timeout = |flag: &str, value: Option<&str>| -> Result<Option<Duration>> {
+            let Some(value) = value else { return Ok(None) };
+            let duration = parse_duration(value)?;
+            if duration.is_zero() {
+                bail!("{} must be greater than zero, got '{}'", flag, value);
+            }
+            Ok(Some(duration))
+        };
+        let connect_timeout = timeout("--connect-timeout", cli.connect_timeout.as_deref())?;
+        let session_duration = cli.session_duration.as_deref().map(parse_duration).transpose()?;
+        if session_duration.is_some_and(|d| !(MIN_SESSION..=MAX_SESSION).contains(&d)) {
+            bail!(
+                "--session-duration must be between 15m and 12h, got '{}'",
+                cli.session_duration.as_deref().unwrap_or_default()
+            );
+        }
+        let session_name = cli.session_name.unwrap_or_else(|| roles::DEFAULT_SESSION_NAME.to_owned());
+        if !is_session_name(&session_name) {
+            bail!(
+                "Invalid --session-name '{}'. Expected 2 to 64 letters, digits, underscores or any of +=,.@-",
+                session_name
+            );
+        }
+        let operation_timeout = timeout("--operation-timeout", cli.operation_timeout.as_deref())?;
+        let max_duration = timeout("--max-duration", cli.max_duration.as_deref())?;
+
+        let storage_headroom = cli.storage_headroom.as_deref().map(parse_percent).transpose()?;
+        let tags = cli.tags.iter().map(|t| parse_tag(t)).collect::<Result<Vec<_>>>()?;
+
+        if let Some(engine) = cli.engine.iter().find(|e| !engines::ENGINES.contains(&e.as_str())) {
+            bail!("Unknown engine '{}'. Expected one of: {}", engine, engines::ENGINES.join(", "));
+        }
+
+        let tf_address = cli
+            .tf_address
+            .unwrap_or_else(|| terraform::DEFAULT_ADDRESS_TEMPLATE.to_owned());
+        terraform::validate_template(&tf_address)?;
+
+        let role_map = match &cli.role_map {
+            Some(path) => RoleMap::load(path)?,
+            None => RoleMap::default(),
+        };
+
+        let mut account_ids = cli.account_ids;
+        if let Some(path) = &cli.accounts_file {
+            let text =
+                fs::read_to_string(path).wrap_err_with(|| format!("Cannot read accounts file {}", path.display()))?;
+            account_ids.extend(text.lines().map(str::to_owned));
+        }
+        let account_ids = parse_account_ids(&account_ids)?;
+
         let mode = if cli.use_org {
             ScanMode::Organization
         } else if !cli.role_arns.is_empty() {
             ScanMode::RoleArns(cli.role_arns)
+        } else if !account_ids.is_empty() {
+            ScanMode::Accounts(account_ids)
         } else {
             ScanMode::CurrentAccount
         };
 
+        let output = if cli.json { Some(OutputFormat::Json) } else { cli.output };
+
         Ok(Config {
-            regions: cli.regions,
+            regions,
+            all_regions: cli.all_regions,
             mode,
+            role_map,
+            account_details: cli.account_details,
+            account_names: cli.account_names,
+            spend_days,
+            arns: cli.arns,
+            topology: cli.topology,
+            stats: cli.stats,
+            maintenance_calendar: cli.maintenance_calendar,
+            ical: cli.ical,
+            blue_green: cli.blue_green,
+            clusters: cli.clusters,
+            include_proxies: cli.include_proxies,
+            global_clusters: cli.global_clusters,
+            cluster_endpoints: cli.cluster_endpoints,
+            maintenance: cli.maintenance,
+            snapshots: cli.snapshots,
+            snapshot_older_than,
+            older_than,
+            newer_than,
+            with_age,
+            with_specs: cli.with_specs,
+            with_network: cli.with_network,
+            engine_family: cli.engine_family,
+            engines: cli.engine,
+            statuses: cli.status.iter().map(|s| s.trim().to_lowercase()).collect(),
+            public_only: cli.public_only,
+            single_az_only: cli.single_az_only,
+            unencrypted_only: cli.unencrypted_only,
+            no_pi_only: cli.no_pi_only,
+            tags,
+            with_cluster: cli.with_cluster,
+            group_by: cli.group_by,
+            with_params: cli.with_params,
+            storage_headroom,
+            upgrade_targets: cli.upgrade_targets,
+            tui: cli.tui,
+            pick: cli.pick,
+            quiet: cli.quiet,
+            emit_tf_imports: cli.emit_tf_imports,
+            tf_address,
+            json: output == Some(OutputFormat::Json),
+            csv: match output {
+                Some(OutputFormat::Csv) => Some(b','),
+                Some(OutputFormat::Tsv) => Some(b'\t'),
+                _ => None,
+            },
+            output,
+            page: Page {
+                limit: cli.limit.map(|n| n as usize),
+                page: cli.page.unwrap_or(1) as usize,
+            },
+            schema: cli.schema,
+            redact: cli.redact,
+            fail_on_unencrypted: cli.fail_on_unencrypted,
+            require_multi_az: cli
+                .require_multi_az
+                .then(|| cli.multi_az_scope.as_deref().map(MultiAzScope::parse).unwrap_or_default()),
+            min_backup_retention: cli.min_backup_retention,
+            ou: cli.ou,
+            account_filter,
+            profile: cli.profile,
+            sso_login: cli.sso_login,
+            sts_region: cli.sts_region,
+            external_id: cli.external_id,
+            session_name,
+            session_duration,
+            max_retries: cli.max_retries,
+            max_concurrency: cli.max_concurrency.map(|n| n as usize),
+            max_api_calls: cli.max_api_calls,
+            max_duration,
+            connect_timeout,
+            operation_timeout,
+            manifest: cli.manifest,
+            resume: cli.resume,
+            timings: cli.timings,
+            api_stats: cli.api_stats,
+            dry_run: cli.dry_run,
+            whoami: cli.whoami,
+            verbose: cli.verbose,
         })
     }
+
+    /// Retries of a throttled AWS call: `--max-retries`, else
+    /// `retry::DEFAULT_RETRIES`
+    pub fn retries(&self) -> u32 {
+        self.max_retries.unwrap_or(retry::DEFAULT_RETRIES)
+    }
+
+    /// Whether the output shows Availability Zone IDs (the `--with-specs`
+    /// AZ-ID column, the JSON document and the TUI), the only reason to
+    /// call DescribeAvailabilityZones
+    pub fn wants_zone_ids(&self) -> bool {
+        self.with_specs || self.json || self.tui
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
             regions: vec!["us-east-1".to_string(), "us-west-2".to_string()],
+            all_regions: false,
             mode: ScanMode::CurrentAccount,
+            role_map: RoleMap::default(),
+            account_details: false,
+            account_names: false,
+            spend_days: None,
+            arns: false,
+            topology: false,
+            stats: false,
+            maintenance_calendar: false,
+            ical: None,
+            blue_green: false,
+            clusters: false,
+            include_proxies: false,
+            global_clusters: false,
+            cluster_endpoints: false,
+            maintenance: false,
+            snapshots: false,
+            snapshot_older_than: None,
+            older_than: None,
+            newer_than: None,
+            with_age: false,
+            with_specs: false,
+            with_network: false,
+            engine_family: None,
+            engines: vec![],
+            statuses: vec![],
+            public_only: false,
+            single_az_only: false,
+            unencrypted_only: false,
+            no_pi_only: false,
+            tags: vec![],
+            with_cluster: false,
+            group_by: None,
+            with_params: vec![],
+            storage_headroom: None,
+            upgrade_targets: false,
+            tui: false,
+            pick: false,
+            quiet: false,
+            emit_tf_imports: false,
+            tf_address: terraform::DEFAULT_ADDRESS_TEMPLATE.to_owned(),
+            json: false,
+            output: None,
+            csv: None,
+            page: Page::default(),
+            schema: false,
+            redact: false,
+            fail_on_unencrypted: false,
+            require_multi_az: None,
+            min_backup_retention: None,
+            ou: None,
+            account_filter: AccountFilter::default(),
+            profile: None,
+            sso_login: false,
+            sts_region: None,
+            external_id: None,
+            session_name: roles::DEFAULT_SESSION_NAME.to_owned(),
+            session_duration: None,
+            max_retries: None,
+            max_concurrency: None,
+            max_api_calls: None,
+            max_duration: None,
+            connect_timeout: None,
+            operation_timeout: None,
+            manifest: None,
+            resume: false,
+            timings: false,
+            api_stats: false,
+            dry_run: false,
+            whoami: false,
+            verbose: false,
         }
     }
 }
 
+/// Pick the output format: an explicit `--output` (or --json) always wins,
+/// otherwise a table on a terminal and tab-separated text when piped.
+pub fn resolve_output(explicit: Option<OutputFormat>, is_tty: bool) -> OutputFormat {
+    match explicit {
+        Some(output) => output,
+        None if is_tty => OutputFormat::Table,
+        None => OutputFormat::Text,
+    }
+}
+
+/// Shortest and longest lifetime STS grants assumed-role credentials
+const MIN_SESSION: Duration = Duration::from_secs(15 * 60);
+const MAX_SESSION: Duration = Duration::from_secs(12 * 3600);
+
+/// Whether `name` is a valid STS role session name: 2 to 64 letters,
+/// digits, underscores or any of `+=,.@-`
+pub fn is_session_name(name: &str) -> bool {
+    (2..=64).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || "_+=,.@-".contains(c))
+}
+
+/// Whether `id` is an Organizations OU ID (`ou-` root part, `-`, 8 to 32
+/// characters) or root ID (`r-` and 4 to 32 characters), lowercase
+/// letters and digits
+pub fn is_ou_id(id: &str) -> bool {
+    let part = |s: &str, len: std::ops::RangeInclusive<usize>| {
+        len.contains(&s.len()) && s.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    };
+    if let Some(root) = id.strip_prefix("r-") {
+        return part(root, 4..=32);
+    }
+    match id.strip_prefix("ou-").and_then(|rest| rest.split_once('-')) {
+        Some((root, ou)) => part(root, 4..=32) && part(ou, 8..=32),
+        None => false,
+    }
+}
+
+/// Whether `id` is a valid STS external ID: 2 to 1224 letters, digits,
+/// underscores or any of `+=,.@:/-`
+pub fn is_external_id(id: &str) -> bool {
+    (2..=1224).contains(&id.len()) && id.chars().all(|c| c.is_ascii_alphanumeric() || "_+=,.@:/-".contains(c))
+}
+
 /// Extract account ID from a role ARN
 pub fn extract_account_from_arn(arn: &str) -> Option<&str> {
     arn.split(':').nth(4)
 }
 
+/// Clean up a list of account IDs: strips `#` comments and blank entries,
+/// checks each is a 12-digit ID and drops duplicates (keeping order).
+pub fn parse_account_ids(entries: &[String]) -> Result<Vec<String>> {
+    let mut ids: Vec<String> = Vec::new();
+    for entry in entries {
+        let id = entry.split('#').next().unwrap_or_default().trim();
+        if id.is_empty() {
+            continue;
+        }
+        if id.len() != 12 || !id.chars().all(|c| c.is_ascii_digit()) {
+            bail!("Invalid account ID '{}'. Expected 12 digits", id);
+        }
+        if !ids.iter().any(|i| i == id) {
+            ids.push(id.to_owned());
+        }
+    }
+    Ok(ids)
+}
+
+/// Parse a percentage such as `20%` or `20` (0–100).
+pub fn parse_percent(s: &str) -> Result<u8> {
+    let trimmed = s.trim();
+    let pct: u8 = trimmed
+        .strip_suffix('%')
+        .unwrap_or(trimmed)
+        .parse()
+        .map_err(|_| eyre!("Invalid percentage '{}'. Expected e.g. 20%", s))?;
+    if pct > 100 {
+        bail!("Percentage must be between 0 and 100, got '{}'", s);
+    }
+    Ok(pct)
+}
+
+/// Parse a `--tag` filter: `KEY=VALUE`, where the value may be empty but
+/// the key may not.
+pub fn parse_tag(s: &str) -> Result<(String, String)> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_owned(), value.to_owned())),
+        _ => bail!("Invalid tag filter '{}'. Expected KEY=VALUE, e.g. team=payments", s),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use clap::Parser;
+
     fn cli_default() -> Cli {
-        Cli {
-            use_org: false,
-            role_arns: vec![],
-            regions: vec!["us-east-1".to_string(), "us-west-2".to_string()],
-        }
+        Cli::parse_from(["ls-rds"])
     }
 
     #[test]
     fn config_from_cli_defaults_to_current_account() {
         let cli = cli_default();
-        let config = Config::try_from(cli).unwrap();
+        let config = from_cli(cli).unwrap();
         assert_eq!(config.mode, ScanMode::CurrentAccount);
         assert_eq!(config.regions.len(), 2);
+        assert_eq!(config.session_name, "ls-rds");
+        assert_eq!(from_args(&["--session-name", "audit-2024"]).unwrap().session_name, "audit-2024");
     }
 
     #[test]
@@ -99,7 +639,7 @@ mod tests {
             use_org: true,
             ..cli_default()
         };
-        let config = Config::try_from(cli).unwrap();
+        let config = from_cli(cli).unwrap();
         assert_eq!(config.mode, ScanMode::Organization);
     }
 
@@ -109,7 +649,7 @@ mod tests {
             role_arns: vec!["arn:aws:iam::123456789012:role/TestRole".to_string()],
             ..cli_default()
         };
-        let config = Config::try_from(cli).unwrap();
+        let config = from_cli(cli).unwrap();
         match config.mode {
             ScanMode::RoleArns(arns) => {
                 assert_eq!(arns.len(), 1);
@@ -118,13 +658,76 @@ mod tests {
         }
     }
 
+    #[test]
+    fn config_from_cli_with_account_ids() {
+        let cli = Cli {
+            account_ids: vec!["111111111111".to_string(), "222222222222".to_string()],
+            ..cli_default()
+        };
+        let config = from_cli(cli).unwrap();
+        assert_eq!(
+            config.mode,
+            ScanMode::Accounts(vec!["111111111111".to_string(), "222222222222".to_string()])
+        );
+    }
+
+    #[test]
+    fn config_rejects_missing_accounts_file() {
+        let cli = Cli {
+            accounts_file: Some(PathBuf::from("/nonexistent/accounts.txt")),
+            ..cli_default()
+        };
+        let err = from_cli(cli).unwrap_err();
+        assert!(err.to_string().contains("accounts file"));
+    }
+
+    #[test]
+    fn parse_account_ids_skips_comments_and_duplicates() {
+        let entries: Vec<String> = ["# prod accounts", "111111111111", "", "222222222222  # payments", "111111111111"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(parse_account_ids(&entries).unwrap(), vec!["111111111111", "222222222222"]);
+        assert!(parse_account_ids(&["12345".to_string()]).is_err());
+    }
+
+    #[test]
+    fn config_parses_sdk_tuning() {
+        let cli = Cli {
+            max_retries: Some(5),
+            connect_timeout: Some("10s".to_string()),
+            operation_timeout: Some("2m".to_string()),
+            ..cli_default()
+        };
+        let config = from_cli(cli).unwrap();
+        assert_eq!(config.max_retries, Some(5));
+        assert_eq!(config.connect_timeout, Some(Duration::from_secs(10)));
+        assert_eq!(config.operation_timeout, Some(Duration::from_secs(120)));
+
+        let cli = Cli {
+            connect_timeout: Some("0s".to_string()),
+            ..cli_default()
+        };
+        assert!(from_cli(cli).unwrap_err().to_string().contains("--connect-timeout"));
+    }
+
+    #[test]
+    fn config_rejects_unknown_region() {
+        let cli = Cli {
+            regions: vec!["nowhere".to_string()],
+            ..cli_default()
+        };
+        let err = from_cli(cli).unwrap_err();
+        assert!(err.to_string().contains("nowhere"));
+    }
+
     #[test]
     fn config_rejects_empty_regions() {
         let cli = Cli {
             regions: vec![],
             ..cli_default()
         };
-        let result = Config::try_from(cli);
+        let result = from_cli(cli);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("region"));
     }
@@ -135,7 +738,7 @@ mod tests {
             role_arns: vec!["invalid-arn".to_string()],
             ..cli_default()
         };
-        let result = Config::try_from(cli);
+        let result = from_cli(cli);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Invalid role ARN"));
     }
@@ -146,7 +749,7 @@ mod tests {
             role_arns: vec!["arn:aws:iam::123456789012:user/TestUser".to_string()],
             ..cli_default()
         };
-        let result = Config::try_from(cli);
+        let result = from_cli(cli);
         assert!(result.is_err());
     }
 
@@ -159,7 +762,7 @@ mod tests {
             ],
             ..cli_default()
         };
-        let config = Config::try_from(cli).unwrap();
+        let config = from_cli(cli).unwrap();
         match config.mode {
             ScanMode::RoleArns(arns) => {
                 assert_eq!(arns.len(), 2);
@@ -199,4 +802,401 @@ mod tests {
         assert_eq!(ScanMode::Organization, ScanMode::Organization);
         assert_ne!(ScanMode::CurrentAccount, ScanMode::Organization);
     }
+
+    #[test]
+    fn config_rejects_spend_window_past_u32_days() {
+        let cli = Cli {
+            with_spend: Some("5000000000d".to_string()),
+            ..cli_default()
+        };
+        assert!(from_cli(cli).is_err());
+    }
+
+    #[test]
+    fn config_carries_cluster_membership_flags() {
+        let config = from_cli(Cli {
+            with_cluster: true,
+            group_by: Some(GroupBy::Cluster),
+            ..cli_default()
+        })
+        .unwrap();
+        assert!(config.with_cluster);
+        assert_eq!(config.group_by, Some(GroupBy::Cluster));
+        assert_eq!(Config::default().group_by, None);
+    }
+
+    #[test]
+    fn config_converts_with_spend_to_days() {
+        let cli = Cli {
+            with_spend: Some("2w".to_string()),
+            ..cli_default()
+        };
+        let config = from_cli(cli).unwrap();
+        assert_eq!(config.spend_days, Some(14));
+    }
+
+    #[test]
+    fn config_instance_age_filters_imply_age_column() {
+        let config = from_cli(Cli {
+            older_than: Some("365d".to_string()),
+            newer_than: Some("2w".to_string()),
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!(config.older_than, Some(Duration::from_secs(365 * 86_400)));
+        assert_eq!(config.newer_than, Some(Duration::from_secs(14 * 86_400)));
+        assert!(config.with_age);
+        assert!(!from_cli(cli_default()).unwrap().with_age);
+        assert!(!Config::default().with_age);
+    }
+
+    #[test]
+    fn config_rejects_sub_day_spend_window() {
+        let cli = Cli {
+            with_spend: Some("12h".to_string()),
+            ..cli_default()
+        };
+        assert!(from_cli(cli).is_err());
+    }
+
+    #[test]
+    fn parse_percent_accepts_with_and_without_sign() {
+        assert_eq!(parse_percent("20%").unwrap(), 20);
+        assert_eq!(parse_percent("15").unwrap(), 15);
+        assert!(parse_percent("120%").is_err());
+        assert!(parse_percent("lots").is_err());
+    }
+
+    #[test]
+    fn config_parses_storage_headroom() {
+        let cli = Cli {
+            storage_headroom: Some("25%".to_string()),
+            ..cli_default()
+        };
+        let config = from_cli(cli).unwrap();
+        assert_eq!(config.storage_headroom, Some(25));
+    }
+
+    #[test]
+    fn config_parses_older_than() {
+        let cli = Cli {
+            snapshots: true,
+            older_than: Some("12w".to_string()),
+            ..cli_default()
+        };
+        let config = from_cli(cli).unwrap();
+        assert!(config.snapshots);
+        assert_eq!(config.snapshot_older_than, Some(Duration::from_secs(84 * 86_400)));
+        assert_eq!(config.older_than, None);
+        assert!(!config.with_age);
+        assert_eq!(Config::default().snapshot_older_than, None);
+    }
+
+    #[test]
+    fn config_rejects_missing_role_map() {
+        let cli = Cli {
+            use_org: true,
+            role_map: Some(PathBuf::from("/nonexistent/roles.yml")),
+            ..cli_default()
+        };
+        let err = from_cli(cli).unwrap_err();
+        assert!(err.to_string().contains("role map"));
+    }
+
+    /// Config from `cli` with no settings file, whatever the developer has
+    fn from_cli(cli: Cli) -> Result<Config> {
+        Config::try_from_with(cli, &Settings::default())
+    }
+
+    fn from_args(args: &[&str]) -> Result<Config> {
+        from_cli(Cli::parse_from(std::iter::once("ls-rds").chain(args.iter().copied())))
+    }
+
+    #[test]
+    fn config_expands_region_groups_from_settings() {
+        let settings = Settings::parse("region-groups:\n  us: [us-east-1, us-west-2]\n").unwrap();
+        let cli = Cli::parse_from(["ls-rds", "--regions", "us,eu-west-1"]);
+        let config = Config::try_from_with(cli, &settings).unwrap();
+        assert_eq!(config.regions, vec!["us-east-1", "us-west-2", "eu-west-1"]);
+        let cli = Cli::parse_from(["ls-rds", "--regions", "us"]);
+        assert!(from_cli(cli).is_err());
+    }
+
+    #[test]
+    fn config_tf_address_defaults_and_validates() {
+        let config = from_cli(cli_default()).unwrap();
+        assert_eq!(config.tf_address, "{type}.{name}");
+
+        let cli = Cli {
+            emit_tf_imports: true,
+            tf_address: Some("module.rds_{account}.{type}.{name}".to_string()),
+            ..cli_default()
+        };
+        let config = from_cli(cli).unwrap();
+        assert!(config.emit_tf_imports);
+        assert_eq!(config.tf_address, "module.rds_{account}.{type}.{name}");
+
+        let cli = Cli {
+            emit_tf_imports: true,
+            tf_address: Some("{type}.{nme}".to_string()),
+            ..cli_default()
+        };
+        assert!(from_cli(cli).is_err());
+    }
+
+    #[test]
+    fn resolve_output_prefers_explicit_then_terminal() {
+        assert_eq!(resolve_output(None, true), OutputFormat::Table);
+        assert_eq!(resolve_output(None, false), OutputFormat::Text);
+        assert_eq!(resolve_output(Some(OutputFormat::Text), true), OutputFormat::Text);
+        assert_eq!(resolve_output(Some(OutputFormat::Table), false), OutputFormat::Table);
+        assert_eq!(resolve_output(Some(OutputFormat::Json), true), OutputFormat::Json);
+    }
+
+    #[test]
+    fn account_filter_matches_ids_and_names() {
+        let account = |id: &str, name: &str| AccountInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let config = from_args(&["--use-org", "--accounts", "111111111111, Prod-*", "--exclude-accounts", "*-legacy"])
+            .unwrap();
+        let filter = &config.account_filter;
+        assert!(filter.selects(&account("111111111111", "sandbox")));
+        assert!(filter.selects(&account("222222222222", "prod-payments")));
+        assert!(!filter.selects(&account("333333333333", "prod-legacy")));
+        assert!(!filter.selects(&account("444444444444", "staging")));
+        assert!(AccountFilter::default().selects(&account("444444444444", "staging")));
+        assert!(from_args(&["--use-org", "--accounts", " "]).is_err());
+    }
+
+    #[test]
+    fn config_parses_tag_filters() {
+        let config = from_args(&["--tag", "team=payments", "--tag", "env="]).unwrap();
+        assert_eq!(
+            config.tags,
+            vec![
+                ("team".to_string(), "payments".to_string()),
+                ("env".to_string(), String::new()),
+            ]
+        );
+        assert!(Config::default().tags.is_empty());
+    }
+
+    #[test]
+    fn config_parses_engine_filter() {
+        let config = from_args(&["--engine", "postgres,mysql,aurora-postgresql"]).unwrap();
+        assert_eq!(config.engines, vec!["postgres", "mysql", "aurora-postgresql"]);
+        assert!(Config::default().engines.is_empty());
+    }
+
+    #[test]
+    fn config_normalizes_status_filter() {
+        let config = from_args(&["--status", "Stopped, available"]).unwrap();
+        assert_eq!(config.statuses, vec!["stopped", "available"]);
+        assert!(Config::default().statuses.is_empty());
+    }
+
+    #[test]
+    fn config_carries_fail_on_unencrypted() {
+        assert!(from_args(&["--fail-on-unencrypted"]).unwrap().fail_on_unencrypted);
+        assert!(!from_cli(cli_default()).unwrap().fail_on_unencrypted);
+        assert!(!Config::default().fail_on_unencrypted);
+    }
+
+    #[test]
+    fn config_keeps_explicit_output_only() {
+        assert_eq!(from_cli(cli_default()).unwrap().output, None);
+        assert_eq!(from_args(&["--output", "table"]).unwrap().output, Some(OutputFormat::Table));
+        assert_eq!(from_args(&["--json"]).unwrap().output, Some(OutputFormat::Json));
+    }
+
+    #[test]
+    fn config_json_follows_json_flag_and_output() {
+        assert!(from_args(&["--json"]).unwrap().json);
+        assert!(from_args(&["--output", "json"]).unwrap().json);
+        assert!(!from_cli(cli_default()).unwrap().json);
+        assert!(from_args(&["--schema"]).unwrap().schema);
+    }
+
+    #[test]
+    fn config_output_tsv_sets_tab_delimiter() {
+        assert_eq!(from_args(&["--output", "tsv"]).unwrap().csv, Some(b'\t'));
+        assert_eq!(from_args(&["--output", "csv"]).unwrap().csv, Some(b','));
+        assert_eq!(from_cli(cli_default()).unwrap().csv, None);
+    }
+
+    #[test]
+    fn config_carries_limit() {
+        assert_eq!(from_args(&["--limit", "10"]).unwrap().page.limit, Some(10));
+        assert_eq!(Config::default().page.limit, None);
+    }
+
+    #[test]
+    fn config_carries_with_specs() {
+        assert!(from_args(&["--with-specs"]).unwrap().with_specs);
+        assert!(!Config::default().with_specs);
+    }
+
+    #[test]
+    fn config_carries_upgrade_targets() {
+        assert!(from_args(&["--upgrade-targets"]).unwrap().upgrade_targets);
+        assert!(!Config::default().upgrade_targets);
+    }
+
+    #[test]
+    fn config_parses_multi_az_scope() {
+        let config = from_args(&["--require-multi-az", "--multi-az-scope", "prod-*"]).unwrap();
+        assert_eq!(config.require_multi_az, Some(MultiAzScope::Name("prod-*".to_string())));
+        assert_eq!(Config::default().require_multi_az, None);
+    }
+
+    #[test]
+    fn config_rejects_malformed_tag_filters() {
+        assert!(from_args(&["--tag", "payments"]).unwrap_err().to_string().contains("KEY=VALUE"));
+        assert!(from_args(&["--tag", "=payments"]).unwrap_err().to_string().contains("KEY=VALUE"));
+    }
+
+    #[test]
+    fn config_rejects_unknown_engine() {
+        let err = from_args(&["--engine", "postgres,postgresql"]).unwrap_err();
+        assert!(err.to_string().contains("Unknown engine 'postgresql'"));
+    }
+
+    #[test]
+    fn config_carries_sts_region() {
+        let config = from_args(&["--sts-region", "eu-west-1"]).unwrap();
+        assert_eq!(config.sts_region.as_deref(), Some("eu-west-1"));
+        assert!(from_args(&["--sts-region", "global"]).unwrap_err().to_string().contains("--sts-region 'global'"));
+    }
+
+    #[test]
+    fn config_carries_resume() {
+        assert!(from_args(&["--manifest", "scan.jsonl", "--resume"]).unwrap().resume);
+        assert!(!Config::default().resume);
+    }
+
+    #[test]
+    fn config_carries_dry_run() {
+        assert!(from_args(&["--dry-run"]).unwrap().dry_run);
+        assert!(!Config::default().dry_run);
+    }
+
+    #[test]
+    fn config_carries_public_only() {
+        assert!(from_args(&["--public-only"]).unwrap().public_only);
+        assert!(!Config::default().public_only);
+    }
+
+    #[test]
+    fn config_carries_stats() {
+        assert!(from_args(&["--stats"]).unwrap().stats);
+        assert!(!Config::default().stats);
+    }
+
+    #[test]
+    fn config_carries_single_az_only() {
+        assert!(from_args(&["--single-az-only"]).unwrap().single_az_only);
+        assert!(!Config::default().single_az_only);
+    }
+
+    #[test]
+    fn config_validates_external_id() {
+        let config = from_args(&["--external-id", "ls-rds-audit"]).unwrap();
+        assert_eq!(config.external_id.as_deref(), Some("ls-rds-audit"));
+        assert!(from_args(&["--external-id", "x"]).unwrap_err().to_string().contains("--external-id 'x'"));
+        let err = from_args(&["--external-id", "has space"]).unwrap_err();
+        assert!(err.to_string().contains("--external-id 'has space'"));
+    }
+
+    #[test]
+    fn config_validates_session_name_and_duration() {
+        let config = from_args(&["--session-duration", "4h"]).unwrap();
+        assert_eq!(config.session_duration, Some(Duration::from_secs(4 * 3_600)));
+        let err = from_args(&["--session-name", "audit/2024"]).unwrap_err();
+        assert!(err.to_string().contains("--session-name 'audit/2024'"));
+        for too_short_or_long in ["10m", "1d"] {
+            let err = from_args(&["--session-duration", too_short_or_long]).unwrap_err();
+            assert!(err.to_string().contains("between 15m and 12h"));
+        }
+    }
+
+    #[test]
+    fn config_carries_with_network() {
+        assert!(from_args(&["--with-network"]).unwrap().with_network);
+        assert!(!Config::default().with_network);
+    }
+
+    #[test]
+    fn config_carries_max_concurrency() {
+        assert_eq!(from_args(&["--max-concurrency", "16"]).unwrap().max_concurrency, Some(16));
+        assert_eq!(Config::default().max_concurrency, None);
+    }
+
+    #[test]
+    fn config_parses_scan_budgets() {
+        let config = from_args(&["--max-api-calls", "5000", "--max-duration", "20m"]).unwrap();
+        assert_eq!(config.max_api_calls, Some(5000));
+        assert_eq!(config.max_duration, Some(Duration::from_secs(1_200)));
+        assert!(from_args(&["--max-duration", "0s"]).unwrap_err().to_string().contains("--max-duration"));
+    }
+
+    #[test]
+    fn config_validates_ou() {
+        let config = from_args(&["--use-org", "--ou", "ou-ab12-cd34ef56"]).unwrap();
+        assert_eq!(config.ou.as_deref(), Some("ou-ab12-cd34ef56"));
+        for bad in ["production", "ou-AB12-cd34ef56"] {
+            let err = from_args(&["--use-org", "--ou", bad]).unwrap_err();
+            assert!(err.to_string().contains(&format!("--ou '{}'", bad)));
+        }
+    }
+
+    #[test]
+    fn config_carries_all_regions() {
+        assert!(from_args(&["--all-regions"]).unwrap().all_regions);
+        assert!(!Config::default().all_regions);
+    }
+
+    #[test]
+    fn config_validates_profile() {
+        assert_eq!(from_args(&["--profile", "audit"]).unwrap().profile.as_deref(), Some("audit"));
+        assert!(from_args(&["--profile", " "]).unwrap_err().to_string().contains("--profile"));
+    }
+
+    #[test]
+    fn config_carries_sso_login() {
+        assert!(from_args(&["--sso-login"]).unwrap().sso_login);
+        assert!(!Config::default().sso_login);
+    }
+
+    #[test]
+    fn config_carries_account_names() {
+        assert!(from_args(&["--account-names"]).unwrap().account_names);
+        assert!(!Config::default().account_names);
+    }
+
+    #[test]
+    fn config_carries_unencrypted_only() {
+        assert!(from_args(&["--unencrypted-only"]).unwrap().unencrypted_only);
+        assert!(!Config::default().unencrypted_only);
+    }
+
+    #[test]
+    fn config_carries_min_backup_retention() {
+        assert_eq!(from_args(&["--min-backup-retention", "14"]).unwrap().min_backup_retention, Some(14));
+        assert_eq!(Config::default().min_backup_retention, None);
+    }
+
+    #[test]
+    fn config_carries_maintenance() {
+        assert!(from_args(&["--maintenance"]).unwrap().maintenance);
+        assert!(!Config::default().maintenance);
+    }
+
+    #[test]
+    fn config_carries_no_pi_only() {
+        assert!(from_args(&["--no-pi-only"]).unwrap().no_pi_only);
+        assert!(!Config::default().no_pi_only);
+    }
 }
@@ -0,0 +1,122 @@
+//! Engine families and editions for ls-rds
+//!
+//! Classifies RDS engine names so commercially licensed instances (Oracle,
+//! SQL Server, Db2) can be filtered and reported for license true-ups.
+
+use crate::cli::EngineFamily;
+
+/// Engine names DescribeDBInstances reports, accepted by `--engine`
+pub const ENGINES: &[&str] = &[
+    "aurora-mysql",
+    "aurora-postgresql",
+    "mysql",
+    "mariadb",
+    "postgres",
+    "oracle-ee",
+    "oracle-ee-cdb",
+    "oracle-se2",
+    "oracle-se2-cdb",
+    "custom-oracle-ee",
+    "custom-oracle-ee-cdb",
+    "custom-oracle-se2",
+    "custom-oracle-se2-cdb",
+    "sqlserver-ee",
+    "sqlserver-se",
+    "sqlserver-ex",
+    "sqlserver-web",
+    "custom-sqlserver-ee",
+    "custom-sqlserver-se",
+    "custom-sqlserver-web",
+    "db2-ae",
+    "db2-se",
+    "docdb",
+    "neptune",
+];
+
+/// Whether an engine is commercially licensed or open source
+pub fn engine_family(engine: &str) -> EngineFamily {
+    let engine = engine.strip_prefix("custom-").unwrap_or(engine);
+    if ["oracle", "sqlserver", "db2"].iter().any(|p| engine.starts_with(p)) {
+        EngineFamily::Commercial
+    } else {
+        EngineFamily::OpenSource
+    }
+}
+
+/// Edition of a commercial engine, from the suffix of its engine name
+/// (e.g. `sqlserver-se` → `Standard`)
+pub fn edition(engine: &str) -> Option<&'static str> {
+    let engine = engine.strip_prefix("custom-").unwrap_or(engine);
+    let edition = match engine.trim_end_matches("-cdb") {
+        "oracle-ee" | "sqlserver-ee" => "Enterprise",
+        "oracle-se2" => "Standard Edition 2",
+        "oracle-se1" => "Standard Edition One",
+        "oracle-se" | "sqlserver-se" | "db2-se" => "Standard",
+        "sqlserver-ex" => "Express",
+        "sqlserver-web" => "Web",
+        "db2-ae" => "Advanced",
+        _ => return None,
+    };
+    Some(edition)
+}
+
+/// Major version of an engine version, the part an upgrade campaign moves:
+/// the first number for PostgreSQL 10+, Oracle and Db2 (`14.9` → `14`),
+/// the first two for MySQL, MariaDB, SQL Server and PostgreSQL before 10
+/// (`8.0.35` → `8.0`, `5.7.mysql_aurora.2.11.2` → `5.7`, `9.6.24` → `9.6`)
+pub fn major_version(engine: &str, version: &str) -> String {
+    let mut parts = version.split('.');
+    let first = parts.next().unwrap_or_default();
+    let single = match engine_family(engine) {
+        EngineFamily::Commercial => !engine.contains("sqlserver"),
+        EngineFamily::OpenSource => {
+            engine.contains("postgres") && first.parse::<u32>().is_ok_and(|major| major >= 10)
+        }
+    };
+    match parts.next() {
+        Some(second) if !single => format!("{}.{}", first, second),
+        _ => first.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn engine_family_classifies_commercial_engines() {
+        for engine in ["oracle-ee", "oracle-se2-cdb", "sqlserver-web", "custom-sqlserver-ee", "db2-ae"] {
+            assert_eq!(engine_family(engine), EngineFamily::Commercial, "{}", engine);
+        }
+        for engine in ["postgres", "mysql", "aurora-postgresql", "mariadb"] {
+            assert_eq!(engine_family(engine), EngineFamily::OpenSource, "{}", engine);
+        }
+    }
+
+    #[test]
+    fn edition_from_engine_suffix() {
+        assert_eq!(edition("oracle-ee-cdb"), Some("Enterprise"));
+        assert_eq!(edition("oracle-se2"), Some("Standard Edition 2"));
+        assert_eq!(edition("custom-sqlserver-se"), Some("Standard"));
+        assert_eq!(edition("sqlserver-ex"), Some("Express"));
+        assert_eq!(edition("postgres"), None);
+    }
+
+    #[test]
+    fn major_version_per_engine() {
+        let cases = [
+            ("postgres", "14.9", "14"),
+            ("aurora-postgresql", "15.4", "15"),
+            ("postgres", "9.6.24", "9.6"),
+            ("mysql", "8.0.35", "8.0"),
+            ("aurora-mysql", "5.7.mysql_aurora.2.11.2", "5.7"),
+            ("mariadb", "10.6.14", "10.6"),
+            ("oracle-ee", "19.0.0.0.ru-2023-10.rur-2023-10.r1", "19"),
+            ("sqlserver-se", "15.00.4316.3.v1", "15.00"),
+            ("postgres", "", ""),
+        ];
+        for (engine, version, major) in cases {
+            assert_eq!(major_version(engine, version), major, "{} {}", engine, version);
+        }
+    }
+}
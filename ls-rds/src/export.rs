@@ -0,0 +1,96 @@
+//! Spreadsheet export for ls-rds
+//!
+//! `--output csv` (or `tsv`) prints the instance listing as delimited
+//! records for spreadsheets and compliance reports: a header row, then one
+//! record per instance with the account, role, region and instance ID
+//! followed by the same optional columns as the table.  Fields are quoted
+//! by the csv crate, so commas and quotes in names and tags survive.
+
+use crate::{Config, RdsInstance, ScanResult, optional_fields, optional_headers};
+use eyre::Result;
+
+/// Render `instances` as delimited records with a header row
+pub fn to_csv(instances: &[RdsInstance], config: &Config, result: &ScanResult, delimiter: u8) -> Result<String> {
+    let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(Vec::new());
+    let mut header = vec![
+        "ACCOUNT".to_owned(),
+        "ROLE".to_owned(),
+        "REGION".to_owned(),
+        "INSTANCE-ID".to_owned(),
+    ];
+    header.extend(optional_headers(config));
+    writer.write_record(&header)?;
+    for inst in instances {
+        let mut record = vec![
+            inst.account_id.clone(),
+            inst.role_arn.clone().unwrap_or_default(),
+            inst.region.clone(),
+            inst.instance_id.clone(),
+        ];
+        record.extend(optional_fields(inst, config, result));
+        writer.write_record(&record)?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountInfo;
+    use std::collections::BTreeMap;
+
+    fn instance(instance_id: &str) -> RdsInstance {
+        RdsInstance {
+            account_id: "111111111111".to_string(),
+            region: "us-east-1".to_string(),
+            instance_id: instance_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn to_csv_quotes_fields_and_follows_optional_columns() {
+        let mut inst = instance("orders,primary");
+        inst.params = BTreeMap::from([("max_connections".to_string(), "say \"100\"".to_string())]);
+        let config = Config {
+            with_params: vec!["max_connections".to_string()],
+            ..Config::default()
+        };
+        let out = to_csv(&[inst], &config, &ScanResult::default(), b',').unwrap();
+        assert_eq!(
+            out,
+            "ACCOUNT,ROLE,REGION,INSTANCE-ID,max_connections\n\
+             111111111111,,us-east-1,\"orders,primary\",\"say \"\"100\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn to_csv_names_the_account_detail_columns() {
+        let mut result = ScanResult::default();
+        result.accounts.insert(
+            "111111111111".to_string(),
+            AccountInfo {
+                id: "111111111111".to_string(),
+                email: "owner@example.com".to_string(),
+                status: "ACTIVE".to_string(),
+                ..Default::default()
+            },
+        );
+        let config = Config {
+            account_details: true,
+            ..Config::default()
+        };
+        let out = to_csv(&[instance("orders")], &config, &result, b',').unwrap();
+        assert_eq!(
+            out,
+            "ACCOUNT,ROLE,REGION,INSTANCE-ID,ACCOUNT-EMAIL,ACCOUNT-STATUS\n\
+             111111111111,,us-east-1,orders,owner@example.com,ACTIVE\n"
+        );
+    }
+
+    #[test]
+    fn to_csv_uses_the_delimiter() {
+        let out = to_csv(&[instance("orders")], &Config::default(), &ScanResult::default(), b'\t').unwrap();
+        assert_eq!(out, "ACCOUNT\tROLE\tREGION\tINSTANCE-ID\n111111111111\t\tus-east-1\torders\n");
+    }
+}
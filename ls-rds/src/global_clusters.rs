@@ -0,0 +1,172 @@
+//! Aurora global databases for ls-rds
+//!
+//! `--global-clusters` lists each global cluster with its primary region and
+//! secondary regions.  DescribeGlobalClusters returns the same global
+//! clusters from every region of an account, so they are de-duplicated by
+//! ARN once all regions have been scanned.
+
+use crate::api_stats::ApiStats;
+use crate::retry;
+use aws_sdk_rds as rds;
+use aws_types::region::Region;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// One regional cluster of a global database
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GlobalMember {
+    pub cluster_arn: String,
+    pub region: String,
+    /// The primary (writer) cluster
+    pub writer: bool,
+}
+
+/// An Aurora global database
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GlobalCluster {
+    pub account_id: String,
+    pub identifier: String,
+    pub arn: String,
+    pub engine: String,
+    pub engine_version: String,
+    pub status: String,
+    pub members: Vec<GlobalMember>,
+}
+
+impl GlobalCluster {
+    /// Region of the primary cluster
+    pub fn primary_region(&self) -> Option<&str> {
+        self.members.iter().find(|m| m.writer).map(|m| m.region.as_str())
+    }
+
+    /// Regions of the secondary clusters, sorted
+    pub fn secondary_regions(&self) -> Vec<&str> {
+        let mut regions: Vec<&str> = self.members.iter().filter(|m| !m.writer).map(|m| m.region.as_str()).collect();
+        regions.sort();
+        regions
+    }
+}
+
+/// Region of a regional resource ARN (`arn:aws:rds:<region>:…`)
+fn arn_region(arn: &str) -> &str {
+    arn.split(':').nth(3).unwrap_or_default()
+}
+
+/// List the global clusters visible from one region
+pub async fn list_global_clusters(
+    client: &rds::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    retries: u32,
+) -> Result<Vec<GlobalCluster>> {
+    let mut globals = Vec::new();
+    let mut marker: Option<String> = None;
+    loop {
+        let page = retry::with_retries(retries, || {
+            stats.record("rds", "DescribeGlobalClusters", region.as_ref(), account_id);
+            client.describe_global_clusters().set_marker(marker.clone()).send()
+        })
+        .await?;
+        for g in page.global_clusters() {
+            globals.push(GlobalCluster {
+                account_id: account_id.to_owned(),
+                identifier: g.global_cluster_identifier().unwrap_or_default().to_owned(),
+                arn: g.global_cluster_arn().unwrap_or_default().to_owned(),
+                engine: g.engine().unwrap_or_default().to_owned(),
+                engine_version: g.engine_version().unwrap_or_default().to_owned(),
+                status: g.status().unwrap_or_default().to_owned(),
+                members: g
+                    .global_cluster_members()
+                    .iter()
+                    .filter_map(|m| {
+                        let arn = m.db_cluster_arn()?;
+                        Some(GlobalMember {
+                            cluster_arn: arn.to_owned(),
+                            region: arn_region(arn).to_owned(),
+                            writer: m.is_writer().unwrap_or(false),
+                        })
+                    })
+                    .collect(),
+            });
+        }
+        match page.marker() {
+            Some(next) => marker = Some(next.to_owned()),
+            None => break,
+        }
+    }
+    Ok(globals)
+}
+
+/// Keep the first of each global cluster seen from several regions
+pub fn dedup_global_clusters(globals: &mut Vec<GlobalCluster>) {
+    let mut seen = BTreeSet::new();
+    globals.retain(|g| seen.insert((g.account_id.clone(), g.arn.clone())));
+}
+
+/// Format a global cluster: identifier, engine, status, primary region and
+/// secondary regions
+pub fn format_global_cluster(g: &GlobalCluster) -> String {
+    let secondaries = g.secondary_regions();
+    format!(
+        "{}\t{} {}\t{}\tprimary={}\tsecondary={}",
+        g.identifier,
+        g.engine,
+        g.engine_version,
+        g.status,
+        g.primary_region().unwrap_or("-"),
+        if secondaries.is_empty() { "-".to_owned() } else { secondaries.join(",") }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn global(account: &str, members: &[(&str, bool)]) -> GlobalCluster {
+        GlobalCluster {
+            account_id: account.to_string(),
+            identifier: "orders-global".to_string(),
+            arn: "arn:aws:rds::111111111111:global-cluster:orders-global".to_string(),
+            engine: "aurora-postgresql".to_string(),
+            engine_version: "15.4".to_string(),
+            status: "available".to_string(),
+            members: members
+                .iter()
+                .map(|(region, writer)| GlobalMember {
+                    cluster_arn: format!("arn:aws:rds:{}:111111111111:cluster:orders", region),
+                    region: region.to_string(),
+                    writer: *writer,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn arn_region_reads_fourth_field() {
+        assert_eq!(arn_region("arn:aws:rds:eu-west-1:111111111111:cluster:orders"), "eu-west-1");
+        assert_eq!(arn_region("orders"), "");
+    }
+
+    #[test]
+    fn dedup_keeps_one_per_account_and_arn() {
+        let mut globals = vec![
+            global("111111111111", &[("us-east-1", true)]),
+            global("111111111111", &[("us-east-1", true)]),
+            global("222222222222", &[("us-east-1", true)]),
+        ];
+        dedup_global_clusters(&mut globals);
+        assert_eq!(globals.len(), 2);
+    }
+
+    #[test]
+    fn format_global_cluster_lists_primary_and_secondaries() {
+        let g = global("111111111111", &[("us-west-2", false), ("us-east-1", true), ("eu-west-1", false)]);
+        assert_eq!(
+            format_global_cluster(&g),
+            "orders-global\taurora-postgresql 15.4\tavailable\tprimary=us-east-1\tsecondary=eu-west-1,us-west-2"
+        );
+        assert!(format_global_cluster(&global("111111111111", &[])).ends_with("primary=-\tsecondary=-"));
+    }
+}
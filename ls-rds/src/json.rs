@@ -0,0 +1,232 @@
+//! Versioned JSON output for ls-rds
+//!
+//! `--json` (or `--output json`) prints the scan as one document following
+//! the schema in `schema/ls-rds.schema.json` (printed by `--schema`).  The
+//! document uses its own structs rather than `RdsInstance` so internal
+//! fields can change freely; adding a field keeps `SCHEMA_VERSION`, removing
+//! or changing one bumps it.
+
+use crate::{RdsCluster, RdsInstance, RdsProxy, ScanResult, VpcPlacement};
+use eyre::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Version of the JSON output, embedded as `schema_version`
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema document describing the output
+pub const SCHEMA: &str = include_str!("../schema/ls-rds.schema.json");
+
+#[derive(Debug, Serialize)]
+pub struct Document<'a> {
+    pub schema_version: u32,
+    pub tool: &'static str,
+    pub instances: Vec<Instance<'a>>,
+    /// Only filled with `--clusters`
+    pub clusters: Vec<Cluster<'a>>,
+    /// Only filled with `--include-proxies`
+    pub proxies: Vec<Proxy<'a>>,
+    pub warnings: &'a [String],
+}
+
+#[derive(Debug, Serialize)]
+pub struct Instance<'a> {
+    pub account_id: &'a str,
+    pub region: &'a str,
+    pub instance_id: &'a str,
+    pub arn: &'a str,
+    pub engine: &'a str,
+    pub engine_version: &'a str,
+    pub instance_class: &'a str,
+    pub availability_zone: Option<&'a str>,
+    pub availability_zone_id: Option<&'a str>,
+    pub secondary_availability_zone: Option<&'a str>,
+    pub secondary_availability_zone_id: Option<&'a str>,
+    pub multi_az: bool,
+    pub vpc_id: Option<&'a str>,
+    pub subnet_ids: &'a [String],
+    /// Only filled with `--with-network`
+    pub network: Option<&'a VpcPlacement>,
+    pub status: &'a str,
+    pub endpoint: Option<&'a str>,
+    pub cluster_id: Option<&'a str>,
+    pub cluster_role: Option<&'a str>,
+    pub replica_source: Option<&'a str>,
+    pub replicas: &'a [String],
+    pub storage_encrypted: bool,
+    pub kms_key_id: Option<&'a str>,
+    pub publicly_accessible: bool,
+    pub performance_insights_enabled: bool,
+    pub allocated_storage: Option<u32>,
+    pub backup_retention_days: Option<i32>,
+    /// RFC 3339, UTC
+    pub latest_restorable_time: Option<String>,
+    /// RFC 3339, UTC
+    pub created: Option<String>,
+    pub tags: &'a BTreeMap<String, String>,
+}
+
+impl<'a> From<&'a RdsInstance> for Instance<'a> {
+    fn from(i: &'a RdsInstance) -> Self {
+        Instance {
+            account_id: &i.account_id,
+            region: &i.region,
+            instance_id: &i.instance_id,
+            arn: &i.arn,
+            engine: &i.engine,
+            engine_version: &i.engine_version,
+            instance_class: &i.instance_class,
+            availability_zone: i.availability_zone.as_deref(),
+            availability_zone_id: i.availability_zone_id.as_deref(),
+            secondary_availability_zone: i.secondary_availability_zone.as_deref(),
+            secondary_availability_zone_id: i.secondary_availability_zone_id.as_deref(),
+            multi_az: i.multi_az,
+            vpc_id: i.vpc_id.as_deref(),
+            subnet_ids: &i.subnet_ids,
+            network: i.network.as_ref(),
+            status: &i.status,
+            endpoint: i.endpoint.as_deref(),
+            cluster_id: i.cluster_id.as_deref(),
+            cluster_role: i.cluster_role.as_deref(),
+            replica_source: i.replica_source.as_deref(),
+            replicas: &i.replicas,
+            storage_encrypted: i.storage_encrypted,
+            kms_key_id: i.kms_key_id.as_deref(),
+            publicly_accessible: i.publicly_accessible,
+            performance_insights_enabled: i.performance_insights_enabled,
+            allocated_storage: i.allocated_storage,
+            backup_retention_days: i.backup_retention_days,
+            latest_restorable_time: i.latest_restorable_time.map(|t| t.to_rfc3339()),
+            created: i.created.map(|t| t.to_rfc3339()),
+            tags: &i.tags,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Cluster<'a> {
+    pub account_id: &'a str,
+    pub region: &'a str,
+    pub cluster_id: &'a str,
+    pub arn: &'a str,
+    pub engine: &'a str,
+    pub engine_version: &'a str,
+    pub engine_mode: &'a str,
+    pub status: &'a str,
+    pub writer: Option<&'a str>,
+    pub members: Vec<&'a str>,
+    /// Serverless v2 ACU range
+    pub min_capacity: Option<f64>,
+    pub max_capacity: Option<f64>,
+    pub storage_encrypted: bool,
+    pub multi_az: bool,
+}
+
+impl<'a> From<&'a RdsCluster> for Cluster<'a> {
+    fn from(c: &'a RdsCluster) -> Self {
+        Cluster {
+            account_id: &c.account_id,
+            region: &c.region,
+            cluster_id: &c.cluster_id,
+            arn: &c.arn,
+            engine: &c.engine,
+            engine_version: &c.engine_version,
+            engine_mode: &c.engine_mode,
+            status: &c.status,
+            writer: c.writer(),
+            members: c.members.iter().map(|m| m.instance_id.as_str()).collect(),
+            min_capacity: c.serverless_capacity.map(|(min, _)| min),
+            max_capacity: c.serverless_capacity.map(|(_, max)| max),
+            storage_encrypted: c.storage_encrypted,
+            multi_az: c.multi_az,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Proxy<'a> {
+    pub account_id: &'a str,
+    pub region: &'a str,
+    pub name: &'a str,
+    pub arn: &'a str,
+    pub engine_family: &'a str,
+    pub endpoint: &'a str,
+    pub status: &'a str,
+    pub vpc_id: &'a str,
+    pub require_tls: bool,
+}
+
+impl<'a> From<&'a RdsProxy> for Proxy<'a> {
+    fn from(p: &'a RdsProxy) -> Self {
+        Proxy {
+            account_id: &p.account_id,
+            region: &p.region,
+            name: &p.name,
+            arn: &p.arn,
+            engine_family: &p.engine_family,
+            endpoint: &p.endpoint,
+            status: &p.status,
+            vpc_id: &p.vpc_id,
+            require_tls: p.require_tls,
+        }
+    }
+}
+
+/// Render a scan as pretty-printed JSON with a trailing newline
+pub fn to_json(result: &ScanResult) -> Result<String> {
+    let doc = Document {
+        schema_version: SCHEMA_VERSION,
+        tool: "ls-rds",
+        instances: result.instances.iter().map(Instance::from).collect(),
+        clusters: result.clusters.iter().map(Cluster::from).collect(),
+        proxies: result.proxies.iter().map(Proxy::from).collect(),
+        warnings: &result.warnings,
+    };
+    Ok(serde_json::to_string_pretty(&doc)? + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use serde_json::Value;
+
+    #[test]
+    fn schema_matches_version() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        assert_eq!(schema["properties"]["schema_version"]["const"], SCHEMA_VERSION);
+        assert_eq!(schema["properties"]["tool"]["const"], "ls-rds");
+    }
+
+    #[test]
+    fn to_json_emits_required_fields() {
+        let result = ScanResult {
+            instances: vec![RdsInstance {
+                account_id: "111111111111".to_string(),
+                region: "us-east-1".to_string(),
+                instance_id: "orders-1".to_string(),
+                engine_version: "8.0.mysql_aurora.3.05.2".to_string(),
+                cluster_id: Some("orders".to_string()),
+                cluster_role: Some("writer".to_string()),
+                created: DateTime::from_timestamp(1_700_000_000, 0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let doc: Value = serde_json::from_str(&to_json(&result).unwrap()).unwrap();
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        for key in schema["required"].as_array().unwrap() {
+            assert!(doc.get(key.as_str().unwrap()).is_some(), "missing {key}");
+        }
+        let inst = &doc["instances"][0];
+        for key in schema["$defs"]["instance"]["required"].as_array().unwrap() {
+            assert!(inst.get(key.as_str().unwrap()).is_some(), "missing {key}");
+        }
+        assert_eq!(doc["schema_version"], SCHEMA_VERSION);
+        assert_eq!(inst["cluster_role"], "writer");
+        assert_eq!(inst["engine_version"], "8.0.mysql_aurora.3.05.2");
+        assert!(inst["availability_zone"].is_null());
+        assert_eq!(inst["created"], "2023-11-14T22:13:20+00:00");
+        assert!(inst["endpoint"].is_null());
+    }
+}
@@ -3,34 +3,360 @@
 //! Core functionality for listing RDS instances across AWS accounts.
 //! This module separates business logic from the CLI shell.
 
+pub mod aggregate;
+pub mod api_stats;
+pub mod calendar;
 pub mod cli;
+pub mod cluster_endpoints;
+pub mod clusters;
+pub mod compliance;
 pub mod config;
+pub mod engines;
+pub mod export;
+pub mod global_clusters;
+pub mod json;
+pub mod maintenance;
+pub mod manifest;
+pub mod network;
+pub mod params;
+pub mod pick;
+pub mod plan;
+pub mod proxies;
+pub mod redact;
+pub mod regions;
+pub mod retry;
+pub mod roles;
+pub mod scanner;
+pub mod snapshots;
+pub mod spend;
+pub mod sso;
+pub mod terraform;
+pub mod timings;
+pub mod tui;
+pub mod upgrades;
+pub mod zones;
 
-pub use cli::Cli;
-pub use config::{Config, ScanMode, extract_account_from_arn};
+pub use api_stats::{ApiCall, ApiStats};
+pub use cli::{Cli, EngineFamily, GroupBy, OutputFormat};
+pub use cluster_endpoints::ClusterEndpoint;
+pub use clusters::{ClusterMember, RdsCluster};
+pub use global_clusters::GlobalCluster;
+pub use config::{AccountFilter, Config, ScanMode, extract_account_from_arn, parse_duration};
+pub use ls_vpc::identity::{CallerIdentity, format_identity};
+pub use ls_vpc::{ScanError, ScanErrorKind, error_json_lines};
+pub use maintenance::PendingAction;
+pub use network::VpcPlacement;
+pub use plan::ScanPlan;
+pub use proxies::RdsProxy;
+pub use roles::RoleMap;
+pub use scanner::{Scanner, ScannerBuilder};
+pub use snapshots::DbSnapshot;
+pub use timings::RegionTiming;
+pub use upgrades::UpgradeTargets;
 
-use aws_config::{meta::region::RegionProviderChain, BehaviorVersion};
+use aws_config::{meta::region::RegionProviderChain, BehaviorVersion, ConfigLoader};
+use aws_config::retry::RetryConfig;
 use aws_config::sts::AssumeRoleProvider;
+use aws_config::timeout::TimeoutConfig;
+use aws_sdk_ec2 as ec2;
+use aws_sdk_iam as iam;
 use aws_sdk_organizations as org;
 use aws_sdk_rds as rds;
+use aws_sdk_rds::error::{DisplayErrorContext, ProvideErrorMetadata};
 use aws_sdk_sts as sts;
 use aws_types::{region::Region, SdkConfig};
+use chrono::{DateTime, Utc};
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
 use eyre::Result;
-use log::{debug, error, info};
-use std::{env, fs, path::PathBuf};
+use futures::stream::StreamExt;
+use log::{debug, info, warn};
+use ls_vpc::api_stats::CountingProvider;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::{env, fs, path::PathBuf, time::{Duration, Instant}};
 
 /// Result from scanning RDS instances
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RdsInstance {
+    pub account_id: String,
     pub region: String,
     pub role_arn: Option<String>,
     pub instance_id: String,
+    pub arn: String,
+    /// Identifier (or ARN, when cross-region) of the instance this one replicates
+    pub replica_source: Option<String>,
+    /// Identifiers (or ARNs, when cross-region) of this instance's read replicas
+    pub replicas: Vec<String>,
+    /// Weekly maintenance window, e.g. `sun:05:00-sun:05:30` (UTC)
+    pub maintenance_window: Option<String>,
+    pub engine: String,
+    /// Engine version, e.g. `15.4` or `8.0.mysql_aurora.3.05.2`
+    pub engine_version: String,
+    /// Aurora cluster the instance belongs to
+    pub cluster_id: Option<String>,
+    /// `writer` or `reader` within the cluster (resolved with
+    /// `--with-cluster` / `--group-by cluster`)
+    pub cluster_role: Option<String>,
+    /// DB subnet group the instance is placed in
+    pub subnet_group: Option<String>,
+    /// VPC of the subnet group
+    pub vpc_id: Option<String>,
+    /// Subnets of the subnet group in the instance's Availability Zone
+    pub subnet_ids: Vec<String>,
+    /// VPC name, CIDRs and subnet placement (resolved with `--with-network`)
+    pub network: Option<VpcPlacement>,
+    /// Storage encryption at rest is enabled
+    pub storage_encrypted: bool,
+    /// ARN of the KMS key encrypting the storage
+    pub kms_key_id: Option<String>,
+    /// A Multi-AZ standby is provisioned (always false for Aurora members,
+    /// whose availability comes from the cluster)
+    pub multi_az: bool,
+    /// The endpoint resolves to a public IP reachable from the internet
+    pub publicly_accessible: bool,
+    /// Performance Insights is collecting database load
+    pub performance_insights_enabled: bool,
+    /// Allocated storage in GiB
+    pub allocated_storage: Option<u32>,
+    /// Storage autoscaling ceiling in GiB (`None` = autoscaling disabled)
+    pub max_allocated_storage: Option<u32>,
+    /// DB parameter groups attached to the instance
+    pub parameter_groups: Vec<String>,
+    /// Values of the parameters requested with `--with-params`
+    pub params: BTreeMap<String, String>,
+    pub instance_class: String,
+    /// Availability Zone of the instance (the primary's, for Multi-AZ)
+    pub availability_zone: Option<String>,
+    /// Zone ID of `availability_zone`, e.g. `use1-az4`; unlike the name it
+    /// is the same physical zone in every account
+    pub availability_zone_id: Option<String>,
+    /// Availability Zone of the Multi-AZ standby
+    pub secondary_availability_zone: Option<String>,
+    /// Zone ID of `secondary_availability_zone`
+    pub secondary_availability_zone_id: Option<String>,
+    /// `license-included`, `bring-your-own-license` or `general-public-license`
+    pub license_model: Option<String>,
+    /// Character set (Oracle) or collation (SQL Server)
+    pub character_set: Option<String>,
+    /// Instance status, e.g. `available`, `stopped`
+    pub status: String,
+    /// `address:port` clients connect to
+    pub endpoint: Option<String>,
+    /// Automated backup retention in days (0 = backups disabled)
+    pub backup_retention_days: Option<i32>,
+    /// Daily backup window, e.g. `03:00-03:30` (UTC)
+    pub backup_window: Option<String>,
+    /// Latest point a point-in-time restore can reach (`None` without
+    /// automated backups)
+    pub latest_restorable_time: Option<DateTime<Utc>>,
+    /// Nearest minor and latest major engine version available (resolved
+    /// with `--upgrade-targets`)
+    pub upgrade_targets: Option<UpgradeTargets>,
+    /// When the instance was created (`None` while it is still being created)
+    pub created: Option<DateTime<Utc>>,
+    pub tags: BTreeMap<String, String>,
+}
+
+/// One member pair of a blue/green switchover
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SwitchoverMember {
+    pub source: String,
+    pub target: String,
+    pub status: String,
+}
+
+/// An RDS blue/green deployment (source = blue, target = green)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlueGreenDeployment {
+    pub account_id: String,
+    pub region: String,
+    pub identifier: String,
+    pub name: String,
+    pub source: String,
+    pub target: String,
+    pub status: String,
+    pub members: Vec<SwitchoverMember>,
+}
+
+/// Organizations metadata for a member account
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountInfo {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    /// `ACTIVE`, `SUSPENDED` or `PENDING_CLOSURE`
+    pub status: String,
 }
 
 /// Result of an RDS scan operation
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ScanResult {
     pub instances: Vec<RdsInstance>,
+    /// DB clusters, including ones without instances (only collected with
+    /// `--clusters`)
+    pub clusters: Vec<RdsCluster>,
+    /// RDS Proxies (only collected with `--include-proxies`)
+    pub proxies: Vec<RdsProxy>,
+    /// Aurora global databases, once each (only collected with
+    /// `--global-clusters`)
+    pub global_clusters: Vec<GlobalCluster>,
+    /// Blue/green deployments (only collected with `--blue-green`)
+    pub blue_green: Vec<BlueGreenDeployment>,
+    /// Aurora cluster endpoints (only collected with `--cluster-endpoints`)
+    pub cluster_endpoints: Vec<ClusterEndpoint>,
+    /// RDS spend (USD) per account over the `--with-spend` window
+    pub spend: BTreeMap<String, f64>,
+    /// DB snapshots (only collected with `--snapshots`)
+    pub snapshots: Vec<DbSnapshot>,
+    /// Pending maintenance actions (only collected with `--maintenance`)
+    pub pending_actions: Vec<PendingAction>,
+    /// Non-fatal problems (skipped regions, failed sub-calls …)
+    pub warnings: Vec<String>,
+    /// Account/regions that could not be scanned at all (also warned
+    /// about); any of them makes the run exit nonzero
+    #[serde(skip)]
+    pub errors: Vec<ScanError>,
+    /// Why the scan stopped early (`--max-api-calls`, `--max-duration`);
+    /// the result then only holds the pairs finished by that point
+    pub aborted: Option<String>,
+    /// Time, API calls and instances per scanned account/region
+    pub timings: Vec<RegionTiming>,
+    /// Organizations metadata per account ID (org mode only)
+    pub accounts: BTreeMap<String, AccountInfo>,
+    /// Accounts in the organization (only collected with `--snapshots`;
+    /// empty when the caller can't list them)
+    pub org_accounts: BTreeSet<String>,
+    /// AWS API calls the scan made (filled in by [`run`])
+    #[serde(skip)]
+    pub api_calls: BTreeMap<ApiCall, u64>,
+}
+
+impl ScanResult {
+    /// Fold the records of another (per-account) result into this one
+    pub fn merge(&mut self, mut other: ScanResult) {
+        self.instances.append(&mut other.instances);
+        self.clusters.append(&mut other.clusters);
+        self.global_clusters.append(&mut other.global_clusters);
+        self.proxies.append(&mut other.proxies);
+        self.blue_green.append(&mut other.blue_green);
+        self.cluster_endpoints.append(&mut other.cluster_endpoints);
+        self.spend.append(&mut other.spend);
+        self.snapshots.append(&mut other.snapshots);
+        self.pending_actions.append(&mut other.pending_actions);
+        self.accounts.append(&mut other.accounts);
+        self.org_accounts.append(&mut other.org_accounts);
+        self.timings.append(&mut other.timings);
+        self.warnings.append(&mut other.warnings);
+        self.errors.append(&mut other.errors);
+    }
+
+    /// Log a non-fatal problem and keep it for the report
+    pub fn warn(&mut self, message: String) {
+        warn!("   {}", message);
+        self.warnings.push(message);
+    }
+
+    /// Record an account/region that could not be scanned because
+    /// `operation` failed: warned about (ending with what was skipped) and
+    /// kept in `errors`
+    pub fn fail<E>(&mut self, account_id: &str, region: &Region, operation: &str, skipped: &str, err: &E)
+    where
+        E: ProvideErrorMetadata + std::error::Error,
+    {
+        let message = DisplayErrorContext(err).to_string();
+        self.warn(format!("{} {}: {} failed, {} skipped: {}", account_id, region, operation, skipped, message));
+        self.errors.push(ScanError {
+            account_id: Some(account_id.to_owned()),
+            region: region.to_string(),
+            operation: Some(operation.to_owned()),
+            kind: ScanErrorKind::of(err),
+            code: err.code().map(str::to_owned),
+            message,
+        });
+    }
+}
+
+/// Build an [`RdsInstance`] from a DescribeDBInstances entry.
+fn instance_from(
+    region: &Region,
+    account_id: &str,
+    role_arn: Option<&str>,
+    inst: &rds::types::DbInstance,
+) -> RdsInstance {
+    RdsInstance {
+        account_id: account_id.to_owned(),
+        region: region.to_string(),
+        role_arn: role_arn.map(str::to_owned),
+        instance_id: inst.db_instance_identifier().unwrap_or_default().to_string(),
+        arn: inst.db_instance_arn().unwrap_or_default().to_string(),
+        replica_source: inst
+            .read_replica_source_db_instance_identifier()
+            .map(str::to_owned),
+        replicas: inst.read_replica_db_instance_identifiers().to_vec(),
+        maintenance_window: inst.preferred_maintenance_window().map(str::to_owned),
+        engine: inst.engine().unwrap_or_default().to_string(),
+        engine_version: inst.engine_version().unwrap_or_default().to_owned(),
+        cluster_id: inst.db_cluster_identifier().map(str::to_owned),
+        subnet_group: inst
+            .db_subnet_group()
+            .and_then(|g| g.db_subnet_group_name())
+            .map(str::to_owned),
+        vpc_id: inst.db_subnet_group().and_then(|g| g.vpc_id()).map(str::to_owned),
+        subnet_ids: inst
+            .db_subnet_group()
+            .map(|g| g.subnets())
+            .unwrap_or_default()
+            .iter()
+            .filter(|s| {
+                inst.availability_zone().is_some()
+                    && s.subnet_availability_zone().and_then(|z| z.name()) == inst.availability_zone()
+            })
+            .filter_map(|s| s.subnet_identifier().map(str::to_owned))
+            .collect(),
+        storage_encrypted: inst.storage_encrypted().unwrap_or(false),
+        kms_key_id: inst.kms_key_id().map(str::to_owned),
+        multi_az: inst.multi_az().unwrap_or(false),
+        publicly_accessible: inst.publicly_accessible().unwrap_or(false),
+        performance_insights_enabled: inst.performance_insights_enabled().unwrap_or(false),
+        allocated_storage: inst.allocated_storage().and_then(|gb| u32::try_from(gb).ok()),
+        max_allocated_storage: inst.max_allocated_storage().and_then(|gb| u32::try_from(gb).ok()),
+        parameter_groups: inst
+            .db_parameter_groups()
+            .iter()
+            .filter_map(|g| g.db_parameter_group_name().map(str::to_owned))
+            .collect(),
+        instance_class: inst.db_instance_class().unwrap_or_default().to_owned(),
+        availability_zone: inst.availability_zone().map(str::to_owned),
+        secondary_availability_zone: inst.secondary_availability_zone().map(str::to_owned),
+        // Resolved per region by `zones::apply_zone_ids`
+        availability_zone_id: None,
+        secondary_availability_zone_id: None,
+        license_model: inst.license_model().map(str::to_owned),
+        character_set: inst.character_set_name().map(str::to_owned),
+        status: inst.db_instance_status().unwrap_or_default().to_owned(),
+        endpoint: inst.endpoint().and_then(|e| {
+            let address = e.address()?;
+            Some(match e.port() {
+                Some(port) => format!("{}:{}", address, port),
+                None => address.to_owned(),
+            })
+        }),
+        backup_retention_days: inst.backup_retention_period(),
+        backup_window: inst.preferred_backup_window().map(str::to_owned),
+        latest_restorable_time: inst
+            .latest_restorable_time()
+            .and_then(|t| DateTime::from_timestamp(t.secs(), 0)),
+        created: inst
+            .instance_create_time()
+            .and_then(|t| DateTime::from_timestamp(t.secs(), 0)),
+        tags: inst
+            .tag_list()
+            .iter()
+            .filter_map(|t| Some((t.key()?.to_owned(), t.value().unwrap_or_default().to_owned())))
+            .collect(),
+        ..Default::default()
+    }
 }
 
 /// Return an OS‑appropriate log directory, creating it if necessary.
@@ -66,190 +392,539 @@ pub fn get_default_region(config: &Config) -> String {
         .unwrap_or_else(|_| config.regions.first().cloned().unwrap_or_else(|| "us-east-1".to_string()))
 }
 
-/// Get the caller's account ID
-pub async fn get_caller_account(base_conf: &SdkConfig) -> Result<String> {
+/// Get the caller's account ID, asking the `sts_region` endpoint when given
+/// and the base config's region otherwise; throttling is retried `retries`
+/// times
+pub async fn get_caller_account(
+    base_conf: &SdkConfig,
+    stats: &ApiStats,
+    sts_region: Option<&Region>,
+    retries: u32,
+) -> Result<String> {
     debug!("Calling STS GetCallerIdentity…");
-    let caller_account = sts::Client::new(base_conf)
-        .get_caller_identity()
-        .send()
-        .await?
-        .account()
-        .unwrap_or_default()
-        .to_owned();
+    let mut sts_conf = sts::config::Builder::from(base_conf);
+    if let Some(region) = sts_region {
+        sts_conf = sts_conf.region(region.clone());
+    }
+    let sts_conf = sts_conf.build();
+    let region = sts_conf.region().map(|r| r.to_string()).unwrap_or_default();
+    let client = sts::Client::from_conf(sts_conf);
+    let caller_account = retry::with_retries(retries, || {
+        stats.record("sts", "GetCallerIdentity", &region, "");
+        client.get_caller_identity().send()
+    })
+    .await?
+    .account()
+    .unwrap_or_default()
+    .to_owned();
     debug!("Caller account = {}", caller_account);
     Ok(caller_account)
 }
 
-/// List RDS instances with existing credentials
-pub async fn list_rds(base_conf: &SdkConfig, regions: &[Region]) -> Result<Vec<RdsInstance>> {
-    debug!("Entering list_rds()");
-    let mut instances = Vec::new();
+/// Identity of the base credentials, resolved in `--sts-region` or else the
+/// default region
+pub async fn whoami(config: &Config) -> Result<CallerIdentity> {
+    let region = config.sts_region.clone().unwrap_or_else(|| get_default_region(config));
+    let base_conf = sdk_loader(config).region(Region::new(region)).load().await;
+    ls_vpc::identity::caller_identity(&base_conf).await
+}
 
-    for region in regions {
-        info!("→ Region {}", region);
+/// SDK config loader with the `--profile`, `--max-retries` and timeout
+/// flags applied
+pub fn sdk_loader(config: &Config) -> ConfigLoader {
+    let mut loader = aws_config::defaults(BehaviorVersion::latest());
+    if let Some(profile) = &config.profile {
+        loader = loader.profile_name(profile);
+    }
+    if let Some(retries) = config.max_retries {
+        loader = loader.retry_config(RetryConfig::standard().with_max_attempts(retries + 1));
+    }
+    if config.connect_timeout.is_some() || config.operation_timeout.is_some() {
+        let mut timeouts = TimeoutConfig::builder();
+        timeouts
+            .set_connect_timeout(config.connect_timeout)
+            .set_operation_timeout(config.operation_timeout);
+        loader = loader.timeout_config(timeouts.build());
+    }
+    loader
+}
 
-        let conf = aws_config::defaults(BehaviorVersion::latest())
-            .region(RegionProviderChain::first_try(region.clone()))
-            .credentials_provider(
-                base_conf
-                    .credentials_provider()
-                    .expect("base config missing credentials provider")
-                    .clone(),
-            )
-            .load()
-            .await;
-
-        let client = rds::Client::new(&conf);
-
-        info!("   Sending DescribeDBInstances…");
-        match client.describe_db_instances().send().await {
-            Ok(output) => {
-                let count = output.db_instances().len();
-                info!("   Got {} instances in {}", count, region);
-                for inst in output.db_instances() {
-                    instances.push(RdsInstance {
-                        region: region.to_string(),
-                        role_arn: None,
-                        instance_id: inst.db_instance_identifier().unwrap_or_default().to_string(),
-                    });
-                }
-            }
-            Err(e) => error!("   Error in {}: {:?}", region, e),
+/// How cross-account roles are assumed, the same for every account
+#[derive(Debug, Clone, Default)]
+pub struct RoleSession {
+    /// Region whose STS endpoint is asked (the scanned region's when unset)
+    pub sts_region: Option<Region>,
+    /// Passed when the role's trust policy requires an external ID
+    pub external_id: Option<String>,
+    pub session_name: String,
+    /// Credential lifetime (the SDK default of one hour when unset)
+    pub duration: Option<Duration>,
+    /// Retries of a throttled AssumeRole
+    pub max_retries: u32,
+}
+
+impl RoleSession {
+    pub fn from_config(config: &Config) -> Self {
+        RoleSession {
+            sts_region: config.sts_region.clone().map(Region::new),
+            external_id: config.external_id.clone(),
+            session_name: config.session_name.clone(),
+            duration: config.session_duration,
+            max_retries: config.retries(),
         }
     }
-    Ok(instances)
 }
 
-/// Scan account via assumed role
-pub async fn scan_account(
+/// Build a per-region SDK config, assuming `role_arn` when one is given
+/// with the `session` settings.  Retry and timeout settings are carried
+/// over from `base_conf`; each AssumeRole call is counted in `stats`, and
+/// retried when throttled.
+pub async fn region_conf(
     base_conf: &SdkConfig,
-    regions: &[Region],
-    role_arn: &str,
-) -> Result<Vec<RdsInstance>> {
-    info!("--- Scanning with role {}", role_arn);
-    let mut instances = Vec::new();
-
-    for region in regions {
-        info!("→ Region {}", region);
-
-        let provider = AssumeRoleProvider::builder(role_arn.to_owned())
-            .session_name("ls-rds")
-            .region(region.clone())
-            .configure(base_conf)
-            .build()
-            .await;
-
-        let conf = aws_config::defaults(BehaviorVersion::latest())
-            .region(RegionProviderChain::first_try(region.clone()))
-            .credentials_provider(provider)
-            .load()
-            .await;
-
-        let client = rds::Client::new(&conf);
-
-        info!("   Sending DescribeDBInstances…");
-        match client.describe_db_instances().send().await {
-            Ok(output) => {
-                let count = output.db_instances().len();
-                info!("   Got {} instances", count);
-                for inst in output.db_instances() {
-                    instances.push(RdsInstance {
-                        region: region.to_string(),
-                        role_arn: Some(role_arn.to_string()),
-                        instance_id: inst.db_instance_identifier().unwrap_or_default().to_string(),
-                    });
+    stats: &ApiStats,
+    region: &Region,
+    role_arn: Option<&str>,
+    session: &RoleSession,
+) -> SdkConfig {
+    let mut loader = aws_config::defaults(BehaviorVersion::latest())
+        .region(RegionProviderChain::first_try(region.clone()));
+    if let Some(retry) = base_conf.retry_config() {
+        loader = loader.retry_config(retry.clone());
+    }
+    if let Some(timeouts) = base_conf.timeout_config() {
+        loader = loader.timeout_config(timeouts.clone());
+    }
+
+    match role_arn {
+        Some(arn) => {
+            let sts_region = session.sts_region.as_ref().unwrap_or(region);
+            let mut builder = AssumeRoleProvider::builder(arn.to_owned())
+                .session_name(&session.session_name)
+                .region(sts_region.clone());
+            if let Some(id) = &session.external_id {
+                builder = builder.external_id(id);
+            }
+            if let Some(duration) = session.duration {
+                builder = builder.session_length(duration);
+            }
+            let provider = builder.configure(base_conf).build().await;
+            // Counted when the SDK fetches credentials, not when the provider is built
+            let calls = stats.for_account(extract_account_from_arn(arn).unwrap_or_default());
+            let sts_region = sts_region.clone();
+            let provider =
+                CountingProvider::new(provider, move || calls.record("sts", "AssumeRole", Some(&sts_region)));
+            let provider = retry::RetryingProvider::new(provider, session.max_retries);
+            loader.credentials_provider(provider).load().await
+        }
+        None => {
+            loader
+                .credentials_provider(
+                    base_conf
+                        .credentials_provider()
+                        .expect("base config missing credentials provider")
+                        .clone(),
+                )
+                .load()
+                .await
+        }
+    }
+}
+
+/// List blue/green deployments in one region
+pub async fn list_blue_green(
+    client: &rds::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    retries: u32,
+) -> Result<Vec<BlueGreenDeployment>> {
+    let mut deployments = Vec::new();
+    let mut marker: Option<String> = None;
+    loop {
+        let page = retry::with_retries(retries, || {
+            stats.record("rds", "DescribeBlueGreenDeployments", region.as_ref(), account_id);
+            client.describe_blue_green_deployments().set_marker(marker.clone()).send()
+        })
+        .await?;
+        for bg in page.blue_green_deployments() {
+            deployments.push(BlueGreenDeployment {
+                account_id: account_id.to_owned(),
+                region: region.to_string(),
+                identifier: bg.blue_green_deployment_identifier().unwrap_or_default().to_owned(),
+                name: bg.blue_green_deployment_name().unwrap_or_default().to_owned(),
+                source: bg.source().unwrap_or_default().to_owned(),
+                target: bg.target().unwrap_or_default().to_owned(),
+                status: bg.status().map(|s| s.to_string()).unwrap_or_default(),
+                members: bg
+                    .switchover_details()
+                    .iter()
+                    .map(|d| SwitchoverMember {
+                        source: d.source_member().unwrap_or_default().to_owned(),
+                        target: d.target_member().unwrap_or_default().to_owned(),
+                        status: d.status().map(|s| s.to_string()).unwrap_or_default(),
+                    })
+                    .collect(),
+            });
+        }
+        match page.marker() {
+            Some(next) => marker = Some(next.to_owned()),
+            None => break,
+        }
+    }
+    Ok(deployments)
+}
+
+/// Scan a single region with an already-resolved SDK config
+async fn scan_region(
+    conf: &SdkConfig,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    role_arn: Option<&str>,
+    config: &Config,
+    result: &mut ScanResult,
+) {
+    let started = Instant::now();
+    let client = rds::Client::new(conf);
+
+    info!("   Sending DescribeDBInstances…");
+    let first = result.instances.len();
+    let retries = config.retries();
+    let listed = retry::with_retries(retries, || {
+        stats.record("rds", "DescribeDBInstances", region.as_ref(), account_id);
+        client.describe_db_instances().send()
+    })
+    .await;
+    match listed {
+        Ok(output) => {
+            let count = output.db_instances().len();
+            info!("   Got {} instances in {}", count, region);
+            for inst in output.db_instances() {
+                let inst = instance_from(region, account_id, role_arn, inst);
+                if config
+                    .engine_family
+                    .is_some_and(|family| engines::engine_family(&inst.engine) != family)
+                    || !engine_matches(&inst, &config.engines)
+                    || !status_matches(&inst, &config.statuses)
+                    || !public_matches(&inst, config.public_only)
+                    || !encryption_matches(&inst, config.unencrypted_only)
+                    || (config.no_pi_only && inst.performance_insights_enabled)
+                    || (config.single_az_only && !is_single_az(&inst))
+                    || !age_matches(&inst, config, Utc::now())
+                    || !tags_match(&inst, &config.tags)
+                {
+                    continue;
+                }
+                result.instances.push(inst);
+            }
+        }
+        Err(e) => {
+            result.fail(account_id, region, "DescribeDBInstances", "region", &e);
+        }
+    }
+
+    if config.wants_zone_ids() && result.instances.len() > first {
+        info!("   Sending DescribeAvailabilityZones…");
+        match zones::zone_ids(&ec2::Client::new(conf), stats, region, account_id).await {
+            Ok(ids) => zones::apply_zone_ids(&mut result.instances[first..], &ids),
+            Err(e) => result.warn(format!("{} {}: cannot resolve Availability Zone IDs: {:#}", account_id, region, e)),
+        }
+    }
+
+    if config.with_network && result.instances.len() > first {
+        info!("   Resolving VPC placement…");
+        for w in network::resolve(conf, stats, region, account_id, &mut result.instances[first..]).await {
+            result.warn(w);
+        }
+    }
+
+    if !config.with_params.is_empty() {
+        let mut cache = params::ParameterCache::new(stats, retries);
+        for inst in &mut result.instances[first..] {
+            info!("   Resolving parameters of {}…", inst.instance_id);
+            let values = cache
+                .instance_parameters(&client, region, account_id, &inst.parameter_groups, None, &config.with_params)
+                .await;
+            inst.params.extend(values);
+        }
+        for w in cache.warnings {
+            result.warn(w);
+        }
+    }
+
+    if config.upgrade_targets {
+        let mut cache = upgrades::UpgradeCache::new(stats, retries);
+        for inst in &mut result.instances[first..] {
+            info!("   Resolving upgrade targets of {}…", inst.instance_id);
+            inst.upgrade_targets = cache
+                .targets(&client, region, account_id, &inst.engine, &inst.engine_version)
+                .await;
+        }
+        for w in cache.warnings {
+            result.warn(w);
+        }
+    }
+
+    let need_roles = config.with_cluster || config.topology || config.group_by == Some(GroupBy::Cluster);
+    if need_roles || config.clusters {
+        info!("   Sending DescribeDBClusters…");
+        match clusters::list_clusters(&client, stats, region, account_id, retries).await {
+            Ok(mut found) => {
+                if need_roles {
+                    let roles = clusters::cluster_roles(&found);
+                    for inst in &mut result.instances[first..] {
+                        inst.cluster_role = roles.get(&inst.instance_id).map(|r| (*r).to_owned());
+                    }
+                }
+                if config.clusters {
+                    result.clusters.append(&mut found);
                 }
             }
-            Err(e) => error!("   Error in {}: {:?}", region, e),
+            Err(e) => result.warn(format!("{} {}: cannot list DB clusters: {:#}", account_id, region, e)),
+        }
+    }
+
+    if config.blue_green {
+        info!("   Sending DescribeBlueGreenDeployments…");
+        match list_blue_green(&client, stats, region, account_id, retries).await {
+            Ok(mut deployments) => result.blue_green.append(&mut deployments),
+            Err(e) => result.warn(format!("{} {}: cannot list blue/green deployments: {:#}", account_id, region, e)),
+        }
+    }
+
+    if config.include_proxies {
+        info!("   Sending DescribeDBProxies…");
+        match proxies::list_proxies(&client, stats, region, account_id, role_arn, retries).await {
+            Ok(mut found) => result.proxies.append(&mut found),
+            Err(e) => result.warn(format!("{} {}: cannot list RDS proxies: {:#}", account_id, region, e)),
         }
     }
 
-    Ok(instances)
+    if config.global_clusters {
+        info!("   Sending DescribeGlobalClusters…");
+        match global_clusters::list_global_clusters(&client, stats, region, account_id, retries).await {
+            Ok(mut globals) => result.global_clusters.append(&mut globals),
+            Err(e) => result.warn(format!("{} {}: cannot list global clusters: {:#}", account_id, region, e)),
+        }
+    }
+
+    if config.cluster_endpoints {
+        info!("   Sending DescribeDBClusterEndpoints…");
+        match cluster_endpoints::list_cluster_endpoints(&client, stats, region, account_id, retries).await {
+            Ok(mut endpoints) => result.cluster_endpoints.append(&mut endpoints),
+            Err(e) => result.warn(format!("{} {}: cannot list cluster endpoints: {:#}", account_id, region, e)),
+        }
+    }
+
+    if config.maintenance {
+        info!("   Sending DescribePendingMaintenanceActions…");
+        match maintenance::list_pending_actions(&client, stats, region, account_id, retries).await {
+            Ok(mut actions) => result.pending_actions.append(&mut actions),
+            Err(e) => result.warn(format!(
+                "{} {}: cannot list pending maintenance actions: {:#}",
+                account_id, region, e
+            )),
+        }
+    }
+
+    if config.snapshots {
+        info!("   Sending DescribeDBSnapshots…");
+        match snapshots::list_snapshots(&client, stats, region, account_id, retries).await {
+            Ok(mut snaps) => result.snapshots.append(&mut snaps),
+            Err(e) => result.warn(format!("{} {}: cannot list snapshots: {:#}", account_id, region, e)),
+        }
+    }
+
+    result.timings.push(RegionTiming {
+        account_id: account_id.to_owned(),
+        region: region.to_string(),
+        duration: started.elapsed(),
+        api_calls: stats.calls_for(region.as_ref(), account_id),
+        instances: result.instances.len() - first,
+    });
 }
 
-/// Enumerate organization accounts and scan each
-pub async fn enumerate_organization(base_conf: &SdkConfig, regions: &[Region]) -> Result<Vec<RdsInstance>> {
-    info!("Enumerating accounts via AWS Organizations…");
+/// List every account in the caller's organization
+pub async fn list_org_accounts(base_conf: &SdkConfig, stats: &ApiStats) -> Result<Vec<AccountInfo>> {
     let org_client = org::Client::new(base_conf);
-    let mut instances = Vec::new();
+    let org_region = base_conf.region().map(|r| r.to_string()).unwrap_or_default();
+    let mut accounts = Vec::new();
 
     let mut pages = org_client.list_accounts().into_paginator().send();
     while let Some(page) = pages.next().await {
-        let page = page?;
-        for acct in page.accounts() {
-            let account_id = acct.id().unwrap_or_default();
-            let role_arn = format!("arn:aws:iam::{}:role/YourCrossAccountRole", account_id);
-            info!("→ Found account {}; attempting {}", account_id, role_arn);
-            let mut acct_instances = scan_account(base_conf, regions, &role_arn).await?;
-            instances.append(&mut acct_instances);
-        }
+        stats.record("organizations", "ListAccounts", &org_region, "");
+        accounts.extend(page?.accounts().iter().map(account_info));
     }
-    Ok(instances)
+    Ok(accounts)
 }
 
-/// Process explicit role ARNs
-pub async fn process_role_arns(
-    base_conf: &SdkConfig,
-    regions: &[Region],
-    caller_account: &str,
-    arns: &[String],
-) -> Result<Vec<RdsInstance>> {
-    info!("Using explicit role ARNs…");
-    let mut instances = Vec::new();
-
-    for arn in arns {
-        let arn_account = arn.split(':').nth(4).unwrap_or_default();
-        debug!("Examining ARN {} (account {})", arn, arn_account);
-
-        if arn_account == caller_account {
-            info!("→ {} is in current account – skipping AssumeRole", arn);
-            let mut current_instances = list_rds(base_conf, regions).await?;
-            instances.append(&mut current_instances);
-        } else {
-            info!("→ Assuming {}", arn);
-            let mut arn_instances = scan_account(base_conf, regions, arn).await?;
-            instances.append(&mut arn_instances);
+/// List the accounts under an Organizational Unit (or root), walking its
+/// nested OUs breadth-first
+pub async fn list_ou_accounts(base_conf: &SdkConfig, stats: &ApiStats, ou_id: &str) -> Result<Vec<AccountInfo>> {
+    let org_client = org::Client::new(base_conf);
+    let org_region = base_conf.region().map(|r| r.to_string()).unwrap_or_default();
+    let mut accounts = Vec::new();
+    let mut parents = std::collections::VecDeque::from([ou_id.to_owned()]);
+
+    while let Some(parent) = parents.pop_front() {
+        let mut pages = org_client
+            .list_accounts_for_parent()
+            .parent_id(&parent)
+            .into_paginator()
+            .send();
+        while let Some(page) = pages.next().await {
+            stats.record("organizations", "ListAccountsForParent", &org_region, "");
+            accounts.extend(page?.accounts().iter().map(account_info));
+        }
+        let mut pages = org_client
+            .list_organizational_units_for_parent()
+            .parent_id(&parent)
+            .into_paginator()
+            .send();
+        while let Some(page) = pages.next().await {
+            stats.record("organizations", "ListOrganizationalUnitsForParent", &org_region, "");
+            parents.extend(page?.organizational_units().iter().filter_map(|ou| ou.id().map(str::to_owned)));
         }
     }
-    Ok(instances)
+    Ok(accounts)
 }
 
-/// Run the RDS scan for given config
-pub async fn run(config: &Config) -> Result<ScanResult> {
-    let default_region = get_default_region(config);
-    debug!("Bootstrap/STS Region: {}", &default_region);
-
-    info!("Loading base AWS config…");
-    let base_conf = aws_config::defaults(BehaviorVersion::latest())
-        .region(Region::new(default_region))
-        .load()
-        .await;
-
-    let caller_account = get_caller_account(&base_conf).await?;
-
-    let regions: Vec<Region> = config
-        .regions
-        .iter()
-        .map(|s| {
-            debug!("Parsed Region arg: {}", s);
-            Region::new(s.trim().to_owned())
-        })
-        .collect();
+fn account_info(acct: &org::types::Account) -> AccountInfo {
+    AccountInfo {
+        id: acct.id().unwrap_or_default().to_owned(),
+        name: acct.name().unwrap_or_default().to_owned(),
+        email: acct.email().unwrap_or_default().to_owned(),
+        status: acct.status().map(|s| s.as_str().to_owned()).unwrap_or_default(),
+    }
+}
+
+/// The IAM alias of the account `conf` signs in to, if it has one
+pub async fn account_alias(
+    conf: &SdkConfig,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+) -> Result<Option<String>> {
+    stats.record("iam", "ListAccountAliases", region.as_ref(), account_id);
+    let output = iam::Client::new(conf).list_account_aliases().send().await?;
+    Ok(output.account_aliases().first().cloned())
+}
 
-    let instances = match &config.mode {
-        ScanMode::Organization => {
-            enumerate_organization(&base_conf, &regions).await?
+/// List the IDs of every account in the caller's organization
+pub async fn org_account_ids(base_conf: &SdkConfig, stats: &ApiStats) -> Result<BTreeSet<String>> {
+    Ok(list_org_accounts(base_conf, stats).await?.into_iter().map(|a| a.id).collect())
+}
+
+/// Why a scan that made `calls` API calls in `elapsed` must stop, if it
+/// has run past `--max-api-calls` or `--max-duration`
+pub fn over_budget(config: &Config, calls: u64, elapsed: Duration) -> Option<String> {
+    if let Some(max) = config.max_api_calls.filter(|max| calls >= *max) {
+        return Some(format!("--max-api-calls {} reached ({} calls made)", max, calls));
+    }
+    if let Some(max) = config.max_duration.filter(|max| elapsed >= *max) {
+        return Some(format!("--max-duration {:.0?} reached", max));
+    }
+    None
+}
+
+/// Run the RDS scan for given config: collect every account/region result
+/// of [`Scanner::scan`] into one, in account then region order.  Past the
+/// `--max-api-calls` / `--max-duration` budget the scans still in flight
+/// are dropped and the pairs finished so far are returned, marked
+/// `aborted`.  Expired SSO credentials fail with the login command to run
+/// (or, with `--sso-login`, are refreshed before one more try).
+pub async fn run(config: &Config) -> Result<ScanResult> {
+    let started = Instant::now();
+    let scanner = Scanner::from_config(config.clone());
+    let scans = match scanner.scan_ordered().await {
+        Ok(scans) => scans,
+        Err(e) if sso::is_expired_session(&e) => {
+            let profile = sso::profile(config);
+            if !config.sso_login {
+                return Err(e.wrap_err(sso::login_hint(profile.as_deref())));
+            }
+            warn!("AWS credentials are expired or missing, running `aws sso login`");
+            sso::login(profile.as_deref())?;
+            scanner.scan_ordered().await?
         }
-        ScanMode::RoleArns(arns) => {
-            process_role_arns(&base_conf, &regions, &caller_account, arns).await?
+        Err(e) => return Err(e),
+    };
+    let mut scans = std::pin::pin!(scans);
+    let deadline = config.max_duration.map(|d| tokio::time::Instant::from_std(started) + d);
+    let mut scanned: Vec<(usize, ScanResult)> = Vec::new();
+    let mut aborted = None;
+    loop {
+        let next = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, scans.next()).await.ok(),
+            None => Some(scans.next().await),
+        };
+        match next {
+            Some(Some(pair)) => scanned.push(pair),
+            Some(None) => break,
+            // Deadline passed with scans still in flight
+            None => {}
         }
-        ScanMode::CurrentAccount => {
-            info!("Listing RDS in current account {}", caller_account);
-            list_rds(&base_conf, &regions).await?
+        if let Some(reason) = over_budget(config, scanner.api_stats().total(), started.elapsed()) {
+            aborted = Some(reason);
+            break;
         }
-    };
+    }
+    scanned.sort_by_key(|(n, _)| *n);
 
-    Ok(ScanResult { instances })
+    let mut result = ScanResult::default();
+    let pairs = scanned.len().saturating_sub(1);
+    for (_, pair) in scanned {
+        result.merge(pair);
+    }
+    if let Some(reason) = aborted {
+        result.warn(format!("scan aborted after {} account/region pair(s): {}", pairs, reason));
+        result.aborted = Some(reason);
+    }
+    global_clusters::dedup_global_clusters(&mut result.global_clusters);
+    result.api_calls = scanner.api_stats().snapshot();
+    Ok(result)
+}
+
+/// Summarize the accounts that could not be fully scanned, one line per
+/// account with its failed regions and why (empty when every region was
+/// scanned).  An account's regions are the ones it has `timings` for; one
+/// failing in all of them (or before any region was reached) usually means
+/// its role could not be assumed.
+pub fn format_failed_accounts(errors: &[ScanError], timings: &[RegionTiming]) -> String {
+    let mut failed: BTreeMap<&str, BTreeMap<&str, ScanErrorKind>> = BTreeMap::new();
+    for e in errors {
+        failed
+            .entry(e.account_id.as_deref().unwrap_or("-"))
+            .or_default()
+            .insert(&e.region, e.kind);
+    }
+    if failed.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("{} account(s) not fully scanned:\n", failed.len());
+    for (account_id, failed_regions) in failed {
+        let kinds: BTreeSet<String> = failed_regions.values().map(ScanErrorKind::to_string).collect();
+        let kinds = kinds.into_iter().collect::<Vec<_>>().join(",");
+        let regions = timings
+            .iter()
+            .filter(|t| t.account_id == account_id)
+            .map(|t| t.region.as_str())
+            .collect::<BTreeSet<_>>()
+            .len();
+        let scope = if failed_regions.len() >= regions {
+            format!("all regions failed ({})", kinds)
+        } else {
+            format!(
+                "{}/{} regions failed: {} ({})",
+                failed_regions.len(),
+                regions,
+                failed_regions.keys().copied().collect::<Vec<_>>().join(","),
+                kinds
+            )
+        };
+        out.push_str(&format!("{}\t{}\n", account_id, scope));
+    }
+    out
 }
 
 /// Format an RDS instance for output
@@ -260,6 +935,308 @@ pub fn format_instance(inst: &RdsInstance) -> String {
     }
 }
 
+/// Classify an instance's storage autoscaling setup.  Aurora storage is
+/// managed by the cluster volume, so it's reported as `n/a`.
+pub fn storage_status(inst: &RdsInstance, headroom_pct: u8) -> &'static str {
+    if inst.engine.starts_with("aurora") {
+        return "n/a";
+    }
+    match (inst.allocated_storage, inst.max_allocated_storage) {
+        (_, None) => "autoscaling-disabled",
+        (Some(used), Some(max)) if u64::from(max.saturating_sub(used)) * 100 < u64::from(max) * u64::from(headroom_pct) => {
+            "near-ceiling"
+        }
+        _ => "ok",
+    }
+}
+
+/// Whether an instance runs one of the `--engine` engines (any engine when
+/// none are given).
+pub fn engine_matches(inst: &RdsInstance, engines: &[String]) -> bool {
+    engines.is_empty() || engines.contains(&inst.engine)
+}
+
+/// Whether an instance is in one of the `--status` states (any state when
+/// none are given).
+pub fn status_matches(inst: &RdsInstance, statuses: &[String]) -> bool {
+    statuses.is_empty() || statuses.contains(&inst.status)
+}
+
+/// Whether an instance passes `--public-only` (any instance without it).
+pub fn public_matches(inst: &RdsInstance, public_only: bool) -> bool {
+    !public_only || inst.publicly_accessible
+}
+
+/// Whether an instance passes `--unencrypted-only` (any instance without it).
+pub fn encryption_matches(inst: &RdsInstance, unencrypted_only: bool) -> bool {
+    !unencrypted_only || !inst.storage_encrypted
+}
+
+/// Whether an instance runs without a Multi-AZ standby.  Aurora members are
+/// never single-AZ here: their availability comes from the cluster, which
+/// `--require-multi-az` checks as a whole.
+pub fn is_single_az(inst: &RdsInstance) -> bool {
+    !inst.multi_az && inst.cluster_id.is_none()
+}
+
+/// Whether an instance carries every `--tag` filter.
+pub fn tags_match(inst: &RdsInstance, filters: &[(String, String)]) -> bool {
+    filters.iter().all(|(key, value)| inst.tags.get(key) == Some(value))
+}
+
+/// Whether an instance passes `--older-than` / `--newer-than`.  An instance
+/// still being created has no create time yet and counts as brand new.
+pub fn age_matches(inst: &RdsInstance, config: &Config, now: DateTime<Utc>) -> bool {
+    let age = inst.created.map_or_else(chrono::Duration::zero, |c| now - c);
+    let limit = |d: std::time::Duration| chrono::Duration::from_std(d).unwrap_or(chrono::Duration::MAX);
+    let old_enough = config.older_than.is_none_or(|d| age > limit(d));
+    let new_enough = config.newer_than.is_none_or(|d| age < limit(d));
+    old_enough && new_enough
+}
+
+/// Days since the instance was created, e.g. `412d` (`-` when unknown)
+pub fn format_age(inst: &RdsInstance, now: DateTime<Utc>) -> String {
+    inst.created
+        .map(|c| format!("{}d", (now - c).num_days()))
+        .unwrap_or_else(|| "-".to_owned())
+}
+
+/// Headers of the optional columns enabled in `config`, in row order
+pub(crate) fn optional_headers(config: &Config) -> Vec<String> {
+    let mut headers: Vec<String> = Vec::new();
+    if config.account_names {
+        headers.push("ACCOUNT-NAME".into());
+    }
+    if config.account_details {
+        headers.extend(["ACCOUNT-EMAIL".into(), "ACCOUNT-STATUS".into()]);
+    }
+    if config.spend_days.is_some() {
+        headers.push("SPEND-USD".into());
+    }
+    if config.with_cluster {
+        headers.extend(["CLUSTER".into(), "CLUSTER-ROLE".into()]);
+    }
+    if config.with_specs {
+        headers.extend(["ENGINE", "VERSION", "CLASS", "STORAGE-GIB", "AZ", "AZ-ID"].map(String::from));
+    }
+    if config.with_network {
+        headers.extend(["VPC-ID", "VPC-NAME", "VPC-CIDR", "PLACEMENT"].map(String::from));
+    }
+    if !config.statuses.is_empty() {
+        headers.push("STATUS".into());
+    }
+    headers.extend(config.with_params.iter().cloned());
+    if config.engine_family == Some(EngineFamily::Commercial) {
+        headers.extend(["LICENSE".into(), "EDITION".into(), "CHARSET".into()]);
+    }
+    if config.storage_headroom.is_some() {
+        headers.extend(["STORAGE".into(), "AUTOSCALING".into()]);
+    }
+    if config.upgrade_targets {
+        headers.extend(["NEXT-MINOR".into(), "LATEST-MAJOR".into()]);
+    }
+    if config.with_age {
+        headers.push("AGE".into());
+    }
+    headers
+}
+
+/// Values of the optional columns enabled in `config`
+pub(crate) fn optional_fields(inst: &RdsInstance, config: &Config, result: &ScanResult) -> Vec<String> {
+    let mut fields = Vec::new();
+    if config.account_names {
+        let name = result.accounts.get(&inst.account_id).map(|a| a.name.as_str());
+        fields.push(name.filter(|n| !n.is_empty()).unwrap_or("-").to_owned());
+    }
+    if config.account_details {
+        let account = result.accounts.get(&inst.account_id);
+        fields.push(account.map(|a| a.email.clone()).unwrap_or_else(|| "-".to_owned()));
+        fields.push(account.map(|a| a.status.clone()).unwrap_or_else(|| "-".to_owned()));
+    }
+    if config.spend_days.is_some() {
+        fields.push(
+            result
+                .spend
+                .get(&inst.account_id)
+                .map(|usd| format!("{:.2}", usd))
+                .unwrap_or_else(|| "-".to_owned()),
+        );
+    }
+    if config.with_cluster {
+        fields.push(inst.cluster_id.clone().unwrap_or_else(|| "-".to_owned()));
+        fields.push(inst.cluster_role.clone().unwrap_or_else(|| "-".to_owned()));
+    }
+    if config.with_specs {
+        fields.push(inst.engine.clone());
+        fields.push(inst.engine_version.clone());
+        fields.push(inst.instance_class.clone());
+        fields.push(inst.allocated_storage.map(|g| g.to_string()).unwrap_or_else(|| "-".to_owned()));
+        fields.push(inst.availability_zone.clone().unwrap_or_else(|| "-".to_owned()));
+        fields.push(inst.availability_zone_id.clone().unwrap_or_else(|| "-".to_owned()));
+    }
+    if config.with_network {
+        let network = inst.network.as_ref();
+        fields.push(inst.vpc_id.clone().unwrap_or_else(|| "-".to_owned()));
+        fields.push(network.and_then(|n| n.vpc_name.clone()).unwrap_or_else(|| "-".to_owned()));
+        fields.push(match network {
+            Some(n) if !n.vpc_cidrs.is_empty() => n.vpc_cidrs.join(","),
+            _ => "-".to_owned(),
+        });
+        fields.push(network.and_then(|n| n.placement.clone()).unwrap_or_else(|| "-".to_owned()));
+    }
+    if !config.statuses.is_empty() {
+        fields.push(inst.status.clone());
+    }
+    for name in &config.with_params {
+        fields.push(inst.params.get(name).cloned().unwrap_or_else(|| "-".to_owned()));
+    }
+    if config.engine_family == Some(EngineFamily::Commercial) {
+        let dash = |v: Option<&str>| v.unwrap_or("-").to_owned();
+        fields.push(dash(inst.license_model.as_deref()));
+        fields.push(dash(engines::edition(&inst.engine)));
+        fields.push(dash(inst.character_set.as_deref()));
+    }
+    if let Some(headroom) = config.storage_headroom {
+        let gib = |v: Option<u32>| v.map(|g| g.to_string()).unwrap_or_else(|| "-".to_owned());
+        fields.push(format!(
+            "{}/{}GiB",
+            gib(inst.allocated_storage),
+            gib(inst.max_allocated_storage)
+        ));
+        fields.push(storage_status(inst, headroom).to_owned());
+    }
+    if config.upgrade_targets {
+        let targets = inst.upgrade_targets.as_ref();
+        let or_dash = |v: Option<&String>| v.cloned().unwrap_or_else(|| "-".to_owned());
+        fields.push(or_dash(targets.and_then(|t| t.nearest_minor.as_ref())));
+        fields.push(or_dash(targets.and_then(|t| t.latest_major.as_ref())));
+    }
+    if config.with_age {
+        fields.push(format_age(inst, Utc::now()));
+    }
+    fields
+}
+
+/// Format an RDS instance plus any optional columns enabled in `config`
+pub fn format_instance_row(inst: &RdsInstance, config: &Config, result: &ScanResult) -> String {
+    let mut fields = vec![format_instance(inst)];
+    fields.extend(optional_fields(inst, config, result));
+    fields.join("\t")
+}
+
+/// Render instances as an ASCII table: the account (or the role assumed in
+/// it), region and instance ID, then any optional columns enabled in `config`
+pub fn format_instance_table(instances: &[RdsInstance], config: &Config, result: &ScanResult) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    let mut header = vec!["ACCOUNT/ROLE".to_owned(), "REGION".to_owned(), "INSTANCE-ID".to_owned()];
+    header.extend(optional_headers(config));
+    table.set_header(header);
+    for inst in instances {
+        let mut row = vec![
+            inst.role_arn.clone().unwrap_or_else(|| inst.account_id.clone()),
+            inst.region.clone(),
+            inst.instance_id.clone(),
+        ];
+        row.extend(optional_fields(inst, config, result));
+        table.add_row(row);
+    }
+    format!("{}\n", table)
+}
+
+/// Find the scanned instance a replica reference points at.  Same-region
+/// references are bare identifiers; cross-region ones are full ARNs.
+fn resolve_replica(instances: &[RdsInstance], reference: &str, context: &RdsInstance) -> Option<usize> {
+    instances.iter().position(|i| {
+        if reference.starts_with("arn:") {
+            i.arn == reference
+        } else {
+            i.instance_id == reference && i.region == context.region && i.account_id == context.account_id
+        }
+    })
+}
+
+fn render_replicas(instances: &[RdsInstance], idx: usize, prefix: &str, seen: &mut Vec<usize>, out: &mut String) {
+    let parent = &instances[idx];
+    let count = parent.replicas.len();
+    for (n, reference) in parent.replicas.iter().enumerate() {
+        let (branch, indent) = if n + 1 == count { ("└── ", "    ") } else { ("├── ", "│   ") };
+        match resolve_replica(instances, reference, parent) {
+            Some(child) if !seen.contains(&child) => {
+                let c = &instances[child];
+                out.push_str(&format!("{}{}{} ({})\n", prefix, branch, c.instance_id, c.region));
+                seen.push(child);
+                render_replicas(instances, child, &format!("{}{}", prefix, indent), seen, out);
+            }
+            _ => out.push_str(&format!("{}{}{}\n", prefix, branch, reference)),
+        }
+    }
+}
+
+/// Render an Aurora cluster as a node with its writer, then its readers,
+/// beneath; any read replicas of a member nest under that member
+fn render_cluster(instances: &[RdsInstance], first: &RdsInstance, cluster: &str, out: &mut String) {
+    let mut members: Vec<usize> = (0..instances.len())
+        .filter(|&i| {
+            let m = &instances[i];
+            m.cluster_id.as_deref() == Some(cluster) && m.region == first.region && m.account_id == first.account_id
+        })
+        .collect();
+    members.sort_by_key(|&i| instances[i].cluster_role.as_deref() != Some("writer"));
+
+    match &first.role_arn {
+        Some(arn) => out.push_str(&format!("{}\t{}\t{} (cluster)\n", arn, first.region, cluster)),
+        None => out.push_str(&format!("{}\t{} (cluster)\n", first.region, cluster)),
+    }
+    let mut seen = members.clone();
+    for (n, &idx) in members.iter().enumerate() {
+        let (branch, indent) = if n + 1 == members.len() { ("└── ", "    ") } else { ("├── ", "│   ") };
+        let m = &instances[idx];
+        out.push_str(&format!("{}{} ({})\n", branch, m.instance_id, m.cluster_role.as_deref().unwrap_or("member")));
+        render_replicas(instances, idx, indent, &mut seen, out);
+    }
+}
+
+/// Render instances as a tree of primaries with their read replicas nested
+/// beneath, and Aurora clusters with their writer and readers.  Replicas
+/// whose source wasn't scanned are shown as roots.
+pub fn format_topology(instances: &[RdsInstance]) -> String {
+    let mut out = String::new();
+    let mut clusters = BTreeSet::new();
+    for (idx, inst) in instances.iter().enumerate() {
+        if let Some(cluster) = inst.cluster_id.as_deref() {
+            if clusters.insert((inst.account_id.as_str(), inst.region.as_str(), cluster)) {
+                render_cluster(instances, inst, cluster, &mut out);
+            }
+            continue;
+        }
+        let source = inst.replica_source.as_deref();
+        if source.is_some_and(|src| resolve_replica(instances, src, inst).is_some()) {
+            continue;
+        }
+        out.push_str(&format_instance(inst));
+        if let Some(src) = source {
+            out.push_str(&format!(" (replica of {})", src));
+        }
+        out.push('\n');
+        let mut seen = vec![idx];
+        render_replicas(instances, idx, "", &mut seen, &mut out);
+    }
+    out
+}
+
+/// Format a blue/green deployment followed by one indented line per member
+pub fn format_blue_green(bg: &BlueGreenDeployment) -> String {
+    let mut out = format!(
+        "{}\t{}\t{}\t{}\t{} -> {}",
+        bg.region, bg.identifier, bg.name, bg.status, bg.source, bg.target
+    );
+    for m in &bg.members {
+        out.push_str(&format!("\n  {} -> {}\t{}", m.source, m.target, m.status));
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -276,6 +1253,7 @@ mod tests {
             region: "us-west-2".to_string(),
             role_arn: None,
             instance_id: "my-db".to_string(),
+            ..Default::default()
         };
         let output = format_instance(&inst);
         assert_eq!(output, "us-west-2\tmy-db");
@@ -287,6 +1265,7 @@ mod tests {
             region: "us-west-2".to_string(),
             role_arn: Some("arn:aws:iam::123456789012:role/TestRole".to_string()),
             instance_id: "my-db".to_string(),
+            ..Default::default()
         };
         let output = format_instance(&inst);
         assert!(output.contains("TestRole"));
@@ -300,6 +1279,7 @@ mod tests {
             region: "us-west-2".to_string(),
             role_arn: None,
             instance_id: "my-db".to_string(),
+            ..Default::default()
         };
         let cloned = inst.clone();
         assert_eq!(cloned.region, inst.region);
@@ -311,10 +1291,628 @@ mod tests {
         let config = Config {
             regions: vec!["us-east-1".to_string()],
             mode: ScanMode::CurrentAccount,
+            ..Config::default()
         };
         // When env vars aren't set, should fall back to config
         let region = get_default_region(&config);
         // Will be from env if set, otherwise from config
         assert!(!region.is_empty());
     }
+
+    #[test]
+    fn format_instance_row_without_optional_columns() {
+        let inst = RdsInstance {
+            region: "us-west-2".to_string(),
+            instance_id: "my-db".to_string(),
+            ..Default::default()
+        };
+        let result = ScanResult::default();
+        assert_eq!(format_instance_row(&inst, &Config::default(), &result), "us-west-2\tmy-db");
+    }
+
+    #[test]
+    fn format_instance_row_joins_account_spend() {
+        let inst = RdsInstance {
+            account_id: "123456789012".to_string(),
+            region: "us-west-2".to_string(),
+            instance_id: "my-db".to_string(),
+            ..Default::default()
+        };
+        let result = ScanResult {
+            spend: BTreeMap::from([("123456789012".to_string(), 1234.5)]),
+            ..Default::default()
+        };
+        let config = Config {
+            spend_days: Some(30),
+            ..Config::default()
+        };
+        assert_eq!(format_instance_row(&inst, &config, &result), "us-west-2\tmy-db\t1234.50");
+    }
+
+    #[test]
+    fn format_instance_row_with_specs() {
+        let inst = RdsInstance {
+            region: "us-west-2".to_string(),
+            instance_id: "my-db".to_string(),
+            engine: "postgres".to_string(),
+            engine_version: "15.4".to_string(),
+            instance_class: "db.r6g.large".to_string(),
+            allocated_storage: Some(100),
+            availability_zone: Some("us-west-2a".to_string()),
+            availability_zone_id: Some("usw2-az1".to_string()),
+            ..Default::default()
+        };
+        let config = Config {
+            with_specs: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            format_instance_row(&inst, &config, &ScanResult::default()),
+            "us-west-2\tmy-db\tpostgres\t15.4\tdb.r6g.large\t100\tus-west-2a\tusw2-az1"
+        );
+    }
+
+    #[test]
+    fn format_instance_row_with_network() {
+        let inst = RdsInstance {
+            region: "us-west-2".to_string(),
+            instance_id: "my-db".to_string(),
+            vpc_id: Some("vpc-0abc".to_string()),
+            network: Some(VpcPlacement {
+                vpc_name: Some("data".to_string()),
+                vpc_cidrs: vec!["10.0.0.0/16".to_string(), "10.1.0.0/16".to_string()],
+                placement: Some("private".to_string()),
+            }),
+            ..Default::default()
+        };
+        let config = Config {
+            with_network: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            format_instance_row(&inst, &config, &ScanResult::default()),
+            "us-west-2\tmy-db\tvpc-0abc\tdata\t10.0.0.0/16,10.1.0.0/16\tprivate"
+        );
+        let unresolved = RdsInstance { network: None, ..inst };
+        assert_eq!(
+            format_instance_row(&unresolved, &config, &ScanResult::default()),
+            "us-west-2\tmy-db\tvpc-0abc\t-\t-\t-"
+        );
+    }
+
+    #[test]
+    fn format_instance_row_with_upgrade_targets() {
+        let inst = RdsInstance {
+            region: "us-west-2".to_string(),
+            instance_id: "my-db".to_string(),
+            upgrade_targets: Some(UpgradeTargets {
+                nearest_minor: None,
+                latest_major: Some("16.2".to_string()),
+            }),
+            ..Default::default()
+        };
+        let config = Config {
+            upgrade_targets: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            format_instance_row(&inst, &config, &ScanResult::default()),
+            "us-west-2\tmy-db\t-\t16.2"
+        );
+    }
+
+    #[test]
+    fn format_instance_table_shows_account_or_role_and_optional_columns() {
+        let instances = vec![
+            RdsInstance {
+                account_id: "111111111111".to_string(),
+                region: "us-east-1".to_string(),
+                instance_id: "orders-db".to_string(),
+                cluster_id: Some("orders".to_string()),
+                ..Default::default()
+            },
+            RdsInstance {
+                account_id: "222222222222".to_string(),
+                role_arn: Some("arn:aws:iam::222222222222:role/Audit".to_string()),
+                region: "eu-west-1".to_string(),
+                instance_id: "billing-db".to_string(),
+                ..Default::default()
+            },
+        ];
+        let config = Config {
+            with_cluster: true,
+            ..Config::default()
+        };
+        let table = format_instance_table(&instances, &config, &ScanResult::default());
+        let header = table.lines().nth(1).unwrap();
+        for column in ["ACCOUNT/ROLE", "REGION", "INSTANCE-ID", "CLUSTER", "CLUSTER-ROLE"] {
+            assert!(header.contains(column), "missing {column}");
+        }
+        let orders = table.lines().find(|l| l.contains("orders-db")).unwrap();
+        assert!(orders.contains("111111111111") && orders.contains("orders"));
+        assert!(table.contains("arn:aws:iam::222222222222:role/Audit"));
+    }
+
+    #[test]
+    fn scan_result_merge_appends_records() {
+        let mut result = ScanResult::default();
+        result.merge(ScanResult {
+            instances: vec![RdsInstance::default()],
+            blue_green: vec![BlueGreenDeployment::default()],
+            cluster_endpoints: vec![ClusterEndpoint::default()],
+            pending_actions: vec![PendingAction::default()],
+            ..Default::default()
+        });
+        result.merge(ScanResult {
+            instances: vec![RdsInstance::default()],
+            org_accounts: BTreeSet::from(["111111111111".to_string()]),
+            ..Default::default()
+        });
+        assert_eq!(result.instances.len(), 2);
+        assert_eq!(result.blue_green.len(), 1);
+        assert_eq!(result.cluster_endpoints.len(), 1);
+        assert_eq!(result.pending_actions.len(), 1);
+        assert!(result.org_accounts.contains("111111111111"));
+    }
+
+    #[test]
+    fn format_instance_row_with_license_columns() {
+        let inst = RdsInstance {
+            region: "us-west-2".to_string(),
+            instance_id: "erp".to_string(),
+            engine: "oracle-se2".to_string(),
+            license_model: Some("bring-your-own-license".to_string()),
+            character_set: Some("AL32UTF8".to_string()),
+            ..Default::default()
+        };
+        let config = Config {
+            engine_family: Some(EngineFamily::Commercial),
+            ..Config::default()
+        };
+        assert_eq!(
+            format_instance_row(&inst, &config, &ScanResult::default()),
+            "us-west-2\terp\tbring-your-own-license\tStandard Edition 2\tAL32UTF8"
+        );
+    }
+
+    #[test]
+    fn format_blue_green_lists_members() {
+        let bg = BlueGreenDeployment {
+            region: "us-west-2".to_string(),
+            identifier: "bgd-abc123".to_string(),
+            name: "orders-pg16".to_string(),
+            source: "arn:aws:rds:us-west-2:123456789012:db:orders".to_string(),
+            target: "arn:aws:rds:us-west-2:123456789012:db:orders-green-x1".to_string(),
+            status: "AVAILABLE".to_string(),
+            members: vec![SwitchoverMember {
+                source: "arn:aws:rds:us-west-2:123456789012:db:orders".to_string(),
+                target: "arn:aws:rds:us-west-2:123456789012:db:orders-green-x1".to_string(),
+                status: "AVAILABLE".to_string(),
+            }],
+            ..Default::default()
+        };
+        let out = format_blue_green(&bg);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("us-west-2\tbgd-abc123\torders-pg16\tAVAILABLE"));
+        assert!(lines[1].starts_with("  arn:aws:rds:us-west-2:123456789012:db:orders ->"));
+    }
+
+    fn topology_fixture() -> Vec<RdsInstance> {
+        let inst = |region: &str, id: &str, source: Option<&str>, replicas: &[&str]| RdsInstance {
+            account_id: "123456789012".to_string(),
+            region: region.to_string(),
+            instance_id: id.to_string(),
+            arn: format!("arn:aws:rds:{}:123456789012:db:{}", region, id),
+            replica_source: source.map(str::to_owned),
+            replicas: replicas.iter().map(|r| r.to_string()).collect(),
+            ..Default::default()
+        };
+        vec![
+            inst(
+                "us-west-2",
+                "orders",
+                None,
+                &["orders-r1", "arn:aws:rds:us-east-1:123456789012:db:orders-dr"],
+            ),
+            inst("us-west-2", "orders-r1", Some("orders"), &["orders-r1a"]),
+            inst("us-west-2", "orders-r1a", Some("orders-r1"), &[]),
+            inst(
+                "us-east-1",
+                "orders-dr",
+                Some("arn:aws:rds:us-west-2:123456789012:db:orders"),
+                &[],
+            ),
+            inst("us-west-2", "billing", None, &[]),
+        ]
+    }
+
+    #[test]
+    fn format_topology_nests_replicas() {
+        let out = format_topology(&topology_fixture());
+        let expected = "\
+us-west-2\torders
+├── orders-r1 (us-west-2)
+│   └── orders-r1a (us-west-2)
+└── orders-dr (us-east-1)
+us-west-2\tbilling
+";
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn format_topology_groups_cluster_members_writer_first() {
+        let member = |id: &str, role: &str| RdsInstance {
+            account_id: "123456789012".to_string(),
+            region: "us-west-2".to_string(),
+            instance_id: id.to_string(),
+            cluster_id: Some("orders".to_string()),
+            cluster_role: Some(role.to_string()),
+            ..Default::default()
+        };
+        let mut instances = vec![member("orders-2", "reader"), member("orders-1", "writer")];
+        instances.push(RdsInstance {
+            account_id: "123456789012".to_string(),
+            region: "us-west-2".to_string(),
+            instance_id: "billing".to_string(),
+            ..Default::default()
+        });
+        let expected = "\
+us-west-2\torders (cluster)
+├── orders-1 (writer)
+└── orders-2 (reader)
+us-west-2\tbilling
+";
+        assert_eq!(format_topology(&instances), expected);
+    }
+
+    #[test]
+    fn format_topology_shows_orphan_replica_as_root() {
+        let mut instances = topology_fixture();
+        instances.remove(0);
+        let out = format_topology(&instances);
+        assert!(out.contains("us-west-2\torders-r1 (replica of orders)"));
+        assert!(out.contains("us-east-1\torders-dr (replica of arn:aws:rds:us-west-2:123456789012:db:orders)"));
+    }
+
+    fn storage_inst(engine: &str, allocated: Option<u32>, max: Option<u32>) -> RdsInstance {
+        RdsInstance {
+            region: "us-west-2".to_string(),
+            instance_id: "my-db".to_string(),
+            engine: engine.to_string(),
+            allocated_storage: allocated,
+            max_allocated_storage: max,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn storage_status_flags_disabled_autoscaling() {
+        assert_eq!(storage_status(&storage_inst("postgres", Some(100), None), 20), "autoscaling-disabled");
+    }
+
+    #[test]
+    fn storage_status_flags_near_ceiling() {
+        assert_eq!(storage_status(&storage_inst("postgres", Some(850), Some(1000)), 20), "near-ceiling");
+        assert_eq!(storage_status(&storage_inst("postgres", Some(800), Some(1000)), 20), "ok");
+        assert_eq!(storage_status(&storage_inst("postgres", Some(100), Some(1000)), 20), "ok");
+    }
+
+    #[test]
+    fn storage_status_skips_aurora() {
+        assert_eq!(storage_status(&storage_inst("aurora-postgresql", Some(1), None), 20), "n/a");
+    }
+
+    #[test]
+    fn format_instance_row_with_storage_columns() {
+        let config = Config {
+            storage_headroom: Some(20),
+            ..Config::default()
+        };
+        let row = format_instance_row(
+            &storage_inst("mysql", Some(950), Some(1000)),
+            &config,
+            &ScanResult::default(),
+        );
+        assert_eq!(row, "us-west-2\tmy-db\t950/1000GiB\tnear-ceiling");
+    }
+
+    #[test]
+    fn format_instance_row_with_params_in_requested_order() {
+        let inst = RdsInstance {
+            region: "us-west-2".to_string(),
+            instance_id: "my-db".to_string(),
+            params: BTreeMap::from([
+                ("max_connections".to_string(), "500".to_string()),
+                ("shared_buffers".to_string(), "{DBInstanceClassMemory/32768}".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let config = Config {
+            with_params: vec!["shared_buffers".to_string(), "max_connections".to_string(), "work_mem".to_string()],
+            ..Config::default()
+        };
+        let row = format_instance_row(&inst, &config, &ScanResult::default());
+        assert_eq!(row, "us-west-2\tmy-db\t{DBInstanceClassMemory/32768}\t500\t-");
+    }
+
+    #[test]
+    fn engine_matches_listed_engines_only() {
+        let inst = RdsInstance {
+            engine: "aurora-postgresql".to_string(),
+            ..Default::default()
+        };
+        let engines = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert!(engine_matches(&inst, &[]));
+        assert!(engine_matches(&inst, &engines(&["postgres", "aurora-postgresql"])));
+        assert!(!engine_matches(&inst, &engines(&["postgres"])));
+    }
+
+    #[test]
+    fn status_matches_listed_states_only() {
+        let inst = RdsInstance {
+            status: "stopped".to_string(),
+            ..Default::default()
+        };
+        assert!(status_matches(&inst, &[]));
+        assert!(status_matches(&inst, &["available".to_string(), "stopped".to_string()]));
+        assert!(!status_matches(&inst, &["available".to_string()]));
+    }
+
+    #[test]
+    fn public_matches_publicly_accessible_only_with_public_only() {
+        let public = RdsInstance {
+            publicly_accessible: true,
+            ..Default::default()
+        };
+        let private = RdsInstance::default();
+        assert!(public_matches(&public, true));
+        assert!(!public_matches(&private, true));
+        assert!(public_matches(&public, false));
+        assert!(public_matches(&private, false));
+    }
+
+    #[test]
+    fn encryption_matches_unencrypted_storage_only_with_unencrypted_only() {
+        let encrypted = RdsInstance {
+            storage_encrypted: true,
+            ..Default::default()
+        };
+        let unencrypted = RdsInstance::default();
+        assert!(!encryption_matches(&encrypted, true));
+        assert!(encryption_matches(&unencrypted, true));
+        assert!(encryption_matches(&encrypted, false));
+        assert!(encryption_matches(&unencrypted, false));
+    }
+
+    #[test]
+    fn is_single_az_skips_standbys_and_aurora_members() {
+        let standalone = RdsInstance::default();
+        assert!(is_single_az(&standalone));
+        let standby = RdsInstance {
+            multi_az: true,
+            secondary_availability_zone: Some("us-east-1b".to_string()),
+            ..Default::default()
+        };
+        assert!(!is_single_az(&standby));
+        let member = RdsInstance {
+            cluster_id: Some("orders".to_string()),
+            ..Default::default()
+        };
+        assert!(!is_single_az(&member));
+    }
+
+    #[test]
+    fn tags_match_requires_every_filter() {
+        let inst = RdsInstance {
+            tags: BTreeMap::from([
+                ("team".to_string(), "payments".to_string()),
+                ("env".to_string(), "prod".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let filter = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        assert!(tags_match(&inst, &[]));
+        assert!(tags_match(&inst, &filter(&[("team", "payments"), ("env", "prod")])));
+        assert!(!tags_match(&inst, &filter(&[("team", "payments"), ("env", "dev")])));
+        assert!(!tags_match(&inst, &filter(&[("owner", "")])));
+    }
+
+    #[test]
+    fn age_matches_older_and_newer_than() {
+        let now = Utc::now();
+        let aged = |days: i64| RdsInstance {
+            created: Some(now - chrono::Duration::days(days)),
+            ..Default::default()
+        };
+        let config = Config {
+            older_than: Some(std::time::Duration::from_secs(365 * 86_400)),
+            ..Config::default()
+        };
+        assert!(age_matches(&aged(400), &config, now));
+        assert!(!age_matches(&aged(30), &config, now));
+        assert!(!age_matches(&RdsInstance::default(), &config, now));
+
+        let config = Config {
+            newer_than: Some(std::time::Duration::from_secs(7 * 86_400)),
+            ..Config::default()
+        };
+        assert!(age_matches(&aged(2), &config, now));
+        assert!(!age_matches(&aged(30), &config, now));
+        assert!(age_matches(&RdsInstance::default(), &config, now));
+    }
+
+    #[test]
+    fn format_instance_row_with_cluster_columns() {
+        let config = Config {
+            with_cluster: true,
+            ..Config::default()
+        };
+        let member = RdsInstance {
+            region: "us-west-2".to_string(),
+            instance_id: "orders-1".to_string(),
+            cluster_id: Some("orders".to_string()),
+            cluster_role: Some("writer".to_string()),
+            ..Default::default()
+        };
+        let row = format_instance_row(&member, &config, &ScanResult::default());
+        assert_eq!(row, "us-west-2\torders-1\torders\twriter");
+        let standalone = RdsInstance {
+            region: "us-west-2".to_string(),
+            instance_id: "legacy".to_string(),
+            ..Default::default()
+        };
+        let row = format_instance_row(&standalone, &config, &ScanResult::default());
+        assert_eq!(row, "us-west-2\tlegacy\t-\t-");
+    }
+
+    #[test]
+    fn format_instance_row_with_age_column() {
+        let config = Config {
+            with_age: true,
+            ..Config::default()
+        };
+        let inst = RdsInstance {
+            region: "us-west-2".to_string(),
+            instance_id: "my-db".to_string(),
+            created: Some(Utc::now() - chrono::Duration::days(412)),
+            ..Default::default()
+        };
+        assert_eq!(format_instance_row(&inst, &config, &ScanResult::default()), "us-west-2\tmy-db\t412d");
+    }
+
+    #[test]
+    fn format_instance_row_with_account_details() {
+        let inst = RdsInstance {
+            account_id: "111111111111".to_string(),
+            region: "us-east-1".to_string(),
+            instance_id: "db1".to_string(),
+            ..Default::default()
+        };
+        let mut result = ScanResult::default();
+        result.accounts.insert(
+            "111111111111".to_string(),
+            AccountInfo {
+                id: "111111111111".to_string(),
+                email: "owner@example.com".to_string(),
+                status: "ACTIVE".to_string(),
+                ..Default::default()
+            },
+        );
+        let config = Config {
+            account_details: true,
+            ..Config::default()
+        };
+        assert_eq!(optional_headers(&config), ["ACCOUNT-EMAIL", "ACCOUNT-STATUS"]);
+        let row = format_instance_row(&inst, &config, &result);
+        assert_eq!(row, "us-east-1\tdb1\towner@example.com\tACTIVE");
+        let other = RdsInstance {
+            account_id: "222222222222".to_string(),
+            ..inst
+        };
+        assert!(format_instance_row(&other, &config, &result).ends_with("\t-\t-"));
+    }
+
+    #[test]
+    fn format_instance_row_with_account_name() {
+        let inst = RdsInstance {
+            account_id: "111111111111".to_string(),
+            region: "us-east-1".to_string(),
+            instance_id: "db1".to_string(),
+            ..Default::default()
+        };
+        let mut result = ScanResult::default();
+        result.accounts.insert(
+            "111111111111".to_string(),
+            AccountInfo {
+                id: "111111111111".to_string(),
+                name: "payments-prod".to_string(),
+                ..Default::default()
+            },
+        );
+        let config = Config {
+            account_names: true,
+            ..Config::default()
+        };
+        assert_eq!(format_instance_row(&inst, &config, &result), "us-east-1\tdb1\tpayments-prod");
+        // An account without an alias, or not looked up
+        result.accounts.get_mut("111111111111").unwrap().name.clear();
+        assert_eq!(format_instance_row(&inst, &config, &result), "us-east-1\tdb1\t-");
+        assert_eq!(optional_headers(&config), vec!["ACCOUNT-NAME"]);
+    }
+
+    #[test]
+    fn scan_result_merge_keeps_warnings() {
+        let mut result = ScanResult::default();
+        result.warn("111 us-east-1: region skipped".to_string());
+        let mut other = ScanResult::default();
+        other.warn("222 us-west-2: cannot list snapshots".to_string());
+        result.merge(other);
+        assert_eq!(
+            result.warnings,
+            vec!["111 us-east-1: region skipped", "222 us-west-2: cannot list snapshots"]
+        );
+    }
+
+    #[test]
+    fn over_budget_names_the_exceeded_limit() {
+        let config = Config {
+            max_api_calls: Some(500),
+            max_duration: Some(Duration::from_secs(1200)),
+            ..Config::default()
+        };
+        assert_eq!(over_budget(&config, 499, Duration::from_secs(60)), None);
+        assert_eq!(
+            over_budget(&config, 500, Duration::from_secs(60)).as_deref(),
+            Some("--max-api-calls 500 reached (500 calls made)")
+        );
+        assert_eq!(
+            over_budget(&config, 10, Duration::from_secs(1200)).as_deref(),
+            Some("--max-duration 1200s reached")
+        );
+        assert_eq!(over_budget(&Config::default(), u64::MAX, Duration::MAX), None);
+    }
+
+    #[test]
+    fn scan_errors_are_grouped_per_account() {
+        let failed = |account: &str, region: &str, kind: ScanErrorKind| ScanResult {
+            errors: vec![ScanError {
+                account_id: Some(account.to_string()),
+                region: region.to_string(),
+                kind,
+                ..Default::default()
+            }],
+            timings: vec![RegionTiming {
+                account_id: account.to_string(),
+                region: region.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let scanned = |account: &str, region: &str| ScanResult {
+            timings: failed(account, region, ScanErrorKind::Other).timings,
+            ..Default::default()
+        };
+        let mut result = failed("111111111111", "us-east-1", ScanErrorKind::AccessDenied);
+        result.merge(failed("111111111111", "eu-west-1", ScanErrorKind::AccessDenied));
+        result.merge(failed("222222222222", "us-east-1", ScanErrorKind::Throttled));
+        result.merge(scanned("222222222222", "eu-west-1"));
+        // An account whose enabled regions could not be listed has no timings
+        result.errors.push(ScanError {
+            account_id: Some("333333333333".to_string()),
+            region: "us-east-1".to_string(),
+            kind: ScanErrorKind::AccessDenied,
+            ..Default::default()
+        });
+        assert_eq!(
+            format_failed_accounts(&result.errors, &result.timings),
+            "3 account(s) not fully scanned:\n\
+             111111111111\tall regions failed (access_denied)\n\
+             222222222222\t1/2 regions failed: us-east-1 (throttled)\n\
+             333333333333\tall regions failed (access_denied)\n"
+        );
+        assert_eq!(format_failed_accounts(&[], &result.timings), "");
+    }
 }
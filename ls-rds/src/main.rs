@@ -7,13 +7,47 @@
 use clap::Parser;
 use eyre::Result;
 use log::info;
-use ls_rds::{format_instance, get_or_create_log_dir, run, Cli, Config};
+use ls_rds::{
+    aggregate, api_stats, calendar, cluster_endpoints, clusters, compliance, error_json_lines, export,
+    format_blue_green, format_failed_accounts, format_identity, format_instance_row, format_instance_table,
+    format_topology, get_or_create_log_dir, global_clusters, json, maintenance, pick, plan, proxies, redact, run,
+    snapshots, terraform, timings, tui, whoami, Cli, Config, GroupBy, OutputFormat, ScanMode, Scanner,
+};
+use ls_rds::config::resolve_output;
+use ls_vpc::settings::Settings;
 use std::{
-    fs::OpenOptions,
-    io::Write,
+    fs::{self, OpenOptions},
+    io::{IsTerminal, Write},
     time::Instant,
 };
 
+/// Scan in the background while the fuzzy finder lists instances as they
+/// arrive.
+async fn pick_instance(config: &Config, redactor: &mut redact::Redactor) -> Result<()> {
+    let (feed, scan) = pick::spawn_feed(config.clone());
+
+    let picked = tokio::task::spawn_blocking(move || pick::pick(feed)).await??;
+    let Some(mut inst) = picked else {
+        if scan.is_finished() {
+            scan.await??;
+        } else {
+            scan.abort();
+        }
+        return Ok(());
+    };
+    scan.abort();
+
+    if config.redact {
+        redactor.redact_instance(&mut inst);
+    }
+    if config.quiet {
+        println!("{}", inst.instance_id);
+    } else {
+        println!("{}", tui::detail_lines(&inst).join("\n"));
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Set up file logging
@@ -41,16 +75,192 @@ async fn main() -> Result<()> {
     info!("Logging to {}", log_file_path.display());
 
     let overall_start = Instant::now();
-    let cli = Cli::parse();
-    let config = Config::try_from(cli)?;
+    let settings = Settings::load()?;
+    let cli = Cli::parse_from(settings.expand_view("ls-rds", std::env::args().collect())?);
+    let config = Config::try_from_with(cli, &settings)?;
 
-    let result = run(&config).await?;
+    if config.schema {
+        print!("{}", json::SCHEMA);
+        return Ok(());
+    }
+
+    if config.whoami {
+        print!("{}", format_identity(&whoami(&config).await?));
+        return Ok(());
+    }
+    if config.verbose {
+        eprint!("{}", format_identity(&whoami(&config).await?));
+    }
+
+    // Every redacted output goes through one redactor so an account keeps
+    // its alias from the scan result to --api-stats
+    let mut redactor = redact::Redactor::default();
+    if config.dry_run {
+        let mut scan_plan = Scanner::from_config(config.clone()).plan().await?;
+        if config.redact {
+            redactor.redact_plan(&mut scan_plan);
+        }
+        print!("{}", plan::format_plan(&scan_plan));
+        return Ok(());
+    }
+    if config.pick {
+        return pick_instance(&config, &mut redactor).await;
+    }
+
+    let mut result = run(&config).await?;
+    if config.redact {
+        redactor.redact_result(&mut result);
+    }
+
+    // --limit/--page only trim what is printed; the account totals and the
+    // compliance gates still see every instance
+    let all_instances = std::mem::take(&mut result.instances);
+    result.instances = config.page.window(all_instances.iter()).cloned().collect();
 
     // Output results
-    for inst in &result.instances {
-        println!("{}", format_instance(inst));
+    if config.tui {
+        tui::run(&result.instances)?;
+    } else if config.json {
+        print!("{}", json::to_json(&result)?);
+    } else if let Some(delimiter) = config.csv {
+        print!("{}", export::to_csv(&result.instances, &config, &result, delimiter)?);
+    } else if config.emit_tf_imports {
+        print!("{}", terraform::format_tf_imports(&result.instances, &config.tf_address));
+    } else if config.stats {
+        print!("{}", aggregate::format_engine_stats(&all_instances));
+    } else if config.blue_green {
+        for bg in &result.blue_green {
+            println!("{}", format_blue_green(bg));
+        }
+    } else if config.clusters {
+        for cluster in &result.clusters {
+            println!("{}", clusters::format_cluster(cluster));
+        }
+    } else if config.global_clusters {
+        for global in &result.global_clusters {
+            println!("{}", global_clusters::format_global_cluster(global));
+        }
+    } else if config.cluster_endpoints {
+        for ep in &result.cluster_endpoints {
+            println!("{}", cluster_endpoints::format_cluster_endpoint(ep));
+        }
+    } else if config.maintenance {
+        print!("{}", maintenance::format_pending_actions(&result.pending_actions));
+    } else if config.snapshots {
+        let now = chrono::Utc::now();
+        let older_than = config.snapshot_older_than.and_then(|d| chrono::Duration::from_std(d).ok());
+        for snap in &result.snapshots {
+            println!("{}", snapshots::format_snapshot(snap, older_than, &result.org_accounts, now));
+        }
+        println!();
+        print!(
+            "{}",
+            snapshots::format_snapshot_counts(&result.snapshots, older_than, &result.org_accounts, now)
+        );
+    } else if config.maintenance_calendar {
+        let now = chrono::Utc::now();
+        print!("{}", calendar::format_calendar(&result.instances, now));
+        if let Some(path) = &config.ical {
+            fs::write(path, calendar::to_ical(&result.instances, now))?;
+            info!("Wrote maintenance calendar to {}", path.display());
+        }
+    } else if config.arns {
+        for inst in &result.instances {
+            println!("{}", inst.arn);
+        }
+        for proxy in &result.proxies {
+            println!("{}", proxy.arn);
+        }
+    } else if config.topology {
+        print!("{}", format_topology(&result.instances));
+    } else if config.group_by == Some(GroupBy::Cluster) {
+        print!("{}", clusters::format_by_cluster(&result.instances, &config, &result));
+    } else {
+        // Multi-account runs lead with per-account totals
+        if config.mode != ScanMode::CurrentAccount {
+            println!("{}", aggregate::format_account_summary(&all_instances));
+        }
+        if resolve_output(config.output, std::io::stdout().is_terminal()) == OutputFormat::Table {
+            print!("{}", format_instance_table(&result.instances, &config, &result));
+            if !result.proxies.is_empty() {
+                print!("{}", proxies::format_proxy_table(&result.proxies));
+            }
+        } else {
+            for inst in &result.instances {
+                println!("{}", format_instance_row(inst, &config, &result));
+            }
+            for proxy in &result.proxies {
+                println!("{}", proxies::format_proxy(proxy));
+            }
+        }
+    }
+
+    if config.page.trims(all_instances.len()) {
+        eprintln!(
+            "Showing {} of {} instance(s) (page {})",
+            result.instances.len(),
+            all_instances.len(),
+            config.page.page
+        );
+    }
+
+    for warning in &result.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if let Some(reason) = &result.aborted {
+        eprintln!("error: scan aborted, the output above is partial: {}", reason);
+    }
+
+    if config.timings {
+        eprint!("{}", timings::format_timings(&result.timings));
+    }
+
+    if config.api_stats {
+        let mut stats = result.api_calls.clone();
+        if config.redact {
+            stats = redactor.redact_api_stats(stats);
+        }
+        eprint!("{}", api_stats::format_api_stats(&stats));
     }
 
     info!("Total runtime: {:.2?}", overall_start.elapsed());
+
+    // Report every failing gate before exiting
+    let mut failed = false;
+    if config.fail_on_unencrypted {
+        let findings = compliance::unencrypted_findings(&all_instances);
+        if !findings.is_empty() {
+            eprintln!("{} resource(s) without storage encryption:", findings.len());
+            eprint!("{}", compliance::format_findings(&findings));
+            failed = true;
+        }
+    }
+    if let Some(scope) = &config.require_multi_az {
+        let findings = compliance::single_az_findings(&all_instances, scope);
+        if !findings.is_empty() {
+            eprintln!("{} database(s) in a single Availability Zone:", findings.len());
+            eprint!("{}", compliance::format_findings(&findings));
+            failed = true;
+        }
+    }
+    if let Some(min_days) = config.min_backup_retention {
+        let findings = compliance::backup_retention_findings(&all_instances, min_days);
+        if !findings.is_empty() {
+            eprintln!("{} database(s) keeping backups for fewer than {} day(s):", findings.len(), min_days);
+            eprint!("{}", compliance::format_findings(&findings));
+            failed = true;
+        }
+    }
+    if !result.errors.is_empty() {
+        if config.json {
+            eprint!("{}", error_json_lines(&result.errors)?);
+        } else {
+            eprint!("{}", format_failed_accounts(&result.errors, &result.timings));
+        }
+        failed = true;
+    }
+    if failed || result.aborted.is_some() {
+        std::process::exit(1);
+    }
     Ok(())
 }
@@ -0,0 +1,160 @@
+//! Pending maintenance actions for ls-rds
+//!
+//! `--maintenance` lists what RDS has queued for each instance and cluster
+//! (engine minor upgrades, OS patches, CA certificate rotations …) with the
+//! date it will be applied, soonest first, so maintenance windows can be
+//! planned across every scanned account.  Actions without a date wait for
+//! someone to opt in and sort last.
+
+use crate::api_stats::ApiStats;
+use crate::retry;
+use aws_sdk_rds as rds;
+use aws_types::region::Region;
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+/// One maintenance action waiting on an instance or cluster
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PendingAction {
+    pub account_id: String,
+    pub region: String,
+    /// Instance or cluster identifier (the last part of its ARN)
+    pub resource_id: String,
+    /// e.g. `system-update`, `db-upgrade`, `ca-certificate-rotation`
+    pub action: String,
+    pub description: Option<String>,
+    /// `immediate`, `next-maintenance` or `undo-opt-in` when someone opted
+    /// in to the action
+    pub opt_in_status: Option<String>,
+    /// Applied in the first maintenance window after this date
+    pub auto_applied_after: Option<DateTime<Utc>>,
+    /// Applied at this date regardless of the maintenance window
+    pub forced_apply: Option<DateTime<Utc>>,
+    /// When RDS will actually apply it, given the dates and any opt-in
+    pub current_apply: Option<DateTime<Utc>>,
+}
+
+impl PendingAction {
+    /// The date the action takes effect: RDS's own estimate, else the
+    /// earlier of the auto-apply and forced dates
+    pub fn apply_date(&self) -> Option<DateTime<Utc>> {
+        self.current_apply
+            .or(match (self.auto_applied_after, self.forced_apply) {
+                (Some(a), Some(f)) => Some(a.min(f)),
+                (a, f) => a.or(f),
+            })
+    }
+}
+
+fn to_utc(t: Option<&rds::primitives::DateTime>) -> Option<DateTime<Utc>> {
+    t.and_then(|t| DateTime::from_timestamp(t.secs(), 0))
+}
+
+/// List the pending maintenance actions of every instance and cluster in
+/// one region
+pub async fn list_pending_actions(
+    client: &rds::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    retries: u32,
+) -> Result<Vec<PendingAction>> {
+    let mut actions = Vec::new();
+    let mut marker: Option<String> = None;
+    loop {
+        let page = retry::with_retries(retries, || {
+            stats.record("rds", "DescribePendingMaintenanceActions", region.as_ref(), account_id);
+            client.describe_pending_maintenance_actions().set_marker(marker.clone()).send()
+        })
+        .await?;
+        for resource in page.pending_maintenance_actions() {
+            let arn = resource.resource_identifier().unwrap_or_default();
+            let resource_id = arn.rsplit(':').next().unwrap_or(arn);
+            for detail in resource.pending_maintenance_action_details() {
+                actions.push(PendingAction {
+                    account_id: account_id.to_owned(),
+                    region: region.to_string(),
+                    resource_id: resource_id.to_owned(),
+                    action: detail.action().unwrap_or_default().to_owned(),
+                    description: detail.description().map(str::to_owned),
+                    opt_in_status: detail.opt_in_status().map(str::to_owned),
+                    auto_applied_after: to_utc(detail.auto_applied_after_date()),
+                    forced_apply: to_utc(detail.forced_apply_date()),
+                    current_apply: to_utc(detail.current_apply_date()),
+                });
+            }
+        }
+        match page.marker() {
+            Some(next) => marker = Some(next.to_owned()),
+            None => break,
+        }
+    }
+    Ok(actions)
+}
+
+/// Format the actions as TSV rows, soonest first: account, region,
+/// resource, action, apply date (`-` = waiting for an opt-in), description
+pub fn format_pending_actions(actions: &[PendingAction]) -> String {
+    let mut rows: Vec<&PendingAction> = actions.iter().collect();
+    // `None` sorts first in an Option, so key on "has no date" first
+    rows.sort_by_key(|&a| (a.apply_date().is_none(), a.apply_date(), &a.account_id, &a.region, &a.resource_id));
+    let mut out = String::new();
+    for a in rows {
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            a.account_id,
+            a.region,
+            a.resource_id,
+            a.action,
+            a.apply_date().map_or_else(|| "-".to_owned(), |d| d.format("%Y-%m-%d %H:%M UTC").to_string()),
+            a.description.as_deref().unwrap_or("-")
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(rfc3339: &str) -> Option<DateTime<Utc>> {
+        Some(DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc))
+    }
+
+    #[test]
+    fn apply_date_prefers_the_current_estimate() {
+        let mut action = PendingAction {
+            auto_applied_after: at("2026-03-01T00:00:00Z"),
+            forced_apply: at("2026-02-01T00:00:00Z"),
+            ..Default::default()
+        };
+        assert_eq!(action.apply_date(), at("2026-02-01T00:00:00Z"));
+        action.current_apply = at("2026-01-15T05:00:00Z");
+        assert_eq!(action.apply_date(), at("2026-01-15T05:00:00Z"));
+        assert_eq!(PendingAction::default().apply_date(), None);
+    }
+
+    #[test]
+    fn format_pending_actions_lists_soonest_first() {
+        let action = |resource_id: &str, forced: Option<DateTime<Utc>>| PendingAction {
+            account_id: "111111111111".to_string(),
+            region: "us-east-1".to_string(),
+            resource_id: resource_id.to_string(),
+            action: "system-update".to_string(),
+            forced_apply: forced,
+            ..Default::default()
+        };
+        let actions = vec![
+            action("opt-in", None),
+            action("later", at("2026-03-01T00:00:00Z")),
+            action("sooner", at("2026-02-01T04:30:00Z")),
+        ];
+        assert_eq!(
+            format_pending_actions(&actions),
+            "111111111111\tus-east-1\tsooner\tsystem-update\t2026-02-01 04:30 UTC\t-\n\
+             111111111111\tus-east-1\tlater\tsystem-update\t2026-03-01 00:00 UTC\t-\n\
+             111111111111\tus-east-1\topt-in\tsystem-update\t-\t-\n"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Scan manifest for ls-rds
+//!
+//! `--manifest FILE` appends one JSON line per account/region as soon as its
+//! scan finishes, holding that pair's full result.  After an interruption,
+//! `--resume` reads the manifest back and only scans the pairs missing from
+//! it, so a multi-hour organization run picks up where it stopped and still
+//! reports every pair.  Pairs that finished with warnings are not recorded
+//! and are scanned again on resume.
+
+use crate::ScanResult;
+use eyre::{Result, WrapErr};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+/// Results of completed scans, by account ID and region
+pub type Completed = BTreeMap<(String, String), ScanResult>;
+
+/// One completed account/region; written from a borrowed result, read
+/// back into an owned one
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry<R> {
+    account_id: String,
+    region: String,
+    result: R,
+}
+
+/// Start an empty manifest, replacing any left by an earlier run
+pub fn create(path: &Path) -> Result<()> {
+    File::create(path).wrap_err_with(|| format!("cannot create manifest {}", path.display()))?;
+    Ok(())
+}
+
+/// Read the completed pairs back from a manifest; a missing file means
+/// nothing has been scanned yet.  Lines that do not parse (the last one is
+/// cut short when a run is killed mid-write) are skipped.
+pub fn load(path: &Path) -> Result<Completed> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Completed::new()),
+        Err(e) => return Err(e).wrap_err_with(|| format!("cannot read manifest {}", path.display())),
+    };
+    Ok(parse(&text, path))
+}
+
+fn parse(text: &str, path: &Path) -> Completed {
+    let mut completed = Completed::new();
+    for (n, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        match serde_json::from_str::<Entry<ScanResult>>(line) {
+            Ok(entry) => {
+                completed.insert((entry.account_id, entry.region), entry.result);
+            }
+            Err(e) => warn!("{}:{}: skipping unreadable manifest line: {}", path.display(), n + 1, e),
+        }
+    }
+    completed
+}
+
+/// Record a finished account/region at the end of the manifest
+pub fn append(path: &Path, account_id: &str, region: &str, result: &ScanResult) -> Result<()> {
+    let entry = Entry {
+        account_id: account_id.to_owned(),
+        region: region.to_owned(),
+        result,
+    };
+    let line = serde_json::to_string(&entry)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .wrap_err_with(|| format!("cannot open manifest {}", path.display()))?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RdsInstance;
+
+    fn result(instance_id: &str) -> ScanResult {
+        ScanResult {
+            instances: vec![RdsInstance {
+                account_id: "111111111111".to_string(),
+                region: "us-east-1".to_string(),
+                instance_id: instance_id.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn append_then_load_round_trips() {
+        let path = std::env::temp_dir().join(format!("ls-rds-manifest-{}.jsonl", std::process::id()));
+        create(&path).unwrap();
+        append(&path, "111111111111", "us-east-1", &result("orders")).unwrap();
+        append(&path, "111111111111", "eu-west-1", &ScanResult::default()).unwrap();
+        let completed = load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(completed.len(), 2);
+        let key = ("111111111111".to_string(), "us-east-1".to_string());
+        assert_eq!(completed[&key].instances[0].instance_id, "orders");
+    }
+
+    #[test]
+    fn load_missing_manifest_is_empty() {
+        assert!(load(Path::new("/nonexistent/ls-rds-manifest.jsonl")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_skips_a_truncated_last_line() {
+        let full = serde_json::to_string(&Entry {
+            account_id: "111111111111".to_string(),
+            region: "us-east-1".to_string(),
+            result: result("orders"),
+        })
+        .unwrap();
+        let text = format!("{}\n{}", full, &full[..full.len() / 2]);
+        let completed = parse(&text, Path::new("manifest.jsonl"));
+        assert_eq!(completed.len(), 1);
+    }
+}
@@ -0,0 +1,169 @@
+//! VPC placement for ls-rds
+//!
+//! `--with-network` answers "where does this database live" in the same
+//! run: the VPC of each instance's subnet group with the VPC's name and
+//! CIDRs, and whether the subnets the instance can be placed in (the
+//! group's subnets in its Availability Zone) route straight to an internet
+//! gateway.  The lookups reuse the ls-vpc library and are made once per
+//! VPC.
+
+use crate::RdsInstance;
+use crate::api_stats::ApiStats;
+use aws_types::SdkConfig;
+use aws_types::region::Region;
+use eyre::Result;
+use ls_vpc::egress::{Egress, subnet_egress};
+use ls_vpc::routes::{RouteTable, list_route_tables};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Network context of an instance's VPC
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VpcPlacement {
+    /// `Name` tag of the VPC
+    pub vpc_name: Option<String>,
+    /// IPv4 and IPv6 CIDR blocks of the VPC
+    pub vpc_cidrs: Vec<String>,
+    /// `public`, `private` or `mixed` (the instance's candidate subnets
+    /// disagree); `None` when the subnet group has no subnet in its zone
+    pub placement: Option<String>,
+}
+
+/// Name, CIDRs and route tables of one VPC
+struct VpcNetwork {
+    name: Option<String>,
+    cidrs: Vec<String>,
+    tables: Vec<RouteTable>,
+}
+
+async fn vpc_network(conf: &SdkConfig, stats: &ApiStats, account_id: &str, vpc_id: &str) -> Result<VpcNetwork> {
+    // The ls-vpc lookups count their own calls
+    let calls = stats.for_account(account_id);
+    let name = ls_vpc::list_vpcs(conf, &calls, &[vpc_id.to_owned()])
+        .await?
+        .into_iter()
+        .find_map(|(_, _, name)| name);
+    let cidrs = ls_vpc::get_cidrs(conf, &calls, vpc_id).await?;
+    let tables = list_route_tables(conf, &calls, vpc_id).await?;
+    Ok(VpcNetwork { name, cidrs, tables })
+}
+
+/// `public` when every subnet routes to an internet gateway, `private`
+/// when none does, `mixed` otherwise (`None` = no subnets)
+pub fn placement(subnet_ids: &[String], tables: &[RouteTable]) -> Option<&'static str> {
+    // NAT gateways are not looked up: any NAT route is a private subnet
+    let public = subnet_ids
+        .iter()
+        .filter(|s| subnet_egress(s, tables, &BTreeMap::new()) == Egress::Igw)
+        .count();
+    if subnet_ids.is_empty() {
+        None
+    } else if public == 0 {
+        Some("private")
+    } else if public == subnet_ids.len() {
+        Some("public")
+    } else {
+        Some("mixed")
+    }
+}
+
+/// VPC with its name, CIDRs and placement, e.g.
+/// `vpc-0abc (data) 10.0.0.0/16, private subnet`; just the ID when the
+/// network was not resolved
+pub fn format_network(inst: &RdsInstance) -> String {
+    let Some(vpc_id) = inst.vpc_id.as_deref() else { return "-".to_owned() };
+    let Some(network) = &inst.network else { return vpc_id.to_owned() };
+    let mut out = vpc_id.to_owned();
+    if let Some(name) = &network.vpc_name {
+        out.push_str(&format!(" ({})", name));
+    }
+    if !network.vpc_cidrs.is_empty() {
+        out.push_str(&format!(" {}", network.vpc_cidrs.join(",")));
+    }
+    if let Some(placement) = &network.placement {
+        out.push_str(&format!(", {} subnet", placement));
+    }
+    out
+}
+
+/// Fill in the VPC placement of `instances`; returns a warning per VPC
+/// that could not be resolved
+pub async fn resolve(
+    conf: &SdkConfig,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    instances: &mut [RdsInstance],
+) -> Vec<String> {
+    let vpc_ids: BTreeSet<String> = instances.iter().filter_map(|i| i.vpc_id.clone()).collect();
+    let mut networks = BTreeMap::new();
+    let mut warnings = Vec::new();
+    for vpc_id in vpc_ids {
+        match vpc_network(conf, stats, account_id, &vpc_id).await {
+            Ok(network) => {
+                networks.insert(vpc_id, network);
+            }
+            Err(e) => warnings.push(format!(
+                "{} {}: cannot resolve the network of {}: {:#}",
+                account_id, region, vpc_id, e
+            )),
+        }
+    }
+    for inst in instances {
+        let Some(network) = inst.vpc_id.as_ref().and_then(|id| networks.get(id)) else { continue };
+        inst.network = Some(VpcPlacement {
+            vpc_name: network.name.clone(),
+            vpc_cidrs: network.cidrs.clone(),
+            placement: placement(&inst.subnet_ids, &network.tables).map(str::to_owned),
+        });
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ls_vpc::routes::Route;
+
+    fn table(subnets: &[&str], main: bool, target: &str) -> RouteTable {
+        RouteTable {
+            main,
+            subnets: subnets.iter().map(|s| s.to_string()).collect(),
+            routes: vec![Route {
+                destination: "0.0.0.0/0".to_string(),
+                target: target.to_string(),
+                blackhole: false,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn ids(subnets: &[&str]) -> Vec<String> {
+        subnets.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn placement_follows_the_default_route() {
+        let tables = vec![table(&["subnet-pub"], false, "igw-1"), table(&[], true, "nat-1")];
+        assert_eq!(placement(&ids(&["subnet-pub"]), &tables), Some("public"));
+        assert_eq!(placement(&ids(&["subnet-db"]), &tables), Some("private"));
+        assert_eq!(placement(&ids(&["subnet-pub", "subnet-db"]), &tables), Some("mixed"));
+        assert_eq!(placement(&[], &tables), None);
+    }
+
+    #[test]
+    fn format_network_degrades_to_the_vpc_id() {
+        let mut inst = RdsInstance {
+            vpc_id: Some("vpc-0abc".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(format_network(&inst), "vpc-0abc");
+        inst.network = Some(VpcPlacement {
+            vpc_name: Some("data".to_string()),
+            vpc_cidrs: vec!["10.0.0.0/16".to_string()],
+            placement: Some("private".to_string()),
+        });
+        assert_eq!(format_network(&inst), "vpc-0abc (data) 10.0.0.0/16, private subnet");
+        assert_eq!(format_network(&RdsInstance::default()), "-");
+    }
+}
@@ -0,0 +1,337 @@
+//! Parameter values for ls-rds
+//!
+//! `--with-params` reports the value each instance runs with: the value its
+//! DB parameter group sets, else (for cluster members) the value its DB
+//! cluster parameter group sets, else the engine default of the groups'
+//! family.  Values RDS derives from the instance class stay formulas, e.g.
+//! `{DBInstanceClassMemory/32768}`; a parameter with no value anywhere is
+//! left to the engine's built-in default and shown as `(engine-default)`.
+
+use crate::api_stats::ApiStats;
+use crate::retry;
+use aws_sdk_rds as rds;
+use aws_sdk_rds::types::Parameter;
+use aws_types::region::Region;
+use eyre::Result;
+use std::collections::BTreeMap;
+
+/// Shown for a parameter neither group nor the engine defaults give a value
+pub const ENGINE_DEFAULT: &str = "(engine-default)";
+
+/// The requested parameters a group sets, and the family whose engine
+/// defaults fill in the rest
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GroupParameters {
+    /// Parameter group family, e.g. `postgres16` or `aurora-mysql8.0`
+    pub family: Option<String>,
+    pub values: BTreeMap<String, String>,
+}
+
+/// Add the requested parameters that have a value to `values`, keeping any
+/// already there; with `set_only`, parameters a group leaves at the engine
+/// default are skipped so the engine defaults decide them
+fn collect(params: &[Parameter], names: &[String], set_only: bool, values: &mut BTreeMap<String, String>) {
+    for p in params {
+        let (Some(name), Some(value)) = (p.parameter_name(), p.parameter_value()) else {
+            continue;
+        };
+        if (set_only && p.source() == Some("engine-default")) || !names.iter().any(|n| n == name) {
+            continue;
+        }
+        values.entry(name.to_owned()).or_insert_with(|| value.to_owned());
+    }
+}
+
+/// Each requested parameter's value from the first layer that has one,
+/// else [`ENGINE_DEFAULT`]
+pub fn resolve(names: &[String], layers: &[&BTreeMap<String, String>]) -> BTreeMap<String, String> {
+    names
+        .iter()
+        .map(|name| {
+            let value = layers
+                .iter()
+                .find_map(|layer| layer.get(name))
+                .map_or(ENGINE_DEFAULT, String::as_str);
+            (name.clone(), value.to_owned())
+        })
+        .collect()
+}
+
+/// Parameter lookups of one region scan: each group and family is read
+/// once however many instances share it, and a failed read is reported
+/// once and treated as empty
+#[derive(Debug, Default)]
+pub struct ParameterCache {
+    stats: ApiStats,
+    retries: u32,
+    groups: BTreeMap<String, GroupParameters>,
+    cluster_groups: BTreeMap<String, GroupParameters>,
+    defaults: BTreeMap<(bool, String), BTreeMap<String, String>>,
+    /// Failed reads, for the scan's warnings
+    pub warnings: Vec<String>,
+}
+
+impl ParameterCache {
+    /// An empty cache counting its reads in `stats` and retrying a
+    /// throttled one `retries` times
+    pub fn new(stats: &ApiStats, retries: u32) -> Self {
+        ParameterCache {
+            stats: stats.clone(),
+            retries,
+            ..Default::default()
+        }
+    }
+
+    /// Values of the requested parameters for an instance in `groups`
+    /// whose cluster, if any, uses `cluster_group`: instance group values,
+    /// then cluster group values, then the instance and cluster engine
+    /// defaults
+    pub async fn instance_parameters(
+        &mut self,
+        client: &rds::Client,
+        region: &Region,
+        account_id: &str,
+        groups: &[String],
+        cluster_group: Option<&str>,
+        names: &[String],
+    ) -> BTreeMap<String, String> {
+        for group in groups {
+            if !self.groups.contains_key(group) {
+                let read = self.group_parameters(client, region, account_id, group, names).await;
+                let parameters = read.unwrap_or_else(|e| {
+                    self.warnings.push(format!(
+                        "{} {}: cannot read parameter group {}: {:#}",
+                        account_id, region, group, e
+                    ));
+                    GroupParameters::default()
+                });
+                self.groups.insert(group.clone(), parameters);
+            }
+        }
+        if let Some(group) = cluster_group.filter(|g| !self.cluster_groups.contains_key(*g)) {
+            let read = self.cluster_group_parameters(client, region, account_id, group, names).await;
+            let parameters = read.unwrap_or_else(|e| {
+                self.warnings.push(format!(
+                    "{} {}: cannot read cluster parameter group {}: {:#}",
+                    account_id, region, group, e
+                ));
+                GroupParameters::default()
+            });
+            self.cluster_groups.insert(group.to_owned(), parameters);
+        }
+
+        let mut families: Vec<(bool, String)> = groups
+            .iter()
+            .filter_map(|g| Some((false, self.groups[g].family.clone()?)))
+            .collect();
+        families.extend(cluster_group.and_then(|g| Some((true, self.cluster_groups[g].family.clone()?))));
+        for key in &families {
+            if !self.defaults.contains_key(key) {
+                let (cluster, family) = key;
+                let read = self.engine_defaults(client, region, account_id, family, *cluster, names).await;
+                let values = read.unwrap_or_else(|e| {
+                    self.warnings.push(format!(
+                        "{} {}: cannot read engine defaults of {}: {:#}",
+                        account_id, region, family, e
+                    ));
+                    BTreeMap::new()
+                });
+                self.defaults.insert(key.clone(), values);
+            }
+        }
+
+        let mut layers: Vec<&BTreeMap<String, String>> = groups.iter().map(|g| &self.groups[g].values).collect();
+        layers.extend(cluster_group.map(|g| &self.cluster_groups[g].values));
+        layers.extend(families.iter().map(|key| &self.defaults[key]));
+        resolve(names, &layers)
+    }
+
+    /// The requested parameters a DB parameter group sets, and its family
+    pub async fn group_parameters(
+        &self,
+        client: &rds::Client,
+        region: &Region,
+        account_id: &str,
+        group: &str,
+        names: &[String],
+    ) -> Result<GroupParameters> {
+        let family = retry::with_retries(self.retries, || {
+            self.stats.record("rds", "DescribeDBParameterGroups", region.as_ref(), account_id);
+            client.describe_db_parameter_groups().db_parameter_group_name(group).send()
+        })
+        .await?
+        .db_parameter_groups()
+        .first()
+        .and_then(|g| g.db_parameter_group_family())
+        .map(str::to_owned);
+
+        let mut values = BTreeMap::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let resp = retry::with_retries(self.retries, || {
+                self.stats.record("rds", "DescribeDBParameters", region.as_ref(), account_id);
+                client
+                    .describe_db_parameters()
+                    .db_parameter_group_name(group)
+                    .set_marker(marker.clone())
+                    .send()
+            })
+            .await?;
+            collect(resp.parameters(), names, true, &mut values);
+            match resp.marker() {
+                Some(next) => marker = Some(next.to_owned()),
+                None => break,
+            }
+        }
+        Ok(GroupParameters { family, values })
+    }
+
+    /// The requested parameters a DB cluster parameter group sets, and its family
+    pub async fn cluster_group_parameters(
+        &self,
+        client: &rds::Client,
+        region: &Region,
+        account_id: &str,
+        group: &str,
+        names: &[String],
+    ) -> Result<GroupParameters> {
+        let family = retry::with_retries(self.retries, || {
+            self.stats.record("rds", "DescribeDBClusterParameterGroups", region.as_ref(), account_id);
+            client.describe_db_cluster_parameter_groups().db_cluster_parameter_group_name(group).send()
+        })
+        .await?
+        .db_cluster_parameter_groups()
+        .first()
+        .and_then(|g| g.db_parameter_group_family())
+        .map(str::to_owned);
+
+        let mut values = BTreeMap::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let resp = retry::with_retries(self.retries, || {
+                self.stats.record("rds", "DescribeDBClusterParameters", region.as_ref(), account_id);
+                client
+                    .describe_db_cluster_parameters()
+                    .db_cluster_parameter_group_name(group)
+                    .set_marker(marker.clone())
+                    .send()
+            })
+            .await?;
+            collect(resp.parameters(), names, true, &mut values);
+            match resp.marker() {
+                Some(next) => marker = Some(next.to_owned()),
+                None => break,
+            }
+        }
+        Ok(GroupParameters { family, values })
+    }
+
+    /// Engine defaults of the requested parameters for a parameter group
+    /// family; `cluster` reads the cluster-level defaults
+    pub async fn engine_defaults(
+        &self,
+        client: &rds::Client,
+        region: &Region,
+        account_id: &str,
+        family: &str,
+        cluster: bool,
+        names: &[String],
+    ) -> Result<BTreeMap<String, String>> {
+        let mut values = BTreeMap::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let defaults = if cluster {
+                retry::with_retries(self.retries, || {
+                    self.stats.record("rds", "DescribeEngineDefaultClusterParameters", region.as_ref(), account_id);
+                    client
+                        .describe_engine_default_cluster_parameters()
+                        .db_parameter_group_family(family)
+                        .set_marker(marker.clone())
+                        .send()
+                })
+                .await?
+                .engine_defaults()
+                .cloned()
+            } else {
+                retry::with_retries(self.retries, || {
+                    self.stats.record("rds", "DescribeEngineDefaultParameters", region.as_ref(), account_id);
+                    client
+                        .describe_engine_default_parameters()
+                        .db_parameter_group_family(family)
+                        .set_marker(marker.clone())
+                        .send()
+                })
+                .await?
+                .engine_defaults()
+                .cloned()
+            };
+            let Some(defaults) = defaults else {
+                break;
+            };
+            collect(defaults.parameters(), names, false, &mut values);
+            match defaults.marker() {
+                Some(next) => marker = Some(next.to_owned()),
+                None => break,
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn param(name: &str, value: Option<&str>, source: &str) -> Parameter {
+        Parameter::builder()
+            .parameter_name(name)
+            .set_parameter_value(value.map(str::to_owned))
+            .source(source)
+            .build()
+    }
+
+    #[test]
+    fn collect_skips_engine_default_values_of_groups() {
+        let params = [
+            param("max_connections", Some("500"), "user"),
+            param("shared_buffers", Some("{DBInstanceClassMemory/32768}"), "engine-default"),
+            param("work_mem", None, "engine-default"),
+            param("unrequested", Some("1"), "user"),
+        ];
+        let wanted = names(&["max_connections", "shared_buffers", "work_mem"]);
+
+        let mut set = BTreeMap::new();
+        collect(&params, &wanted, true, &mut set);
+        assert_eq!(set, BTreeMap::from([("max_connections".to_string(), "500".to_string())]));
+
+        let mut defaults = BTreeMap::new();
+        collect(&params, &wanted, false, &mut defaults);
+        assert_eq!(defaults.len(), 2);
+        assert_eq!(defaults["shared_buffers"], "{DBInstanceClassMemory/32768}");
+    }
+
+    #[test]
+    fn resolve_takes_the_first_layer_with_a_value() {
+        let instance = BTreeMap::from([("max_connections".to_string(), "500".to_string())]);
+        let cluster = BTreeMap::from([
+            ("max_connections".to_string(), "900".to_string()),
+            ("binlog_format".to_string(), "ROW".to_string()),
+        ]);
+        let defaults = BTreeMap::from([
+            ("binlog_format".to_string(), "OFF".to_string()),
+            ("innodb_buffer_pool_size".to_string(), "{DBInstanceClassMemory*3/4}".to_string()),
+        ]);
+        let resolved = resolve(
+            &names(&["max_connections", "binlog_format", "innodb_buffer_pool_size", "wait_timeout"]),
+            &[&instance, &cluster, &defaults],
+        );
+        assert_eq!(resolved["max_connections"], "500");
+        assert_eq!(resolved["binlog_format"], "ROW");
+        assert_eq!(resolved["innodb_buffer_pool_size"], "{DBInstanceClassMemory*3/4}");
+        assert_eq!(resolved["wait_timeout"], ENGINE_DEFAULT);
+    }
+}
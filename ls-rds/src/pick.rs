@@ -0,0 +1,162 @@
+//! Fuzzy picker for ls-rds
+//!
+//! `--pick`: a built-in fuzzy finder.  A background scan feeds the instances
+//! of each account/region to the picker as soon as it is done, and the
+//! picker lists them while the remaining ones are still being scanned.  The
+//! matching is ls-vpc's.
+
+use crate::{Config, RdsInstance, Scanner, sso};
+use eyre::Result;
+use futures::StreamExt;
+use ls_vpc::pick::ranked;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use std::pin::pin;
+use std::sync::mpsc::{Receiver, TryRecvError, channel};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// An instance as sent to the feed
+pub type PickItem = RdsInstance;
+
+/// Start scanning with `config` in the background, sending every instance
+/// to the returned feed as its account/region completes.  The feed closes
+/// when the scan ends; the task yields the scan's error, if any (with the
+/// login command to run when the SSO session has expired).
+pub fn spawn_feed(config: Config) -> (Receiver<PickItem>, JoinHandle<Result<()>>) {
+    let (tx, rx) = channel();
+    let scan = tokio::spawn(async move {
+        let scanner = Scanner::from_config(config);
+        let results = match scanner.scan().await {
+            Ok(results) => results,
+            Err(e) if sso::is_expired_session(&e) => {
+                let profile = sso::profile(scanner.config());
+                return Err(e.wrap_err(sso::login_hint(profile.as_deref())));
+            }
+            Err(e) => return Err(e),
+        };
+        let mut results = pin!(results);
+        while let Some(result) = results.next().await {
+            for inst in result.instances {
+                // The picker has gone away once something was picked
+                if tx.send(inst).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    });
+    (rx, scan)
+}
+
+/// Text the picker matches against and displays for an instance
+pub fn label(inst: &PickItem) -> String {
+    format!("{} {} {} {}", inst.account_id, inst.region, inst.instance_id, inst.engine)
+}
+
+/// Run the picker over the feed until the user picks an instance (`Some`) or
+/// cancels with Esc / Ctrl-C (`None`).
+pub fn pick(feed: Receiver<PickItem>) -> Result<Option<PickItem>> {
+    let mut terminal = ratatui::init();
+    let outcome = pick_loop(&mut terminal, feed);
+    ratatui::restore();
+    outcome
+}
+
+fn pick_loop(terminal: &mut ratatui::DefaultTerminal, feed: Receiver<PickItem>) -> Result<Option<PickItem>> {
+    let mut items: Vec<PickItem> = Vec::new();
+    let mut labels: Vec<String> = Vec::new();
+    let mut query = String::new();
+    let mut selected = 0usize;
+    let mut scanning = true;
+
+    loop {
+        while scanning {
+            match feed.try_recv() {
+                Ok(item) => {
+                    labels.push(label(&item));
+                    items.push(item);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => scanning = false,
+            }
+        }
+
+        let matches = ranked(&query, &labels);
+        selected = selected.min(matches.len().saturating_sub(1));
+
+        terminal.draw(|frame| {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(1)])
+                .split(frame.area());
+            let progress = if scanning { "scanning…" } else { "done" };
+            frame.render_widget(
+                Paragraph::new(format!("> {query}")).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!("{}/{} instances ({progress})", matches.len(), labels.len())),
+                ),
+                rows[0],
+            );
+            let list: Vec<ListItem> = matches.iter().map(|&i| ListItem::new(labels[i].clone())).collect();
+            let mut state = ListState::default();
+            if !matches.is_empty() {
+                state.select(Some(selected));
+            }
+            frame.render_stateful_widget(
+                List::new(list).highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+                rows[1],
+                &mut state,
+            );
+        })?;
+
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if ctrl => return Ok(None),
+            KeyCode::Enter => {
+                return Ok(matches.get(selected).map(|&i| items.swap_remove(i)));
+            }
+            KeyCode::Down => selected += 1,
+            KeyCode::Char('n') if ctrl => selected += 1,
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => selected = selected.saturating_sub(1),
+            KeyCode::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_includes_account_region_id_and_engine() {
+        let inst = RdsInstance {
+            account_id: "111111111111".to_string(),
+            region: "us-east-1".to_string(),
+            instance_id: "orders-db".to_string(),
+            engine: "postgres".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(label(&inst), "111111111111 us-east-1 orders-db postgres");
+    }
+}
@@ -0,0 +1,231 @@
+//! Dry-run scan plan for ls-rds
+//!
+//! `--dry-run` resolves the caller, the accounts to scan (listing the
+//! organization in org mode) and the role assumed in each, then prints every
+//! account/region pair with the calls its scan would make and an estimate
+//! of the total, without scanning.  With `--all-regions` each account's
+//! enabled regions are read as the scan reads them.  The per-region operations
+//! mirror `scan_region`; estimates count one call per operation, so
+//! paginated listings and per-instance lookups come on top.
+
+use crate::{Config, GroupBy};
+
+/// One account the scan would visit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlannedAccount {
+    pub account_id: String,
+    /// Role assumed in the account (`None` = the caller's own credentials)
+    pub role_arn: Option<String>,
+    /// Regions scanned in the account (none when its enabled regions
+    /// cannot be read)
+    pub regions: Vec<String>,
+}
+
+/// Everything a scan would do, resolved without scanning
+#[derive(Debug, Clone, Default)]
+pub struct ScanPlan {
+    pub caller_account: String,
+    pub accounts: Vec<PlannedAccount>,
+    /// Calls made once per account/region
+    pub region_operations: Vec<&'static str>,
+    /// Calls made per instance found (not in the estimate)
+    pub instance_operations: Vec<&'static str>,
+    /// Calls made once for the whole run
+    pub run_operations: Vec<&'static str>,
+}
+
+impl ScanPlan {
+    /// Account/region pairs to scan
+    pub fn pairs(&self) -> usize {
+        self.accounts.iter().map(|a| a.regions.len()).sum()
+    }
+
+    /// Estimated API calls: the run-wide calls, then per pair an AssumeRole
+    /// (when a role is assumed) and one call per region operation
+    pub fn estimated_calls(&self) -> usize {
+        let assumed: usize = self
+            .accounts
+            .iter()
+            .filter(|a| a.role_arn.is_some())
+            .map(|a| a.regions.len())
+            .sum();
+        self.run_operations.len() + assumed + self.pairs() * self.region_operations.len()
+    }
+}
+
+/// Operations `scan_region` runs in every account/region for `config`
+pub fn region_operations(config: &Config) -> Vec<&'static str> {
+    let mut ops = vec!["rds:DescribeDBInstances"];
+    if config.wants_zone_ids() {
+        ops.push("ec2:DescribeAvailabilityZones");
+    }
+    if config.with_cluster || config.topology || config.group_by == Some(GroupBy::Cluster) || config.clusters {
+        ops.push("rds:DescribeDBClusters");
+    }
+    if config.blue_green {
+        ops.push("rds:DescribeBlueGreenDeployments");
+    }
+    if config.include_proxies {
+        ops.push("rds:DescribeDBProxies");
+    }
+    if config.global_clusters {
+        ops.push("rds:DescribeGlobalClusters");
+    }
+    if config.cluster_endpoints {
+        ops.push("rds:DescribeDBClusterEndpoints");
+    }
+    if config.maintenance {
+        ops.push("rds:DescribePendingMaintenanceActions");
+    }
+    if config.snapshots {
+        ops.push("rds:DescribeDBSnapshots");
+    }
+    ops
+}
+
+/// Operations repeated for each instance found (cached per parameter group,
+/// engine version or VPC)
+pub fn instance_operations(config: &Config) -> Vec<&'static str> {
+    let mut ops = Vec::new();
+    if !config.with_params.is_empty() {
+        ops.push("rds:DescribeDBParameters");
+    }
+    if config.upgrade_targets {
+        ops.push("rds:DescribeDBEngineVersions");
+    }
+    if config.with_network {
+        ops.extend(["ec2:DescribeVpcs", "ec2:DescribeVpcs", "ec2:DescribeRouteTables"]);
+    }
+    ops
+}
+
+/// Operations run once per scan, after the caller and accounts are resolved
+pub fn run_operations(config: &Config) -> Vec<&'static str> {
+    let mut ops = Vec::new();
+    if config.snapshots {
+        ops.push("organizations:ListAccounts");
+    }
+    if config.spend_days.is_some() {
+        ops.push("ce:GetCostAndUsage");
+    }
+    ops
+}
+
+/// Render the plan: a header with the totals, the operations, then one line
+/// per account with its role and regions
+pub fn format_plan(plan: &ScanPlan) -> String {
+    let list = |ops: &[&str]| if ops.is_empty() { "-".to_owned() } else { ops.join(", ") };
+    let mut out = format!(
+        "Dry run as account {}: {} accounts, {} account/region scans, ~{} API calls\n",
+        plan.caller_account,
+        plan.accounts.len(),
+        plan.pairs(),
+        plan.estimated_calls()
+    );
+    let first = plan.accounts.first().map(|a| &a.regions);
+    let shared = first.filter(|regions| plan.accounts.iter().all(|a| &a.regions == *regions));
+    let regions = shared.map_or_else(|| "per account".to_owned(), |r| r.join(", "));
+    out.push_str(&format!("Regions:\t{}\n", regions));
+    out.push_str(&format!("Per region:\t{}\n", list(&plan.region_operations)));
+    out.push_str(&format!("Per instance:\t{}\n", list(&plan.instance_operations)));
+    out.push_str(&format!("Once:\t{}\n", list(&plan.run_operations)));
+    out.push_str("ACCOUNT\tROLE\tREGIONS\n");
+    for account in &plan.accounts {
+        let regions = if account.regions.is_empty() {
+            "(enabled regions unreadable, skipped)".to_owned()
+        } else {
+            account.regions.join(",")
+        };
+        out.push_str(&format!(
+            "{}\t{}\t{}\n",
+            account.account_id,
+            account.role_arn.as_deref().unwrap_or("(caller credentials)"),
+            regions
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(roles: &[Option<&str>], regions: &[&str]) -> ScanPlan {
+        let config = Config {
+            clusters: true,
+            snapshots: true,
+            with_specs: true,
+            ..Config::default()
+        };
+        ScanPlan {
+            caller_account: "111111111111".to_string(),
+            accounts: roles
+                .iter()
+                .enumerate()
+                .map(|(n, role)| PlannedAccount {
+                    account_id: format!("{:012}", n + 1),
+                    role_arn: role.map(str::to_owned),
+                    regions: regions.iter().map(|r| r.to_string()).collect(),
+                })
+                .collect(),
+            region_operations: region_operations(&config),
+            instance_operations: instance_operations(&config),
+            run_operations: run_operations(&config),
+        }
+    }
+
+    #[test]
+    fn region_operations_follow_the_flags() {
+        assert_eq!(region_operations(&Config::default()), vec!["rds:DescribeDBInstances"]);
+        let config = Config {
+            with_cluster: true,
+            include_proxies: true,
+            json: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            region_operations(&config),
+            vec![
+                "rds:DescribeDBInstances",
+                "ec2:DescribeAvailabilityZones",
+                "rds:DescribeDBClusters",
+                "rds:DescribeDBProxies"
+            ]
+        );
+    }
+
+    #[test]
+    fn estimated_calls_count_role_assumptions_per_region() {
+        // ListAccounts, 4 operations per pair and an AssumeRole per
+        // assumed pair
+        let p = plan(&[None, Some("arn:aws:iam::000000000002:role/Audit")], &["us-east-1"]);
+        assert_eq!(p.estimated_calls(), 1 + 2 * 4 + 1);
+        let p = plan(&[Some("arn:aws:iam::000000000001:role/Audit")], &["us-east-1", "eu-west-1"]);
+        assert_eq!(p.estimated_calls(), 1 + 2 * 4 + 2);
+    }
+
+    #[test]
+    fn format_plan_lists_accounts_and_roles() {
+        let out = format_plan(&plan(&[None, Some("arn:aws:iam::000000000002:role/Audit")], &["us-east-1"]));
+        assert!(out.starts_with("Dry run as account 111111111111: 2 accounts, 2 account/region scans"));
+        assert!(out.contains("Regions:\tus-east-1\n"));
+        assert!(out.contains("000000000001\t(caller credentials)\tus-east-1\n"));
+        assert!(out.contains("000000000002\tarn:aws:iam::000000000002:role/Audit\tus-east-1\n"));
+    }
+
+    #[test]
+    fn format_plan_lists_enabled_regions_per_account() {
+        let mut p = plan(&[None, Some("arn:aws:iam::000000000002:role/Audit")], &["us-east-1", "eu-west-1"]);
+        p.accounts[1].regions.push("ap-south-2".to_string());
+        p.accounts.push(PlannedAccount {
+            account_id: "000000000003".to_string(),
+            role_arn: Some("arn:aws:iam::000000000003:role/Audit".to_string()),
+            regions: Vec::new(),
+        });
+        assert_eq!(p.pairs(), 5);
+        let out = format_plan(&p);
+        assert!(out.contains("Regions:\tper account\n"));
+        assert!(out.contains("000000000002\tarn:aws:iam::000000000002:role/Audit\tus-east-1,eu-west-1,ap-south-2\n"));
+        assert!(out.contains("000000000003\tarn:aws:iam::000000000003:role/Audit\t(enabled regions unreadable"));
+    }
+}
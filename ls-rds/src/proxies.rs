@@ -0,0 +1,147 @@
+//! RDS Proxies for ls-rds
+//!
+//! `--include-proxies` lists each account/region's RDS Proxies after its
+//! instances: engine family, endpoint, status, VPC and whether TLS is
+//! required, so the proxies in front of the databases are inventoried too.
+
+use crate::api_stats::ApiStats;
+use crate::retry;
+use aws_sdk_rds as rds;
+use aws_types::region::Region;
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+/// An RDS Proxy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RdsProxy {
+    pub account_id: String,
+    pub region: String,
+    pub role_arn: Option<String>,
+    pub name: String,
+    pub arn: String,
+    /// `MYSQL`, `POSTGRESQL` or `SQLSERVER`
+    pub engine_family: String,
+    pub endpoint: String,
+    /// e.g. `available`, `modifying`
+    pub status: String,
+    pub vpc_id: String,
+    pub require_tls: bool,
+    /// Seconds a client connection may idle before it is closed
+    pub idle_client_timeout: Option<i32>,
+}
+
+/// List the RDS Proxies of one region
+pub async fn list_proxies(
+    client: &rds::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    role_arn: Option<&str>,
+    retries: u32,
+) -> Result<Vec<RdsProxy>> {
+    let mut proxies = Vec::new();
+    let mut marker: Option<String> = None;
+    loop {
+        let page = retry::with_retries(retries, || {
+            stats.record("rds", "DescribeDBProxies", region.as_ref(), account_id);
+            client.describe_db_proxies().set_marker(marker.clone()).send()
+        })
+        .await?;
+        for p in page.db_proxies() {
+            proxies.push(RdsProxy {
+                account_id: account_id.to_owned(),
+                region: region.to_string(),
+                role_arn: role_arn.map(str::to_owned),
+                name: p.db_proxy_name().unwrap_or_default().to_owned(),
+                arn: p.db_proxy_arn().unwrap_or_default().to_owned(),
+                engine_family: p.engine_family().unwrap_or_default().to_owned(),
+                endpoint: p.endpoint().unwrap_or_default().to_owned(),
+                status: p.status().map(|s| s.as_str().to_owned()).unwrap_or_default(),
+                vpc_id: p.vpc_id().unwrap_or_default().to_owned(),
+                require_tls: p.require_tls().unwrap_or(false),
+                idle_client_timeout: p.idle_client_timeout(),
+            });
+        }
+        match page.marker() {
+            Some(next) => marker = Some(next.to_owned()),
+            None => break,
+        }
+    }
+    Ok(proxies)
+}
+
+/// Format a proxy like an instance row (role ARN when assumed, region,
+/// name) followed by `proxy`, engine family, endpoint and status
+pub fn format_proxy(p: &RdsProxy) -> String {
+    let fields = format!("{}\t{}\tproxy\t{}\t{}\t{}", p.region, p.name, p.engine_family, p.endpoint, p.status);
+    match &p.role_arn {
+        Some(arn) => format!("{}\t{}", arn, fields),
+        None => fields,
+    }
+}
+
+/// Render proxies as an ASCII table
+pub fn format_proxy_table(proxies: &[RdsProxy]) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec![
+        "ACCOUNT/ROLE",
+        "REGION",
+        "PROXY",
+        "ENGINE-FAMILY",
+        "ENDPOINT",
+        "STATUS",
+        "VPC-ID",
+        "TLS",
+    ]);
+    for p in proxies {
+        table.add_row(vec![
+            p.role_arn.clone().unwrap_or_else(|| p.account_id.clone()),
+            p.region.clone(),
+            p.name.clone(),
+            p.engine_family.clone(),
+            p.endpoint.clone(),
+            p.status.clone(),
+            p.vpc_id.clone(),
+            if p.require_tls { "required" } else { "optional" }.to_owned(),
+        ]);
+    }
+    format!("{}\n", table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy(role_arn: Option<&str>) -> RdsProxy {
+        RdsProxy {
+            account_id: "111111111111".to_string(),
+            region: "us-east-1".to_string(),
+            role_arn: role_arn.map(str::to_owned),
+            name: "orders-proxy".to_string(),
+            engine_family: "POSTGRESQL".to_string(),
+            endpoint: "orders-proxy.proxy-abc.us-east-1.rds.amazonaws.com".to_string(),
+            status: "available".to_string(),
+            require_tls: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn format_proxy_matches_instance_rows() {
+        assert_eq!(
+            format_proxy(&proxy(None)),
+            "us-east-1\torders-proxy\tproxy\tPOSTGRESQL\torders-proxy.proxy-abc.us-east-1.rds.amazonaws.com\tavailable"
+        );
+        assert!(format_proxy(&proxy(Some("arn:aws:iam::1:role/R"))).starts_with("arn:aws:iam::1:role/R\tus-east-1\t"));
+    }
+
+    #[test]
+    fn format_proxy_table_shows_tls() {
+        let table = format_proxy_table(&[proxy(None)]);
+        let row = table.lines().find(|l| l.contains("orders-proxy")).unwrap();
+        assert!(row.contains("111111111111") && row.contains("required"));
+    }
+}
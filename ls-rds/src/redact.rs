@@ -0,0 +1,295 @@
+//! Redaction for shareable ls-rds output
+//!
+//! `--redact` replaces account IDs, ARNs and identifiers with stable aliases
+//! before anything is printed: the same value always maps to the same alias
+//! within a run, so relationships (replicas, clusters, shares) survive while
+//! the account topology does not.  Account IDs stay 12 digits
+//! (`000000000001` …) so downstream tooling keeps working.  One redactor
+//! serves the whole run (the scan result and `--api-stats`), so an account
+//! keeps one alias across both.
+
+use crate::api_stats::ApiCall;
+use crate::{AccountInfo, RdsInstance, ScanPlan, ScanResult};
+use ls_vpc::redact::Aliases;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Redacts an RDS scan with one set of aliases for the whole run
+#[derive(Debug, Default)]
+pub struct Redactor {
+    aliases: Aliases,
+}
+
+impl Redactor {
+    /// Alias for `value` in the `kind` namespace, e.g. `db-3`
+    pub fn alias(&mut self, kind: &'static str, value: &str) -> String {
+        self.aliases.alias(kind, value)
+    }
+
+    pub fn account(&mut self, account_id: &str) -> String {
+        self.aliases.account(account_id)
+    }
+
+    /// ARN with its account and resource name aliased; the partition,
+    /// service, region and resource type are kept
+    pub fn arn(&mut self, arn: &str, kind: &'static str) -> String {
+        let parts: Vec<&str> = arn.splitn(6, ':').collect();
+        if parts.len() < 6 || parts[0] != "arn" {
+            return self.alias(kind, arn);
+        }
+        let account = self.account(parts[4]);
+        let cut = parts[5].rfind([':', '/']).map_or(0, |i| i + 1);
+        let (prefix, name) = parts[5].split_at(cut);
+        format!(
+            "arn:{}:{}:{}:{}:{}{}",
+            parts[1],
+            parts[2],
+            parts[3],
+            account,
+            prefix,
+            self.alias(kind, name)
+        )
+    }
+
+    /// An identifier that may also be given as a full ARN (replica links)
+    pub fn reference(&mut self, reference: &str, kind: &'static str) -> String {
+        if reference.starts_with("arn:") { self.arn(reference, kind) } else { self.alias(kind, reference) }
+    }
+
+    /// Replace every value aliased so far inside free text (warnings)
+    pub fn text(&self, text: &str) -> String {
+        self.aliases.text(text)
+    }
+
+    pub fn redact_instance(&mut self, inst: &mut RdsInstance) {
+        inst.account_id = self.account(&inst.account_id);
+        inst.role_arn = inst.role_arn.as_deref().map(|a| self.arn(a, "role"));
+        inst.instance_id = self.alias("db", &inst.instance_id);
+        inst.arn = self.arn(&inst.arn, "db");
+        inst.replica_source = inst.replica_source.as_deref().map(|r| self.reference(r, "db"));
+        inst.replicas = inst.replicas.iter().map(|r| self.reference(r, "db")).collect();
+        inst.cluster_id = inst.cluster_id.as_deref().map(|c| self.alias("cluster", c));
+        inst.subnet_group = inst.subnet_group.as_deref().map(|g| self.alias("subnet-group", g));
+        inst.vpc_id = inst.vpc_id.as_deref().map(|v| self.alias("vpc", v));
+        inst.subnet_ids = inst.subnet_ids.iter().map(|s| self.alias("subnet", s)).collect();
+        inst.kms_key_id = inst.kms_key_id.as_deref().map(|k| self.arn(k, "kms-key"));
+        if let Some(network) = &mut inst.network {
+            network.vpc_name = network.vpc_name.as_deref().map(|n| self.alias("vpc-name", n));
+        }
+        inst.parameter_groups = inst
+            .parameter_groups
+            .iter()
+            .map(|g| if g.starts_with("default.") { g.clone() } else { self.alias("param-group", g) })
+            .collect();
+        inst.endpoint = inst.endpoint.as_deref().map(|e| match e.rsplit_once(':') {
+            Some((host, port)) => format!("{}:{}", self.alias("host", host), port),
+            None => self.alias("host", e),
+        });
+        inst.tags = inst
+            .tags
+            .iter()
+            .map(|(k, v)| (k.clone(), self.alias("tag", v)))
+            .collect();
+    }
+
+    /// API call counters with their accounts aliased, for `--api-stats`
+    pub fn redact_api_stats(&mut self, stats: BTreeMap<ApiCall, u64>) -> BTreeMap<ApiCall, u64> {
+        let mut out = BTreeMap::new();
+        for (mut call, count) in stats {
+            call.account = self.account(&call.account);
+            *out.entry(call).or_default() += count;
+        }
+        out
+    }
+
+    /// Redact every record of a scan result in place
+    pub fn redact_result(&mut self, result: &mut ScanResult) {
+        for inst in &mut result.instances {
+            self.redact_instance(inst);
+        }
+        for bg in &mut result.blue_green {
+            bg.account_id = self.account(&bg.account_id);
+            bg.identifier = self.alias("bgd", &bg.identifier);
+            bg.name = self.alias("bgd-name", &bg.name);
+            bg.source = self.reference(&bg.source, "db");
+            bg.target = self.reference(&bg.target, "db");
+            for m in &mut bg.members {
+                m.source = self.reference(&m.source, "db");
+                m.target = self.reference(&m.target, "db");
+            }
+        }
+        for c in &mut result.clusters {
+            c.account_id = self.account(&c.account_id);
+            c.cluster_id = self.alias("cluster", &c.cluster_id);
+            c.arn = self.arn(&c.arn, "cluster");
+            for m in &mut c.members {
+                m.instance_id = self.alias("db", &m.instance_id);
+            }
+        }
+        for p in &mut result.proxies {
+            p.account_id = self.account(&p.account_id);
+            p.role_arn = p.role_arn.as_deref().map(|a| self.arn(a, "role"));
+            p.name = self.alias("proxy", &p.name);
+            p.arn = self.arn(&p.arn, "proxy");
+            p.endpoint = self.alias("host", &p.endpoint);
+            p.vpc_id = self.alias("vpc", &p.vpc_id);
+        }
+        for g in &mut result.global_clusters {
+            g.account_id = self.account(&g.account_id);
+            g.identifier = self.alias("global-cluster", &g.identifier);
+            g.arn = self.arn(&g.arn, "global-cluster");
+            for m in &mut g.members {
+                m.cluster_arn = self.arn(&m.cluster_arn, "cluster");
+            }
+        }
+        for ep in &mut result.cluster_endpoints {
+            ep.account_id = self.account(&ep.account_id);
+            ep.cluster_id = self.alias("cluster", &ep.cluster_id);
+            ep.identifier = ep.identifier.as_deref().map(|i| self.alias("cluster-endpoint", i));
+            ep.address = self.alias("host", &ep.address);
+            ep.static_members = ep.static_members.iter().map(|m| self.alias("db", m)).collect();
+            ep.excluded_members = ep.excluded_members.iter().map(|m| self.alias("db", m)).collect();
+        }
+        for action in &mut result.pending_actions {
+            action.account_id = self.account(&action.account_id);
+            action.resource_id = self.alias("db", &action.resource_id);
+        }
+        for snap in &mut result.snapshots {
+            snap.account_id = self.account(&snap.account_id);
+            snap.identifier = self.alias("snapshot", &snap.identifier);
+            snap.arn = self.arn(&snap.arn, "snapshot");
+            snap.instance_id = self.alias("db", &snap.instance_id);
+            snap.shared_with = snap.shared_with.iter().map(|a| self.account(a)).collect();
+        }
+        for t in &mut result.timings {
+            t.account_id = self.account(&t.account_id);
+        }
+        result.spend = std::mem::take(&mut result.spend)
+            .into_iter()
+            .map(|(a, usd)| (self.account(&a), usd))
+            .collect::<BTreeMap<_, _>>();
+        result.accounts = std::mem::take(&mut result.accounts)
+            .into_values()
+            .map(|a| {
+                let id = self.account(&a.id);
+                let info = AccountInfo {
+                    id: id.clone(),
+                    name: self.alias("account-name", &a.name),
+                    email: self.alias("email", &a.email),
+                    status: a.status,
+                };
+                (id, info)
+            })
+            .collect();
+        for e in &mut result.errors {
+            e.account_id = e.account_id.as_deref().map(|a| self.account(a));
+            e.message = self.text(&e.message);
+        }
+        result.org_accounts = std::mem::take(&mut result.org_accounts)
+            .iter()
+            .map(|a| self.account(a))
+            .collect::<BTreeSet<_>>();
+        result.warnings = result.warnings.iter().map(|w| self.text(w)).collect();
+    }
+
+    pub fn redact_plan(&mut self, plan: &mut ScanPlan) {
+        plan.caller_account = self.account(&plan.caller_account);
+        for account in &mut plan.accounts {
+            account.account_id = self.account(&account.account_id);
+            account.role_arn = account.role_arn.as_deref().map(|a| self.arn(a, "role"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alias_is_stable_per_kind() {
+        let mut r = Redactor::default();
+        assert_eq!(r.alias("db", "orders"), "db-1");
+        assert_eq!(r.alias("db", "billing"), "db-2");
+        assert_eq!(r.alias("db", "orders"), "db-1");
+        assert_eq!(r.alias("cluster", "orders"), "cluster-1");
+        assert_eq!(r.account("123456789012"), "000000000001");
+        assert_eq!(r.alias("db", ""), "");
+    }
+
+    #[test]
+    fn arn_keeps_shape_and_links_to_identifiers() {
+        let mut r = Redactor::default();
+        let arn = r.arn("arn:aws:rds:us-east-1:123456789012:db:orders", "db");
+        assert_eq!(arn, "arn:aws:rds:us-east-1:000000000001:db:db-1");
+        assert_eq!(r.alias("db", "orders"), "db-1");
+        assert_eq!(
+            r.arn("arn:aws:iam::123456789012:role/Auditor", "role"),
+            "arn:aws:iam::000000000001:role/role-1"
+        );
+    }
+
+    #[test]
+    fn redact_instance_keeps_replica_links() {
+        let mut r = Redactor::default();
+        let mut primary = RdsInstance {
+            account_id: "123456789012".to_string(),
+            instance_id: "orders".to_string(),
+            replicas: vec!["orders-replica".to_string()],
+            endpoint: Some("orders.abc.us-east-1.rds.amazonaws.com:5432".to_string()),
+            parameter_groups: vec!["default.postgres16".to_string(), "orders-pg".to_string()],
+            ..Default::default()
+        };
+        let mut replica = RdsInstance {
+            account_id: "123456789012".to_string(),
+            instance_id: "orders-replica".to_string(),
+            replica_source: Some("orders".to_string()),
+            ..Default::default()
+        };
+        r.redact_instance(&mut primary);
+        r.redact_instance(&mut replica);
+        assert_eq!(primary.instance_id, "db-1");
+        assert_eq!(primary.replicas, vec!["db-2"]);
+        assert_eq!(replica.instance_id, "db-2");
+        assert_eq!(replica.replica_source.as_deref(), Some("db-1"));
+        assert_eq!(primary.endpoint.as_deref(), Some("host-1:5432"));
+        assert_eq!(primary.parameter_groups, vec!["default.postgres16", "param-group-1"]);
+        assert_eq!(replica.account_id, primary.account_id);
+    }
+
+    #[test]
+    fn redact_result_rewrites_warnings_and_account_maps() {
+        let mut result = ScanResult {
+            instances: vec![RdsInstance {
+                account_id: "123456789012".to_string(),
+                instance_id: "orders".to_string(),
+                ..Default::default()
+            }],
+            spend: BTreeMap::from([("123456789012".to_string(), 10.0)]),
+            warnings: vec!["123456789012 us-east-1: cannot read parameter group for orders".to_string()],
+            ..Default::default()
+        };
+        Redactor::default().redact_result(&mut result);
+        assert!(result.spend.contains_key("000000000001"));
+        assert_eq!(result.warnings[0], "000000000001 us-east-1: cannot read parameter group for db-1");
+    }
+
+    #[test]
+    fn one_redactor_keeps_accounts_consistent_across_outputs() {
+        let mut r = Redactor::default();
+        let mut inst = RdsInstance {
+            account_id: "123456789012".to_string(),
+            ..Default::default()
+        };
+        r.redact_instance(&mut inst);
+        assert_eq!(inst.account_id, "000000000001");
+
+        let call = |account: &str| ApiCall {
+            service: "rds",
+            operation: "DescribeDBInstances",
+            region: "us-east-1".to_string(),
+            account: account.to_string(),
+        };
+        let stats = r.redact_api_stats(BTreeMap::from([(call("123456789012"), 3), (call(""), 1)]));
+        assert_eq!(stats.get(&call("000000000001")), Some(&3));
+        assert_eq!(stats.get(&call("")), Some(&1));
+    }
+}
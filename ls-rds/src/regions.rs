@@ -0,0 +1,54 @@
+//! Region discovery for ls-rds
+//!
+//! `--all-regions` scans every region an account has enabled instead of a
+//! fixed `--regions` list.  Accounts enable opt-in regions (`af-south-1`,
+//! `me-central-1` …) one by one, so the list is read per account from EC2
+//! DescribeRegions; regions the account has not opted in to are left out,
+//! since every call there would fail.
+
+use crate::api_stats::ApiStats;
+use aws_sdk_ec2 as ec2;
+use aws_sdk_ec2::error::SdkError;
+use aws_sdk_ec2::operation::describe_regions::DescribeRegionsError;
+use aws_types::region::Region;
+
+/// Opt-in statuses of regions an account can call
+const ENABLED_STATUSES: &[&str] = &["opt-in-not-required", "opted-in"];
+
+/// Whether a region with this opt-in status is enabled for the account
+pub fn is_enabled(opt_in_status: Option<&str>) -> bool {
+    opt_in_status.is_some_and(|s| ENABLED_STATUSES.contains(&s))
+}
+
+/// The account's enabled regions, sorted; `region` is where the call is
+/// sent
+pub async fn enabled_regions(
+    client: &ec2::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+) -> Result<Vec<String>, SdkError<DescribeRegionsError>> {
+    stats.record("ec2", "DescribeRegions", region.as_ref(), account_id);
+    let output = client.describe_regions().all_regions(true).send().await?;
+    let mut regions: Vec<String> = output
+        .regions()
+        .iter()
+        .filter(|r| is_enabled(r.opt_in_status()))
+        .filter_map(|r| r.region_name().map(str::to_owned))
+        .collect();
+    regions.sort();
+    Ok(regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_enabled_regions_are_scanned() {
+        assert!(is_enabled(Some("opt-in-not-required")));
+        assert!(is_enabled(Some("opted-in")));
+        assert!(!is_enabled(Some("not-opted-in")));
+        assert!(!is_enabled(None));
+    }
+}
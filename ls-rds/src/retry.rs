@@ -0,0 +1,178 @@
+//! Retries for throttled AWS calls
+//!
+//! The SDK's standard retry gives up after a few quick attempts, which a
+//! large organization scan (many regions of many accounts at once) can
+//! exhaust before the account's request rate recovers; the region was then
+//! skipped.  `with_retries` wraps a call and retries throttling errors, and
+//! only those (a denied or invalid call fails the same way again), with
+//! exponential backoff and full jitter so scans backing off together don't
+//! come back in lockstep.  `RetryingProvider` does the same for the
+//! AssumeRole calls behind cross-account credentials.
+
+use aws_credential_types::provider::error::CredentialsError;
+use aws_credential_types::provider::{ProvideCredentials, future};
+use aws_sdk_rds::error::ProvideErrorMetadata;
+use aws_sdk_sts::error::SdkError;
+use aws_sdk_sts::operation::assume_role::AssumeRoleError;
+use log::warn;
+use ls_vpc::ScanErrorKind;
+use std::error::Error;
+use std::future::Future;
+use std::hash::{BuildHasher, RandomState};
+use std::time::Duration;
+
+/// Retries per call unless `--max-retries` says otherwise
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// Ceiling of the first backoff; doubled on every retry
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest single backoff
+const MAX_DELAY: Duration = Duration::from_secs(20);
+
+/// Whether `code` is one AWS services use when a caller exceeds its
+/// request rate, as ls-vpc classifies it
+pub fn is_throttling(code: Option<&str>) -> bool {
+    code.is_some_and(|c| ScanErrorKind::from_code(c) == ScanErrorKind::Throttled)
+}
+
+/// Wait before retry `attempt` (0-based): a `jitter` fraction (0..1) of an
+/// exponentially growing ceiling, capped at `MAX_DELAY`
+pub fn backoff(attempt: u32, jitter: f64) -> Duration {
+    let ceiling = BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt)).min(MAX_DELAY);
+    ceiling.mul_f64(jitter.clamp(0.0, 1.0))
+}
+
+/// A fresh fraction in 0..1 from the randomly seeded std hasher
+fn jitter() -> f64 {
+    RandomState::new().hash_one(0u8) as f64 / u64::MAX as f64
+}
+
+/// Run `call`, retrying throttling errors up to `max_retries` times; any
+/// other error, or the last throttling one, is returned
+pub async fn with_retries<T, E, F, Fut>(max_retries: u32, call: F) -> Result<T, E>
+where
+    E: ProvideErrorMetadata,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_on_code(max_retries, |e: &E| e.code().map(str::to_owned), call).await
+}
+
+/// [`with_retries`] for errors whose AWS error code `code_of` digs out
+async fn retry_on_code<T, E, F, Fut>(
+    max_retries: u32,
+    code_of: impl Fn(&E) -> Option<String>,
+    mut call: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 0;
+    loop {
+        match call().await {
+            Err(e) if attempt < max_retries && is_throttling(code_of(&e).as_deref()) => {
+                let delay = backoff(attempt, jitter());
+                warn!(
+                    "   {} (retry {}/{} in {:.1?})",
+                    code_of(&e).unwrap_or_default(),
+                    attempt + 1,
+                    max_retries,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Error code of the STS AssumeRole call behind a credentials error
+fn assume_role_code(err: &CredentialsError) -> Option<String> {
+    let mut source = Error::source(err);
+    while let Some(e) = source {
+        if let Some(e) = e.downcast_ref::<SdkError<AssumeRoleError>>() {
+            return e.code().map(str::to_owned);
+        }
+        source = e.source();
+    }
+    None
+}
+
+/// Credentials provider that retries its inner provider when the AssumeRole
+/// call it makes is throttled.  The SDK fetches credentials lazily, inside
+/// whichever call needs them first, so this is the only place a throttled
+/// AssumeRole can be retried.
+#[derive(Debug)]
+pub struct RetryingProvider<P> {
+    inner: P,
+    max_retries: u32,
+}
+
+impl<P> RetryingProvider<P> {
+    pub fn new(inner: P, max_retries: u32) -> Self {
+        RetryingProvider { inner, max_retries }
+    }
+}
+
+impl<P: ProvideCredentials> ProvideCredentials for RetryingProvider<P> {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::new(retry_on_code(self.max_retries, assume_role_code, || {
+            self.inner.provide_credentials()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(0, 1.0), Duration::from_millis(500));
+        assert_eq!(backoff(3, 1.0), Duration::from_secs(4));
+        assert_eq!(backoff(10, 1.0), MAX_DELAY);
+        assert_eq!(backoff(40, 1.0), MAX_DELAY);
+        assert_eq!(backoff(3, 0.5), Duration::from_secs(2));
+        assert!((0.0..=1.0).contains(&jitter()));
+    }
+
+    #[tokio::test]
+    async fn with_retries_stops_at_the_first_other_error() {
+        use aws_sdk_rds::error::ErrorMetadata;
+        let error = |code: &str| ErrorMetadata::builder().code(code).build();
+        let mut codes = vec!["AccessDenied", "Throttling"];
+        let mut calls = 0;
+        let result: Result<(), ErrorMetadata> = with_retries(5, || {
+            calls += 1;
+            let code = codes.pop().unwrap();
+            async move { Err(error(code)) }
+        })
+        .await;
+        assert_eq!(result.unwrap_err().code(), Some("AccessDenied"));
+        assert_eq!(calls, 2);
+
+        let result: Result<(), ErrorMetadata> = with_retries(0, || async { Err(error("Throttling")) }).await;
+        assert_eq!(result.unwrap_err().code(), Some("Throttling"));
+    }
+
+    #[test]
+    fn only_throttling_codes_are_retried() {
+        assert!(is_throttling(Some("Throttling")));
+        assert!(is_throttling(Some("RequestLimitExceeded")));
+        assert!(is_throttling(Some("SlowDown")));
+        assert!(!is_throttling(Some("AccessDenied")));
+        assert!(!is_throttling(None));
+    }
+
+    #[test]
+    fn credentials_errors_without_an_sts_call_have_no_code() {
+        assert_eq!(assume_role_code(&CredentialsError::not_loaded("no profile")), None);
+        assert_eq!(assume_role_code(&CredentialsError::provider_error("network down")), None);
+    }
+}
@@ -0,0 +1,94 @@
+//! Cross-account role mapping for ls-rds
+//!
+//! Org mode assumes one role per member account.  A `--role-map` YAML file
+//! overrides the role for individual accounts and sets the default name:
+//!
+//! ```yaml
+//! default: OrganizationAccountAccessRole
+//! accounts:
+//!   "111111111111": arn:aws:iam::111111111111:role/LegacyReadOnly
+//! ```
+
+use eyre::{Result, WrapErr, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Role name assumed in member accounts when no mapping says otherwise
+pub const DEFAULT_ROLE_NAME: &str = "YourCrossAccountRole";
+
+/// Session name of assumed roles, as seen in CloudTrail
+pub const DEFAULT_SESSION_NAME: &str = "ls-rds";
+
+/// Account-id → role-ARN overrides plus a default role name
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct RoleMap {
+    /// Role name used for accounts without an override
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Per-account role ARN overrides
+    #[serde(default)]
+    pub accounts: BTreeMap<String, String>,
+}
+
+impl RoleMap {
+    /// Parse and validate a role map from YAML text
+    pub fn parse(yaml: &str) -> Result<Self> {
+        let map: RoleMap = serde_yaml::from_str(yaml)?;
+        for (account, arn) in &map.accounts {
+            if !arn.starts_with("arn:aws:iam::") || !arn.contains(":role/") {
+                bail!("Invalid role ARN '{}' for account {} in role map", arn, account);
+            }
+        }
+        Ok(map)
+    }
+
+    /// Read a role map file
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).wrap_err_with(|| format!("Cannot read role map {}", path.display()))?;
+        Self::parse(&text).wrap_err_with(|| format!("Invalid role map {}", path.display()))
+    }
+
+    /// Role ARN to assume in `account_id`
+    pub fn role_arn_for(&self, account_id: &str) -> String {
+        match self.accounts.get(account_id) {
+            Some(arn) => arn.clone(),
+            None => format!(
+                "arn:aws:iam::{}:role/{}",
+                account_id,
+                self.default.as_deref().unwrap_or(DEFAULT_ROLE_NAME)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn role_arn_for_uses_override_then_default() {
+        let map = RoleMap::parse(
+            "default: OrgReader\naccounts:\n  \"111111111111\": arn:aws:iam::111111111111:role/Legacy\n",
+        )
+        .unwrap();
+        assert_eq!(map.role_arn_for("111111111111"), "arn:aws:iam::111111111111:role/Legacy");
+        assert_eq!(map.role_arn_for("222222222222"), "arn:aws:iam::222222222222:role/OrgReader");
+    }
+
+    #[test]
+    fn empty_map_falls_back_to_builtin_role() {
+        let map = RoleMap::default();
+        assert_eq!(
+            map.role_arn_for("333333333333"),
+            format!("arn:aws:iam::333333333333:role/{}", DEFAULT_ROLE_NAME)
+        );
+    }
+
+    #[test]
+    fn parse_rejects_non_role_arn() {
+        let err = RoleMap::parse("accounts:\n  \"1\": arn:aws:iam::1:user/bob\n").unwrap_err();
+        assert!(err.to_string().contains("Invalid role ARN"));
+    }
+}
@@ -0,0 +1,547 @@
+//! Library entry point for embedding ls-rds
+//!
+//! `Scanner::builder()` configures a scan without going through the
+//! CLI-shaped `Config`, and `Scanner::scan()` yields one `ScanResult` per
+//! account/region as each finishes, at most `concurrency` at a time:
+//!
+//! ```no_run
+//! # async fn example() -> eyre::Result<()> {
+//! use futures::StreamExt;
+//! use ls_rds::{ScanMode, Scanner};
+//!
+//! let scanner = Scanner::builder()
+//!     .regions(["us-east-1", "eu-west-1"])
+//!     .mode(ScanMode::Organization)
+//!     .concurrency(8)
+//!     .build()?;
+//! let mut results = scanner.scan().await?;
+//! while let Some(result) = results.next().await {
+//!     for inst in &result.instances {
+//!         println!("{} {} {}", inst.account_id, inst.region, inst.instance_id);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    AccountFilter, AccountInfo, ApiStats, Config, EngineFamily, RoleMap, RoleSession, ScanMode, ScanResult,
+    account_alias, extract_account_from_arn, get_caller_account, get_default_region, list_org_accounts,
+    list_ou_accounts, manifest, org_account_ids, region_conf, regions, scan_region, sdk_loader, spend,
+};
+use crate::plan::{self, PlannedAccount, ScanPlan};
+use aws_sdk_ec2 as ec2;
+use aws_types::{SdkConfig, region::Region};
+use eyre::{Result, bail};
+use futures::stream::{self, Stream, StreamExt};
+use log::info;
+use std::time::Duration;
+
+/// Account/region pairs scanned at once unless overridden
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// A configured RDS scan; build one with [`Scanner::builder`]
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    config: Config,
+    concurrency: usize,
+    stats: ApiStats,
+}
+
+/// Builder for [`Scanner`]; unset options take the CLI defaults
+#[derive(Debug, Clone)]
+pub struct ScannerBuilder {
+    config: Config,
+    concurrency: usize,
+}
+
+/// One account to scan and how to reach it
+#[derive(Debug, Clone)]
+struct Target {
+    account_id: String,
+    role_arn: Option<String>,
+    info: Option<AccountInfo>,
+}
+
+impl Scanner {
+    pub fn builder() -> ScannerBuilder {
+        ScannerBuilder {
+            config: Config::default(),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// The configuration the scan runs with
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Account/region pairs scanned at once
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// AWS API calls this scanner has made so far
+    pub fn api_stats(&self) -> &ApiStats {
+        &self.stats
+    }
+
+    /// A scanner for an already-validated CLI config
+    pub fn from_config(config: Config) -> Scanner {
+        Scanner {
+            concurrency: config.max_concurrency.unwrap_or(DEFAULT_CONCURRENCY),
+            config,
+            stats: ApiStats::default(),
+        }
+    }
+
+    /// Resolve the accounts to scan, then stream one result per
+    /// account/region in completion order.  Failures inside a region end up
+    /// in that result's `warnings`; only failing to resolve the caller or
+    /// the organization's accounts is an error.
+    pub async fn scan(&self) -> Result<impl Stream<Item = ScanResult> + '_> {
+        Ok(self.scan_ordered().await?.map(|(_, result)| result))
+    }
+
+    /// [`Scanner::scan`], with each result numbered by its place in account
+    /// then region order so a collector can restore that order.  Result 0
+    /// carries the run-wide lookups: the organization's accounts for
+    /// `--snapshots` and the `--with-spend` spend.
+    pub(crate) async fn scan_ordered(&self) -> Result<impl Stream<Item = (usize, ScanResult)> + '_> {
+        let config = &self.config;
+        let stats = &self.stats;
+        let base_conf = sdk_loader(config).region(Region::new(get_default_region(config))).load().await;
+        let session = RoleSession::from_config(config);
+        let caller_account =
+            get_caller_account(&base_conf, stats, session.sts_region.as_ref(), config.retries()).await?;
+        info!("Scanning as account {}", caller_account);
+        let targets = self.targets(&base_conf, &caller_account).await?;
+
+        let mut run_wide = ScanResult::default();
+        if config.snapshots {
+            match org_account_ids(&base_conf, stats).await {
+                Ok(ids) => run_wide.org_accounts = ids,
+                Err(e) => run_wide.warn(format!(
+                    "cannot list organization accounts, external share check skipped: {:#}",
+                    e
+                )),
+            }
+        }
+        if let Some(days) = config.spend_days {
+            match spend::rds_spend_by_account(&base_conf, stats, days).await {
+                Ok(spend) => run_wide.spend = spend,
+                Err(e) => run_wide.warn(format!("cannot read RDS spend from Cost Explorer: {:#}", e)),
+            }
+        }
+
+        let targets = if config.account_names {
+            self.account_aliases(&base_conf, &session, targets, &mut run_wide).await
+        } else {
+            targets
+        };
+        let targets = self.target_regions(&base_conf, &session, targets, &mut run_wide).await;
+
+        let mut completed = match &config.manifest {
+            Some(path) if config.resume => manifest::load(path)?,
+            Some(path) => {
+                manifest::create(path)?;
+                manifest::Completed::new()
+            }
+            None => manifest::Completed::new(),
+        };
+        if !completed.is_empty() {
+            info!("Resuming: {} account/region pairs already scanned", completed.len());
+        }
+
+        let jobs: Vec<(usize, Target, Region, Option<ScanResult>)> = targets
+            .into_iter()
+            .flat_map(|(target, regions)| regions.into_iter().map(move |r| (target.clone(), Region::new(r))))
+            .enumerate()
+            .map(|(n, (target, region))| {
+                let resumed = completed.remove(&(target.account_id.clone(), region.to_string()));
+                (n + 1, target, region, resumed)
+            })
+            .collect();
+
+        let scans = stream::iter(jobs)
+            .map(move |(n, target, region, resumed)| {
+                let base_conf = base_conf.clone();
+                let session = session.clone();
+                async move {
+                    if let Some(result) = resumed {
+                        return (n, result);
+                    }
+                    let role_arn = target.role_arn.as_deref();
+                    let conf = region_conf(&base_conf, stats, &region, role_arn, &session).await;
+                    let mut result = ScanResult::default();
+                    scan_region(&conf, stats, &region, &target.account_id, role_arn, config, &mut result).await;
+                    if let Some(info) = target.info {
+                        result.accounts.insert(info.id.clone(), info);
+                    }
+                    // Pairs with warnings or errors stay out of the manifest
+                    // so a resumed run scans them again
+                    let recorded = config
+                        .manifest
+                        .as_deref()
+                        .filter(|_| result.warnings.is_empty() && result.errors.is_empty())
+                        .map(|path| manifest::append(path, &target.account_id, region.as_ref(), &result));
+                    if let Some(Err(e)) = recorded {
+                        result.warn(format!(
+                            "{} {}: cannot record the scan in the manifest: {:#}",
+                            target.account_id, region, e
+                        ));
+                    }
+                    (n, result)
+                }
+            })
+            .buffer_unordered(self.concurrency);
+        Ok(stream::iter([(0, run_wide)]).chain(scans))
+    }
+
+    /// Resolve the caller, accounts and roles the scan would use and the
+    /// calls it would make, without scanning (`--dry-run`)
+    pub async fn plan(&self) -> Result<ScanPlan> {
+        let config = &self.config;
+        let base_conf = sdk_loader(config).region(Region::new(get_default_region(config))).load().await;
+        let session = RoleSession::from_config(config);
+        let caller_account =
+            get_caller_account(&base_conf, &self.stats, session.sts_region.as_ref(), config.retries()).await?;
+        let targets = self.targets(&base_conf, &caller_account).await?;
+        // An account whose enabled regions cannot be read keeps no regions,
+        // as the scan skips it
+        let mut accounts: Vec<PlannedAccount> = targets
+            .iter()
+            .map(|target| PlannedAccount {
+                account_id: target.account_id.clone(),
+                role_arn: target.role_arn.clone(),
+                regions: Vec::new(),
+            })
+            .collect();
+        let resolved = self.target_regions(&base_conf, &session, targets, &mut ScanResult::default()).await;
+        for (target, regions) in resolved {
+            if let Some(account) = accounts.iter_mut().find(|a| a.account_id == target.account_id) {
+                account.regions = regions;
+            }
+        }
+        Ok(ScanPlan {
+            caller_account,
+            accounts,
+            region_operations: plan::region_operations(config),
+            instance_operations: plan::instance_operations(config),
+            run_operations: plan::run_operations(config),
+        })
+    }
+
+    /// Name the targets Organizations did not (`--account-names`) after
+    /// their IAM account alias; a failed lookup only leaves the name out
+    async fn account_aliases(
+        &self,
+        base_conf: &SdkConfig,
+        session: &RoleSession,
+        targets: Vec<Target>,
+        run_wide: &mut ScanResult,
+    ) -> Vec<Target> {
+        let stats = &self.stats;
+        let home = Region::new(get_default_region(&self.config));
+        let looked_up: Vec<_> = stream::iter(targets)
+            .map(|target| {
+                let home = &home;
+                async move {
+                    if target.info.is_some() {
+                        return (target, None);
+                    }
+                    let conf = region_conf(base_conf, stats, home, target.role_arn.as_deref(), session).await;
+                    let alias = account_alias(&conf, stats, home, &target.account_id).await;
+                    (target, Some(alias))
+                }
+            })
+            .buffered(self.concurrency)
+            .collect()
+            .await;
+        let mut targets = Vec::new();
+        for (mut target, alias) in looked_up {
+            match alias {
+                Some(Ok(alias)) => {
+                    target.info = Some(AccountInfo {
+                        id: target.account_id.clone(),
+                        name: alias.unwrap_or_default(),
+                        ..Default::default()
+                    })
+                }
+                Some(Err(e)) => run_wide.warn(format!("{}: cannot read the account alias: {:#}", target.account_id, e)),
+                None => {}
+            }
+            targets.push(target);
+        }
+        targets
+    }
+
+    /// Pair each target with the regions to scan in it: the `--region` list,
+    /// or with `--all-regions` the regions the account has enabled
+    async fn target_regions(
+        &self,
+        base_conf: &SdkConfig,
+        session: &RoleSession,
+        targets: Vec<Target>,
+        run_wide: &mut ScanResult,
+    ) -> Vec<(Target, Vec<String>)> {
+        if self.config.all_regions {
+            self.enabled_regions(base_conf, session, targets, run_wide).await
+        } else {
+            let regions: Vec<String> = self.config.regions.iter().map(|r| r.trim().to_owned()).collect();
+            targets.into_iter().map(|target| (target, regions.clone())).collect()
+        }
+    }
+
+    /// Pair each target with the regions it has enabled (`--all-regions`);
+    /// an account whose regions cannot be read is skipped and recorded as
+    /// failed in `run_wide`
+    async fn enabled_regions(
+        &self,
+        base_conf: &SdkConfig,
+        session: &RoleSession,
+        targets: Vec<Target>,
+        run_wide: &mut ScanResult,
+    ) -> Vec<(Target, Vec<String>)> {
+        let stats = &self.stats;
+        let home = Region::new(get_default_region(&self.config));
+        let found: Vec<_> = stream::iter(targets)
+            .map(|target| {
+                let home = &home;
+                async move {
+                    let conf = region_conf(base_conf, stats, home, target.role_arn.as_deref(), session).await;
+                    let found = regions::enabled_regions(&ec2::Client::new(&conf), stats, home, &target.account_id);
+                    (found.await, target)
+                }
+            })
+            .buffered(self.concurrency)
+            .collect()
+            .await;
+        let mut targets = Vec::new();
+        for (found, target) in found {
+            match found {
+                Ok(regions) => {
+                    info!("{}: {} enabled regions", target.account_id, regions.len());
+                    targets.push((target, regions));
+                }
+                Err(e) => run_wide.fail(&target.account_id, &home, "DescribeRegions", "account", &e),
+            }
+        }
+        targets
+    }
+
+    /// Accounts selected by the scan mode; the caller's own account is
+    /// scanned with its existing credentials
+    async fn targets(&self, base_conf: &SdkConfig, caller_account: &str) -> Result<Vec<Target>> {
+        let via_role = |account_id: &str, role_arn: String| Target {
+            account_id: account_id.to_owned(),
+            role_arn: (account_id != caller_account).then_some(role_arn),
+            info: None,
+        };
+        Ok(match &self.config.mode {
+            ScanMode::CurrentAccount => vec![Target {
+                account_id: caller_account.to_owned(),
+                role_arn: None,
+                info: None,
+            }],
+            ScanMode::Organization => {
+                let accounts = match &self.config.ou {
+                    Some(ou) => list_ou_accounts(base_conf, &self.stats, ou).await?,
+                    None => list_org_accounts(base_conf, &self.stats).await?,
+                };
+                accounts
+                    .into_iter()
+                    .filter(|account| self.config.account_filter.selects(account))
+                    .map(|account| Target {
+                        account_id: account.id.clone(),
+                        role_arn: Some(self.config.role_map.role_arn_for(&account.id)),
+                        info: Some(account),
+                    })
+                    .collect()
+            }
+            ScanMode::RoleArns(arns) => arns
+                .iter()
+                .map(|arn| via_role(extract_account_from_arn(arn).unwrap_or_default(), arn.clone()))
+                .collect(),
+            ScanMode::Accounts(ids) => ids
+                .iter()
+                .map(|id| via_role(id, self.config.role_map.role_arn_for(id)))
+                .collect(),
+        })
+    }
+}
+
+impl ScannerBuilder {
+    /// Regions to scan in every account
+    pub fn regions<I, S>(mut self, regions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.regions = regions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Scan every region each account has enabled instead of `regions`
+    pub fn all_regions(mut self, all_regions: bool) -> Self {
+        self.config.all_regions = all_regions;
+        self
+    }
+
+    /// Which accounts to scan
+    pub fn mode(mut self, mode: ScanMode) -> Self {
+        self.config.mode = mode;
+        self
+    }
+
+    /// Limit organization mode to the accounts under this OU (or root),
+    /// nested OUs included
+    pub fn ou(mut self, ou: impl Into<String>) -> Self {
+        self.config.ou = Some(ou.into());
+        self
+    }
+
+    /// Organization accounts to scan or skip, by ID or name pattern
+    pub fn account_filter(mut self, filter: AccountFilter) -> Self {
+        self.config.account_filter = filter;
+        self
+    }
+
+    /// Role to assume per account in organization and account-list modes
+    pub fn role_map(mut self, role_map: RoleMap) -> Self {
+        self.config.role_map = role_map;
+        self
+    }
+
+    /// Account/region pairs to scan at once
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Only keep instances of this engine family
+    pub fn engine_family(mut self, family: EngineFamily) -> Self {
+        self.config.engine_family = Some(family);
+        self
+    }
+
+    /// Resolve each clustered instance's writer/reader role
+    pub fn with_cluster(mut self, with_cluster: bool) -> Self {
+        self.config.with_cluster = with_cluster;
+        self
+    }
+
+    /// Parameter values to read from each instance's parameter groups
+    pub fn with_params<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.with_params = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Retry throttled or failed AWS calls up to this many times
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.config.max_retries = Some(retries);
+        self
+    }
+
+    /// Named profile the base credentials come from
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.config.profile = Some(profile.into());
+        self
+    }
+
+    /// Connect timeout for AWS endpoints
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for a whole AWS call including retries
+    pub fn operation_timeout(mut self, timeout: Duration) -> Self {
+        self.config.operation_timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(self) -> Result<Scanner> {
+        if self.config.regions.iter().all(|r| r.trim().is_empty()) {
+            bail!("At least one region must be specified");
+        }
+        if self.concurrency == 0 {
+            bail!("Concurrency must be at least 1");
+        }
+        Ok(Scanner {
+            config: self.config,
+            concurrency: self.concurrency,
+            stats: ApiStats::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_defaults_match_cli() {
+        let scanner = Scanner::builder().build().unwrap();
+        assert_eq!(scanner.config().regions, Config::default().regions);
+        assert_eq!(scanner.config().mode, ScanMode::CurrentAccount);
+        assert_eq!(scanner.concurrency(), DEFAULT_CONCURRENCY);
+    }
+
+    #[test]
+    fn builder_sets_options() {
+        let scanner = Scanner::builder()
+            .regions(["eu-west-1"])
+            .all_regions(true)
+            .mode(ScanMode::Accounts(vec!["111111111111".to_string()]))
+            .ou("ou-ab12-cd34ef56")
+            .concurrency(16)
+            .engine_family(EngineFamily::OpenSource)
+            .with_cluster(true)
+            .with_params(["max_connections"])
+            .max_retries(5)
+            .profile("audit")
+            .operation_timeout(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let config = scanner.config();
+        assert_eq!(config.regions, vec!["eu-west-1"]);
+        assert!(config.all_regions);
+        assert_eq!(config.mode, ScanMode::Accounts(vec!["111111111111".to_string()]));
+        assert_eq!(scanner.concurrency(), 16);
+        assert_eq!(config.ou.as_deref(), Some("ou-ab12-cd34ef56"));
+        assert_eq!(config.engine_family, Some(EngineFamily::OpenSource));
+        assert!(config.with_cluster);
+        assert_eq!(config.with_params, vec!["max_connections"]);
+        assert_eq!(config.max_retries, Some(5));
+        assert_eq!(config.profile.as_deref(), Some("audit"));
+        assert_eq!(config.operation_timeout, Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn from_config_keeps_the_cli_options() {
+        let config = Config {
+            snapshots: true,
+            ..Config::default()
+        };
+        let scanner = Scanner::from_config(config);
+        assert_eq!(scanner.concurrency(), DEFAULT_CONCURRENCY);
+        assert!(scanner.config().snapshots);
+
+        let scanner = Scanner::from_config(Config {
+            max_concurrency: Some(12),
+            ..Config::default()
+        });
+        assert_eq!(scanner.concurrency(), 12);
+    }
+
+    #[test]
+    fn build_rejects_empty_regions_and_zero_concurrency() {
+        assert!(Scanner::builder().regions(Vec::<String>::new()).build().is_err());
+        assert!(Scanner::builder().concurrency(0).build().is_err());
+    }
+}
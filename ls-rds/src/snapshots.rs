@@ -0,0 +1,286 @@
+//! Snapshot audit for ls-rds
+//!
+//! Lists DB snapshots and flags the ones worth sweeping: manual snapshots
+//! whose restore attribute is shared with `all` (public) or with accounts
+//! outside the organization, and snapshots older than the
+//! `--older-than` cut-off.
+
+use crate::api_stats::ApiStats;
+use crate::retry;
+use aws_sdk_rds as rds;
+use aws_types::region::Region;
+use chrono::{DateTime, Duration, Utc};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A DB snapshot and the attributes the audit cares about
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DbSnapshot {
+    pub account_id: String,
+    pub region: String,
+    pub identifier: String,
+    pub arn: String,
+    /// Instance the snapshot was taken from
+    pub instance_id: String,
+    /// `manual`, `automated`, `awsbackup` …
+    pub snapshot_type: String,
+    pub created: Option<DateTime<Utc>>,
+    /// Restorable by any AWS account
+    pub public: bool,
+    /// Account IDs the snapshot is shared with
+    pub shared_with: Vec<String>,
+}
+
+impl DbSnapshot {
+    /// Whether the snapshot is older than `max_age` at `now`
+    pub fn is_older_than(&self, max_age: Duration, now: DateTime<Utc>) -> bool {
+        self.created.is_some_and(|c| now - c > max_age)
+    }
+
+    /// Accounts the snapshot is shared with that aren't in `org_accounts`.
+    /// An empty `org_accounts` means membership is unknown, so nothing is
+    /// reported.
+    pub fn external_shares<'a>(&'a self, org_accounts: &BTreeSet<String>) -> Vec<&'a str> {
+        if org_accounts.is_empty() {
+            return Vec::new();
+        }
+        self.shared_with
+            .iter()
+            .filter(|a| !org_accounts.contains(*a))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Issues found on a snapshot, e.g. `public`, `external-share`, `stale`
+pub fn snapshot_flags(
+    snap: &DbSnapshot,
+    older_than: Option<Duration>,
+    org_accounts: &BTreeSet<String>,
+    now: DateTime<Utc>,
+) -> Vec<&'static str> {
+    let mut flags = Vec::new();
+    if snap.public {
+        flags.push("public");
+    }
+    if !snap.external_shares(org_accounts).is_empty() {
+        flags.push("external-share");
+    }
+    if older_than.is_some_and(|age| snap.is_older_than(age, now)) {
+        flags.push("stale");
+    }
+    flags
+}
+
+/// Values of the snapshot's `restore` attribute: account IDs it is shared
+/// with, or `all` when it is public
+async fn restore_attribute(
+    client: &rds::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    identifier: &str,
+    retries: u32,
+) -> Result<Vec<String>> {
+    let output = retry::with_retries(retries, || {
+        stats.record("rds", "DescribeDBSnapshotAttributes", region.as_ref(), account_id);
+        client.describe_db_snapshot_attributes().db_snapshot_identifier(identifier).send()
+    })
+    .await?;
+    Ok(output
+        .db_snapshot_attributes_result()
+        .map(|r| r.db_snapshot_attributes())
+        .unwrap_or_default()
+        .iter()
+        .filter(|a| a.attribute_name() == Some("restore"))
+        .flat_map(|a| a.attribute_values().to_vec())
+        .collect())
+}
+
+/// List DB snapshots in one region.  Only manual snapshots can be shared, so
+/// only those have their attributes looked up.
+pub async fn list_snapshots(
+    client: &rds::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    retries: u32,
+) -> Result<Vec<DbSnapshot>> {
+    let mut snapshots = Vec::new();
+    let mut marker: Option<String> = None;
+    loop {
+        let page = retry::with_retries(retries, || {
+            stats.record("rds", "DescribeDBSnapshots", region.as_ref(), account_id);
+            client.describe_db_snapshots().set_marker(marker.clone()).send()
+        })
+        .await?;
+        for s in page.db_snapshots() {
+            snapshots.push(DbSnapshot {
+                account_id: account_id.to_owned(),
+                region: region.to_string(),
+                identifier: s.db_snapshot_identifier().unwrap_or_default().to_owned(),
+                arn: s.db_snapshot_arn().unwrap_or_default().to_owned(),
+                instance_id: s.db_instance_identifier().unwrap_or_default().to_owned(),
+                snapshot_type: s.snapshot_type().unwrap_or_default().to_owned(),
+                created: s
+                    .snapshot_create_time()
+                    .and_then(|t| DateTime::from_timestamp(t.secs(), 0)),
+                ..Default::default()
+            });
+        }
+        match page.marker() {
+            Some(next) => marker = Some(next.to_owned()),
+            None => break,
+        }
+    }
+    for snap in snapshots.iter_mut().filter(|s| s.snapshot_type == "manual") {
+        let restore = restore_attribute(client, stats, region, account_id, &snap.identifier, retries).await?;
+        snap.public = restore.iter().any(|v| v == "all");
+        snap.shared_with = restore.into_iter().filter(|v| v != "all").collect();
+    }
+    Ok(snapshots)
+}
+
+/// Format a snapshot as a TSV row: region, id, instance, type, age, shared
+/// accounts, flags
+pub fn format_snapshot(
+    snap: &DbSnapshot,
+    older_than: Option<Duration>,
+    org_accounts: &BTreeSet<String>,
+    now: DateTime<Utc>,
+) -> String {
+    let age = snap
+        .created
+        .map(|c| format!("{}d", (now - c).num_days()))
+        .unwrap_or_else(|| "-".to_owned());
+    let dash_if_empty = |v: String| if v.is_empty() { "-".to_owned() } else { v };
+    let flags = snapshot_flags(snap, older_than, org_accounts, now);
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        snap.region,
+        snap.identifier,
+        snap.instance_id,
+        snap.snapshot_type,
+        age,
+        dash_if_empty(snap.shared_with.join(",")),
+        if flags.is_empty() { "-".to_owned() } else { flags.join(",") }
+    )
+}
+
+/// Per-account totals: snapshots, public, shared outside the organization,
+/// older than the cut-off
+pub fn format_snapshot_counts(
+    snapshots: &[DbSnapshot],
+    older_than: Option<Duration>,
+    org_accounts: &BTreeSet<String>,
+    now: DateTime<Utc>,
+) -> String {
+    let mut counts: BTreeMap<&str, [usize; 4]> = BTreeMap::new();
+    for snap in snapshots {
+        let entry = counts.entry(snap.account_id.as_str()).or_default();
+        entry[0] += 1;
+        let flags = snapshot_flags(snap, older_than, org_accounts, now);
+        for (i, flag) in ["public", "external-share", "stale"].iter().enumerate() {
+            if flags.contains(flag) {
+                entry[i + 1] += 1;
+            }
+        }
+    }
+    let mut out = String::from("ACCOUNT\tSNAPSHOTS\tPUBLIC\tEXTERNAL\tSTALE\n");
+    for (account, [total, public, external, stale]) in counts {
+        out.push_str(&format!("{}\t{}\t{}\t{}\t{}\n", account, total, public, external, stale));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap()
+    }
+
+    fn snap(account: &str, days_old: i64, public: bool) -> DbSnapshot {
+        DbSnapshot {
+            account_id: account.to_string(),
+            region: "us-east-1".to_string(),
+            identifier: format!("snap-{days_old}"),
+            instance_id: "db1".to_string(),
+            snapshot_type: "manual".to_string(),
+            created: Some(now() - Duration::days(days_old)),
+            public,
+            ..Default::default()
+        }
+    }
+
+    fn org(ids: &[&str]) -> BTreeSet<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn snapshot_flags_public_and_stale() {
+        let age = Some(Duration::days(90));
+        let none = BTreeSet::new();
+        assert_eq!(snapshot_flags(&snap("1", 10, false), age, &none, now()), Vec::<&str>::new());
+        assert_eq!(snapshot_flags(&snap("1", 120, false), age, &none, now()), vec!["stale"]);
+        assert_eq!(snapshot_flags(&snap("1", 120, true), age, &none, now()), vec!["public", "stale"]);
+        assert_eq!(snapshot_flags(&snap("1", 120, false), None, &none, now()), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn external_shares_only_lists_non_org_accounts() {
+        let s = DbSnapshot {
+            shared_with: vec!["111".to_string(), "999".to_string()],
+            ..snap("111", 1, false)
+        };
+        assert_eq!(s.external_shares(&org(&["111", "222"])), vec!["999"]);
+        assert!(s.external_shares(&org(&["111", "999"])).is_empty());
+        assert!(s.external_shares(&BTreeSet::new()).is_empty());
+        assert_eq!(snapshot_flags(&s, None, &org(&["111"]), now()), vec!["external-share"]);
+    }
+
+    #[test]
+    fn snapshot_without_create_time_is_never_stale() {
+        let s = DbSnapshot {
+            created: None,
+            ..snap("1", 0, false)
+        };
+        assert!(!s.is_older_than(Duration::days(1), now()));
+    }
+
+    #[test]
+    fn format_snapshot_shows_age_and_flags() {
+        let none = BTreeSet::new();
+        let row = format_snapshot(&snap("1", 100, true), Some(Duration::days(30)), &none, now());
+        assert_eq!(row, "us-east-1\tsnap-100\tdb1\tmanual\t100d\t-\tpublic,stale");
+        let row = format_snapshot(&snap("1", 5, false), None, &none, now());
+        assert!(row.ends_with("\t5d\t-\t-"));
+    }
+
+    #[test]
+    fn format_snapshot_lists_shared_accounts() {
+        let s = DbSnapshot {
+            shared_with: vec!["222".to_string(), "999".to_string()],
+            ..snap("111", 5, false)
+        };
+        let row = format_snapshot(&s, None, &org(&["111", "222"]), now());
+        assert!(row.ends_with("\t222,999\texternal-share"));
+    }
+
+    #[test]
+    fn format_snapshot_counts_groups_by_account() {
+        let shared = DbSnapshot {
+            shared_with: vec!["999".to_string()],
+            ..snap("222", 200, false)
+        };
+        let snaps = vec![snap("111", 100, true), snap("111", 5, false), shared];
+        let out = format_snapshot_counts(&snaps, Some(Duration::days(30)), &org(&["111", "222"]), now());
+        assert_eq!(
+            out,
+            "ACCOUNT\tSNAPSHOTS\tPUBLIC\tEXTERNAL\tSTALE\n111\t2\t1\t0\t1\n222\t1\t0\t1\t1\n"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Cost Explorer integration for ls-rds
+//!
+//! Pulls RDS spend grouped by linked account so it can be joined into the
+//! inventory output.
+
+use crate::api_stats::ApiStats;
+use aws_sdk_costexplorer as ce;
+use aws_sdk_costexplorer::types::{
+    DateInterval, Dimension, DimensionValues, Expression, Granularity, GroupDefinition,
+    GroupDefinitionType, ResultByTime,
+};
+use aws_types::{SdkConfig, region::Region};
+use chrono::Utc;
+use eyre::Result;
+use log::{debug, info};
+use ls_vpc::spend::spend_window;
+use std::collections::BTreeMap;
+
+/// Cost Explorer's SERVICE dimension value for RDS
+pub const RDS_SERVICE: &str = "Amazon Relational Database Service";
+
+/// Cost Explorer metric summed into the report
+pub const SPEND_METRIC: &str = "UnblendedCost";
+
+/// Add every linked-account group in `results` to the running totals.
+pub fn accumulate_spend(results: &[ResultByTime], totals: &mut BTreeMap<String, f64>) {
+    for period in results {
+        for group in period.groups() {
+            let Some(account) = group.keys().first() else {
+                continue;
+            };
+            let amount = group
+                .metrics()
+                .and_then(|m| m.get(SPEND_METRIC))
+                .and_then(|v| v.amount())
+                .and_then(|a| a.parse::<f64>().ok())
+                .unwrap_or_default();
+            *totals.entry(account.clone()).or_default() += amount;
+        }
+    }
+}
+
+/// Query Cost Explorer for RDS spend per linked account over the last `days`.
+pub async fn rds_spend_by_account(base_conf: &SdkConfig, stats: &ApiStats, days: u32) -> Result<BTreeMap<String, f64>> {
+    info!("Querying Cost Explorer for {} days of RDS spend…", days);
+    // Cost Explorer is served from us-east-1 only.
+    let conf = ce::config::Builder::from(base_conf)
+        .region(Region::new("us-east-1"))
+        .build();
+    let client = ce::Client::from_conf(conf);
+
+    let (start, end) = spend_window(Utc::now().date_naive(), days);
+    let mut totals = BTreeMap::new();
+    let mut token: Option<String> = None;
+
+    loop {
+        stats.record("ce", "GetCostAndUsage", "us-east-1", "");
+        let resp = client
+            .get_cost_and_usage()
+            .time_period(DateInterval::builder().start(&start).end(&end).build()?)
+            .granularity(Granularity::Monthly)
+            .metrics(SPEND_METRIC)
+            .filter(
+                Expression::builder()
+                    .dimensions(
+                        DimensionValues::builder()
+                            .key(Dimension::Service)
+                            .values(RDS_SERVICE)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .group_by(
+                GroupDefinition::builder()
+                    .r#type(GroupDefinitionType::Dimension)
+                    .key("LINKED_ACCOUNT")
+                    .build(),
+            )
+            .set_next_page_token(token.take())
+            .send()
+            .await?;
+
+        accumulate_spend(resp.results_by_time(), &mut totals);
+
+        match resp.next_page_token() {
+            Some(next) => token = Some(next.to_owned()),
+            None => break,
+        }
+    }
+
+    debug!("Spend per account: {:?}", totals);
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_costexplorer::types::{Group, MetricValue};
+
+    fn group(account: &str, amount: &str) -> Group {
+        Group::builder()
+            .keys(account)
+            .metrics(SPEND_METRIC, MetricValue::builder().amount(amount).unit("USD").build())
+            .build()
+    }
+
+    #[test]
+    fn accumulate_spend_sums_across_periods() {
+        let results = vec![
+            ResultByTime::builder()
+                .groups(group("111111111111", "10.25"))
+                .groups(group("222222222222", "5"))
+                .build(),
+            ResultByTime::builder().groups(group("111111111111", "4.75")).build(),
+        ];
+        let mut totals = BTreeMap::new();
+        accumulate_spend(&results, &mut totals);
+        assert_eq!(totals.get("111111111111"), Some(&15.0));
+        assert_eq!(totals.get("222222222222"), Some(&5.0));
+    }
+
+    #[test]
+    fn accumulate_spend_ignores_unparseable_amounts() {
+        let results = vec![ResultByTime::builder().groups(group("111111111111", "n/a")).build()];
+        let mut totals = BTreeMap::new();
+        accumulate_spend(&results, &mut totals);
+        assert_eq!(totals.get("111111111111"), Some(&0.0));
+    }
+}
@@ -0,0 +1,90 @@
+//! Expired SSO sessions for ls-rds
+//!
+//! With an IAM Identity Center (SSO) profile, an expired session only shows
+//! up as a credentials-provider failure of the first call, GetCallerIdentity,
+//! several wrappers deep.  `run` recognizes it and fails with the command
+//! that fixes it, or with `--sso-login` runs `aws sso login` itself and
+//! tries once more.
+
+use crate::Config;
+use aws_sdk_sts::error::SdkError;
+use aws_sdk_sts::operation::get_caller_identity::GetCallerIdentityError;
+use eyre::{Report, Result, bail};
+use ls_vpc::errors::{ScanErrorKind, code_in};
+use std::env;
+use std::process::Command;
+
+/// Lowercased fragments of the SDK's messages for a missing, expired or
+/// unusable SSO session
+const EXPIRED_MESSAGES: &[&str] = &[
+    "sso session",
+    "sso token",
+    "token has expired",
+    "is expired",
+    "no providers in chain provided credentials",
+];
+
+/// Whether `err` means the base credentials are expired or missing
+pub fn is_expired_session(err: &Report) -> bool {
+    let code = code_in::<SdkError<GetCallerIdentityError>>(err);
+    if code.is_some_and(|c| ScanErrorKind::from_code(&c) == ScanErrorKind::ExpiredCredentials) {
+        return true;
+    }
+    err.chain().any(|e| {
+        let message = e.to_string().to_lowercase();
+        EXPIRED_MESSAGES.iter().any(|m| message.contains(m))
+    })
+}
+
+/// Profile the base credentials come from: `--profile`, else AWS_PROFILE
+pub fn profile(config: &Config) -> Option<String> {
+    config.profile.clone().or_else(|| env::var("AWS_PROFILE").ok())
+}
+
+/// Arguments of the `aws` command that refreshes the session
+pub fn login_args(profile: Option<&str>) -> Vec<String> {
+    let mut args = vec!["sso".to_owned(), "login".to_owned()];
+    if let Some(profile) = profile {
+        args.extend(["--profile".to_owned(), profile.to_owned()]);
+    }
+    args
+}
+
+/// What to tell the user, e.g. "run `aws sso login --profile prod`"
+pub fn login_hint(profile: Option<&str>) -> String {
+    format!(
+        "AWS credentials are expired or missing; run `aws {}` and try again (or pass --sso-login)",
+        login_args(profile).join(" ")
+    )
+}
+
+/// Refresh the session with the AWS CLI, which opens the browser sign-in
+pub fn login(profile: Option<&str>) -> Result<()> {
+    let status = Command::new("aws").args(login_args(profile)).status();
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => bail!("`aws {}` failed ({})", login_args(profile).join(" "), status),
+        Err(e) => bail!("cannot run `aws {}`: {}", login_args(profile).join(" "), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::eyre;
+
+    #[test]
+    fn expired_sessions_are_recognized_anywhere_in_the_chain() {
+        let expired = eyre!("the SSO session has expired or is invalid").wrap_err("dispatch failure");
+        assert!(is_expired_session(&expired));
+        let missing = eyre!("no providers in chain provided credentials");
+        assert!(is_expired_session(&missing));
+        assert!(!is_expired_session(&eyre!("AccessDenied: not authorized to perform sts:AssumeRole")));
+    }
+
+    #[test]
+    fn login_hint_names_the_profile() {
+        assert_eq!(login_args(None), vec!["sso", "login"]);
+        assert!(login_hint(Some("audit")).contains("run `aws sso login --profile audit`"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Terraform import blocks for ls-rds
+//!
+//! `--emit-tf-imports` prints Terraform 1.5+ `import` blocks for the scanned
+//! DB instances and the clusters, subnet groups and parameter groups they
+//! use.  Resource addresses come from a template (`--tf-address`) so the
+//! blocks can target resources inside modules, e.g.
+//! `module.rds_{account}.{type}.{name}`.
+
+use crate::RdsInstance;
+use eyre::{Result, bail};
+use ls_vpc::terraform::tf_name;
+use std::collections::BTreeSet;
+
+/// Default address template: a top-level resource named after the identifier
+pub const DEFAULT_ADDRESS_TEMPLATE: &str = "{type}.{name}";
+
+const PLACEHOLDERS: [&str; 5] = ["type", "name", "id", "account", "region"];
+
+/// Check that a `--tf-address` template only uses known placeholders and
+/// names the resource type and name.
+pub fn validate_template(template: &str) -> Result<()> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            bail!("Unclosed placeholder in --tf-address '{}'", template);
+        };
+        let key = &rest[start + 1..start + len];
+        if !PLACEHOLDERS.contains(&key) {
+            bail!(
+                "Unknown placeholder '{{{}}}' in --tf-address. Use one of {{type}}, {{name}}, {{id}}, {{account}}, {{region}}",
+                key
+            );
+        }
+        rest = &rest[start + len + 1..];
+    }
+    if !template.contains("{type}") || !template.contains("{name}") {
+        bail!("--tf-address '{}' must contain {{type}} and {{name}}", template);
+    }
+    Ok(())
+}
+
+/// One resource to import
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Import {
+    rtype: &'static str,
+    id: String,
+    account_id: String,
+    region: String,
+}
+
+/// Expand the address template for one resource.
+fn address(template: &str, import: &Import) -> String {
+    template
+        .replace("{type}", import.rtype)
+        .replace("{name}", &tf_name(&import.id))
+        .replace("{id}", &import.id)
+        .replace("{account}", &import.account_id)
+        .replace("{region}", &import.region)
+}
+
+/// Resources behind the scanned instances, de-duplicated per account and
+/// region.  Aurora members are `aws_rds_cluster_instance`s, which import by
+/// the same identifier.  AWS-managed `default` groups can't be managed, so
+/// they are left out.
+fn imports(instances: &[RdsInstance]) -> BTreeSet<Import> {
+    let mut out = BTreeSet::new();
+    for inst in instances {
+        let mut add = |rtype, id: &str| {
+            out.insert(Import {
+                rtype,
+                id: id.to_owned(),
+                account_id: inst.account_id.clone(),
+                region: inst.region.clone(),
+            });
+        };
+        match &inst.cluster_id {
+            Some(cluster) => {
+                add("aws_rds_cluster_instance", &inst.instance_id);
+                add("aws_rds_cluster", cluster);
+            }
+            None => add("aws_db_instance", &inst.instance_id),
+        }
+        if let Some(group) = inst.subnet_group.as_deref().filter(|g| *g != "default") {
+            add("aws_db_subnet_group", group);
+        }
+        for group in inst.parameter_groups.iter().filter(|g| !g.starts_with("default.")) {
+            add("aws_db_parameter_group", group);
+        }
+    }
+    out
+}
+
+/// Render `import` blocks for the instances and their dependencies.  Repeated
+/// addresses (same name in two accounts under a template without
+/// `{account}`) get a `_2`, `_3` … suffix.
+pub fn format_tf_imports(instances: &[RdsInstance], template: &str) -> String {
+    let mut used = BTreeSet::new();
+    let mut out = String::new();
+    for import in imports(instances) {
+        let base = address(template, &import);
+        let mut addr = base.clone();
+        let mut n = 1;
+        while !used.insert(addr.clone()) {
+            n += 1;
+            addr = format!("{}_{}", base, n);
+        }
+        out.push_str(&format!(
+            "# {} {}\nimport {{\n  to = {}\n  id = \"{}\"\n}}\n\n",
+            import.account_id, import.region, addr, import.id
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inst(account: &str, id: &str) -> RdsInstance {
+        RdsInstance {
+            account_id: account.to_string(),
+            region: "us-east-1".to_string(),
+            instance_id: id.to_string(),
+            subnet_group: Some("db-private".to_string()),
+            parameter_groups: vec!["default.postgres15".to_string(), "orders-pg15".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn member(account: &str, id: &str) -> RdsInstance {
+        RdsInstance {
+            cluster_id: Some("orders".to_string()),
+            ..inst(account, id)
+        }
+    }
+
+    #[test]
+    fn validate_template_checks_placeholders() {
+        assert!(validate_template(DEFAULT_ADDRESS_TEMPLATE).is_ok());
+        assert!(validate_template("module.rds_{account}.{type}.{name}").is_ok());
+        assert!(validate_template("{type}.{bogus}").is_err());
+        assert!(validate_template("{type}.{name").is_err());
+        assert!(validate_template("aws_db_instance.{name}").is_err());
+    }
+
+    #[test]
+    fn format_tf_imports_covers_dependencies_once() {
+        let out = format_tf_imports(&[member("111", "orders-1"), member("111", "orders-2")], DEFAULT_ADDRESS_TEMPLATE);
+        assert!(out.contains("import {\n  to = aws_rds_cluster_instance.orders_1\n  id = \"orders-1\"\n}\n"));
+        assert!(out.contains("to = aws_rds_cluster_instance.orders_2"));
+        assert!(!out.contains("aws_db_instance"));
+        assert_eq!(out.matches("to = aws_rds_cluster.orders\n").count(), 1);
+        assert_eq!(out.matches("to = aws_db_subnet_group.db_private\n").count(), 1);
+        assert_eq!(out.matches("to = aws_db_parameter_group.orders_pg15\n").count(), 1);
+        assert!(!out.contains("default.postgres15"));
+    }
+
+    #[test]
+    fn format_tf_imports_keeps_standalone_instances_as_db_instances() {
+        let out = format_tf_imports(&[inst("111", "billing")], DEFAULT_ADDRESS_TEMPLATE);
+        assert!(out.contains("import {\n  to = aws_db_instance.billing\n  id = \"billing\"\n}\n"));
+        assert!(!out.contains("aws_rds_cluster"));
+    }
+
+    #[test]
+    fn format_tf_imports_applies_template_and_dedupes() {
+        let instances = [inst("111", "orders-1"), inst("222", "orders-1")];
+        let out = format_tf_imports(&instances, "module.rds_{account}.{type}.{name}");
+        assert!(out.contains("to = module.rds_111.aws_db_instance.orders_1\n"));
+        assert!(out.contains("to = module.rds_222.aws_db_instance.orders_1\n"));
+
+        let out = format_tf_imports(&instances, DEFAULT_ADDRESS_TEMPLATE);
+        assert!(out.contains("to = aws_db_instance.orders_1\n"));
+        assert!(out.contains("to = aws_db_instance.orders_1_2\n"));
+    }
+}
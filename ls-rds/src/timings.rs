@@ -0,0 +1,68 @@
+//! Per-account/per-region timing for ls-rds
+//!
+//! Each region scan records how long it took, how many API calls it made and
+//! how many instances it found, so `--timings` can point at the slow spots of
+//! a multi-account run.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Cost of scanning one region of one account
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegionTiming {
+    pub account_id: String,
+    pub region: String,
+    pub duration: Duration,
+    pub api_calls: u64,
+    pub instances: usize,
+}
+
+/// Format timings as tab-separated lines, slowest first, with a total.
+pub fn format_timings(timings: &[RegionTiming]) -> String {
+    let mut rows: Vec<&RegionTiming> = timings.iter().collect();
+    rows.sort_by_key(|t| std::cmp::Reverse(t.duration));
+
+    let mut out = String::from("ACCOUNT\tREGION\tDURATION\tCALLS\tINSTANCES\n");
+    for t in rows {
+        out.push_str(&format!(
+            "{}\t{}\t{:.2?}\t{}\t{}\n",
+            t.account_id, t.region, t.duration, t.api_calls, t.instances
+        ));
+    }
+    out.push_str(&format!(
+        "TOTAL\t\t{:.2?}\t{}\t{}\n",
+        timings.iter().map(|t| t.duration).sum::<Duration>(),
+        timings.iter().map(|t| t.api_calls).sum::<u64>(),
+        timings.iter().map(|t| t.instances).sum::<usize>()
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(account: &str, region: &str, ms: u64, calls: u64, instances: usize) -> RegionTiming {
+        RegionTiming {
+            account_id: account.to_string(),
+            region: region.to_string(),
+            duration: Duration::from_millis(ms),
+            api_calls: calls,
+            instances,
+        }
+    }
+
+    #[test]
+    fn format_timings_sorts_slowest_first_and_totals() {
+        let timings = vec![
+            timing("111111111111", "us-east-1", 250, 3, 2),
+            timing("222222222222", "us-west-2", 1_500, 7, 10),
+        ];
+        let out = format_timings(&timings);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "ACCOUNT\tREGION\tDURATION\tCALLS\tINSTANCES");
+        assert_eq!(lines[1], "222222222222\tus-west-2\t1.50s\t7\t10");
+        assert_eq!(lines[2], "111111111111\tus-east-1\t250.00ms\t3\t2");
+        assert_eq!(lines[3], "TOTAL\t\t1.75s\t10\t12");
+    }
+}
@@ -0,0 +1,359 @@
+//! Interactive terminal UI for ls-rds
+//!
+//! `--tui` shows the scanned instances as a filterable table (`/`) with a
+//! detail pane for the selected one.  `y` copies the identifier, `e` the
+//! endpoint and `a` the ARN to the clipboard via OSC 52, which also works
+//! over SSH and inside tmux.
+
+use crate::RdsInstance;
+use crate::network::format_network;
+use crate::zones::format_zone;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use eyre::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::Write;
+
+const HELP: &str = "/ filter  j/k move  y copy id  e copy endpoint  a copy ARN  q quit";
+
+/// Everything the TUI renders, independent of the terminal
+pub struct App<'a> {
+    instances: &'a [RdsInstance],
+    pub filter: String,
+    pub filtering: bool,
+    /// Index into [`App::visible`]
+    pub selected: usize,
+    pub status: String,
+    pub quit: bool,
+}
+
+impl<'a> App<'a> {
+    pub fn new(instances: &'a [RdsInstance]) -> Self {
+        App {
+            instances,
+            filter: String::new(),
+            filtering: false,
+            selected: 0,
+            status: HELP.to_owned(),
+            quit: false,
+        }
+    }
+
+    fn matches(&self, inst: &RdsInstance) -> bool {
+        let needle = self.filter.to_lowercase();
+        needle.is_empty()
+            || [&inst.account_id, &inst.region, &inst.instance_id, &inst.engine, &inst.instance_class]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&needle))
+            || inst.tags.values().any(|v| v.to_lowercase().contains(&needle))
+    }
+
+    /// Instances matching the current filter
+    pub fn visible(&self) -> Vec<&'a RdsInstance> {
+        self.instances.iter().filter(|i| self.matches(i)).collect()
+    }
+
+    /// Selected instance, if any match the filter
+    pub fn current(&self) -> Option<&'a RdsInstance> {
+        self.visible().get(self.selected).copied()
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let len = self.visible().len();
+        self.selected = if len == 0 {
+            0
+        } else {
+            (self.selected as isize + delta).clamp(0, len as isize - 1) as usize
+        };
+    }
+
+    fn copy(&mut self, what: &str, value: Option<String>) -> Option<String> {
+        match value {
+            Some(v) => {
+                self.status = format!("copied {} {}", what, v);
+                Some(v)
+            }
+            None => {
+                self.status = format!("no {} to copy", what);
+                None
+            }
+        }
+    }
+
+    /// Apply one key press.  Returns text to copy to the clipboard, if any.
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<String> {
+        if self.filtering {
+            match code {
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.filtering = false;
+                }
+                KeyCode::Enter => self.filtering = false,
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => {}
+            }
+            self.selected = 0;
+            return None;
+        }
+
+        let current = self.current();
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('/') => self.filtering = true,
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown => self.move_by(10),
+            KeyCode::PageUp => self.move_by(-10),
+            KeyCode::Char('y') => return self.copy("identifier", current.map(|i| i.instance_id.clone())),
+            KeyCode::Char('e') => return self.copy("endpoint", current.and_then(|i| i.endpoint.clone())),
+            KeyCode::Char('a') => return self.copy("ARN", current.map(|i| i.arn.clone())),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Detail pane text for one instance
+pub fn detail_lines(inst: &RdsInstance) -> Vec<String> {
+    let or_dash = |v: Option<String>| v.unwrap_or_else(|| "-".to_owned());
+    let mut lines = vec![
+        format!("Identifier  {}", inst.instance_id),
+        format!("ARN         {}", inst.arn),
+        format!("Account     {}", inst.account_id),
+        format!("Region      {}", inst.region),
+        format!("Engine      {} {}", inst.engine, inst.engine_version).trim_end().to_owned(),
+        format!("Class       {}", inst.instance_class),
+        format!(
+            "AZ          {}",
+            format_zone(inst.availability_zone.as_deref(), inst.availability_zone_id.as_deref())
+        ),
+        match &inst.secondary_availability_zone {
+            Some(standby) => format!(
+                "Multi-AZ    yes, standby in {}",
+                format_zone(Some(standby), inst.secondary_availability_zone_id.as_deref())
+            ),
+            None => format!("Multi-AZ    {}", if inst.multi_az { "yes" } else { "no" }),
+        },
+        format!("VPC         {}", format_network(inst)),
+        format!("Status      {}", inst.status),
+        format!("Endpoint    {}", or_dash(inst.endpoint.clone())),
+        format!("Public      {}", if inst.publicly_accessible { "yes" } else { "no" }),
+        format!("Insights    {}", if inst.performance_insights_enabled { "on" } else { "off" }),
+        match (&inst.kms_key_id, inst.storage_encrypted) {
+            (Some(key), true) => format!("Encrypted   yes, {}", key),
+            (None, true) => "Encrypted   yes".to_owned(),
+            (_, false) => "Encrypted   no".to_owned(),
+        },
+        format!(
+            "Storage     {}/{} GiB",
+            or_dash(inst.allocated_storage.map(|g| g.to_string())),
+            or_dash(inst.max_allocated_storage.map(|g| g.to_string()))
+        ),
+        format!(
+            "Backups     {} day(s) retention, window {}",
+            or_dash(inst.backup_retention_days.map(|d| d.to_string())),
+            or_dash(inst.backup_window.clone())
+        ),
+        format!(
+            "Restorable  {}",
+            or_dash(inst.latest_restorable_time.map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string()))
+        ),
+        format!("Maintenance {}", or_dash(inst.maintenance_window.clone())),
+    ];
+    if inst.tags.is_empty() {
+        lines.push("Tags        -".to_owned());
+    } else {
+        lines.push("Tags".to_owned());
+        lines.extend(inst.tags.iter().map(|(k, v)| format!("  {} = {}", k, v)));
+    }
+    lines
+}
+
+/// Put `text` on the clipboard with an OSC 52 escape sequence.
+fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut out = std::io::stdout();
+    write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    out.flush()?;
+    Ok(())
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(frame.area());
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(rows[0]);
+
+    let visible = app.visible();
+    let table = Table::new(
+        visible.iter().map(|i| {
+            Row::new(vec![
+                i.account_id.clone(),
+                i.region.clone(),
+                i.instance_id.clone(),
+                i.engine.clone(),
+                i.instance_class.clone(),
+            ])
+        }),
+        [
+            Constraint::Length(12),
+            Constraint::Length(14),
+            Constraint::Min(16),
+            Constraint::Length(18),
+            Constraint::Length(16),
+        ],
+    )
+    .header(
+        Row::new(vec!["ACCOUNT", "REGION", "IDENTIFIER", "ENGINE", "CLASS"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::default().borders(Borders::ALL).title(format!("Instances ({})", visible.len())))
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = TableState::default();
+    if !visible.is_empty() {
+        state.select(Some(app.selected));
+    }
+    frame.render_stateful_widget(table, panes[0], &mut state);
+
+    let detail = app.current().map(detail_lines).unwrap_or_default().join("\n");
+    frame.render_widget(
+        Paragraph::new(detail).block(Block::default().borders(Borders::ALL).title("Detail")),
+        panes[1],
+    );
+
+    let status = if app.filtering || !app.filter.is_empty() {
+        format!("/{}", app.filter)
+    } else {
+        app.status.clone()
+    };
+    frame.render_widget(Paragraph::new(status), rows[1]);
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| draw(frame, app))?;
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                if let Some(text) = app.handle_key(key.code) {
+                    copy_to_clipboard(&text)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Browse the scanned instances until the user quits.
+pub fn run(instances: &[RdsInstance]) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let mut app = App::new(instances);
+    let outcome = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn instances() -> Vec<RdsInstance> {
+        vec![
+            RdsInstance {
+                account_id: "111111111111".to_string(),
+                region: "us-east-1".to_string(),
+                instance_id: "orders-db".to_string(),
+                arn: "arn:aws:rds:us-east-1:111111111111:db:orders-db".to_string(),
+                engine: "postgres".to_string(),
+                endpoint: Some("orders-db.abc.us-east-1.rds.amazonaws.com:5432".to_string()),
+                ..Default::default()
+            },
+            RdsInstance {
+                account_id: "222222222222".to_string(),
+                region: "us-west-2".to_string(),
+                instance_id: "billing-db".to_string(),
+                engine: "mysql".to_string(),
+                tags: BTreeMap::from([("team".to_string(), "Payments".to_string())]),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn filter_matches_fields_and_tag_values() {
+        let insts = instances();
+        let mut app = App::new(&insts);
+        for (text, expected) in [("ORDERS", "orders-db"), ("mysql", "billing-db"), ("payments", "billing-db")] {
+            app.filter = text.to_string();
+            let visible = app.visible();
+            assert_eq!(visible.len(), 1, "filter {text}");
+            assert_eq!(visible[0].instance_id, expected);
+        }
+    }
+
+    #[test]
+    fn copy_keys_return_identifier_endpoint_and_arn() {
+        let insts = instances();
+        let mut app = App::new(&insts);
+        assert_eq!(app.handle_key(KeyCode::Char('y')).as_deref(), Some("orders-db"));
+        assert_eq!(
+            app.handle_key(KeyCode::Char('e')).as_deref(),
+            Some("orders-db.abc.us-east-1.rds.amazonaws.com:5432")
+        );
+        assert_eq!(
+            app.handle_key(KeyCode::Char('a')).as_deref(),
+            Some("arn:aws:rds:us-east-1:111111111111:db:orders-db")
+        );
+        app.handle_key(KeyCode::Char('j'));
+        assert_eq!(app.handle_key(KeyCode::Char('e')), None);
+        assert_eq!(app.status, "no endpoint to copy");
+    }
+
+    #[test]
+    fn filtering_resets_selection_and_esc_clears() {
+        let insts = instances();
+        let mut app = App::new(&insts);
+        app.handle_key(KeyCode::Char('j'));
+        assert_eq!(app.selected, 1);
+        app.handle_key(KeyCode::Char('/'));
+        app.handle_key(KeyCode::Char('o'));
+        assert_eq!(app.selected, 0);
+        app.handle_key(KeyCode::Esc);
+        assert!(app.filter.is_empty());
+        assert!(!app.quit);
+    }
+
+    #[test]
+    fn detail_lines_include_backups_and_tags() {
+        let inst = RdsInstance {
+            backup_retention_days: Some(7),
+            backup_window: Some("03:00-03:30".to_string()),
+            ..instances().remove(1)
+        };
+        let lines = detail_lines(&inst);
+        assert!(lines.contains(&"Backups     7 day(s) retention, window 03:00-03:30".to_string()));
+        assert!(lines.contains(&"  team = Payments".to_string()));
+        assert!(lines.contains(&"Endpoint    -".to_string()));
+        assert!(lines.contains(&"Multi-AZ    no".to_string()));
+        assert!(lines.contains(&"Insights    off".to_string()));
+
+        let standby = RdsInstance {
+            multi_az: true,
+            secondary_availability_zone: Some("us-east-1b".to_string()),
+            secondary_availability_zone_id: Some("use1-az6".to_string()),
+            ..inst
+        };
+        assert!(detail_lines(&standby).contains(&"Multi-AZ    yes, standby in us-east-1b (use1-az6)".to_string()));
+    }
+}
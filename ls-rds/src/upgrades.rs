@@ -0,0 +1,165 @@
+//! Engine version upgrade targets for ls-rds
+//!
+//! `--upgrade-targets` asks RDS which versions each instance's engine version
+//! can be upgraded to and reports the nearest minor version and the latest
+//! major version, as input for the quarterly patching plan.  Lookups are
+//! cached per region, engine and version since a fleet runs only a handful.
+
+use crate::api_stats::ApiStats;
+use crate::retry;
+use aws_sdk_rds as rds;
+use aws_types::region::Region;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Where an engine version can be upgraded to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeTargets {
+    /// Lowest minor-version upgrade (`None` = already on the latest minor)
+    pub nearest_minor: Option<String>,
+    /// Highest major-version upgrade (`None` = no major upgrade offered)
+    pub latest_major: Option<String>,
+}
+
+/// Compare engine versions part by part, numerically where both parts are
+/// numbers, so `13.10` sorts after `13.9` and
+/// `8.0.mysql_aurora.3.05.2` after `8.0.mysql_aurora.3.04.1`
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut left = a.split(['.', '-']);
+    let mut right = b.split(['.', '-']);
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) => {
+                let ord = match (l.parse::<u64>(), r.parse::<u64>()) {
+                    (Ok(l), Ok(r)) => l.cmp(&r),
+                    _ => l.cmp(r),
+                };
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
+}
+
+/// Pick the nearest minor and latest major version from valid upgrade
+/// targets, given as `(version, is_major_version_upgrade)`
+pub fn pick_targets(targets: &[(String, bool)]) -> UpgradeTargets {
+    let versions = |major: bool| targets.iter().filter(move |(_, m)| *m == major).map(|(v, _)| v);
+    UpgradeTargets {
+        nearest_minor: versions(false).min_by(|a, b| compare_versions(a, b)).cloned(),
+        latest_major: versions(true).max_by(|a, b| compare_versions(a, b)).cloned(),
+    }
+}
+
+/// Valid upgrade targets of one engine version
+pub async fn upgrade_targets(
+    client: &rds::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+    engine: &str,
+    version: &str,
+    retries: u32,
+) -> Result<UpgradeTargets> {
+    let resp = retry::with_retries(retries, || {
+        stats.record("rds", "DescribeDBEngineVersions", region.as_ref(), account_id);
+        client.describe_db_engine_versions().engine(engine).engine_version(version).send()
+    })
+    .await?;
+    let targets: Vec<(String, bool)> = resp
+        .db_engine_versions()
+        .iter()
+        .flat_map(|v| v.valid_upgrade_target())
+        .filter_map(|t| Some((t.engine_version()?.to_owned(), t.is_major_version_upgrade().unwrap_or(false))))
+        .collect();
+    Ok(pick_targets(&targets))
+}
+
+/// Upgrade targets already looked up in one region, by engine and version
+#[derive(Debug)]
+pub struct UpgradeCache<'a> {
+    stats: &'a ApiStats,
+    retries: u32,
+    targets: BTreeMap<(String, String), UpgradeTargets>,
+    /// Lookups that failed, for the scan's warnings
+    pub warnings: Vec<String>,
+}
+
+impl<'a> UpgradeCache<'a> {
+    pub fn new(stats: &'a ApiStats, retries: u32) -> Self {
+        UpgradeCache {
+            stats,
+            retries,
+            targets: BTreeMap::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Upgrade targets of `engine` `version`, `None` if they cannot be read
+    pub async fn targets(
+        &mut self,
+        client: &rds::Client,
+        region: &Region,
+        account_id: &str,
+        engine: &str,
+        version: &str,
+    ) -> Option<UpgradeTargets> {
+        let key = (engine.to_owned(), version.to_owned());
+        if let Some(targets) = self.targets.get(&key) {
+            return Some(targets.clone());
+        }
+        match upgrade_targets(client, self.stats, region, account_id, engine, version, self.retries).await {
+            Ok(targets) => {
+                self.targets.insert(key, targets.clone());
+                Some(targets)
+            }
+            Err(e) => {
+                self.warnings.push(format!(
+                    "{} {}: cannot read upgrade targets of {} {}: {:#}",
+                    account_id, region, engine, version, e
+                ));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_versions_is_numeric() {
+        assert_eq!(compare_versions("13.10", "13.9"), Ordering::Greater);
+        assert_eq!(compare_versions("15.4", "15.4"), Ordering::Equal);
+        assert_eq!(compare_versions("8.0.35", "8.0.35.R2"), Ordering::Less);
+        assert_eq!(
+            compare_versions("8.0.mysql_aurora.3.05.2", "8.0.mysql_aurora.3.04.1"),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn pick_targets_takes_nearest_minor_and_latest_major() {
+        let targets: Vec<(String, bool)> = [
+            ("13.12", false),
+            ("13.10", false),
+            ("15.6", true),
+            ("16.2", true),
+            ("14.11", true),
+        ]
+        .iter()
+        .map(|(v, major)| (v.to_string(), *major))
+        .collect();
+        let picked = pick_targets(&targets);
+        assert_eq!(picked.nearest_minor.as_deref(), Some("13.10"));
+        assert_eq!(picked.latest_major.as_deref(), Some("16.2"));
+        assert_eq!(pick_targets(&[]), UpgradeTargets::default());
+    }
+}
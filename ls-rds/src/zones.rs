@@ -0,0 +1,83 @@
+//! Availability Zone IDs for ls-rds
+//!
+//! Zone names are mapped to physical zones per account: `us-east-1a` in one
+//! account can be `us-east-1c` in another, while zone IDs such as `use1-az4`
+//! mean the same zone everywhere.  RDS only reports names, so each scanned
+//! region's name → ID map is read from EC2 and joined onto the instances,
+//! keeping placement comparable across accounts.
+
+use crate::RdsInstance;
+use crate::api_stats::ApiStats;
+use aws_sdk_ec2 as ec2;
+use aws_types::region::Region;
+use eyre::Result;
+use std::collections::BTreeMap;
+
+/// Zone name → zone ID for one account/region
+pub async fn zone_ids(
+    client: &ec2::Client,
+    stats: &ApiStats,
+    region: &Region,
+    account_id: &str,
+) -> Result<BTreeMap<String, String>> {
+    stats.record("ec2", "DescribeAvailabilityZones", region.as_ref(), account_id);
+    let output = client.describe_availability_zones().send().await?;
+    Ok(output
+        .availability_zones()
+        .iter()
+        .filter_map(|z| Some((z.zone_name()?.to_owned(), z.zone_id()?.to_owned())))
+        .collect())
+}
+
+/// Fill in the zone IDs of the instances' primary and standby zones
+pub fn apply_zone_ids(instances: &mut [RdsInstance], ids: &BTreeMap<String, String>) {
+    let lookup = |name: &Option<String>| name.as_ref().and_then(|n| ids.get(n)).cloned();
+    for inst in instances {
+        inst.availability_zone_id = lookup(&inst.availability_zone);
+        inst.secondary_availability_zone_id = lookup(&inst.secondary_availability_zone);
+    }
+}
+
+/// Zone name with its ID, e.g. `us-east-1a (use1-az4)`
+pub fn format_zone(name: Option<&str>, id: Option<&str>) -> String {
+    match (name, id) {
+        (Some(name), Some(id)) => format!("{} ({})", name, id),
+        (Some(name), None) => name.to_owned(),
+        (None, _) => "-".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_zone_ids_maps_primary_and_standby() {
+        let ids = BTreeMap::from([
+            ("us-east-1a".to_string(), "use1-az4".to_string()),
+            ("us-east-1b".to_string(), "use1-az6".to_string()),
+        ]);
+        let mut instances = vec![
+            RdsInstance {
+                availability_zone: Some("us-east-1a".to_string()),
+                secondary_availability_zone: Some("us-east-1b".to_string()),
+                ..Default::default()
+            },
+            RdsInstance {
+                availability_zone: Some("us-east-1f".to_string()),
+                ..Default::default()
+            },
+        ];
+        apply_zone_ids(&mut instances, &ids);
+        assert_eq!(instances[0].availability_zone_id.as_deref(), Some("use1-az4"));
+        assert_eq!(instances[0].secondary_availability_zone_id.as_deref(), Some("use1-az6"));
+        assert_eq!(instances[1].availability_zone_id, None);
+    }
+
+    #[test]
+    fn format_zone_adds_the_id_when_known() {
+        assert_eq!(format_zone(Some("us-east-1a"), Some("use1-az4")), "us-east-1a (use1-az4)");
+        assert_eq!(format_zone(Some("us-east-1a"), None), "us-east-1a");
+        assert_eq!(format_zone(None, None), "-");
+    }
+}
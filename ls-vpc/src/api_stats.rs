@@ -0,0 +1,230 @@
+//! api_stats.rs
+//! ---------------------------------------------------------------------------
+//! AWS API call accounting shared by ls-vpc and ls-rds.  Every SDK call site
+//! records itself in the scan's `ApiStats` so `--api-stats` can report how
+//! many calls of each type a run made, per region and account.
+
+use aws_credential_types::provider::{ProvideCredentials, future};
+use aws_types::region::Region;
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// One kind of API call, keyed by where it was made.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ApiCall {
+    pub service: &'static str,
+    pub operation: &'static str,
+    pub region: String,
+    pub account: String,
+}
+
+/// API call counters of one scan; clones share the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct ApiStats {
+    calls: Arc<Mutex<BTreeMap<ApiCall, u64>>>,
+}
+
+impl ApiStats {
+    /// Record a single API call.
+    pub fn record(&self, service: &'static str, operation: &'static str, region: &str, account: &str) {
+        let key = ApiCall {
+            service,
+            operation,
+            region: region.to_owned(),
+            account: account.to_owned(),
+        };
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        *calls.entry(key).or_default() += 1;
+    }
+
+    /// Counter for the calls made with one account's credentials.
+    pub fn for_account(&self, account: &str) -> CallCounter {
+        CallCounter {
+            stats: self.clone(),
+            account: account.to_owned(),
+        }
+    }
+
+    /// Copy of the counters recorded so far.
+    pub fn snapshot(&self) -> BTreeMap<ApiCall, u64> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Total calls recorded so far against one account in one region.
+    pub fn calls_for(&self, region: &str, account: &str) -> u64 {
+        self.calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|(call, _)| call.region == region && call.account == account)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Total calls recorded so far, across every account and region.
+    pub fn total(&self) -> u64 {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).values().sum()
+    }
+}
+
+/// `ApiStats` bound to the account whose credentials an SDK config carries,
+/// so call sites only name the call and its region.
+#[derive(Debug, Clone, Default)]
+pub struct CallCounter {
+    stats: ApiStats,
+    account: String,
+}
+
+impl CallCounter {
+    /// Record a single API call made against `region`.
+    pub fn record(&self, service: &'static str, operation: &'static str, region: Option<&Region>) {
+        let region = region.map(|r| r.to_string()).unwrap_or_default();
+        self.stats.record(service, operation, &region, &self.account);
+    }
+}
+
+/// Credentials provider that calls `on_fetch` every time it fetches from its
+/// inner provider.  The SDK caches credentials until they expire, so around
+/// an `AssumeRoleProvider` each fetch is one STS AssumeRole call, counted
+/// when it is made rather than when the provider is built.
+pub struct CountingProvider<P> {
+    inner: P,
+    on_fetch: Box<dyn Fn() + Send + Sync>,
+}
+
+impl<P> CountingProvider<P> {
+    pub fn new(inner: P, on_fetch: impl Fn() + Send + Sync + 'static) -> Self {
+        CountingProvider {
+            inner,
+            on_fetch: Box::new(on_fetch),
+        }
+    }
+}
+
+impl<P: fmt::Debug> fmt::Debug for CountingProvider<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountingProvider").field("inner", &self.inner).finish()
+    }
+}
+
+impl<P: ProvideCredentials> ProvideCredentials for CountingProvider<P> {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        (self.on_fetch)();
+        self.inner.provide_credentials()
+    }
+}
+
+/// Render call counts as a table with a trailing total row.
+pub fn format_api_stats(stats: &BTreeMap<ApiCall, u64>) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec!["SERVICE", "OPERATION", "REGION", "ACCOUNT", "CALLS"]);
+    for (call, count) in stats {
+        table.add_row(vec![
+            call.service.to_owned(),
+            call.operation.to_owned(),
+            call.region.clone(),
+            call.account.clone(),
+            count.to_string(),
+        ]);
+    }
+    table.add_row(vec![
+        "TOTAL".to_owned(),
+        String::new(),
+        String::new(),
+        String::new(),
+        stats.values().sum::<u64>().to_string(),
+    ]);
+    table.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_increments_counter() {
+        let stats = ApiStats::default();
+        let calls = stats.for_account("111111111111");
+        let region = Region::new("test-region-1");
+        calls.record("ec2", "DescribeVpcs", Some(&region));
+        calls.record("ec2", "DescribeVpcs", Some(&region));
+        let key = ApiCall {
+            service: "ec2",
+            operation: "DescribeVpcs",
+            region: "test-region-1".to_string(),
+            account: "111111111111".to_string(),
+        };
+        assert_eq!(stats.snapshot().get(&key), Some(&2));
+    }
+
+    #[test]
+    fn calls_for_sums_operations_in_region_and_account() {
+        let stats = ApiStats::default();
+        stats.record("rds", "DescribeDBInstances", "us-east-1", "111111111111");
+        stats.record("rds", "DescribeDBSnapshots", "us-east-1", "111111111111");
+        stats.record("rds", "DescribeDBSnapshots", "us-east-1", "222222222222");
+        assert_eq!(stats.calls_for("us-east-1", "111111111111"), 2);
+        assert_eq!(stats.calls_for("us-east-1", "222222222222"), 1);
+        assert_eq!(stats.total(), 3);
+    }
+
+    #[test]
+    fn scans_count_separately_and_clones_share() {
+        let (first, second) = (ApiStats::default(), ApiStats::default());
+        first.record("ec2", "DescribeRegions", "us-east-1", "111111111111");
+        first.for_account("111111111111").record("ec2", "DescribeRegions", Some(&Region::new("us-east-1")));
+        assert_eq!(first.total(), 2);
+        assert_eq!(second.total(), 0);
+    }
+
+    #[tokio::test]
+    async fn counting_provider_counts_each_fetch() {
+        use aws_credential_types::Credentials;
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&fetches);
+        let provider = CountingProvider::new(Credentials::new("AKID", "SECRET", None, None, "test"), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        assert_eq!(fetches.load(Ordering::SeqCst), 0);
+        provider.provide_credentials().await.unwrap();
+        provider.provide_credentials().await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn format_api_stats_includes_total() {
+        let mut stats = BTreeMap::new();
+        stats.insert(
+            ApiCall {
+                service: "ec2",
+                operation: "DescribeVpcs",
+                region: "us-west-2".to_string(),
+                account: "111111111111".to_string(),
+            },
+            2,
+        );
+        stats.insert(
+            ApiCall {
+                service: "elbv2",
+                operation: "DescribeLoadBalancers",
+                region: "us-west-2".to_string(),
+                account: "111111111111".to_string(),
+            },
+            3,
+        );
+        let out = format_api_stats(&stats);
+        assert!(out.contains("DescribeLoadBalancers"));
+        assert!(out.contains("TOTAL"));
+        assert!(out.contains('5'));
+    }
+}
@@ -3,22 +3,62 @@
 //! This module contains only the clap derive structs.
 //! Validation happens in config.rs.
 
-use clap::{Parser, ValueHint};
+use clap::{Parser, ValueEnum, ValueHint};
+
+/// Output format for `--sg-graph`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SgGraphFormat {
+    Table,
+    Dot,
+}
+
+/// Output format for the VPC report
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// ASCII tables (summary or detail view)
+    #[default]
+    Table,
+    /// Tab-separated values, one header line then one line per row
+    Tsv,
+    /// draw.io / mxGraph XML diagram of VPCs, subnets, gateways and peerings
+    Drawio,
+    /// One JSON document following the versioned schema (see `--schema`)
+    Json,
+}
+
+/// Body format for `--email-to`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmailFormat {
+    /// HTML with the report preformatted, plus a plain-text alternative
+    #[default]
+    Html,
+    /// Plain text only
+    Text,
+}
+
+/// Ordering of resources in the detail view
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    /// Oldest first; resources without a creation time last
+    Age,
+}
 
 #[derive(Parser, Debug, Clone)]
 #[command(name = "ls-vpc", author, version = env!("GIT_DESCRIBE"), about)]
 pub struct Cli {
-    /// AWS Regions to query (pick one or both of `us-east-1`, `us-west-2`).
+    /// AWS Regions to query, or region groups from the shared config file
+    /// (`~/.config/aws-tools/config.yml`).
     ///
     /// Examples:
     ///   ls-vpc                    # uses the default us-east-1 us-west-2
     ///   ls-vpc -r us-east-1       # east only
     ///   ls-vpc -r us-west-2       # west only
     ///   ls-vpc -r us-east-1 -r us-west-2   # both
+    ///   ls-vpc -r us,eu           # region groups
     #[clap(
         short = 'r',
         long = "regions",
-        value_parser = clap::builder::PossibleValuesParser::new(["us-east-1", "us-west-2"]),
+        value_delimiter = ',',
         num_args = 0..,
         default_values_t = vec![
             "us-east-1".to_string(),
@@ -27,6 +67,217 @@ pub struct Cli {
     )]
     pub regions: Vec<String>,
 
+    /// Run the whole scan under this IAM role
+    /// (e.g. `arn:aws:iam::123456789012:role/Auditor`)
+    #[clap(long, value_name = "ROLE_ARN")]
+    pub assume_role: Option<String>,
+
+    /// External ID to pass when assuming --assume-role
+    #[clap(long, value_name = "ID", requires = "assume_role")]
+    pub external_id: Option<String>,
+
+    /// Send STS calls (AssumeRole, GetCallerIdentity) to this region's
+    /// endpoint instead of each scanned region's (e.g. `us-east-1`)
+    #[clap(long, value_name = "REGION")]
+    pub sts_region: Option<String>,
+
+    /// Output format; defaults to `table` on a terminal and `tsv` when
+    /// stdout is piped.  `drawio` writes a diagram to stdout
+    /// (e.g. `ls-vpc --format drawio > network.drawio`)
+    #[clap(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    /// Annotate each resource with its owning CloudFormation stack (detail mode).
+    #[clap(long)]
+    pub with_stacks: bool,
+
+    /// Add a column per tag key to the detail table, for every resource
+    /// type (e.g. `--show-tags Team,Environment`)
+    #[clap(long, value_name = "KEYS", value_delimiter = ',')]
+    pub show_tags: Vec<String>,
+
+    /// After the normal output, report NAT gateway and data-transfer spend
+    /// per VPC from Cost Explorer over this window (e.g. `30d`)
+    #[clap(long, value_name = "PERIOD")]
+    pub with_spend: Option<String>,
+
+    /// Cost allocation tag whose value is the VPC-ID or Name a resource's
+    /// spend belongs to
+    #[clap(long, value_name = "KEY", default_value = "vpc-id", requires = "with_spend")]
+    pub spend_tag: String,
+
+    /// Order each VPC's resources, e.g. `--sort-by age` to surface the
+    /// oldest and newest infrastructure
+    #[clap(long, value_enum, value_name = "KEY")]
+    pub sort_by: Option<SortBy>,
+
+    /// Print the graph of security groups referencing other security groups
+    /// (as a table, or Graphviz DOT with `--sg-graph=dot`) instead of VPCs
+    #[clap(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "table"
+    )]
+    pub sg_graph: Option<SgGraphFormat>,
+
+    /// List security groups attached to no network interface (excluding the
+    /// default group) per VPC instead of VPCs
+    #[clap(long, conflicts_with_all = ["sg_graph", "tui", "pick", "emit_tf_imports"])]
+    pub unused_sgs: bool,
+
+    /// After the normal output, report detected problems (e.g. blackhole routes,
+    /// peerings that only route one way, S3/DynamoDB traffic paying for NAT,
+    /// DB subnet groups in a single AZ)
+    #[clap(long)]
+    pub issues: bool,
+
+    /// Highlight subnets whose used-IP percentage is at or above this value
+    /// and exit non-zero if any are found
+    #[clap(long, value_name = "PCT", value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub ip_threshold: Option<u8>,
+
+    /// After the normal output, estimate the monthly cost of interface VPC
+    /// endpoints (the region's per-AZ-hour list price × attached AZs; the
+    /// us-east-1 price for regions without one) per VPC
+    #[clap(long)]
+    pub with_cost: bool,
+
+    /// After the normal output, report each VPC's Network Address Usage (from
+    /// CloudWatch) against the per-VPC and peered quotas
+    #[clap(long)]
+    pub nau: bool,
+
+    /// After the normal output, report how each VPC reaches the internet
+    /// (internet gateway, NAT, transit gateway, VPN, appliance or none),
+    /// counting subnets per path, from its route tables and NAT gateways
+    #[clap(long)]
+    pub egress: bool,
+
+    /// After the normal output, report VPC names shared by different VPCs
+    /// and resource names (detail mode) shared across VPCs, the usual way
+    /// people end up working in the wrong environment
+    #[clap(long)]
+    pub duplicates: bool,
+
+    /// After the normal output, report each VPC's flow logs: destination
+    /// (CloudWatch Logs group or S3 bucket), traffic type, aggregation
+    /// interval and log group retention
+    #[clap(long)]
+    pub flow_logs: bool,
+
+    /// After the normal output, report the VPC endpoint services (PrivateLink
+    /// offerings) hosted from each VPC: acceptance setting, allowed
+    /// principals and connected endpoints
+    #[clap(long)]
+    pub endpoint_services: bool,
+
+    /// Exit non-zero, listing the matching VPCs on stderr, if any VPC
+    /// matches this condition, e.g. `--fail-on 'public && !flow_logs'`.
+    /// Fields: public, flow_logs, named, peers, cidrs, issues; operators:
+    /// ! && || ( ) == != < <= > >=
+    #[clap(long, value_name = "EXPR")]
+    pub fail_on: Option<String>,
+
+    /// Print Terraform (1.5+) `import` blocks for the discovered VPCs, subnets,
+    /// route tables and gateways instead of tables
+    #[clap(long, conflicts_with_all = ["sg_graph", "tui", "pick"])]
+    pub emit_tf_imports: bool,
+
+    /// Replace account IDs, resource IDs, names, tag values and exact CIDRs
+    /// with stable aliases so the output can be shared; peerings, overlaps
+    /// and CIDR sizes are preserved
+    #[clap(long)]
+    pub redact: bool,
+
+    /// Browse the scan in an interactive terminal UI (VPC list with live
+    /// filtering, resources grouped by type, ARN copying)
+    #[clap(long, conflicts_with = "sg_graph")]
+    pub tui: bool,
+
+    /// Pick a VPC with a fuzzy finder as the scan discovers them, then print
+    /// its detail view
+    #[clap(long, conflicts_with_all = ["tui", "sg_graph"])]
+    pub pick: bool,
+
+    /// With --pick, print only the picked VPC-ID (for shell pipelines)
+    #[clap(short, long, requires = "pick")]
+    pub quiet: bool,
+
+    /// Print at most this many VPCs, in output order (e.g. `--limit 50`)
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub limit: Option<u32>,
+
+    /// With --limit, print this page of VPCs (1-based)
+    #[clap(long, value_name = "P", requires = "limit", value_parser = clap::value_parser!(u32).range(1..))]
+    pub page: Option<u32>,
+
+    /// Scan at most this many regions at once
+    #[clap(long, value_name = "N", default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    pub max_concurrent_regions: u16,
+
+    /// Scan at most this many VPCs at once within each region
+    #[clap(long, value_name = "N", default_value_t = 8, value_parser = clap::value_parser!(u16).range(1..))]
+    pub max_concurrent_vpcs: u16,
+
+    /// Retry throttled or failed AWS calls up to this many times
+    #[clap(long, value_name = "N")]
+    pub max_retries: Option<u32>,
+
+    /// Give up connecting to an AWS endpoint after this long (e.g. `5s`)
+    #[clap(long, value_name = "DURATION")]
+    pub connect_timeout: Option<String>,
+
+    /// Give up on a single AWS call, retries included, after this long
+    /// (e.g. `2m`)
+    #[clap(long, value_name = "DURATION")]
+    pub operation_timeout: Option<String>,
+
+    /// Also email the report to these addresses through SES, using the
+    /// scan's credentials in the first region (e.g.
+    /// `--email-to ops@example.com`).  Output defaults to tables even when
+    /// stdout is piped
+    #[clap(long, value_name = "ADDR", value_delimiter = ',', conflicts_with_all = ["tui", "pick"])]
+    pub email_to: Vec<String>,
+
+    /// Sender address for --email-to (must be verified in SES); defaults to
+    /// the first recipient
+    #[clap(long, value_name = "ADDR", requires = "email_to")]
+    pub email_from: Option<String>,
+
+    /// Body format for --email-to
+    #[clap(long, value_enum, default_value_t = EmailFormat::Html, requires = "email_to")]
+    pub email_format: EmailFormat,
+
+    /// Print the JSON Schema of `--format json` output and exit
+    #[clap(long)]
+    pub schema: bool,
+
+    /// Print a per-service/region count of AWS API calls made by the run
+    #[clap(long)]
+    pub api_stats: bool,
+
+    /// Add the arguments of a named view from the settings file
+    /// (`~/.config/aws-tools/config.yml`), e.g. `--view security-audit`
+    #[clap(long, value_name = "NAME")]
+    pub view: Option<String>,
+
+    /// Resolve the account, role, regions and scanners, print the calls the
+    /// scan would make with an estimated count, and exit without scanning
+    #[clap(long)]
+    pub dry_run: bool,
+
+    /// Print the account, ARN, partition and credential source of the base
+    /// credentials and exit
+    #[clap(long)]
+    pub whoami: bool,
+
+    /// Print the --whoami identity to stderr before the scan starts
+    #[clap(short, long)]
+    pub verbose: bool,
+
     /// Optional VPC IDs. If omitted → summary mode.
     #[clap(value_name = "VPC_ID", value_hint = ValueHint::Other)]
     pub vpc_ids: Vec<String>,
@@ -47,6 +298,20 @@ mod tests {
         let cli = Cli::parse_from(["ls-vpc"]);
         assert_eq!(cli.regions, vec!["us-east-1", "us-west-2"]);
         assert!(cli.vpc_ids.is_empty());
+        assert!(!cli.with_stacks);
+    }
+
+    #[test]
+    fn cli_parses_sts_region() {
+        let cli = Cli::parse_from(["ls-vpc", "--sts-region", "eu-west-1"]);
+        assert_eq!(cli.sts_region.as_deref(), Some("eu-west-1"));
+        assert!(Cli::parse_from(["ls-vpc"]).sts_region.is_none());
+    }
+
+    #[test]
+    fn cli_parses_comma_separated_regions() {
+        let cli = Cli::parse_from(["ls-vpc", "-r", "us,eu-west-1"]);
+        assert_eq!(cli.regions, vec!["us", "eu-west-1"]);
     }
 
     #[test]
@@ -55,6 +320,43 @@ mod tests {
         assert_eq!(cli.regions, vec!["us-west-2"]);
     }
 
+    #[test]
+    fn cli_parses_assume_role_with_external_id() {
+        let cli = Cli::parse_from([
+            "ls-vpc",
+            "--assume-role",
+            "arn:aws:iam::123456789012:role/Auditor",
+            "--external-id",
+            "abc123",
+        ]);
+        assert_eq!(cli.assume_role.as_deref(), Some("arn:aws:iam::123456789012:role/Auditor"));
+        assert_eq!(cli.external_id.as_deref(), Some("abc123"));
+        assert!(Cli::try_parse_from(["ls-vpc", "--external-id", "abc123"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_emit_tf_imports() {
+        assert!(Cli::parse_from(["ls-vpc", "--emit-tf-imports"]).emit_tf_imports);
+        assert!(Cli::try_parse_from(["ls-vpc", "--emit-tf-imports", "--tui"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_format() {
+        assert_eq!(Cli::parse_from(["ls-vpc"]).format, None);
+        assert_eq!(Cli::parse_from(["ls-vpc", "--format", "table"]).format, Some(OutputFormat::Table));
+        assert_eq!(Cli::parse_from(["ls-vpc", "--format", "tsv"]).format, Some(OutputFormat::Tsv));
+        assert_eq!(Cli::parse_from(["ls-vpc", "--format", "drawio"]).format, Some(OutputFormat::Drawio));
+        assert_eq!(Cli::parse_from(["ls-vpc", "--format", "json"]).format, Some(OutputFormat::Json));
+        assert!(Cli::try_parse_from(["ls-vpc", "--format", "svg"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_sort_by() {
+        assert_eq!(Cli::parse_from(["ls-vpc"]).sort_by, None);
+        assert_eq!(Cli::parse_from(["ls-vpc", "--sort-by", "age"]).sort_by, Some(SortBy::Age));
+        assert!(Cli::try_parse_from(["ls-vpc", "--sort-by", "size"]).is_err());
+    }
+
     #[test]
     fn cli_parses_multiple_regions() {
         let cli = Cli::parse_from(["ls-vpc", "-r", "us-east-1", "-r", "us-west-2"]);
@@ -68,6 +370,217 @@ mod tests {
         assert_eq!(cli.vpc_ids, vec!["vpc-123", "vpc-456"]);
     }
 
+    #[test]
+    fn cli_parses_sg_graph_default_and_dot() {
+        let cli = Cli::parse_from(["ls-vpc", "--sg-graph"]);
+        assert_eq!(cli.sg_graph, Some(SgGraphFormat::Table));
+        let cli = Cli::parse_from(["ls-vpc", "--sg-graph=dot"]);
+        assert_eq!(cli.sg_graph, Some(SgGraphFormat::Dot));
+    }
+
+    #[test]
+    fn cli_sg_graph_leaves_following_vpc_id_positional() {
+        let cli = Cli::parse_from(["ls-vpc", "--sg-graph", "vpc-123"]);
+        assert_eq!(cli.sg_graph, Some(SgGraphFormat::Table));
+        assert_eq!(cli.vpc_ids, vec!["vpc-123"]);
+    }
+
+    #[test]
+    fn cli_parses_fail_on() {
+        let cli = Cli::parse_from(["ls-vpc", "--fail-on", "public && !flow_logs"]);
+        assert_eq!(cli.fail_on.as_deref(), Some("public && !flow_logs"));
+    }
+
+    #[test]
+    fn cli_parses_unused_sgs() {
+        assert!(Cli::parse_from(["ls-vpc", "--unused-sgs"]).unused_sgs);
+        assert!(Cli::try_parse_from(["ls-vpc", "--unused-sgs", "--sg-graph"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_issues() {
+        let cli = Cli::parse_from(["ls-vpc", "--issues"]);
+        assert!(cli.issues);
+    }
+
+    #[test]
+    fn cli_parses_ip_threshold() {
+        let cli = Cli::parse_from(["ls-vpc", "--ip-threshold", "90"]);
+        assert_eq!(cli.ip_threshold, Some(90));
+    }
+
+    #[test]
+    fn cli_parses_egress() {
+        assert!(Cli::parse_from(["ls-vpc", "--egress"]).egress);
+        assert!(!Cli::parse_from(["ls-vpc"]).egress);
+    }
+
+    #[test]
+    fn cli_parses_duplicates() {
+        assert!(Cli::parse_from(["ls-vpc", "--duplicates"]).duplicates);
+        assert!(!Cli::parse_from(["ls-vpc"]).duplicates);
+    }
+
+    #[test]
+    fn cli_parses_nau() {
+        let cli = Cli::parse_from(["ls-vpc", "--nau"]);
+        assert!(cli.nau);
+    }
+
+    #[test]
+    fn cli_parses_flow_logs() {
+        assert!(Cli::parse_from(["ls-vpc", "--flow-logs"]).flow_logs);
+        assert!(!Cli::parse_from(["ls-vpc"]).flow_logs);
+    }
+
+    #[test]
+    fn cli_parses_endpoint_services() {
+        assert!(Cli::parse_from(["ls-vpc", "--endpoint-services"]).endpoint_services);
+        assert!(!Cli::parse_from(["ls-vpc"]).endpoint_services);
+    }
+
+    #[test]
+    fn cli_parses_email_flags() {
+        let cli = Cli::parse_from([
+            "ls-vpc",
+            "--email-to",
+            "ops@example.com,sre@example.com",
+            "--email-from",
+            "ls-vpc@example.com",
+            "--email-format",
+            "text",
+        ]);
+        assert_eq!(cli.email_to, vec!["ops@example.com", "sre@example.com"]);
+        assert_eq!(cli.email_from.as_deref(), Some("ls-vpc@example.com"));
+        assert_eq!(cli.email_format, EmailFormat::Text);
+        assert_eq!(Cli::parse_from(["ls-vpc"]).email_format, EmailFormat::Html);
+        assert!(Cli::try_parse_from(["ls-vpc", "--email-from", "a@example.com"]).is_err());
+        assert!(Cli::try_parse_from(["ls-vpc", "--email-to", "a@example.com", "--tui"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_schema() {
+        assert!(Cli::parse_from(["ls-vpc", "--schema"]).schema);
+        assert!(!Cli::parse_from(["ls-vpc"]).schema);
+    }
+
+    #[test]
+    fn cli_parses_redact() {
+        let cli = Cli::parse_from(["ls-vpc", "--redact", "--sg-graph=dot"]);
+        assert!(cli.redact);
+        assert!(!Cli::parse_from(["ls-vpc"]).redact);
+    }
+
+    #[test]
+    fn cli_parses_limit_and_page() {
+        let cli = Cli::parse_from(["ls-vpc", "--limit", "20", "--page", "3"]);
+        assert_eq!((cli.limit, cli.page), (Some(20), Some(3)));
+        assert_eq!(Cli::parse_from(["ls-vpc"]).limit, None);
+        assert!(Cli::try_parse_from(["ls-vpc", "--page", "2"]).is_err());
+        assert!(Cli::try_parse_from(["ls-vpc", "--limit", "0"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_concurrency_limits() {
+        let cli = Cli::parse_from(["ls-vpc"]);
+        assert_eq!((cli.max_concurrent_regions, cli.max_concurrent_vpcs), (4, 8));
+        let cli = Cli::parse_from(["ls-vpc", "--max-concurrent-regions", "1", "--max-concurrent-vpcs", "2"]);
+        assert_eq!((cli.max_concurrent_regions, cli.max_concurrent_vpcs), (1, 2));
+        assert!(Cli::try_parse_from(["ls-vpc", "--max-concurrent-vpcs", "0"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_sdk_tuning() {
+        let cli = Cli::parse_from([
+            "ls-vpc",
+            "--max-retries",
+            "10",
+            "--connect-timeout",
+            "30s",
+            "--operation-timeout",
+            "5m",
+        ]);
+        assert_eq!(cli.max_retries, Some(10));
+        assert_eq!(cli.connect_timeout.as_deref(), Some("30s"));
+        assert_eq!(cli.operation_timeout.as_deref(), Some("5m"));
+    }
+
+    #[test]
+    fn cli_rejects_out_of_range_ip_threshold() {
+        assert!(Cli::try_parse_from(["ls-vpc", "--ip-threshold", "101"]).is_err());
+        assert!(Cli::try_parse_from(["ls-vpc", "--ip-threshold", "0"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_tui() {
+        assert!(Cli::parse_from(["ls-vpc", "--tui"]).tui);
+        assert!(Cli::try_parse_from(["ls-vpc", "--tui", "--sg-graph"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_pick_quiet() {
+        let cli = Cli::parse_from(["ls-vpc", "--pick", "-q"]);
+        assert!(cli.pick);
+        assert!(cli.quiet);
+        assert!(Cli::try_parse_from(["ls-vpc", "--quiet"]).is_err());
+        assert!(Cli::try_parse_from(["ls-vpc", "--pick", "--tui"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_with_cost() {
+        let cli = Cli::parse_from(["ls-vpc", "--with-cost"]);
+        assert!(cli.with_cost);
+    }
+
+    #[test]
+    fn cli_parses_api_stats() {
+        let cli = Cli::parse_from(["ls-vpc", "--api-stats"]);
+        assert!(cli.api_stats);
+    }
+
+    #[test]
+    fn cli_parses_view() {
+        let cli = Cli::parse_from(["ls-vpc", "--view", "capacity", "vpc-123"]);
+        assert_eq!(cli.view.as_deref(), Some("capacity"));
+        assert_eq!(cli.vpc_ids, vec!["vpc-123"]);
+    }
+
+    #[test]
+    fn cli_parses_dry_run() {
+        assert!(Cli::parse_from(["ls-vpc", "--dry-run", "vpc-123"]).dry_run);
+        assert!(!Cli::parse_from(["ls-vpc"]).dry_run);
+    }
+
+    #[test]
+    fn cli_parses_whoami_and_verbose() {
+        assert!(Cli::parse_from(["ls-vpc", "--whoami"]).whoami);
+        let cli = Cli::parse_from(["ls-vpc", "-v"]);
+        assert!(cli.verbose);
+        assert!(!cli.whoami);
+    }
+
+    #[test]
+    fn cli_parses_show_tags() {
+        let cli = Cli::parse_from(["ls-vpc", "--show-tags", "Team,Environment", "vpc-123"]);
+        assert_eq!(cli.show_tags, vec!["Team", "Environment"]);
+        assert!(Cli::parse_from(["ls-vpc"]).show_tags.is_empty());
+    }
+
+    #[test]
+    fn cli_parses_with_stacks() {
+        let cli = Cli::parse_from(["ls-vpc", "--with-stacks", "vpc-123"]);
+        assert!(cli.with_stacks);
+        assert_eq!(cli.vpc_ids, vec!["vpc-123"]);
+    }
+
+    #[test]
+    fn cli_parses_with_spend() {
+        let cli = Cli::parse_from(["ls-vpc", "--with-spend", "30d", "--spend-tag", "VpcName"]);
+        assert_eq!(cli.with_spend.as_deref(), Some("30d"));
+        assert_eq!(cli.spend_tag, "VpcName");
+        assert_eq!(Cli::parse_from(["ls-vpc"]).spend_tag, "vpc-id");
+    }
+
     #[test]
     fn cli_parses_region_and_vpc_ids() {
         // Explicit region followed by VPC IDs
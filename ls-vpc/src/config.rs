@@ -2,8 +2,12 @@
 //!
 //! This module validates CLI arguments and provides defaults.
 
-use crate::cli::Cli;
-use eyre::{Result, bail};
+use crate::cli::{Cli, EmailFormat, OutputFormat, SgGraphFormat, SortBy};
+use crate::policy::Policy;
+use crate::settings::{Settings, is_region_name};
+use crate::utils::Page;
+use eyre::{Result, bail, eyre};
+use std::time::Duration;
 
 /// Validated configuration for ls-vpc
 #[derive(Debug, Clone)]
@@ -12,18 +16,118 @@ pub struct Config {
     pub regions: Vec<String>,
     /// VPC IDs to filter (empty = all VPCs)
     pub vpc_ids: Vec<String>,
+    /// Role to run the scan under
+    pub assume_role: Option<String>,
+    /// External ID for the assumed role
+    pub external_id: Option<String>,
+    /// Region whose STS endpoint serves AssumeRole and GetCallerIdentity
+    /// (the scanned region's when unset)
+    pub sts_region: Option<String>,
     /// Whether to show summary only (no resources)
     pub summary_only: bool,
+    /// Format picked with `--format`; `None` leaves it to [`resolve_format`]
+    /// once stdout is known
+    pub format: Option<OutputFormat>,
+    /// Resolve the owning CloudFormation stack of each resource
+    pub with_stacks: bool,
+    /// Tag keys shown as extra detail columns
+    pub show_tags: Vec<String>,
+    /// Resource ordering in the detail view (scan order when unset)
+    pub sort_by: Option<SortBy>,
+    /// Render the security-group reference graph instead of VPCs
+    pub sg_graph: Option<SgGraphFormat>,
+    /// List unattached security groups instead of VPCs
+    pub unused_sgs: bool,
+    /// Run the issue checks and print an issues report
+    pub issues: bool,
+    /// Used-IP percentage at which subnets are flagged
+    pub ip_threshold: Option<u8>,
+    /// Estimate interface endpoint cost per VPC
+    pub with_cost: bool,
+    /// Days of Cost Explorer network spend to report per VPC
+    pub spend_days: Option<u32>,
+    /// Cost allocation tag naming the VPC a resource's spend belongs to
+    pub spend_tag: String,
+    /// Report Network Address Usage per VPC
+    pub nau: bool,
+    /// Report outbound internet paths per VPC
+    pub egress: bool,
+    /// Report names shared across VPCs
+    pub duplicates: bool,
+    /// Report flow log destinations per VPC
+    pub flow_logs: bool,
+    /// Report PrivateLink endpoint services per VPC
+    pub endpoint_services: bool,
+    /// Policy gate: fail when any VPC matches
+    pub fail_on: Option<Policy>,
+    /// Print Terraform import blocks instead of tables
+    pub emit_tf_imports: bool,
+    /// Anonymize the scan before printing
+    pub redact: bool,
+    /// Browse results in the interactive TUI
+    pub tui: bool,
+    /// Pick one VPC with the fuzzy finder
+    pub pick: bool,
+    /// Print only the picked VPC-ID
+    pub quiet: bool,
+    /// Window of VPCs to print
+    pub page: Page,
+    /// Regions scanned concurrently
+    pub max_concurrent_regions: usize,
+    /// VPCs scanned concurrently within a region
+    pub max_concurrent_vpcs: usize,
+    /// Retries per AWS call (SDK default when unset)
+    pub max_retries: Option<u32>,
+    /// Connect timeout for AWS endpoints
+    pub connect_timeout: Option<Duration>,
+    /// Timeout for a whole AWS call including retries
+    pub operation_timeout: Option<Duration>,
+    /// Recipients of the emailed report (none = no email)
+    pub email_to: Vec<String>,
+    /// Sender of the emailed report
+    pub email_from: Option<String>,
+    /// Body format of the emailed report
+    pub email_format: EmailFormat,
+    /// Print the JSON Schema instead of scanning
+    pub schema: bool,
+    /// Report API call counts at the end of the run
+    pub api_stats: bool,
+    /// Print the scan plan instead of scanning
+    pub dry_run: bool,
+    /// Print the caller identity instead of scanning
+    pub whoami: bool,
+    /// Print the caller identity before scanning
+    pub verbose: bool,
 }
 
 impl TryFrom<Cli> for Config {
     type Error = eyre::Error;
 
     fn try_from(cli: Cli) -> Result<Self> {
+        Config::try_from_with(cli, &Settings::load()?)
+    }
+}
+
+impl Config {
+    /// Validate the arguments, expanding region groups from `settings`
+    /// instead of reading the settings file
+    pub fn try_from_with(cli: Cli, settings: &Settings) -> Result<Self> {
         // Validate regions
         if cli.regions.is_empty() {
             bail!("At least one region must be specified");
         }
+        let regions = settings.expand_regions(&cli.regions)?;
+
+        let timeout = |flag: &str, value: Option<&str>| -> Result<Option<Duration>> {
+            let Some(value) = value else { return Ok(None) };
+            let duration = parse_duration(value)?;
+            if duration.is_zero() {
+                bail!("{} must be greater than zero, got '{}'", flag, value);
+            }
+            Ok(Some(duration))
+        };
+        let connect_timeout = timeout("--connect-timeout", cli.connect_timeout.as_deref())?;
+        let operation_timeout = timeout("--operation-timeout", cli.operation_timeout.as_deref())?;
 
         // Validate VPC IDs format if provided
         for vpc_id in &cli.vpc_ids {
@@ -32,10 +136,95 @@ impl TryFrom<Cli> for Config {
             }
         }
 
+        let spend_days = match cli.with_spend.as_deref() {
+            Some(period) => {
+                let days = parse_duration(period)?.as_secs() / 86_400;
+                if days == 0 {
+                    bail!("--with-spend must cover at least one day, got '{}'", period);
+                }
+                Some(u32::try_from(days).map_err(|_| eyre!("--with-spend period '{}' is too long", period))?)
+            }
+            None => None,
+        };
+
+        // Validate the role ARN format if provided
+        if let Some(arn) = cli
+            .assume_role
+            .as_deref()
+            .filter(|arn| !arn.starts_with("arn:aws:iam::") || !arn.contains(":role/"))
+        {
+            bail!(
+                "Invalid role ARN format: '{}'. Expected format: arn:aws:iam::<account>:role/<name>",
+                arn
+            );
+        }
+
+        if let Some(region) = cli.sts_region.as_deref().filter(|r| !is_region_name(r)) {
+            bail!("Invalid --sts-region '{}'. Expected a region name like us-east-1", region);
+        }
+
+        let fail_on = cli.fail_on.as_deref().map(Policy::parse).transpose()?;
+
+        for addr in cli.email_to.iter().chain(&cli.email_from) {
+            if !addr.contains('@') {
+                bail!("Invalid email address: '{}'", addr);
+            }
+        }
+        let email_from = cli.email_from.or_else(|| cli.email_to.first().cloned());
+
         Ok(Config {
-            regions: cli.regions,
-            summary_only: cli.vpc_ids.is_empty(),
+            regions,
+            assume_role: cli.assume_role,
+            external_id: cli.external_id,
+            sts_region: cli.sts_region,
+            // The TUI, picker detail view, diagrams and import blocks need
+            // resources even without VPC-IDs
+            summary_only: cli.vpc_ids.is_empty()
+                && !cli.tui
+                && (!cli.pick || cli.quiet)
+                && cli.format != Some(OutputFormat::Drawio)
+                && !cli.emit_tf_imports,
             vpc_ids: cli.vpc_ids,
+            format: cli.format,
+            with_stacks: cli.with_stacks,
+            show_tags: cli.show_tags,
+            sort_by: cli.sort_by,
+            sg_graph: cli.sg_graph,
+            unused_sgs: cli.unused_sgs,
+            // A --fail-on expression reading `issues` needs the checks to run
+            issues: cli.issues || fail_on.as_ref().is_some_and(|p| p.expr.uses("issues")),
+            ip_threshold: cli.ip_threshold,
+            with_cost: cli.with_cost,
+            spend_days,
+            spend_tag: cli.spend_tag,
+            nau: cli.nau,
+            egress: cli.egress,
+            duplicates: cli.duplicates,
+            flow_logs: cli.flow_logs,
+            endpoint_services: cli.endpoint_services,
+            fail_on,
+            emit_tf_imports: cli.emit_tf_imports,
+            redact: cli.redact,
+            tui: cli.tui,
+            pick: cli.pick,
+            quiet: cli.quiet,
+            page: Page {
+                limit: cli.limit.map(|n| n as usize),
+                page: cli.page.unwrap_or(1) as usize,
+            },
+            max_concurrent_regions: usize::from(cli.max_concurrent_regions),
+            max_concurrent_vpcs: usize::from(cli.max_concurrent_vpcs),
+            max_retries: cli.max_retries,
+            connect_timeout,
+            operation_timeout,
+            email_to: cli.email_to,
+            email_from,
+            email_format: cli.email_format,
+            schema: cli.schema,
+            api_stats: cli.api_stats,
+            dry_run: cli.dry_run,
+            whoami: cli.whoami,
+            verbose: cli.verbose,
         })
     }
 }
@@ -45,19 +234,112 @@ impl Default for Config {
         Config {
             regions: vec!["us-east-1".to_string(), "us-west-2".to_string()],
             vpc_ids: vec![],
+            assume_role: None,
+            external_id: None,
+            sts_region: None,
             summary_only: true,
+            format: None,
+            with_stacks: false,
+            show_tags: Vec::new(),
+            sort_by: None,
+            sg_graph: None,
+            unused_sgs: false,
+            issues: false,
+            ip_threshold: None,
+            with_cost: false,
+            spend_days: None,
+            spend_tag: "vpc-id".to_string(),
+            nau: false,
+            egress: false,
+            duplicates: false,
+            flow_logs: false,
+            endpoint_services: false,
+            fail_on: None,
+            emit_tf_imports: false,
+            redact: false,
+            tui: false,
+            pick: false,
+            quiet: false,
+            page: Page::default(),
+            max_concurrent_regions: 4,
+            max_concurrent_vpcs: 8,
+            max_retries: None,
+            connect_timeout: None,
+            operation_timeout: None,
+            email_to: vec![],
+            email_from: None,
+            email_format: EmailFormat::Html,
+            schema: false,
+            api_stats: false,
+            dry_run: false,
+            whoami: false,
+            verbose: false,
         }
     }
 }
 
+/// Pick the output format: an explicit `--format` always wins, otherwise
+/// tables on a terminal and TSV when stdout is piped.
+pub fn resolve_format(explicit: Option<OutputFormat>, is_tty: bool) -> OutputFormat {
+    match explicit {
+        Some(format) => format,
+        None if is_tty => OutputFormat::Table,
+        None => OutputFormat::Tsv,
+    }
+}
+
+/// Parse a human-friendly duration such as `45s`, `20m`, `2h`, `30d` or `2w`
+/// (shared with ls-rds).
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let n: u64 = num
+        .parse()
+        .map_err(|_| eyre!("Invalid duration '{}'. Expected e.g. 30s, 5m, 1h", s))?;
+    let unit_secs: u64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        _ => bail!("Invalid duration unit in '{}'. Use one of s, m, h, d, w", s),
+    };
+    let Some(secs) = n.checked_mul(unit_secs) else {
+        bail!("Duration '{}' is too long", s);
+    };
+    Ok(Duration::from_secs(secs))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use clap::Parser;
+
+    fn cli_default() -> Cli {
+        Cli::parse_from(["ls-vpc"])
+    }
+
+    /// Config from `cli` with no settings file, whatever the developer has
+    fn from_cli(cli: Cli) -> Result<Config> {
+        Config::try_from_with(cli, &Settings::default())
+    }
+
+    #[test]
+    fn config_expands_region_groups_from_settings() {
+        let settings = Settings::parse("region-groups:\n  us: [us-east-1, us-west-2]\n").unwrap();
+        let cli = Cli::parse_from(["ls-vpc", "--regions", "us,eu-west-1"]);
+        let config = Config::try_from_with(cli, &settings).unwrap();
+        assert_eq!(config.regions, vec!["us-east-1", "us-west-2", "eu-west-1"]);
+        let cli = Cli::parse_from(["ls-vpc", "--regions", "us"]);
+        assert!(from_cli(cli).is_err());
+    }
+
     fn cli_with_regions(regions: Vec<String>) -> Cli {
         Cli {
             regions,
-            vpc_ids: vec![],
+            ..cli_default()
         }
     }
 
@@ -65,16 +347,24 @@ mod tests {
         Cli {
             regions: vec!["us-west-2".to_string()],
             vpc_ids,
+            ..cli_default()
         }
     }
 
     #[test]
-    fn config_from_cli_with_defaults() {
+    fn config_rejects_unknown_region() {
         let cli = Cli {
-            regions: vec!["us-east-1".to_string(), "us-west-2".to_string()],
-            vpc_ids: vec![],
+            regions: vec!["nowhere".to_string()],
+            ..cli_default()
         };
-        let config = Config::try_from(cli).unwrap();
+        let err = from_cli(cli).unwrap_err();
+        assert!(err.to_string().contains("nowhere"));
+    }
+
+    #[test]
+    fn config_from_cli_with_defaults() {
+        let cli = cli_default();
+        let config = from_cli(cli).unwrap();
         assert_eq!(config.regions.len(), 2);
         assert!(config.summary_only);
         assert!(config.vpc_ids.is_empty());
@@ -82,19 +372,143 @@ mod tests {
 
     #[test]
     fn config_from_cli_with_vpc_ids() {
-        let cli = Cli {
-            regions: vec!["us-west-2".to_string()],
-            vpc_ids: vec!["vpc-123".to_string(), "vpc-456".to_string()],
-        };
-        let config = Config::try_from(cli).unwrap();
+        let cli = cli_with_vpc_ids(vec!["vpc-123".to_string(), "vpc-456".to_string()]);
+        let config = from_cli(cli).unwrap();
         assert!(!config.summary_only);
         assert_eq!(config.vpc_ids.len(), 2);
     }
 
+    #[test]
+    fn config_carries_concurrency_limits() {
+        let config = from_cli(Cli {
+            max_concurrent_regions: 1,
+            max_concurrent_vpcs: 3,
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!((config.max_concurrent_regions, config.max_concurrent_vpcs), (1, 3));
+        let config = Config::default();
+        assert_eq!((config.max_concurrent_regions, config.max_concurrent_vpcs), (4, 8));
+    }
+
+    #[test]
+    fn config_carries_unused_sgs() {
+        let config = from_cli(Cli {
+            unused_sgs: true,
+            ..cli_default()
+        })
+        .unwrap();
+        assert!(config.unused_sgs);
+        assert!(!Config::default().unused_sgs);
+    }
+
+    #[test]
+    fn config_email_from_defaults_to_first_recipient() {
+        let config = from_cli(Cli {
+            email_to: vec!["ops@example.com".to_string(), "sre@example.com".to_string()],
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!(config.email_from.as_deref(), Some("ops@example.com"));
+        assert_eq!(config.format, None);
+
+        let config = from_cli(Cli {
+            email_to: vec!["ops@example.com".to_string()],
+            email_from: Some("ls-vpc@example.com".to_string()),
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!(config.email_from.as_deref(), Some("ls-vpc@example.com"));
+    }
+
+    #[test]
+    fn config_rejects_invalid_email_address() {
+        let result = from_cli(Cli {
+            email_to: vec!["ops".to_string()],
+            ..cli_default()
+        });
+        assert!(result.unwrap_err().to_string().contains("Invalid email address"));
+    }
+
+    #[test]
+    fn config_carries_schema() {
+        let config = from_cli(Cli {
+            schema: true,
+            ..cli_default()
+        })
+        .unwrap();
+        assert!(config.schema);
+        assert!(!Config::default().schema);
+    }
+
+    #[test]
+    fn config_parses_fail_on() {
+        let config = from_cli(Cli {
+            fail_on: Some("public && !flow_logs".to_string()),
+            ..cli_default()
+        })
+        .unwrap();
+        assert!(!config.issues);
+        assert_eq!(config.fail_on.map(|p| p.source), Some("public && !flow_logs".to_string()));
+        let config = from_cli(Cli {
+            fail_on: Some("issues > 0".to_string()),
+            ..cli_default()
+        })
+        .unwrap();
+        assert!(config.issues);
+        let result = from_cli(Cli {
+            fail_on: Some("public &&".to_string()),
+            ..cli_default()
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn config_carries_redact() {
+        let cli = Cli {
+            redact: true,
+            ..cli_with_vpc_ids(vec![])
+        };
+        let config = from_cli(cli).unwrap();
+        assert!(config.redact);
+        assert!(!Config::default().redact);
+    }
+
+    #[test]
+    fn config_carries_egress() {
+        let cli = Cli {
+            egress: true,
+            ..cli_with_vpc_ids(vec![])
+        };
+        assert!(from_cli(cli).unwrap().egress);
+        assert!(!Config::default().egress);
+    }
+
+    #[test]
+    fn config_carries_duplicates() {
+        let cli = Cli {
+            duplicates: true,
+            ..cli_with_vpc_ids(vec![])
+        };
+        assert!(from_cli(cli).unwrap().duplicates);
+        assert!(!Config::default().duplicates);
+    }
+
+    #[test]
+    fn config_carries_sort_by() {
+        let cli = Cli {
+            sort_by: Some(SortBy::Age),
+            ..cli_with_vpc_ids(vec!["vpc-123".to_string()])
+        };
+        let config = from_cli(cli).unwrap();
+        assert_eq!(config.sort_by, Some(SortBy::Age));
+        assert_eq!(Config::default().sort_by, None);
+    }
+
     #[test]
     fn config_rejects_empty_regions() {
         let cli = cli_with_regions(vec![]);
-        let result = Config::try_from(cli);
+        let result = from_cli(cli);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("region"));
     }
@@ -102,7 +516,7 @@ mod tests {
     #[test]
     fn config_rejects_invalid_vpc_id_format() {
         let cli = cli_with_vpc_ids(vec!["invalid-vpc-id".to_string()]);
-        let result = Config::try_from(cli);
+        let result = from_cli(cli);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("vpc-"));
     }
@@ -110,7 +524,7 @@ mod tests {
     #[test]
     fn config_accepts_valid_vpc_ids() {
         let cli = cli_with_vpc_ids(vec!["vpc-12345678".to_string(), "vpc-abcdef01".to_string()]);
-        let config = Config::try_from(cli).unwrap();
+        let config = from_cli(cli).unwrap();
         assert_eq!(config.vpc_ids.len(), 2);
     }
 
@@ -127,10 +541,188 @@ mod tests {
         assert!(config.summary_only);
     }
 
+    #[test]
+    fn config_carries_with_stacks() {
+        let cli = Cli {
+            with_stacks: true,
+            ..cli_with_vpc_ids(vec!["vpc-123".to_string()])
+        };
+        let config = from_cli(cli).unwrap();
+        assert!(config.with_stacks);
+    }
+
+    #[test]
+    fn resolve_format_follows_tty_unless_explicit() {
+        assert_eq!(resolve_format(None, true), OutputFormat::Table);
+        assert_eq!(resolve_format(None, false), OutputFormat::Tsv);
+        assert_eq!(resolve_format(Some(OutputFormat::Table), false), OutputFormat::Table);
+        assert_eq!(resolve_format(Some(OutputFormat::Tsv), true), OutputFormat::Tsv);
+    }
+
+    #[test]
+    fn config_tsv_stays_summary_only() {
+        let config = from_cli(Cli {
+            format: Some(OutputFormat::Tsv),
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!(config.format, Some(OutputFormat::Tsv));
+        assert!(config.summary_only);
+    }
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("45s").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7_200));
+        assert_eq!(parse_duration("30d").unwrap(), Duration::from_secs(2_592_000));
+        assert_eq!(parse_duration("2w").unwrap(), Duration::from_secs(1_209_600));
+        assert!(parse_duration("5").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("30y").is_err());
+        assert!(parse_duration("18446744073709551615w").is_err());
+        assert!(parse_duration("18446744073709551615s").is_ok());
+    }
+
+    #[test]
+    fn config_converts_with_spend_to_days() {
+        let config = from_cli(Cli {
+            with_spend: Some("2w".to_string()),
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!(config.spend_days, Some(14));
+        assert_eq!(config.spend_tag, "vpc-id");
+
+        for period in ["12h", "5000000000d"] {
+            let cli = Cli {
+                with_spend: Some(period.to_string()),
+                ..cli_default()
+            };
+            assert!(from_cli(cli).is_err(), "{period}");
+        }
+    }
+
     #[test]
     fn config_clone_works() {
         let config = Config::default();
         let cloned = config.clone();
         assert_eq!(cloned.regions, config.regions);
     }
+
+    #[test]
+    fn config_tui_scans_resources_without_vpc_ids() {
+        let cli = Cli {
+            tui: true,
+            ..cli_default()
+        };
+        let config = from_cli(cli).unwrap();
+        assert!(config.tui);
+        assert!(!config.summary_only);
+    }
+
+    #[test]
+    fn config_quiet_pick_stays_summary_only() {
+        let pick = from_cli(Cli {
+            pick: true,
+            ..cli_default()
+        })
+        .unwrap();
+        assert!(!pick.summary_only);
+        let quiet = from_cli(Cli {
+            pick: true,
+            quiet: true,
+            ..cli_default()
+        })
+        .unwrap();
+        assert!(quiet.summary_only);
+    }
+
+    #[test]
+    fn config_drawio_scans_resources() {
+        let config = from_cli(Cli {
+            format: Some(OutputFormat::Drawio),
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!(config.format, Some(OutputFormat::Drawio));
+        assert!(!config.summary_only);
+    }
+
+    #[test]
+    fn config_tf_imports_scans_resources() {
+        let config = from_cli(Cli {
+            emit_tf_imports: true,
+            ..cli_default()
+        })
+        .unwrap();
+        assert!(config.emit_tf_imports);
+        assert!(!config.summary_only);
+    }
+
+    #[test]
+    fn config_validates_assume_role() {
+        let config = from_cli(Cli {
+            assume_role: Some("arn:aws:iam::123456789012:role/Auditor".to_string()),
+            external_id: Some("abc123".to_string()),
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!(config.assume_role.as_deref(), Some("arn:aws:iam::123456789012:role/Auditor"));
+        assert_eq!(config.external_id.as_deref(), Some("abc123"));
+
+        let result = from_cli(Cli {
+            assume_role: Some("Auditor".to_string()),
+            ..cli_default()
+        });
+        assert!(result.unwrap_err().to_string().contains("Invalid role ARN"));
+    }
+
+    #[test]
+    fn config_validates_sts_region() {
+        let config = from_cli(Cli {
+            sts_region: Some("eu-west-1".to_string()),
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!(config.sts_region.as_deref(), Some("eu-west-1"));
+
+        let result = from_cli(Cli {
+            sts_region: Some("global".to_string()),
+            ..cli_default()
+        });
+        assert!(result.unwrap_err().to_string().contains("--sts-region 'global'"));
+    }
+
+    #[test]
+    fn config_parses_sdk_tuning() {
+        let config = from_cli(Cli {
+            max_retries: Some(0),
+            connect_timeout: Some("5s".to_string()),
+            operation_timeout: Some("1m".to_string()),
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!(config.max_retries, Some(0));
+        assert_eq!(config.connect_timeout, Some(Duration::from_secs(5)));
+        assert_eq!(config.operation_timeout, Some(Duration::from_secs(60)));
+
+        let result = from_cli(Cli {
+            operation_timeout: Some("0m".to_string()),
+            ..cli_default()
+        });
+        assert!(result.unwrap_err().to_string().contains("--operation-timeout"));
+    }
+
+    #[test]
+    fn config_carries_limit_and_page() {
+        let config = from_cli(Cli {
+            limit: Some(25),
+            page: Some(2),
+            ..cli_default()
+        })
+        .unwrap();
+        assert_eq!(config.page, Page { limit: Some(25), page: 2 });
+        assert_eq!(from_cli(cli_default()).unwrap().page, Page::default());
+    }
 }
@@ -0,0 +1,81 @@
+//! db_subnet_groups.rs
+//! ---------------------------------------------------------------------------
+//! RDS DB subnet groups placed in a VPC and the Availability Zones their
+//! subnets cover.  Multi-AZ failover needs a standby subnet in a second AZ,
+//! so a group covering fewer than [`MIN_AZS`] is flagged before a database
+//! ends up in it.
+
+use crate::api_stats::CallCounter;
+use aws_sdk_rds as rds;
+use aws_types::SdkConfig;
+use eyre::Result;
+
+/// Availability Zones a DB subnet group needs for Multi-AZ failover.
+pub const MIN_AZS: usize = 2;
+
+/// A DB subnet group and the subnets it spans.
+#[derive(Debug, Clone, Default)]
+pub struct DbSubnetGroup {
+    pub name: String,
+    pub arn: String,
+    /// Usually `Complete`
+    pub status: String,
+    pub subnets: Vec<String>,
+    /// Distinct AZs of the subnets, sorted
+    pub azs: Vec<String>,
+}
+
+impl DbSubnetGroup {
+    /// The subnets span enough AZs for a Multi-AZ deployment.
+    pub fn multi_az_capable(&self) -> bool {
+        self.azs.len() >= MIN_AZS
+    }
+}
+
+/// List the DB subnet groups whose subnets belong to a VPC.
+pub async fn list_db_subnet_groups(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<DbSubnetGroup>> {
+    let client = rds::Client::new(conf);
+    let mut groups = Vec::new();
+
+    let mut pages = client.describe_db_subnet_groups().into_paginator().send();
+    while let Some(page) = pages.next().await {
+        calls.record("rds", "DescribeDBSubnetGroups", conf.region());
+        for g in page?.db_subnet_groups().iter().filter(|g| g.vpc_id() == Some(vpc_id)) {
+            let mut azs: Vec<String> = g
+                .subnets()
+                .iter()
+                .filter_map(|s| s.subnet_availability_zone()?.name().map(str::to_owned))
+                .collect();
+            azs.sort();
+            azs.dedup();
+            groups.push(DbSubnetGroup {
+                name: g.db_subnet_group_name().unwrap_or_default().to_owned(),
+                arn: g.db_subnet_group_arn().unwrap_or_default().to_owned(),
+                status: g.subnet_group_status().unwrap_or_default().to_owned(),
+                subnets: g
+                    .subnets()
+                    .iter()
+                    .filter_map(|s| s.subnet_identifier().map(str::to_owned))
+                    .collect(),
+                azs,
+            });
+        }
+    }
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_az_capable_needs_two_zones() {
+        let group = |azs: &[&str]| DbSubnetGroup {
+            azs: azs.iter().map(|az| az.to_string()).collect(),
+            ..Default::default()
+        };
+        assert!(!group(&[]).multi_az_capable());
+        assert!(!group(&["us-east-1a"]).multi_az_capable());
+        assert!(group(&["us-east-1a", "us-east-1b"]).multi_az_capable());
+    }
+}
@@ -0,0 +1,222 @@
+//! drawio.rs
+//! ---------------------------------------------------------------------------
+//! `--format drawio`: lays the scanned VPCs out as an mxGraph (draw.io) file.
+//! Each VPC is a container holding its gateways and subnets; peerings are
+//! dashed edges between containers (peers outside the scan get a stub node).
+
+use crate::VpcSummary;
+use std::collections::{BTreeMap, BTreeSet};
+
+const SUBNET_W: u32 = 180;
+const SUBNET_H: u32 = 60;
+const GATEWAY_H: u32 = 40;
+const PAD: u32 = 20;
+const HEADER: u32 = 30;
+const SUBNET_COLUMNS: u32 = 2;
+const VPC_GAP: u32 = 80;
+
+const VPC_STYLE: &str = "swimlane;rounded=1;fillColor=#f5f5f5;strokeColor=#248814;fontStyle=1;";
+const SUBNET_STYLE: &str = "rounded=1;whiteSpace=wrap;fillColor=#dae8fc;strokeColor=#6c8ebf;";
+const GATEWAY_STYLE: &str = "shape=hexagon;whiteSpace=wrap;fillColor=#fff2cc;strokeColor=#d6b656;";
+const EXTERNAL_STYLE: &str = "rounded=1;dashed=1;whiteSpace=wrap;fillColor=#ffffff;";
+const PEERING_STYLE: &str = "endArrow=none;dashed=1;strokeWidth=2;strokeColor=#9673a6;";
+
+/// Escape text for an XML attribute; newlines become draw.io line breaks.
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "&#xa;")
+}
+
+/// Append a vertex; `geometry` is `[x, y, width, height]` relative to `parent`.
+fn vertex(out: &mut String, id: &str, value: &str, style: &str, parent: &str, geometry: [u32; 4]) {
+    let [x, y, w, h] = geometry;
+    out.push_str(&format!(
+        "        <mxCell id=\"{}\" value=\"{}\" style=\"{}\" vertex=\"1\" parent=\"{}\">\n          \
+         <mxGeometry x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" as=\"geometry\"/>\n        </mxCell>\n",
+        xml_escape(id),
+        xml_escape(value),
+        style,
+        xml_escape(parent),
+    ));
+}
+
+/// Gateways drawn along the top of a VPC: its internet gateway (when
+/// public) and any NAT gateways or VPC endpoints found by the scanners.
+fn gateways(vpc_id: &str, s: &VpcSummary) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    if s.public {
+        out.push((format!("{vpc_id}-igw"), "Internet gateway".to_owned()));
+    }
+    for r in &s.resources {
+        if matches!(r.rtype, "ec2.nat-gateway" | "ec2.vpc-endpoint") {
+            let kind = if r.rtype == "ec2.nat-gateway" { "NAT gateway" } else { "VPC endpoint" };
+            out.push((r.arn.clone(), format!("{kind}\n{}", r.name)));
+        }
+    }
+    out
+}
+
+/// Render the VPCs as a draw.io document.
+pub fn to_drawio(vpcs: &BTreeMap<(String, String, String), VpcSummary>) -> String {
+    let mut cells = String::new();
+    let mut x = 0;
+    let scanned: BTreeSet<&str> = vpcs.keys().map(|(_, _, id)| id.as_str()).collect();
+
+    for ((account, region, vpc_id), s) in vpcs {
+        let gws = gateways(vpc_id, s);
+        let rows = (s.subnets.len() as u32).div_ceil(SUBNET_COLUMNS);
+        let gw_band = if gws.is_empty() { 0 } else { GATEWAY_H + PAD };
+        let width = (PAD + SUBNET_W) * SUBNET_COLUMNS.max(gws.len() as u32).max(1) + PAD;
+        let height = HEADER + PAD + gw_band + rows * (SUBNET_H + PAD);
+
+        let title = format!(
+            "{} {} ({} {}) {}",
+            s.name.as_deref().unwrap_or_default(),
+            vpc_id,
+            account,
+            region,
+            s.cidrs.join(",")
+        );
+        vertex(&mut cells, vpc_id, title.trim(), VPC_STYLE, "1", [x, 0, width, height.max(HEADER + PAD * 2)]);
+
+        for (i, (id, label)) in gws.iter().enumerate() {
+            let gx = PAD + i as u32 * (SUBNET_W + PAD);
+            vertex(&mut cells, id, label, GATEWAY_STYLE, vpc_id, [gx, HEADER + PAD, SUBNET_W, GATEWAY_H]);
+        }
+
+        for (i, sn) in s.subnets.iter().enumerate() {
+            let (col, row) = (i as u32 % SUBNET_COLUMNS, i as u32 / SUBNET_COLUMNS);
+            let label = format!(
+                "{}\n{} {}\n{:.0}% used",
+                sn.name.as_deref().unwrap_or(&sn.id),
+                sn.cidr,
+                sn.zone(),
+                sn.used_pct()
+            );
+            vertex(
+                &mut cells,
+                &sn.id,
+                &label,
+                SUBNET_STYLE,
+                vpc_id,
+                [
+                    PAD + col * (SUBNET_W + PAD),
+                    HEADER + PAD + gw_band + row * (SUBNET_H + PAD),
+                    SUBNET_W,
+                    SUBNET_H,
+                ],
+            );
+        }
+
+        x += width + VPC_GAP;
+    }
+
+    // Peerings, once per pair; peers that weren't scanned get a stub node.
+    let mut drawn = BTreeSet::new();
+    let mut externals = BTreeSet::new();
+    for ((_, _, vpc_id), s) in vpcs {
+        for peer in &s.peers {
+            let pair = if vpc_id < peer { (vpc_id.clone(), peer.clone()) } else { (peer.clone(), vpc_id.clone()) };
+            if !drawn.insert(pair.clone()) {
+                continue;
+            }
+            if !scanned.contains(peer.as_str()) && externals.insert(peer.clone()) {
+                let label = format!("{peer}\n(not scanned)");
+                vertex(&mut cells, peer, &label, EXTERNAL_STYLE, "1", [x, 0, SUBNET_W, SUBNET_H]);
+                x += SUBNET_W + VPC_GAP;
+            }
+            cells.push_str(&format!(
+                "        <mxCell id=\"peer-{}-{}\" style=\"{}\" edge=\"1\" parent=\"1\" source=\"{}\" target=\"{}\">\n          \
+                 <mxGeometry relative=\"1\" as=\"geometry\"/>\n        </mxCell>\n",
+                xml_escape(&pair.0),
+                xml_escape(&pair.1),
+                PEERING_STYLE,
+                xml_escape(&pair.0),
+                xml_escape(&pair.1),
+            ));
+        }
+    }
+
+    format!(
+        "<mxfile host=\"ls-vpc\">\n  <diagram id=\"ls-vpc\" name=\"VPCs\">\n    <mxGraphModel>\n      <root>\n        \
+         <mxCell id=\"0\"/>\n        <mxCell id=\"1\" parent=\"0\"/>\n{cells}      </root>\n    </mxGraphModel>\n  \
+         </diagram>\n</mxfile>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResourceRecord;
+    use crate::subnets::Subnet;
+
+    fn vpc(id: &str, peers: &[&str]) -> ((String, String, String), VpcSummary) {
+        (
+            ("111111111111".to_string(), "us-east-1".to_string(), id.to_string()),
+            VpcSummary {
+                name: Some("a&b".to_string()),
+                public: true,
+                cidrs: vec!["10.0.0.0/16".to_string()],
+                peers: peers.iter().map(|p| p.to_string()).collect(),
+                subnets: vec![
+                    Subnet {
+                        id: format!("{id}-subnet-1"),
+                        cidr: "10.0.1.0/24".to_string(),
+                        az: "us-east-1a".to_string(),
+                        ..Default::default()
+                    },
+                    Subnet {
+                        id: format!("{id}-subnet-2"),
+                        cidr: "10.0.2.0/24".to_string(),
+                        az: "us-east-1b".to_string(),
+                        ..Default::default()
+                    },
+                ],
+                resources: vec![ResourceRecord {
+                    arn: format!("nat-{id}"),
+                    rtype: "ec2.nat-gateway",
+                    name: format!("nat-{id}"),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn to_drawio_emits_vpc_subnets_and_gateways() {
+        let vpcs = BTreeMap::from([vpc("vpc-a", &[])]);
+        let xml = to_drawio(&vpcs);
+        assert!(xml.starts_with("<mxfile"));
+        assert!(xml.contains("id=\"vpc-a\" value=\"a&amp;b vpc-a (111111111111 us-east-1) 10.0.0.0/16\""));
+        assert!(xml.contains("id=\"vpc-a-subnet-2\""));
+        assert!(xml.contains("parent=\"vpc-a\""));
+        assert!(xml.contains("Internet gateway"));
+        assert!(xml.contains("NAT gateway&#xa;nat-vpc-a"));
+    }
+
+    #[test]
+    fn to_drawio_draws_each_peering_once() {
+        let vpcs = BTreeMap::from([vpc("vpc-a", &["vpc-b"]), vpc("vpc-b", &["vpc-a"])]);
+        let xml = to_drawio(&vpcs);
+        assert_eq!(xml.matches("edge=\"1\"").count(), 1);
+        assert!(xml.contains("source=\"vpc-a\" target=\"vpc-b\""));
+        assert!(!xml.contains("not scanned"));
+    }
+
+    #[test]
+    fn to_drawio_stubs_unscanned_peers() {
+        let vpcs = BTreeMap::from([vpc("vpc-a", &["vpc-z"])]);
+        let xml = to_drawio(&vpcs);
+        assert!(xml.contains("id=\"vpc-z\" value=\"vpc-z&#xa;(not scanned)\""));
+        assert!(xml.contains("source=\"vpc-a\" target=\"vpc-z\""));
+    }
+
+    #[test]
+    fn xml_escape_handles_markup_and_newlines() {
+        assert_eq!(xml_escape("<a & \"b\">\nc"), "&lt;a &amp; &quot;b&quot;&gt;&#xa;c");
+    }
+}
@@ -0,0 +1,130 @@
+//! duplicates.rs
+//! ---------------------------------------------------------------------------
+//! `--duplicates`: names shared by different VPCs, and resource names shared
+//! across VPCs (two VPCs called "prod", a `payments-db` in both staging and
+//! production).  Such twins are how people end up operating on the wrong
+//! environment.  Names are compared case-insensitively with `_` and spaces
+//! treated as `-`, so `Prod`, `prod` and `PROD ` collide; resources only
+//! collide with resources of the same type, and only across VPCs (a name
+//! repeated inside one VPC is the scanners' business, not a mix-up).
+//! Resource names are only known in detail mode.
+
+use crate::VpcSummary;
+use crate::cli::OutputFormat;
+use crate::utils::render_table;
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Where a name was seen: (account, region, VPC-ID).
+pub type Place = (String, String, String);
+
+/// A name found in more than one VPC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicate {
+    /// `vpc`, or the resource type, e.g. `rds.instance`
+    pub kind: String,
+    /// The name as first seen
+    pub name: String,
+    pub places: BTreeSet<Place>,
+}
+
+/// Comparison key of a name: lowercase, `_` and spaces as `-`, trimmed.
+pub fn name_key(name: &str) -> String {
+    name.trim().to_lowercase().replace(['_', ' '], "-")
+}
+
+/// Every VPC and resource name that appears in more than one VPC.
+pub fn find_duplicates(vpcs: &BTreeMap<Place, VpcSummary>) -> Vec<Duplicate> {
+    let mut seen: BTreeMap<(String, String), Duplicate> = BTreeMap::new();
+    let mut note = |kind: &str, name: &str, place: &Place| {
+        let key = name_key(name);
+        if key.is_empty() {
+            return;
+        }
+        seen.entry((kind.to_owned(), key))
+            .or_insert_with(|| Duplicate {
+                kind: kind.to_owned(),
+                name: name.to_owned(),
+                places: BTreeSet::new(),
+            })
+            .places
+            .insert(place.clone());
+    };
+    for (place, s) in vpcs {
+        if let Some(name) = &s.name {
+            note("vpc", name, place);
+        }
+        for r in &s.resources {
+            note(r.rtype, &r.name, place);
+        }
+    }
+    seen.into_values().filter(|d| d.places.len() > 1).collect()
+}
+
+/// Render the duplicates, one row per VPC a name appears in.
+pub fn format_duplicates_table(duplicates: &[Duplicate], format: OutputFormat) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec!["KIND", "NAME", "ACCOUNT", "REGION", "VPC-ID"]);
+    for d in duplicates {
+        for (account, region, vpc_id) in &d.places {
+            table.add_row(vec![
+                d.kind.clone(),
+                d.name.clone(),
+                account.clone(),
+                region.clone(),
+                vpc_id.clone(),
+            ]);
+        }
+    }
+    render_table(&table, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::ResourceRecord;
+
+    fn place(account: &str, region: &str, vpc_id: &str) -> Place {
+        (account.to_string(), region.to_string(), vpc_id.to_string())
+    }
+
+    fn vpc(name: &str, resources: &[(&'static str, &str)]) -> VpcSummary {
+        VpcSummary {
+            name: Some(name.to_string()),
+            resources: resources
+                .iter()
+                .map(|(rtype, name)| ResourceRecord {
+                    rtype: *rtype,
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn names_collide_across_vpcs_and_accounts() {
+        let vpcs = BTreeMap::from([
+            (place("111", "us-east-1", "vpc-a"), vpc("prod", &[("rds.instance", "payments-db")])),
+            (place("222", "us-east-1", "vpc-b"), vpc("Prod ", &[("rds.instance", "payments_db")])),
+            (place("111", "eu-west-1", "vpc-c"), vpc("staging", &[("ec2.instance", "payments-db")])),
+        ]);
+        let dups = find_duplicates(&vpcs);
+        assert_eq!(dups.len(), 2);
+        assert_eq!((dups[0].kind.as_str(), dups[0].places.len()), ("rds.instance", 2));
+        assert_eq!((dups[1].kind.as_str(), dups[1].name.as_str()), ("vpc", "prod"));
+    }
+
+    #[test]
+    fn repeats_inside_one_vpc_are_not_duplicates() {
+        let vpcs = BTreeMap::from([(
+            place("111", "us-east-1", "vpc-a"),
+            vpc("prod", &[("ec2.instance", "worker"), ("ec2.instance", "worker")]),
+        )]);
+        assert!(find_duplicates(&vpcs).is_empty());
+        assert_eq!(name_key(" Payments_DB "), "payments-db");
+    }
+}
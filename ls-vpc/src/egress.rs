@@ -0,0 +1,240 @@
+//! egress.rs
+//! ---------------------------------------------------------------------------
+//! `--egress`: how each VPC reaches the internet.  Every subnet follows the
+//! default route of its route table (the main table when it has no explicit
+//! association); a NAT gateway is followed one hop further, to the default
+//! route of the subnet it sits in, so a NAT that hands traffic to a transit
+//! gateway (central egress VPC) is told apart from one behind an internet
+//! gateway.  The EGRESS column counts subnets per path.
+
+use crate::VpcSummary;
+use crate::api_stats::CallCounter;
+use crate::cli::OutputFormat;
+use crate::routes::RouteTable;
+use crate::subnets::Subnet;
+use crate::utils::render_table;
+use aws_sdk_ec2 as ec2;
+use aws_sdk_ec2::types::NatGatewayState;
+use aws_types::SdkConfig;
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use eyre::Result;
+use std::collections::BTreeMap;
+
+/// Destinations of a default route, IPv4 first.
+const DEFAULT_ROUTES: [&str; 2] = ["0.0.0.0/0", "::/0"];
+
+/// The path outbound internet traffic takes from a subnet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Egress {
+    /// Straight out of an internet (or egress-only internet) gateway
+    Igw,
+    /// Through a NAT gateway in a subnet with an internet gateway route
+    Nat,
+    /// Through a transit gateway, usually to a central egress VPC
+    Tgw,
+    /// Through a virtual private gateway back to the on-premises network
+    Vpn,
+    /// Through an instance or network interface (firewall, NAT instance)
+    Appliance,
+    /// No default route, a blackholed one or one that leads nowhere
+    None,
+}
+
+impl Egress {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Egress::Igw => "igw",
+            Egress::Nat => "nat",
+            Egress::Tgw => "tgw",
+            Egress::Vpn => "vpn",
+            Egress::Appliance => "appliance",
+            Egress::None => "none",
+        }
+    }
+}
+
+/// Subnet of each available NAT gateway in a VPC.
+pub async fn nat_subnets(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<BTreeMap<String, String>> {
+    let client = ec2::Client::new(conf);
+    let mut subnets = BTreeMap::new();
+
+    calls.record("ec2", "DescribeNatGateways", conf.region());
+    let mut pages = client
+        .describe_nat_gateways()
+        .filter(
+            ec2::types::Filter::builder()
+                .name("vpc-id")
+                .values(vpc_id)
+                .build(),
+        )
+        .into_paginator()
+        .items()
+        .send();
+    while let Some(ngw) = pages.next().await {
+        let ngw = ngw?;
+        if ngw.state() != Some(&NatGatewayState::Available) {
+            continue;
+        }
+        if let (Some(id), Some(subnet)) = (ngw.nat_gateway_id(), ngw.subnet_id()) {
+            subnets.insert(id.to_owned(), subnet.to_owned());
+        }
+    }
+    Ok(subnets)
+}
+
+/// Route table a subnet uses: its explicit association, else the main table.
+fn table_for<'a>(tables: &'a [RouteTable], subnet_id: &str) -> Option<&'a RouteTable> {
+    tables
+        .iter()
+        .find(|t| t.subnets.iter().any(|s| s == subnet_id))
+        .or_else(|| tables.iter().find(|t| t.main))
+}
+
+/// Target of a table's default route (`None` when blackholed or missing).
+fn default_target(table: &RouteTable) -> Option<&str> {
+    DEFAULT_ROUTES.iter().find_map(|dest| {
+        table
+            .routes
+            .iter()
+            .find(|r| r.destination == *dest)
+            .filter(|r| !r.blackhole)
+            .map(|r| r.target.as_str())
+    })
+}
+
+/// Egress path of a route table, following a NAT gateway to its subnet.
+fn table_egress(table: &RouteTable, tables: &[RouteTable], nats: &BTreeMap<String, String>) -> Egress {
+    let Some(target) = default_target(table) else { return Egress::None };
+    if target.starts_with("igw-") || target.starts_with("eigw-") {
+        Egress::Igw
+    } else if target.starts_with("nat-") {
+        // A NAT we could not place is assumed to be a public one
+        let Some(subnet) = nats.get(target) else { return Egress::Nat };
+        match table_for(tables, subnet).and_then(default_target) {
+            Some(t) if t.starts_with("igw-") => Egress::Nat,
+            Some(t) if t.starts_with("tgw-") => Egress::Tgw,
+            _ => Egress::None,
+        }
+    } else if target.starts_with("tgw-") {
+        Egress::Tgw
+    } else if target.starts_with("vgw-") {
+        Egress::Vpn
+    } else if target.starts_with("eni-") || target.starts_with("i-") {
+        Egress::Appliance
+    } else {
+        Egress::None
+    }
+}
+
+/// Egress path of one subnet (NAT gateways missing from `nats` count as
+/// public ones).
+pub fn subnet_egress(subnet_id: &str, tables: &[RouteTable], nats: &BTreeMap<String, String>) -> Egress {
+    table_for(tables, subnet_id).map_or(Egress::None, |t| table_egress(t, tables, nats))
+}
+
+/// Count the VPC's subnets per egress path.
+pub fn classify(
+    subnets: &[Subnet],
+    tables: &[RouteTable],
+    nats: &BTreeMap<String, String>,
+) -> BTreeMap<Egress, usize> {
+    let mut paths = BTreeMap::new();
+    for sn in subnets {
+        *paths.entry(subnet_egress(&sn.id, tables, nats)).or_default() += 1;
+    }
+    paths
+}
+
+/// Paths with their subnet counts, e.g. `igw:2,nat:4` (`-` = no subnets).
+pub fn format_egress(paths: &BTreeMap<Egress, usize>) -> String {
+    if paths.is_empty() {
+        return "-".to_owned();
+    }
+    paths
+        .iter()
+        .map(|(path, n)| format!("{}:{}", path.as_str(), n))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Render the egress paths of every VPC.
+pub fn format_egress_table(vpcs: &BTreeMap<(String, String, String), VpcSummary>, format: OutputFormat) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec!["ACCOUNT", "REGION", "VPC-ID", "NAME", "EGRESS"]);
+    for ((account, region, vpc_id), s) in vpcs {
+        table.add_row(vec![
+            account.clone(),
+            region.clone(),
+            vpc_id.clone(),
+            s.name.clone().unwrap_or_default(),
+            format_egress(&s.egress),
+        ]);
+    }
+    render_table(&table, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::Route;
+
+    fn table(id: &str, main: bool, subnets: &[&str], default: Option<&str>) -> RouteTable {
+        RouteTable {
+            id: id.to_string(),
+            main,
+            subnets: subnets.iter().map(|s| s.to_string()).collect(),
+            routes: default
+                .map(|target| Route {
+                    destination: "0.0.0.0/0".to_string(),
+                    target: target.to_string(),
+                    blackhole: false,
+                })
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn subnets(ids: &[&str]) -> Vec<Subnet> {
+        ids.iter()
+            .map(|id| Subnet {
+                id: id.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn classify_follows_nat_to_its_subnet() {
+        let tables = vec![
+            table("rtb-public", false, &["subnet-pub"], Some("igw-1")),
+            table("rtb-private", true, &[], Some("nat-1")),
+        ];
+        let nats = BTreeMap::from([("nat-1".to_string(), "subnet-pub".to_string())]);
+        let paths = classify(&subnets(&["subnet-pub", "subnet-a", "subnet-b"]), &tables, &nats);
+        assert_eq!(format_egress(&paths), "igw:1,nat:2");
+    }
+
+    #[test]
+    fn classify_sends_nat_behind_tgw_to_tgw() {
+        let tables = vec![
+            table("rtb-nat", false, &["subnet-nat"], Some("tgw-1")),
+            table("rtb-app", false, &["subnet-app"], Some("nat-1")),
+            table("rtb-main", true, &[], None),
+        ];
+        let nats = BTreeMap::from([("nat-1".to_string(), "subnet-nat".to_string())]);
+        let paths = classify(&subnets(&["subnet-nat", "subnet-app", "subnet-db"]), &tables, &nats);
+        assert_eq!(paths[&Egress::Tgw], 2);
+        assert_eq!(paths[&Egress::None], 1);
+    }
+
+    #[test]
+    fn blackholed_default_route_has_no_egress() {
+        let mut rt = table("rtb-1", true, &[], Some("nat-gone"));
+        rt.routes[0].blackhole = true;
+        assert_eq!(table_egress(&rt, &[], &BTreeMap::new()), Egress::None);
+        assert_eq!(format_egress(&BTreeMap::new()), "-");
+    }
+}
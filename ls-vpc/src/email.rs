@@ -0,0 +1,91 @@
+//! email.rs
+//! ---------------------------------------------------------------------------
+//! `--email-to`: deliver the rendered report through SES (v2 API) with the
+//! same credentials as the scan, so a cron job no longer needs `| mailx`.
+//! HTML mail wraps the report in `<pre>` to keep the tables aligned and
+//! carries the plain text as an alternative part.
+
+use crate::api_stats::CallCounter;
+use crate::cli::EmailFormat;
+use aws_sdk_sesv2 as ses;
+use aws_types::SdkConfig;
+use eyre::{Result, WrapErr};
+use ses::types::{Body, Content, Destination, EmailContent, Message};
+
+/// Escape the characters that are significant in HTML text
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Render the report as a minimal HTML document
+pub fn render_html(subject: &str, report: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{}</title></head>\n\
+         <body>\n<pre style=\"font-family: monospace\">{}</pre>\n</body>\n</html>\n",
+        escape_html(subject),
+        escape_html(report)
+    )
+}
+
+fn content(data: &str) -> Result<Content> {
+    Ok(Content::builder().data(data).charset("UTF-8").build()?)
+}
+
+/// Send `report` to `to` from `from` via SES in the config's region
+pub async fn send_report(
+    conf: &SdkConfig,
+    calls: &CallCounter,
+    from: &str,
+    to: &[String],
+    subject: &str,
+    report: &str,
+    format: EmailFormat,
+) -> Result<()> {
+    let mut body = Body::builder().text(content(report)?);
+    if format == EmailFormat::Html {
+        body = body.html(content(&render_html(subject, report))?);
+    }
+    let message = Message::builder()
+        .subject(content(subject)?)
+        .body(body.build())
+        .build();
+
+    let client = ses::Client::new(conf);
+    calls.record("sesv2", "SendEmail", conf.region());
+    client
+        .send_email()
+        .from_email_address(from)
+        .destination(Destination::builder().set_to_addresses(Some(to.to_vec())).build())
+        .content(EmailContent::builder().simple(message).build())
+        .send()
+        .await
+        .wrap_err_with(|| format!("Failed to send the report to {} via SES", to.join(", ")))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_html_escapes_markup() {
+        assert_eq!(escape_html("a < b && \"c\" > d"), "a &lt; b &amp;&amp; &quot;c&quot; &gt; d");
+    }
+
+    #[test]
+    fn render_html_keeps_report_preformatted() {
+        let html = render_html("ls-vpc report", "+---+\n| <vpc> |\n+---+");
+        assert!(html.contains("<title>ls-vpc report</title>"));
+        assert!(html.contains("<pre style=\"font-family: monospace\">+---+\n| &lt;vpc&gt; |\n+---+</pre>"));
+    }
+}
@@ -0,0 +1,237 @@
+//! endpoint_services.rs
+//! ---------------------------------------------------------------------------
+//! `--endpoint-services`: the VPC endpoint services (our PrivateLink
+//! offerings) hosted from each VPC.  A service belongs to the VPC of the
+//! Network or Gateway Load Balancers behind it; for each one we report
+//! whether connections need acceptance, which principals may connect and
+//! how many endpoints are connected right now.
+
+use crate::VpcSummary;
+use crate::api_stats::CallCounter;
+use crate::cli::OutputFormat;
+use crate::utils::render_table;
+use aws_sdk_ec2 as ec2;
+use aws_sdk_elasticloadbalancingv2 as elbv2;
+use aws_types::SdkConfig;
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use eyre::Result;
+use std::collections::BTreeMap;
+
+/// Connection state of an endpoint that is passing traffic.
+pub const CONNECTED: &str = "available";
+
+/// A VPC endpoint service offered from a VPC.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointService {
+    pub id: String,
+    /// e.g. `com.amazonaws.vpce.us-east-1.vpce-svc-0123456789abcdef0`
+    pub name: String,
+    /// `Interface` or `GatewayLoadBalancer`
+    pub kind: String,
+    /// `Available`, `Pending`, `Deleting` …
+    pub state: String,
+    /// New connections wait for manual acceptance
+    pub acceptance_required: bool,
+    /// Principals allowed to connect (`*` = everyone)
+    pub allowed_principals: Vec<String>,
+    /// Endpoints currently connected
+    pub connections: usize,
+    /// Load balancers behind the service
+    pub load_balancers: Vec<String>,
+}
+
+impl EndpointService {
+    /// Any AWS principal may create an endpoint to the service.
+    pub fn open_to_all(&self) -> bool {
+        self.allowed_principals.iter().any(|p| p == "*")
+    }
+}
+
+/// VPC of each load balancer ARN.
+async fn load_balancer_vpcs(
+    conf: &SdkConfig,
+    calls: &CallCounter,
+    arns: &[String],
+) -> Result<BTreeMap<String, String>> {
+    let client = elbv2::Client::new(conf);
+    let mut vpcs = BTreeMap::new();
+    for chunk in arns.chunks(20) {
+        calls.record("elbv2", "DescribeLoadBalancers", conf.region());
+        let resp = client
+            .describe_load_balancers()
+            .set_load_balancer_arns(Some(chunk.to_vec()))
+            .send()
+            .await?;
+        for lb in resp.load_balancers() {
+            if let (Some(arn), Some(vpc)) = (lb.load_balancer_arn(), lb.vpc_id()) {
+                vpcs.insert(arn.to_owned(), vpc.to_owned());
+            }
+        }
+    }
+    Ok(vpcs)
+}
+
+/// List the endpoint services whose load balancers live in a VPC.
+pub async fn list_endpoint_services(
+    conf: &SdkConfig,
+    calls: &CallCounter,
+    vpc_id: &str,
+) -> Result<Vec<EndpointService>> {
+    let client = ec2::Client::new(conf);
+    let mut services = Vec::new();
+
+    calls.record("ec2", "DescribeVpcEndpointServiceConfigurations", conf.region());
+    let mut pages = client
+        .describe_vpc_endpoint_service_configurations()
+        .into_paginator()
+        .items()
+        .send();
+    while let Some(svc) = pages.next().await {
+        let svc = svc?;
+        let mut load_balancers = svc.network_load_balancer_arns().to_vec();
+        load_balancers.extend_from_slice(svc.gateway_load_balancer_arns());
+        services.push(EndpointService {
+            id: svc.service_id().unwrap_or_default().to_owned(),
+            name: svc.service_name().unwrap_or_default().to_owned(),
+            kind: svc
+                .service_type()
+                .first()
+                .and_then(|t| t.service_type())
+                .map(|t| t.as_str().to_owned())
+                .unwrap_or_default(),
+            state: svc.service_state().map(|s| s.as_str().to_owned()).unwrap_or_default(),
+            acceptance_required: svc.acceptance_required().unwrap_or(false),
+            load_balancers,
+            ..Default::default()
+        });
+    }
+    if services.is_empty() {
+        return Ok(services);
+    }
+
+    let arns: Vec<String> = services.iter().flat_map(|s| s.load_balancers.clone()).collect();
+    let vpcs = load_balancer_vpcs(conf, calls, &arns).await?;
+    services.retain(|s| s.load_balancers.iter().any(|lb| vpcs.get(lb).map(String::as_str) == Some(vpc_id)));
+
+    for svc in &mut services {
+        calls.record("ec2", "DescribeVpcEndpointServicePermissions", conf.region());
+        let mut principals = client
+            .describe_vpc_endpoint_service_permissions()
+            .service_id(&svc.id)
+            .into_paginator()
+            .items()
+            .send();
+        while let Some(p) = principals.next().await {
+            if let Some(principal) = p?.principal() {
+                svc.allowed_principals.push(principal.to_owned());
+            }
+        }
+
+        calls.record("ec2", "DescribeVpcEndpointConnections", conf.region());
+        let mut connections = client
+            .describe_vpc_endpoint_connections()
+            .filters(
+                ec2::types::Filter::builder()
+                    .name("service-id")
+                    .values(&svc.id)
+                    .build(),
+            )
+            .into_paginator()
+            .items()
+            .send();
+        while let Some(c) = connections.next().await {
+            if c?.vpc_endpoint_state().map(|s| s.as_str()) == Some(CONNECTED) {
+                svc.connections += 1;
+            }
+        }
+    }
+    Ok(services)
+}
+
+/// Render every VPC's endpoint services, one row each; VPCs offering none
+/// are left out.
+pub fn format_endpoint_services_table(
+    vpcs: &BTreeMap<(String, String, String), VpcSummary>,
+    format: OutputFormat,
+) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec![
+        "ACCOUNT",
+        "REGION",
+        "VPC-ID",
+        "SERVICE-ID",
+        "TYPE",
+        "STATE",
+        "ACCEPTANCE",
+        "ALLOWED-PRINCIPALS",
+        "CONNECTIONS",
+    ]);
+
+    for ((account, region, vpc_id), s) in vpcs {
+        for svc in &s.endpoint_services {
+            let principals = if svc.allowed_principals.is_empty() {
+                "none".to_owned()
+            } else {
+                svc.allowed_principals.join(", ")
+            };
+            table.add_row(vec![
+                account.clone(),
+                region.clone(),
+                vpc_id.clone(),
+                svc.id.clone(),
+                svc.kind.clone(),
+                svc.state.clone(),
+                if svc.acceptance_required { "manual" } else { "auto" }.to_owned(),
+                principals,
+                svc.connections.to_string(),
+            ]);
+        }
+    }
+    render_table(&table, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(principals: &[&str]) -> EndpointService {
+        EndpointService {
+            id: "vpce-svc-0123456789abcdef0".to_string(),
+            kind: "Interface".to_string(),
+            state: "Available".to_string(),
+            acceptance_required: true,
+            allowed_principals: principals.iter().map(|p| p.to_string()).collect(),
+            connections: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn open_to_all_needs_wildcard_principal() {
+        assert!(service(&["*"]).open_to_all());
+        assert!(!service(&["arn:aws:iam::111111111111:root"]).open_to_all());
+        assert!(!service(&[]).open_to_all());
+    }
+
+    #[test]
+    fn format_endpoint_services_table_lists_settings() {
+        let mut vpcs = BTreeMap::new();
+        vpcs.insert(
+            ("111111111111".to_string(), "us-east-1".to_string(), "vpc-a".to_string()),
+            VpcSummary {
+                endpoint_services: vec![service(&["arn:aws:iam::222222222222:root"])],
+                ..Default::default()
+            },
+        );
+        vpcs.insert(("111111111111".to_string(), "us-east-1".to_string(), "vpc-b".to_string()), VpcSummary::default());
+        let table = format_endpoint_services_table(&vpcs, OutputFormat::Tsv);
+        assert_eq!(
+            table.lines().nth(1).unwrap(),
+            "111111111111\tus-east-1\tvpc-a\tvpce-svc-0123456789abcdef0\tInterface\tAvailable\tmanual\t\
+             arn:aws:iam::222222222222:root\t3"
+        );
+        assert!(!table.contains("vpc-b"));
+    }
+}
@@ -0,0 +1,203 @@
+//! endpoints.rs
+//! ---------------------------------------------------------------------------
+//! VPC endpoint collection and the `--with-cost` monthly estimate.  Gateway
+//! endpoints (S3, DynamoDB) are free; interface endpoints bill per AZ-hour
+//! for every subnet they are attached to, at the scanned region's list price.
+
+use crate::VpcSummary;
+use crate::api_stats::CallCounter;
+use crate::scanner::ec2_tags;
+use crate::cli::OutputFormat;
+use crate::utils::render_table;
+use aws_sdk_ec2 as ec2;
+use aws_types::SdkConfig;
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use eyre::Result;
+use std::collections::BTreeMap;
+
+/// List price of one interface endpoint ENI, per AZ-hour (USD, us-east-1).
+/// Also the estimate for regions missing from [`REGIONAL_AZ_HOURLY_USD`].
+pub const INTERFACE_AZ_HOURLY_USD: f64 = 0.01;
+
+/// Interface endpoint list prices per AZ-hour (USD) by region.
+pub const REGIONAL_AZ_HOURLY_USD: &[(&str, f64)] = &[
+    ("af-south-1", 0.013),
+    ("ap-east-1", 0.013),
+    ("ap-northeast-1", 0.014),
+    ("ap-northeast-2", 0.013),
+    ("ap-northeast-3", 0.014),
+    ("ap-south-1", 0.011),
+    ("ap-southeast-1", 0.013),
+    ("ap-southeast-2", 0.013),
+    ("ca-central-1", 0.011),
+    ("eu-central-1", 0.012),
+    ("eu-north-1", 0.011),
+    ("eu-south-1", 0.012),
+    ("eu-west-1", 0.011),
+    ("eu-west-2", 0.011),
+    ("eu-west-3", 0.012),
+    ("me-south-1", 0.013),
+    ("sa-east-1", 0.017),
+    ("us-east-1", 0.01),
+    ("us-east-2", 0.01),
+    ("us-west-1", 0.011),
+    ("us-west-2", 0.01),
+];
+
+/// Per-AZ-hour price in `region`, falling back to the us-east-1 price.
+pub fn interface_az_hourly_usd(region: &str) -> f64 {
+    REGIONAL_AZ_HOURLY_USD
+        .iter()
+        .find(|(r, _)| *r == region)
+        .map_or(INTERFACE_AZ_HOURLY_USD, |(_, usd)| *usd)
+}
+
+/// Hours AWS bills in an average month.
+pub const HOURS_PER_MONTH: f64 = 730.0;
+
+/// A VPC endpoint and the subnets it is attached to.
+#[derive(Debug, Clone, Default)]
+pub struct VpcEndpoint {
+    pub id: String,
+    /// Service the endpoint fronts, e.g. `com.amazonaws.us-east-1.ssm`
+    pub service: String,
+    /// `Interface`, `Gateway` or `GatewayLoadBalancer`
+    pub kind: String,
+    /// One subnet (and therefore one billed ENI) per AZ
+    pub subnets: Vec<String>,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl VpcEndpoint {
+    pub fn is_interface(&self) -> bool {
+        self.kind == "Interface"
+    }
+
+    /// Estimated monthly cost in `region` (hourly charge only, data
+    /// processing excluded).
+    pub fn monthly_cost(&self, region: &str) -> f64 {
+        if !self.is_interface() {
+            return 0.0;
+        }
+        self.subnets.len() as f64 * interface_az_hourly_usd(region) * HOURS_PER_MONTH
+    }
+}
+
+/// List every VPC endpoint in a VPC.
+pub async fn list_vpc_endpoints(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<VpcEndpoint>> {
+    let client = ec2::Client::new(conf);
+    let mut endpoints = Vec::new();
+
+    calls.record("ec2", "DescribeVpcEndpoints", conf.region());
+    let mut pages = client
+        .describe_vpc_endpoints()
+        .filters(
+            ec2::types::Filter::builder()
+                .name("vpc-id")
+                .values(vpc_id)
+                .build(),
+        )
+        .into_paginator()
+        .items()
+        .send();
+    while let Some(ep) = pages.next().await {
+        let ep = ep?;
+        endpoints.push(VpcEndpoint {
+            id: ep.vpc_endpoint_id().unwrap_or_default().to_owned(),
+            service: ep.service_name().unwrap_or_default().to_owned(),
+            kind: ep.vpc_endpoint_type().map(|t| t.as_str().to_owned()).unwrap_or_default(),
+            subnets: ep.subnet_ids().to_vec(),
+            tags: ec2_tags(ep.tags()),
+        });
+    }
+    Ok(endpoints)
+}
+
+/// Render the per-VPC interface endpoint estimate with a grand total.
+pub fn format_cost_table(vpcs: &BTreeMap<(String, String, String), VpcSummary>, format: OutputFormat) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec!["ACCOUNT", "REGION", "VPC-ID", "INTERFACE-ENDPOINTS", "AZ-ATTACHMENTS", "EST-USD/MONTH"]);
+
+    let mut total = 0.0;
+    for ((account, region, vpc_id), s) in vpcs {
+        let interfaces: Vec<_> = s.endpoints.iter().filter(|e| e.is_interface()).collect();
+        let cost: f64 = interfaces.iter().map(|e| e.monthly_cost(region)).sum();
+        total += cost;
+        table.add_row(vec![
+            account.clone(),
+            region.clone(),
+            vpc_id.clone(),
+            interfaces.len().to_string(),
+            interfaces.iter().map(|e| e.subnets.len()).sum::<usize>().to_string(),
+            format!("{cost:.2}"),
+        ]);
+    }
+    table.add_row(vec![
+        "TOTAL".to_owned(),
+        String::new(),
+        String::new(),
+        String::new(),
+        String::new(),
+        format!("{total:.2}"),
+    ]);
+    render_table(&table, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(kind: &str, azs: usize) -> VpcEndpoint {
+        VpcEndpoint {
+            id: "vpce-1".to_string(),
+            kind: kind.to_string(),
+            subnets: (0..azs).map(|i| format!("subnet-{i}")).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn monthly_cost_scales_with_attached_azs() {
+        assert!((endpoint("Interface", 1).monthly_cost("us-east-1") - 7.30).abs() < 1e-9);
+        assert!((endpoint("Interface", 3).monthly_cost("us-east-1") - 21.90).abs() < 1e-9);
+    }
+
+    #[test]
+    fn monthly_cost_uses_the_regional_price() {
+        assert!((endpoint("Interface", 1).monthly_cost("sa-east-1") - 12.41).abs() < 1e-9);
+        assert_eq!(interface_az_hourly_usd("eu-central-1"), 0.012);
+        assert_eq!(interface_az_hourly_usd("xx-nowhere-1"), INTERFACE_AZ_HOURLY_USD);
+    }
+
+    #[test]
+    fn gateway_endpoints_are_free() {
+        assert_eq!(endpoint("Gateway", 0).monthly_cost("us-east-1"), 0.0);
+        assert_eq!(endpoint("GatewayLoadBalancer", 2).monthly_cost("eu-west-1"), 0.0);
+    }
+
+    #[test]
+    fn format_cost_table_totals_per_vpc() {
+        let mut vpcs = BTreeMap::new();
+        vpcs.insert(
+            ("111111111111".to_string(), "us-east-1".to_string(), "vpc-a".to_string()),
+            VpcSummary {
+                endpoints: vec![endpoint("Interface", 2), endpoint("Gateway", 0)],
+                ..Default::default()
+            },
+        );
+        vpcs.insert(
+            ("111111111111".to_string(), "us-west-2".to_string(), "vpc-b".to_string()),
+            VpcSummary {
+                endpoints: vec![endpoint("Interface", 3)],
+                ..Default::default()
+            },
+        );
+        let table = format_cost_table(&vpcs, OutputFormat::Table);
+        assert!(table.contains("14.60"));
+        assert!(table.contains("21.90"));
+        assert!(table.contains("TOTAL"));
+        assert!(table.contains("36.50"));
+    }
+}
@@ -0,0 +1,194 @@
+//! errors.rs
+//! ---------------------------------------------------------------------------
+//! Scan failures in a form wrappers can branch on instead of scraping log
+//! text.  Each failure carries a `ScanErrorKind` classified from the AWS
+//! error code, the operation that failed and the message; with
+//! `--format json` they are also written to stderr as one JSON object per
+//! line.
+
+use aws_sdk_ec2::error::ProvideErrorMetadata;
+use eyre::Report;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+/// Error codes meaning the credentials lack permission for the call.
+const ACCESS_DENIED_CODES: &[&str] = &[
+    "AccessDenied",
+    "AccessDeniedException",
+    "AuthorizationError",
+    "UnauthorizedOperation",
+];
+
+/// Error codes of calls still throttled once the retries ran out.
+const THROTTLED_CODES: &[&str] = &[
+    "Throttling",
+    "ThrottlingException",
+    "ThrottledException",
+    "RequestLimitExceeded",
+    "RequestThrottled",
+    "RequestThrottledException",
+    "TooManyRequestsException",
+    "SlowDown",
+];
+
+/// Error codes of expired credentials, typically an SSO session.
+const EXPIRED_CODES: &[&str] = &["ExpiredToken", "ExpiredTokenException", "RequestExpired"];
+
+/// Error codes of a region the account has not enabled.
+const NOT_OPTED_IN_CODES: &[&str] = &["OptInRequired", "RegionDisabledException"];
+
+/// Why a region or account could not be scanned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanErrorKind {
+    AccessDenied,
+    Throttled,
+    ExpiredCredentials,
+    NotOptedIn,
+    #[default]
+    Other,
+}
+
+impl ScanErrorKind {
+    /// Classify an AWS error code such as `AccessDenied`.
+    pub fn from_code(code: &str) -> ScanErrorKind {
+        if ACCESS_DENIED_CODES.contains(&code) {
+            ScanErrorKind::AccessDenied
+        } else if THROTTLED_CODES.contains(&code) {
+            ScanErrorKind::Throttled
+        } else if EXPIRED_CODES.contains(&code) {
+            ScanErrorKind::ExpiredCredentials
+        } else if NOT_OPTED_IN_CODES.contains(&code) {
+            ScanErrorKind::NotOptedIn
+        } else {
+            ScanErrorKind::Other
+        }
+    }
+
+    /// Classify an SDK error by its error code.
+    pub fn of(err: &impl ProvideErrorMetadata) -> ScanErrorKind {
+        err.code().map_or(ScanErrorKind::Other, ScanErrorKind::from_code)
+    }
+
+    /// The kind's name as written in JSON, e.g. `access_denied`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScanErrorKind::AccessDenied => "access_denied",
+            ScanErrorKind::Throttled => "throttled",
+            ScanErrorKind::ExpiredCredentials => "expired_credentials",
+            ScanErrorKind::NotOptedIn => "not_opted_in",
+            ScanErrorKind::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for ScanErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The AWS error code of the first `E` (an SDK error type) in the chain of
+/// an error that was wrapped on its way up.
+pub fn code_in<E>(err: &Report) -> Option<String>
+where
+    E: ProvideErrorMetadata + Error + 'static,
+{
+    err.chain()
+        .find_map(|e| e.downcast_ref::<E>())
+        .and_then(|e| e.code())
+        .map(str::to_owned)
+}
+
+/// An account/region that could not be scanned, so its resources are
+/// missing.  Serialized as the schema's `error` object.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ScanError {
+    pub kind: ScanErrorKind,
+    /// AWS error code, e.g. `UnauthorizedOperation`.
+    pub code: Option<String>,
+    /// `None` when the account the scan runs in could not be resolved.
+    pub account_id: Option<String>,
+    pub region: String,
+    /// AWS operation that failed, e.g. `DescribeVpcs`.
+    pub operation: Option<String>,
+    pub message: String,
+}
+
+/// Format a scan failure as `REGION: MESSAGE (kind)`.
+pub fn format_scan_error(err: &ScanError) -> String {
+    format!("{}: {} ({})", err.region, err.message, err.kind)
+}
+
+/// Render scan failures as one compact JSON object per line, for stderr in
+/// `--format json` runs.
+pub fn error_json_lines(errors: &[ScanError]) -> serde_json::Result<String> {
+    let mut out = String::new();
+    for error in errors {
+        out.push_str(&serde_json::to_string(error)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_ec2::error::ErrorMetadata;
+    use aws_sdk_ec2::operation::describe_vpcs::DescribeVpcsError;
+
+    fn service_error(code: &str) -> DescribeVpcsError {
+        DescribeVpcsError::generic(ErrorMetadata::builder().code(code).message("denied").build())
+    }
+
+    #[test]
+    fn from_code_classifies_known_codes() {
+        assert_eq!(ScanErrorKind::from_code("UnauthorizedOperation"), ScanErrorKind::AccessDenied);
+        assert_eq!(ScanErrorKind::from_code("RequestLimitExceeded"), ScanErrorKind::Throttled);
+        assert_eq!(ScanErrorKind::from_code("ExpiredToken"), ScanErrorKind::ExpiredCredentials);
+        assert_eq!(ScanErrorKind::from_code("OptInRequired"), ScanErrorKind::NotOptedIn);
+        assert_eq!(ScanErrorKind::from_code("InternalError"), ScanErrorKind::Other);
+    }
+
+    #[test]
+    fn code_in_finds_the_aws_error_behind_a_report() {
+        let report = Report::new(service_error("UnauthorizedOperation")).wrap_err("listing VPCs");
+        let code = code_in::<DescribeVpcsError>(&report);
+        assert_eq!(code.as_deref(), Some("UnauthorizedOperation"));
+        assert_eq!(ScanErrorKind::of(&service_error("Throttling")), ScanErrorKind::Throttled);
+        assert_eq!(code_in::<DescribeVpcsError>(&eyre::eyre!("no SDK error")), None);
+    }
+
+    #[test]
+    fn error_json_lines_are_one_error_per_line() {
+        let errors = vec![
+            ScanError {
+                kind: ScanErrorKind::NotOptedIn,
+                code: Some("OptInRequired".to_string()),
+                account_id: Some("111111111111".to_string()),
+                region: "ap-east-1".to_string(),
+                operation: Some("DescribeVpcs".to_string()),
+                message: "cannot list VPCs, region skipped".to_string(),
+            },
+            ScanError {
+                region: "us-west-2".to_string(),
+                message: "timed out".to_string(),
+                ..Default::default()
+            },
+        ];
+        let text = error_json_lines(&errors).unwrap();
+        let lines: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["kind"], "not_opted_in");
+        assert_eq!(lines[0]["account_id"], "111111111111");
+        assert_eq!(lines[1]["kind"], "other");
+        assert!(lines[1]["account_id"].is_null());
+    }
+
+    #[test]
+    fn kinds_serialize_in_snake_case() {
+        assert_eq!(serde_json::to_string(&ScanErrorKind::NotOptedIn).unwrap(), "\"not_opted_in\"");
+        assert_eq!(ScanErrorKind::ExpiredCredentials.to_string(), "expired_credentials");
+    }
+}
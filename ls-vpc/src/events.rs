@@ -0,0 +1,290 @@
+//! events.rs
+//! ---------------------------------------------------------------------------
+//! Streaming scan for embedding ls-vpc.  `scan_stream` emits a `VpcEvent` as
+//! each VPC is listed and as its resources are found, so a UI or server can
+//! render progressively instead of waiting for the complete map `run`
+//! returns:
+//!
+//! ```no_run
+//! # async fn example(config: ls_vpc::Config) -> eyre::Result<()> {
+//! use futures::StreamExt;
+//! use ls_vpc::{VpcEvent, scan_stream};
+//!
+//! let stats = ls_vpc::ApiStats::default();
+//! let mut events = std::pin::pin!(scan_stream(&config, &stats));
+//! while let Some(event) = events.next().await {
+//!     if let VpcEvent::ResourceDiscovered { vpc_id, resource, .. } = event? {
+//!         println!("{vpc_id} {} {}", resource.rtype, resource.arn);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Regions are scanned at most `--max-concurrent-regions` at a time and
+//! their events interleave.  Spend and cross-VPC checks such as asymmetric
+//! peerings need every VPC, so they are only applied by `run`.
+
+use crate::api_stats::ApiStats;
+use crate::errors::{self, ScanError, ScanErrorKind, format_scan_error};
+use crate::{
+    Config, ResourceRecord, ServiceScanner, VpcSummary, list_vpcs, region_conf, scan_account, scan_vpc,
+    service_scanners,
+};
+use async_stream::try_stream;
+use aws_sdk_ec2 as ec2;
+use aws_sdk_ec2::operation::describe_vpcs::DescribeVpcsError;
+use eyre::Result;
+use futures::stream::{self, Stream, StreamExt};
+use log::warn;
+use std::pin::pin;
+use tokio::sync::OnceCell;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// A finished VPC scan: account, VPC ID and its summary and warnings
+type Scanned = (String, String, Result<(VpcSummary, Vec<String>)>);
+
+/// Whichever of a region's resource reports and VPC scans is ready first
+enum Next {
+    Found(VpcEvent),
+    /// `None` once every VPC has been scanned
+    Scanned(Option<Scanned>),
+}
+
+/// Progress of a streaming scan
+#[derive(Debug, Clone)]
+pub enum VpcEvent {
+    /// A VPC was listed; its resources follow as its scanners return them
+    VpcDiscovered {
+        account: String,
+        region: String,
+        vpc_id: String,
+        name: Option<String>,
+    },
+    /// A resource was found in a VPC, as soon as its scanner returned it
+    /// (its stack and `--show-tags` values are only in `VpcScanned`)
+    ResourceDiscovered {
+        account: String,
+        region: String,
+        vpc_id: String,
+        resource: ResourceRecord,
+    },
+    /// A VPC is complete; `summary` holds everything collected for it,
+    /// including the resources already sent as `ResourceDiscovered`
+    VpcScanned {
+        account: String,
+        region: String,
+        vpc_id: String,
+        summary: Box<VpcSummary>,
+    },
+    /// A non-fatal problem (failed scanner, unreadable metrics …)
+    Warning(String),
+    /// A region could not be scanned
+    RegionFailed(ScanError),
+    /// Every VPC of a region has been scanned (also sent after `RegionFailed`)
+    RegionScanned { region: String },
+}
+
+/// Events of one region, scanning at most `--max-concurrent-vpcs` VPCs at a
+/// time
+fn region_events<'a>(
+    region: String,
+    config: &'a Config,
+    stats: &'a ApiStats,
+    account: &'a OnceCell<String>,
+    scanners: &'a [Box<dyn ServiceScanner>],
+) -> impl Stream<Item = Result<VpcEvent>> + 'a {
+    try_stream! {
+        let account_id = account.get_or_init(|| scan_account(config, stats)).await;
+        let calls = stats.for_account(account_id);
+        let conf = region_conf(&region, config, &calls).await;
+        match list_vpcs(&conf, &calls, &config.vpc_ids).await {
+            Err(e) => {
+                let code = errors::code_in::<ec2::error::SdkError<DescribeVpcsError>>(&e);
+                let error = ScanError {
+                    account_id: Some(account_id.clone()).filter(|id| !id.is_empty()),
+                    region: region.clone(),
+                    operation: Some("DescribeVpcs".to_owned()),
+                    kind: code.as_deref().map_or(ScanErrorKind::Other, ScanErrorKind::from_code),
+                    code,
+                    message: format!("cannot list VPCs, region skipped: {e:#}"),
+                };
+                warn!("{}", format_scan_error(&error));
+                yield VpcEvent::RegionFailed(error);
+            }
+            Ok(listed) => {
+                for (vpc_id, account, name) in &listed {
+                    yield VpcEvent::VpcDiscovered {
+                        account: account.clone(),
+                        region: region.clone(),
+                        vpc_id: vpc_id.clone(),
+                        name: name.clone(),
+                    };
+                }
+
+                let (found_tx, found_rx) = mpsc::unbounded_channel();
+                let scans = stream::iter(listed)
+                    .map(|(vpc_id, account, vpc_name)| {
+                        let (conf, calls, region, found_tx) = (&conf, &calls, region.as_str(), &found_tx);
+                        async move {
+                            let found = |resources: &[ResourceRecord]| {
+                                for resource in resources {
+                                    // The receiver lives as long as this stream
+                                    let _ = found_tx.send(VpcEvent::ResourceDiscovered {
+                                        account: account.clone(),
+                                        region: region.to_owned(),
+                                        vpc_id: vpc_id.clone(),
+                                        resource: resource.clone(),
+                                    });
+                                }
+                            };
+                            let scanned = scan_vpc(conf, calls, &vpc_id, vpc_name, config, scanners, &found).await;
+                            (account, vpc_id, scanned)
+                        }
+                    })
+                    .buffered(config.max_concurrent_vpcs);
+                let mut events = pin!(vpc_events(&region, scans, found_rx));
+                while let Some(event) = events.next().await {
+                    yield event?;
+                }
+            }
+        }
+        yield VpcEvent::RegionScanned { region };
+    }
+}
+
+/// Events of a region's VPC scans: each resource as its scanner reports it
+/// on `found`, then, once a VPC's scan completes, its warnings and
+/// `VpcScanned` (after every resource it reported)
+fn vpc_events<'a, S>(
+    region: &'a str,
+    mut scans: S,
+    mut found: UnboundedReceiver<VpcEvent>,
+) -> impl Stream<Item = Result<VpcEvent>> + 'a
+where
+    S: Stream<Item = Scanned> + Unpin + 'a,
+{
+    try_stream! {
+        loop {
+            // Resources first: a scan can report and finish in the same poll
+            let next = tokio::select! {
+                biased;
+                Some(event) = found.recv() => Next::Found(event),
+                scanned = scans.next() => Next::Scanned(scanned),
+            };
+            let (account, vpc_id, scanned) = match next {
+                Next::Found(event) => {
+                    yield event;
+                    continue;
+                }
+                Next::Scanned(Some(scanned)) => scanned,
+                Next::Scanned(None) => break,
+            };
+            while let Ok(event) = found.try_recv() {
+                yield event;
+            }
+            let (mut summary, warnings) = scanned?;
+            summary.account = account.clone();
+            for warning in warnings {
+                yield VpcEvent::Warning(warning);
+            }
+            yield VpcEvent::VpcScanned {
+                account,
+                region: region.to_owned(),
+                vpc_id,
+                summary: Box::new(summary),
+            };
+        }
+    }
+}
+
+/// Scan every configured region, emitting events as VPCs and their
+/// resources are found and counting the calls made in `stats`.  A region
+/// whose VPCs cannot be listed is reported as `RegionFailed`; any other
+/// failure ends the stream with an error.
+pub fn scan_stream<'a>(config: &'a Config, stats: &'a ApiStats) -> impl Stream<Item = Result<VpcEvent>> + 'a {
+    try_stream! {
+        let scanners = service_scanners();
+        // Resolved by the first region, so a run without regions makes no calls
+        let account = OnceCell::new();
+        let mut events = stream::iter(config.regions.clone())
+            .map(|region| Box::pin(region_events(region, config, stats, &account, &scanners)))
+            .flatten_unordered(config.max_concurrent_regions);
+        while let Some(event) = events.next().await {
+            yield event?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn no_regions_emit_no_events() {
+        let config = Config {
+            regions: Vec::new(),
+            ..Config::default()
+        };
+        let events: Vec<_> = scan_stream(&config, &ApiStats::default()).collect().await;
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn resources_stream_before_their_vpc_completes() {
+        let (found_tx, found_rx) = mpsc::unbounded_channel();
+        let found = |vpc_id: &str, arn: &str| VpcEvent::ResourceDiscovered {
+            account: "111111111111".to_string(),
+            region: "us-east-1".to_string(),
+            vpc_id: vpc_id.to_string(),
+            resource: ResourceRecord {
+                arn: arn.to_string(),
+                ..Default::default()
+            },
+        };
+        let (vpc_2_sent, tx) = (tokio::sync::Notify::new(), &found_tx);
+        let scans = stream::iter(["vpc-1", "vpc-2"])
+            .map(|vpc_id| {
+                let (vpc_2_sent, found) = (&vpc_2_sent, &found);
+                async move {
+                    if vpc_id == "vpc-1" {
+                        tx.send(found(vpc_id, "i-1")).unwrap();
+                        // vpc-1 finishes after vpc-2 reported its resource
+                        vpc_2_sent.notified().await;
+                        tx.send(found(vpc_id, "i-2")).unwrap();
+                    } else {
+                        tx.send(found(vpc_id, "i-3")).unwrap();
+                        vpc_2_sent.notify_one();
+                    }
+                    let warnings = vec![format!("{vpc_id} warned")];
+                    ("111111111111".to_string(), vpc_id.to_string(), Ok((VpcSummary::default(), warnings)))
+                }
+            })
+            .buffered(2);
+        let events: Vec<VpcEvent> = vpc_events("us-east-1", scans, found_rx)
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+        let order: Vec<String> = events
+            .iter()
+            .map(|e| match e {
+                VpcEvent::ResourceDiscovered { resource, .. } => resource.arn.clone(),
+                VpcEvent::Warning(w) => w.clone(),
+                VpcEvent::VpcScanned { vpc_id, summary, .. } => format!("{vpc_id} done {}", summary.account),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            order,
+            [
+                "i-1",
+                "i-3",
+                "i-2",
+                "vpc-1 warned",
+                "vpc-1 done 111111111111",
+                "vpc-2 warned",
+                "vpc-2 done 111111111111"
+            ]
+        );
+    }
+}
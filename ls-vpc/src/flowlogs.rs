@@ -0,0 +1,215 @@
+//! flowlogs.rs
+//! ---------------------------------------------------------------------------
+//! `--flow-logs`: where each VPC's flow logs are delivered (CloudWatch Logs
+//! group, S3 bucket or Firehose stream), which traffic they capture and how
+//! often records are aggregated.  Log groups are looked up in CloudWatch
+//! Logs for their retention, since a group without one keeps (and bills for)
+//! flow records forever.
+
+use crate::VpcSummary;
+use crate::api_stats::CallCounter;
+use crate::cli::OutputFormat;
+use crate::utils::render_table;
+use aws_sdk_cloudwatchlogs as logs;
+use aws_sdk_ec2 as ec2;
+use aws_types::SdkConfig;
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use eyre::Result;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// `LogDestinationType` of flow logs delivering to CloudWatch Logs.
+pub const CLOUDWATCH_LOGS: &str = "cloud-watch-logs";
+
+/// Retention of a flow log's CloudWatch Logs group.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Retention {
+    /// Not a log group, or the group could not be read
+    #[default]
+    Unknown,
+    /// No retention policy: events never expire
+    Never,
+    Days(i32),
+}
+
+impl fmt::Display for Retention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Retention::Unknown => write!(f, "-"),
+            Retention::Never => write!(f, "never"),
+            Retention::Days(days) => write!(f, "{days}d"),
+        }
+    }
+}
+
+/// A flow log attached to a VPC.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlowLog {
+    pub id: String,
+    /// `cloud-watch-logs`, `s3` or `kinesis-data-firehose`
+    pub destination_type: String,
+    /// Log group name for CloudWatch Logs, otherwise the destination ARN
+    pub destination: String,
+    /// `ACCEPT`, `REJECT` or `ALL`
+    pub traffic_type: String,
+    /// Maximum aggregation interval in seconds (60 or 600)
+    pub aggregation_interval: Option<i32>,
+    /// Retention of the log group (CloudWatch Logs destinations only)
+    pub retention: Retention,
+}
+
+impl FlowLog {
+    /// Delivers to a CloudWatch Logs group that never expires its events.
+    pub fn unretained(&self) -> bool {
+        self.destination_type == CLOUDWATCH_LOGS && self.retention == Retention::Never
+    }
+}
+
+/// List the flow logs attached to a VPC.
+pub async fn list_flow_logs(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<FlowLog>> {
+    let client = ec2::Client::new(conf);
+    calls.record("ec2", "DescribeFlowLogs", conf.region());
+    let resp = client
+        .describe_flow_logs()
+        .filter(
+            ec2::types::Filter::builder()
+                .name("resource-id")
+                .values(vpc_id)
+                .build(),
+        )
+        .send()
+        .await?;
+    Ok(resp
+        .flow_logs()
+        .iter()
+        .map(|fl| {
+            let destination_type = fl
+                .log_destination_type()
+                .map(|t| t.as_str().to_owned())
+                .unwrap_or_else(|| CLOUDWATCH_LOGS.to_owned());
+            let destination = match fl.log_group_name() {
+                Some(group) if destination_type == CLOUDWATCH_LOGS => group.to_owned(),
+                _ => fl.log_destination().unwrap_or_default().to_owned(),
+            };
+            FlowLog {
+                id: fl.flow_log_id().unwrap_or_default().to_owned(),
+                destination_type,
+                destination,
+                traffic_type: fl.traffic_type().map(|t| t.as_str().to_owned()).unwrap_or_default(),
+                aggregation_interval: fl.max_aggregation_interval(),
+                retention: Retention::Unknown,
+            }
+        })
+        .collect())
+}
+
+/// Look up the retention of every CloudWatch Logs group the flow logs
+/// deliver to.
+pub async fn resolve_retention(conf: &SdkConfig, calls: &CallCounter, flow_logs: &mut [FlowLog]) -> Result<()> {
+    let client = logs::Client::new(conf);
+    for fl in flow_logs.iter_mut().filter(|fl| fl.destination_type == CLOUDWATCH_LOGS) {
+        calls.record("logs", "DescribeLogGroups", conf.region());
+        let resp = client
+            .describe_log_groups()
+            .log_group_name_prefix(&fl.destination)
+            .send()
+            .await?;
+        let group = resp
+            .log_groups()
+            .iter()
+            .find(|g| g.log_group_name() == Some(fl.destination.as_str()));
+        fl.retention = match group {
+            Some(group) => group.retention_in_days().map_or(Retention::Never, Retention::Days),
+            None => Retention::Unknown,
+        };
+    }
+    Ok(())
+}
+
+/// Render every VPC's flow logs, one row each; VPCs without any get a
+/// single `none` row.
+pub fn format_flow_logs_table(vpcs: &BTreeMap<(String, String, String), VpcSummary>, format: OutputFormat) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec![
+        "ACCOUNT",
+        "REGION",
+        "VPC-ID",
+        "FLOW-LOG",
+        "DESTINATION-TYPE",
+        "DESTINATION",
+        "TRAFFIC",
+        "INTERVAL",
+        "RETENTION",
+    ]);
+
+    for ((account, region, vpc_id), s) in vpcs {
+        if s.flow_log_details.is_empty() {
+            table.add_row(vec![account.as_str(), region.as_str(), vpc_id.as_str(), "none", "-", "-", "-", "-", "-"]);
+        }
+        for fl in &s.flow_log_details {
+            table.add_row(vec![
+                account.clone(),
+                region.clone(),
+                vpc_id.clone(),
+                fl.id.clone(),
+                fl.destination_type.clone(),
+                fl.destination.clone(),
+                fl.traffic_type.clone(),
+                fl.aggregation_interval.map(|i| format!("{i}s")).unwrap_or_else(|| "-".to_owned()),
+                fl.retention.to_string(),
+            ]);
+        }
+    }
+    render_table(&table, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cloudwatch(retention: Retention) -> FlowLog {
+        FlowLog {
+            id: "fl-0123456789abcdef0".to_string(),
+            destination_type: CLOUDWATCH_LOGS.to_string(),
+            destination: "/vpc/flow-logs".to_string(),
+            traffic_type: "ALL".to_string(),
+            aggregation_interval: Some(600),
+            retention,
+        }
+    }
+
+    #[test]
+    fn unretained_only_for_log_groups_without_policy() {
+        assert!(cloudwatch(Retention::Never).unretained());
+        assert!(!cloudwatch(Retention::Days(30)).unretained());
+        assert!(!cloudwatch(Retention::Unknown).unretained());
+        let s3 = FlowLog {
+            destination_type: "s3".to_string(),
+            ..cloudwatch(Retention::Never)
+        };
+        assert!(!s3.unretained());
+    }
+
+    #[test]
+    fn format_flow_logs_table_lists_destinations() {
+        let mut vpcs = BTreeMap::new();
+        vpcs.insert(
+            ("111111111111".to_string(), "us-east-1".to_string(), "vpc-a".to_string()),
+            VpcSummary {
+                flow_log_details: vec![cloudwatch(Retention::Days(14))],
+                ..Default::default()
+            },
+        );
+        vpcs.insert(("111111111111".to_string(), "us-east-1".to_string(), "vpc-b".to_string()), VpcSummary::default());
+        let table = format_flow_logs_table(&vpcs, OutputFormat::Tsv);
+        let row_a = table.lines().find(|l| l.contains("vpc-a")).unwrap();
+        assert_eq!(
+            row_a,
+            "111111111111\tus-east-1\tvpc-a\tfl-0123456789abcdef0\tcloud-watch-logs\t/vpc/flow-logs\tALL\t600s\t14d"
+        );
+        let row_b = table.lines().find(|l| l.contains("vpc-b")).unwrap();
+        assert!(row_b.contains("\tnone\t"));
+    }
+}
@@ -0,0 +1,103 @@
+//! identity.rs
+//! ---------------------------------------------------------------------------
+//! Caller identity shared by ls-vpc and ls-rds.  `--whoami` prints it and
+//! exits; `--verbose` prints it as a banner before the scan starts, so a
+//! long scan can be checked against the intended account up front.
+
+use aws_sdk_sts as sts;
+use aws_types::SdkConfig;
+use eyre::Result;
+use std::env;
+
+/// Who the base credentials resolve to
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CallerIdentity {
+    pub account: String,
+    pub arn: String,
+    /// `aws`, `aws-cn`, `aws-us-gov` …
+    pub partition: String,
+    /// Where the credentials came from: a profile name or `environment`
+    pub source: String,
+}
+
+/// Partition of an ARN (`arn:<partition>:…`)
+pub fn partition_of(arn: &str) -> &str {
+    arn.split(':').nth(1).unwrap_or_default()
+}
+
+/// Account of an ARN (`arn:aws:iam::<account>:…`)
+pub fn account_of(arn: &str) -> &str {
+    arn.split(':').nth(4).unwrap_or_default()
+}
+
+/// Credential source as the SDK picks it: static keys in the environment
+/// win over `AWS_PROFILE`, which falls back to `default`
+pub fn credential_source(access_key: Option<&str>, profile: Option<&str>) -> String {
+    match (access_key, profile) {
+        (Some(key), _) if !key.is_empty() => "environment".to_owned(),
+        (_, Some(profile)) if !profile.is_empty() => profile.to_owned(),
+        _ => "default".to_owned(),
+    }
+}
+
+/// Resolve the identity behind `conf` with STS GetCallerIdentity
+pub async fn caller_identity(conf: &SdkConfig) -> Result<CallerIdentity> {
+    let resp = sts::Client::new(conf).get_caller_identity().send().await?;
+    let arn = resp.arn().unwrap_or_default().to_owned();
+    Ok(CallerIdentity {
+        account: resp.account().unwrap_or_default().to_owned(),
+        partition: partition_of(&arn).to_owned(),
+        arn,
+        source: credential_source(
+            env::var("AWS_ACCESS_KEY_ID").ok().as_deref(),
+            env::var("AWS_PROFILE").ok().as_deref(),
+        ),
+    })
+}
+
+/// One `Label: value` line per identity field
+pub fn format_identity(id: &CallerIdentity) -> String {
+    format!(
+        "Account:   {}\nARN:       {}\nPartition: {}\nSource:    {}\n",
+        id.account, id.arn, id.partition, id.source
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_of_reads_second_field() {
+        assert_eq!(partition_of("arn:aws:iam::123456789012:user/alice"), "aws");
+        assert_eq!(partition_of("arn:aws-us-gov:sts::123456789012:assumed-role/R/s"), "aws-us-gov");
+        assert_eq!(partition_of(""), "");
+    }
+
+    #[test]
+    fn account_of_reads_fifth_field() {
+        assert_eq!(account_of("arn:aws:iam::123456789012:role/Audit"), "123456789012");
+        assert_eq!(account_of("not-an-arn"), "");
+    }
+
+    #[test]
+    fn credential_source_prefers_environment_keys() {
+        assert_eq!(credential_source(Some("AKIAEXAMPLE"), Some("prod")), "environment");
+        assert_eq!(credential_source(None, Some("prod")), "prod");
+        assert_eq!(credential_source(Some(""), None), "default");
+    }
+
+    #[test]
+    fn format_identity_lists_every_field() {
+        let id = CallerIdentity {
+            account: "123456789012".to_string(),
+            arn: "arn:aws:iam::123456789012:user/alice".to_string(),
+            partition: "aws".to_string(),
+            source: "prod".to_string(),
+        };
+        let text = format_identity(&id);
+        assert!(text.contains("Account:   123456789012\n"));
+        assert!(text.ends_with("Source:    prod\n"));
+        assert_eq!(text.lines().count(), 4);
+    }
+}
@@ -0,0 +1,411 @@
+//! issues.rs
+//! ---------------------------------------------------------------------------
+//! Findings surfaced by `--issues`.  Each check is a pure function over data
+//! already collected into a [`VpcSummary`](crate::VpcSummary).
+
+use crate::VpcSummary;
+use crate::cli::OutputFormat;
+use crate::db_subnet_groups::{DbSubnetGroup, MIN_AZS};
+use crate::flowlogs::FlowLog;
+use crate::nat::TRAFFIC_WINDOW_DAYS;
+use crate::peering::unrouted_cidrs;
+use crate::routes::RouteTable;
+use crate::subnets::Subnet;
+use crate::utils::render_table;
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use std::collections::BTreeMap;
+
+/// A single finding against a VPC.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Issue {
+    /// Short machine-friendly category, e.g. `blackhole-route`
+    pub kind: &'static str,
+    /// Resource the finding is about
+    pub resource: String,
+    pub detail: String,
+}
+
+/// Flag routes whose target has been deleted (peering, TGW, NAT, ENI …).
+pub fn blackhole_routes(tables: &[RouteTable]) -> Vec<Issue> {
+    tables
+        .iter()
+        .flat_map(|rt| {
+            rt.routes.iter().filter(|r| r.blackhole).map(move |r| Issue {
+                kind: "blackhole-route",
+                resource: rt.id.clone(),
+                detail: format!("{} -> {} is blackholed", r.destination, r.target),
+            })
+        })
+        .collect()
+}
+
+/// Flag subnets whose used-IP percentage is at or above `threshold`.
+pub fn exhausted_subnets(subnets: &[Subnet], threshold: u8) -> Vec<Issue> {
+    subnets
+        .iter()
+        .filter(|s| s.used_pct() >= f64::from(threshold))
+        .map(|s| Issue {
+            kind: "subnet-ip-exhaustion",
+            resource: s.id.clone(),
+            detail: format!(
+                "{:.1}% of {} usable IPs in use (threshold {}%)",
+                s.used_pct(),
+                s.usable_ips(),
+                threshold
+            ),
+        })
+        .collect()
+}
+
+/// Flag DB subnet groups whose subnets cover fewer AZs than Multi-AZ
+/// failover needs.
+pub fn single_az_db_subnet_groups(groups: &[DbSubnetGroup]) -> Vec<Issue> {
+    groups
+        .iter()
+        .filter(|g| !g.multi_az_capable())
+        .map(|g| Issue {
+            kind: "db-subnet-group-az-coverage",
+            resource: g.arn.clone(),
+            detail: format!(
+                "DB subnet group {} spans {} AZ(s) ({}); Multi-AZ needs at least {}",
+                g.name,
+                g.azs.len(),
+                if g.azs.is_empty() { "-".to_owned() } else { g.azs.join(",") },
+                MIN_AZS
+            ),
+        })
+        .collect()
+}
+
+/// Flag flow logs delivering to CloudWatch Logs groups with no retention
+/// policy, which keep every record forever.
+pub fn unretained_flow_logs(flow_logs: &[FlowLog]) -> Vec<Issue> {
+    flow_logs
+        .iter()
+        .filter(|fl| fl.unretained())
+        .map(|fl| Issue {
+            kind: "flow-log-no-retention",
+            resource: fl.id.clone(),
+            detail: format!("log group {} has no retention policy; flow records never expire", fl.destination),
+        })
+        .collect()
+}
+
+/// Flag VPCs whose S3 / DynamoDB traffic goes through NAT gateways because
+/// the (free) gateway endpoint is missing.  The S3 finding includes the NAT
+/// data-processing cost over the traffic window as an upper bound on what
+/// the endpoint would save.
+pub fn missing_gateway_endpoints(s: &VpcSummary) -> Vec<Issue> {
+    if s.nat_gateways.is_empty() {
+        return Vec::new();
+    }
+    let nats: Vec<&str> = s.nat_gateways.iter().map(|n| n.id.as_str()).collect();
+    let mut found = Vec::new();
+    for service in ["s3", "dynamodb"] {
+        let suffix = format!(".{service}");
+        if s.endpoints.iter().any(|e| e.kind == "Gateway" && e.service.ends_with(&suffix)) {
+            continue;
+        }
+        let mut detail = format!("no {service} gateway endpoint; {service} traffic goes through {}", nats.join(","));
+        let cost = s.nat_gateways.iter().filter_map(|n| n.processing_cost()).reduce(|a, b| a + b);
+        if let (Some(cost), "s3") = (cost, service) {
+            detail.push_str(&format!(
+                " (up to ${cost:.2} of NAT data processing avoidable over {TRAFFIC_WINDOW_DAYS}d)"
+            ));
+        }
+        found.push(Issue {
+            kind: "missing-gateway-endpoint",
+            resource: service.to_owned(),
+            detail,
+        });
+    }
+    found
+}
+
+/// Check every active peering from each scanned VPC's side: the VPC must
+/// route the peer's CIDRs through the peering connection.  Peers that were
+/// scanned too are checked the same way, so a one-way setup produces a single
+/// finding against the side that is missing its routes.
+pub fn asymmetric_peerings(
+    vpcs: &BTreeMap<(String, String, String), VpcSummary>,
+) -> Vec<((String, String, String), Issue)> {
+    let mut found = Vec::new();
+    for ((account, region, vpc_id), s) in vpcs {
+        for pc in &s.peerings {
+            let peer = pc.other_side(vpc_id);
+            let missing = unrouted_cidrs(&s.route_tables, &pc.id, &peer.cidrs);
+            if missing.is_empty() {
+                continue;
+            }
+            let here = pc.other_side(&peer.vpc_id);
+            let back = match vpcs.get(&(peer.owner.clone(), peer.region.clone(), peer.vpc_id.clone())) {
+                Some(p) if unrouted_cidrs(&p.route_tables, &pc.id, &here.cidrs).is_empty() => "peer routes back",
+                Some(_) => "peer has no route back either",
+                None => "peer side not scanned",
+            };
+            found.push((
+                (account.clone(), region.clone(), vpc_id.clone()),
+                Issue {
+                    kind: "peering-asymmetry",
+                    resource: pc.id.clone(),
+                    detail: format!(
+                        "{} has no route to {} ({}) via {}; {}",
+                        vpc_id,
+                        peer.vpc_id,
+                        missing.join(","),
+                        pc.id,
+                        back
+                    ),
+                },
+            ));
+        }
+    }
+    found
+}
+
+/// Render every VPC's issues as a single table.
+pub fn format_issues_table(vpcs: &BTreeMap<(String, String, String), VpcSummary>, format: OutputFormat) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec!["ACCOUNT", "REGION", "VPC-ID", "ISSUE", "RESOURCE", "DETAIL"]);
+    for ((account, region, vpc_id), s) in vpcs {
+        for i in &s.issues {
+            table.add_row(vec![
+                account.clone(),
+                region.clone(),
+                vpc_id.clone(),
+                i.kind.to_owned(),
+                i.resource.clone(),
+                i.detail.clone(),
+            ]);
+        }
+    }
+    render_table(&table, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::Route;
+
+    fn table_with(routes: Vec<Route>) -> RouteTable {
+        RouteTable {
+            id: "rtb-123".to_string(),
+            routes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn blackhole_routes_flags_only_blackholed() {
+        let tables = vec![table_with(vec![
+            Route {
+                destination: "10.0.0.0/16".to_string(),
+                target: "local".to_string(),
+                blackhole: false,
+            },
+            Route {
+                destination: "10.1.0.0/16".to_string(),
+                target: "pcx-0dead".to_string(),
+                blackhole: true,
+            },
+        ])];
+        let issues = blackhole_routes(&tables);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, "blackhole-route");
+        assert_eq!(issues[0].resource, "rtb-123");
+        assert!(issues[0].detail.contains("pcx-0dead"));
+    }
+
+    #[test]
+    fn blackhole_routes_empty_when_healthy() {
+        let tables = vec![table_with(vec![Route {
+            destination: "0.0.0.0/0".to_string(),
+            target: "igw-1".to_string(),
+            blackhole: false,
+        }])];
+        assert!(blackhole_routes(&tables).is_empty());
+    }
+
+    #[test]
+    fn single_az_db_subnet_groups_flags_narrow_groups() {
+        let group = |name: &str, azs: &[&str]| DbSubnetGroup {
+            name: name.to_string(),
+            arn: format!("arn:aws:rds:us-east-1:123456789012:subgrp:{name}"),
+            azs: azs.iter().map(|az| az.to_string()).collect(),
+            ..Default::default()
+        };
+        let groups = [group("wide", &["us-east-1a", "us-east-1b"]), group("narrow", &["us-east-1a"])];
+        let issues = single_az_db_subnet_groups(&groups);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, "db-subnet-group-az-coverage");
+        assert!(issues[0].resource.ends_with(":subgrp:narrow"));
+        assert!(issues[0].detail.contains("spans 1 AZ(s) (us-east-1a)"));
+    }
+
+    #[test]
+    fn unretained_flow_logs_flags_log_groups_without_retention() {
+        use crate::flowlogs::{CLOUDWATCH_LOGS, Retention};
+        let flow_log = |id: &str, retention| FlowLog {
+            id: id.to_string(),
+            destination_type: CLOUDWATCH_LOGS.to_string(),
+            destination: "/vpc/flow-logs".to_string(),
+            retention,
+            ..Default::default()
+        };
+        let flow_logs = [flow_log("fl-kept", Retention::Days(90)), flow_log("fl-forever", Retention::Never)];
+        let issues = unretained_flow_logs(&flow_logs);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, "flow-log-no-retention");
+        assert_eq!(issues[0].resource, "fl-forever");
+        assert!(issues[0].detail.contains("/vpc/flow-logs"));
+    }
+
+    #[test]
+    fn exhausted_subnets_respects_threshold() {
+        let subnets = vec![
+            Subnet {
+                id: "subnet-full".to_string(),
+                cidr: "10.0.0.0/28".to_string(),
+                available_ips: 1,
+                ..Default::default()
+            },
+            Subnet {
+                id: "subnet-empty".to_string(),
+                cidr: "10.0.1.0/24".to_string(),
+                available_ips: 250,
+                ..Default::default()
+            },
+        ];
+        let issues = exhausted_subnets(&subnets, 90);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].resource, "subnet-full");
+        assert!(issues[0].detail.contains("threshold 90%"));
+        assert_eq!(exhausted_subnets(&subnets, 95).len(), 0);
+    }
+
+    fn peered(vpc: &str, routes: Vec<Route>) -> ((String, String, String), VpcSummary) {
+        use crate::peering::{Peering, PeeringSide};
+        let side = |id: &str, cidr: &str| PeeringSide {
+            vpc_id: id.to_string(),
+            region: "us-east-1".to_string(),
+            owner: "111111111111".to_string(),
+            cidrs: vec![cidr.to_string()],
+        };
+        (
+            ("111111111111".to_string(), "us-east-1".to_string(), vpc.to_string()),
+            VpcSummary {
+                peerings: vec![Peering {
+                    id: "pcx-1".to_string(),
+                    requester: side("vpc-a", "10.0.0.0/16"),
+                    accepter: side("vpc-b", "10.1.0.0/16"),
+                }],
+                route_tables: vec![table_with(routes)],
+                ..Default::default()
+            },
+        )
+    }
+
+    fn via_pcx(destination: &str) -> Vec<Route> {
+        vec![Route {
+            destination: destination.to_string(),
+            target: "pcx-1".to_string(),
+            blackhole: false,
+        }]
+    }
+
+    #[test]
+    fn asymmetric_peerings_flags_missing_side() {
+        let vpcs = BTreeMap::from([
+            peered("vpc-a", via_pcx("10.1.0.0/16")),
+            peered("vpc-b", vec![]),
+        ]);
+        let found = asymmetric_peerings(&vpcs);
+        assert_eq!(found.len(), 1);
+        let ((_, _, vpc), issue) = &found[0];
+        assert_eq!(vpc, "vpc-b");
+        assert_eq!(issue.kind, "peering-asymmetry");
+        assert_eq!(issue.resource, "pcx-1");
+        assert!(issue.detail.contains("10.0.0.0/16"));
+        assert!(issue.detail.contains("peer routes back"));
+    }
+
+    #[test]
+    fn asymmetric_peerings_quiet_when_symmetric() {
+        let vpcs = BTreeMap::from([
+            peered("vpc-a", via_pcx("10.1.0.0/16")),
+            peered("vpc-b", via_pcx("10.0.0.0/8")),
+        ]);
+        assert!(asymmetric_peerings(&vpcs).is_empty());
+    }
+
+    #[test]
+    fn asymmetric_peerings_notes_unscanned_peer() {
+        let vpcs = BTreeMap::from([peered("vpc-a", vec![])]);
+        let found = asymmetric_peerings(&vpcs);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].1.detail.contains("peer side not scanned"));
+    }
+
+    fn behind_nat(endpoints: &[(&str, &str)]) -> VpcSummary {
+        use crate::endpoints::VpcEndpoint;
+        use crate::nat::NatGateway;
+        VpcSummary {
+            nat_gateways: vec![NatGateway {
+                id: "nat-1".to_string(),
+                processed_bytes: Some(200.0 * 1024.0 * 1024.0 * 1024.0),
+            }],
+            endpoints: endpoints
+                .iter()
+                .map(|(service, kind)| VpcEndpoint {
+                    service: format!("com.amazonaws.us-east-1.{service}"),
+                    kind: kind.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn missing_gateway_endpoints_flags_both_services_with_s3_estimate() {
+        let found = missing_gateway_endpoints(&behind_nat(&[]));
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].kind, "missing-gateway-endpoint");
+        assert_eq!(found[0].resource, "s3");
+        assert!(found[0].detail.contains("nat-1"));
+        assert!(found[0].detail.contains("$9.00"));
+        assert_eq!(found[1].resource, "dynamodb");
+        assert!(!found[1].detail.contains('$'));
+    }
+
+    #[test]
+    fn missing_gateway_endpoints_ignores_covered_services() {
+        let found = missing_gateway_endpoints(&behind_nat(&[("s3", "Gateway"), ("dynamodb", "Interface")]));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].resource, "dynamodb");
+        assert!(missing_gateway_endpoints(&behind_nat(&[("s3", "Gateway"), ("dynamodb", "Gateway")])).is_empty());
+        assert!(missing_gateway_endpoints(&VpcSummary::default()).is_empty());
+    }
+
+    #[test]
+    fn format_issues_table_lists_each_issue() {
+        let mut vpcs = BTreeMap::new();
+        vpcs.insert(
+            ("111111111111".to_string(), "us-west-2".to_string(), "vpc-123".to_string()),
+            VpcSummary {
+                issues: vec![Issue {
+                    kind: "blackhole-route",
+                    resource: "rtb-123".to_string(),
+                    detail: "10.1.0.0/16 -> pcx-0dead is blackholed".to_string(),
+                }],
+                ..Default::default()
+            },
+        );
+        let table = format_issues_table(&vpcs, OutputFormat::Table);
+        assert!(table.contains("ISSUE"));
+        assert!(table.contains("blackhole-route"));
+        assert!(table.contains("vpc-123"));
+    }
+}
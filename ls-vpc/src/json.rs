@@ -0,0 +1,182 @@
+//! json.rs
+//! ---------------------------------------------------------------------------
+//! `--format json`: the scan as one JSON document following the versioned
+//! schema in `schema/ls-vpc.schema.json` (printed by `--schema`).  The
+//! document is built from dedicated structs rather than `VpcSummary` so the
+//! internals can change without breaking consumers; adding a field keeps
+//! `SCHEMA_VERSION`, removing or changing one bumps it.
+
+use crate::{ScanError, ScanResult};
+use eyre::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Version of the JSON output, embedded as `schema_version`
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema document describing the output
+pub const SCHEMA: &str = include_str!("../schema/ls-vpc.schema.json");
+
+#[derive(Debug, Serialize)]
+pub struct Document<'a> {
+    pub schema_version: u32,
+    pub tool: &'static str,
+    pub regions_scanned: usize,
+    pub vpcs: Vec<Vpc<'a>>,
+    pub warnings: &'a [String],
+    /// Regions that could not be scanned
+    pub errors: &'a [ScanError],
+}
+
+#[derive(Debug, Serialize)]
+pub struct Vpc<'a> {
+    pub account: &'a str,
+    pub region: &'a str,
+    pub vpc_id: &'a str,
+    pub name: Option<&'a str>,
+    pub public: bool,
+    pub cidrs: &'a [String],
+    pub peers: &'a [String],
+    pub resources: Vec<Resource<'a>>,
+    pub resource_count: usize,
+    pub issues: Vec<Issue<'a>>,
+    pub spend_usd: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Resource<'a> {
+    #[serde(rename = "type")]
+    pub rtype: &'a str,
+    pub name: &'a str,
+    pub identifier: &'a str,
+    pub created: Option<i64>,
+    pub stack: Option<&'a str>,
+    pub tags: &'a BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Issue<'a> {
+    pub kind: &'a str,
+    pub resource: &'a str,
+    pub detail: &'a str,
+}
+
+/// Build the versioned document for a scan
+pub fn document(result: &ScanResult) -> Document<'_> {
+    let vpcs = result
+        .vpcs
+        .iter()
+        .map(|((account, region, vpc_id), s)| Vpc {
+            account,
+            region,
+            vpc_id,
+            name: s.name.as_deref(),
+            public: s.public,
+            cidrs: &s.cidrs,
+            peers: &s.peers,
+            resources: s
+                .resources
+                .iter()
+                .map(|r| Resource {
+                    rtype: r.rtype,
+                    name: &r.name,
+                    identifier: &r.arn,
+                    created: r.created,
+                    stack: r.stack.as_deref(),
+                    tags: &r.tags,
+                })
+                .collect(),
+            resource_count: s.resource_count,
+            issues: s
+                .issues
+                .iter()
+                .map(|i| Issue {
+                    kind: i.kind,
+                    resource: &i.resource,
+                    detail: &i.detail,
+                })
+                .collect(),
+            spend_usd: s.spend,
+        })
+        .collect();
+    Document {
+        schema_version: SCHEMA_VERSION,
+        tool: "ls-vpc",
+        regions_scanned: result.regions_scanned,
+        vpcs,
+        warnings: &result.warnings,
+        errors: &result.errors,
+    }
+}
+
+/// Render a scan as pretty-printed JSON with a trailing newline
+pub fn to_json(result: &ScanResult) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&document(result))? + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ResourceRecord, ScanErrorKind, VpcSummary};
+    use serde_json::Value;
+
+    #[test]
+    fn schema_matches_version() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        assert_eq!(schema["properties"]["schema_version"]["const"], SCHEMA_VERSION);
+        assert_eq!(schema["properties"]["tool"]["const"], "ls-vpc");
+    }
+
+    #[test]
+    fn to_json_emits_required_fields() {
+        let mut result = ScanResult {
+            regions_scanned: 1,
+            errors: vec![ScanError {
+                region: "ap-east-1".to_string(),
+                operation: Some("DescribeVpcs".to_string()),
+                kind: ScanErrorKind::NotOptedIn,
+                code: Some("OptInRequired".to_string()),
+                message: "cannot list VPCs, region skipped".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        result.vpcs.insert(
+            ("111111111111".to_string(), "us-east-1".to_string(), "vpc-123".to_string()),
+            VpcSummary {
+                name: Some("prod".to_string()),
+                public: true,
+                cidrs: vec!["10.0.0.0/16".to_string()],
+                resources: vec![ResourceRecord {
+                    arn: "i-0123456789abcdef0".to_string(),
+                    rtype: "ec2.instance",
+                    name: "web".to_string(),
+                    created: Some(1_700_000_000),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+        let doc: Value = serde_json::from_str(&to_json(&result).unwrap()).unwrap();
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        for key in schema["required"].as_array().unwrap() {
+            assert!(doc.get(key.as_str().unwrap()).is_some(), "missing {key}");
+        }
+        let vpc = &doc["vpcs"][0];
+        for key in schema["$defs"]["vpc"]["required"].as_array().unwrap() {
+            assert!(vpc.get(key.as_str().unwrap()).is_some(), "missing {key}");
+        }
+        let error = &doc["errors"][0];
+        for key in schema["$defs"]["error"]["required"].as_array().unwrap() {
+            assert!(error.get(key.as_str().unwrap()).is_some(), "missing {key}");
+        }
+        assert_eq!(error["kind"], "not_opted_in");
+        assert!(error["account_id"].is_null());
+        assert_eq!(doc["schema_version"], SCHEMA_VERSION);
+        assert_eq!(vpc["account"], "111111111111");
+        assert_eq!(vpc["vpc_id"], "vpc-123");
+        assert_eq!(vpc["resources"][0]["type"], "ec2.instance");
+        assert_eq!(vpc["resources"][0]["created"], 1_700_000_000);
+        assert!(vpc["resources"][0]["stack"].is_null());
+    }
+}
@@ -3,80 +3,193 @@
 //! Core functionality for listing VPCs and their resources.
 //! This module separates business logic from the CLI shell.
 
+pub mod api_stats;
 pub mod cli;
 pub mod config;
+pub mod db_subnet_groups;
+pub mod drawio;
+pub mod duplicates;
+pub mod egress;
+pub mod email;
+pub mod endpoint_services;
+pub mod endpoints;
+pub mod errors;
+pub mod events;
+pub mod flowlogs;
+pub mod identity;
+pub mod issues;
+pub mod json;
+pub mod nat;
+pub mod nau;
+pub mod peering;
+pub mod pick;
+pub mod plan;
+pub mod policy;
+pub mod redact;
+pub mod routes;
 pub mod scanner;
+pub mod settings;
+pub mod sg;
+pub mod spend;
+pub mod subnets;
+pub mod terraform;
+pub mod tui;
 pub mod utils;
 
-pub use cli::Cli;
+pub use api_stats::{ApiCall, ApiStats, CallCounter};
+pub use cli::{Cli, EmailFormat, OutputFormat, SortBy};
 pub use config::Config;
-pub use scanner::{Ec2Scanner, ElbScanner, RdsScanner, ResourceRecord, ServiceScanner};
-pub use utils::{get_or_create_log_dir, terminal_width, wrap_identifier};
+pub use db_subnet_groups::DbSubnetGroup;
+pub use duplicates::{find_duplicates, format_duplicates_table};
+pub use egress::{Egress, format_egress_table};
+pub use endpoint_services::{EndpointService, format_endpoint_services_table};
+pub use endpoints::{VpcEndpoint, format_cost_table};
+pub use errors::{ScanError, ScanErrorKind, error_json_lines, format_scan_error};
+pub use events::{VpcEvent, scan_stream};
+pub use flowlogs::{FlowLog, format_flow_logs_table};
+pub use identity::{CallerIdentity, format_identity};
+pub use issues::{Issue, format_issues_table};
+pub use nat::NatGateway;
+pub use nau::{NauUsage, format_nau_table};
+pub use peering::Peering;
+pub use policy::Policy;
+pub use redact::Redactor;
+pub use routes::RouteTable;
+pub use subnets::Subnet;
+pub use sg::{SecurityGroup, format_sg_dot, format_sg_table, format_unused_sgs_table};
+pub use terraform::format_tf_imports;
+pub use scanner::{
+    Ec2Scanner, ElbScanner, RdsScanner, ResourceRecord, ServiceScanner, resolve_stacks, resolve_tags,
+    service_scanners, sort_by_age, tag_values,
+};
+pub use spend::format_spend_table;
+pub use utils::{format_age, get_or_create_log_dir, render_table, terminal_width, unix_now, wrap_identifier};
 
-use aws_config::BehaviorVersion;
+use api_stats::CountingProvider;
+use aws_config::{BehaviorVersion, ConfigLoader};
+use aws_config::retry::RetryConfig;
+use aws_config::sts::AssumeRoleProvider;
+use aws_config::timeout::TimeoutConfig;
 use aws_sdk_docdb::error::ProvideErrorMetadata;
 use aws_sdk_ec2 as ec2;
 use aws_types::{region::Region, SdkConfig};
 use comfy_table::presets::{ASCII_FULL, ASCII_FULL_CONDENSED};
 use comfy_table::Table;
 use eyre::Result;
-use log::trace;
+use futures::stream::StreamExt;
+use log::{trace, warn};
 use std::collections::BTreeMap;
+use std::pin::pin;
 
 /// Summary information about a VPC
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct VpcSummary {
+    /// Account that owns the VPC
+    pub account: String,
     pub name: Option<String>,
     pub public: bool,
     pub cidrs: Vec<String>,
     pub peers: Vec<String>,
     pub resources: Vec<ResourceRecord>,
+    /// Resources in the VPC: the scanners' records in detail mode, network
+    /// interfaces (every instance, load balancer, database and endpoint has
+    /// one or more) in summary mode
+    pub resource_count: usize,
+    /// Security groups (only collected for `--sg-graph`)
+    pub security_groups: Vec<SecurityGroup>,
+    /// Route tables (only collected for `--issues`, `--egress` and
+    /// `--emit-tf-imports`)
+    pub route_tables: Vec<RouteTable>,
+    /// Active peering connections (only collected for `--issues`)
+    pub peerings: Vec<Peering>,
+    /// Subnets (only collected for `--ip-threshold`, `--egress`,
+    /// `--format drawio` and `--emit-tf-imports`)
+    pub subnets: Vec<Subnet>,
+    /// VPC endpoints (only collected for `--with-cost` and `--issues`)
+    pub endpoints: Vec<VpcEndpoint>,
+    /// DB subnet groups placed in the VPC (only collected for `--issues`)
+    pub db_subnet_groups: Vec<DbSubnetGroup>,
+    /// Available NAT gateways with their traffic (only collected for `--issues`)
+    pub nat_gateways: Vec<NatGateway>,
+    /// Network Address Usage (only collected for `--nau`)
+    pub nau: NauUsage,
+    /// Subnets per outbound internet path (only collected for `--egress`)
+    pub egress: BTreeMap<Egress, usize>,
+    /// A flow log is attached (only checked for `--flow-logs`, `--issues` and
+    /// when `--fail-on` uses `flow_logs`)
+    pub flow_logs: bool,
+    /// Flow log destinations (only collected for `--flow-logs` and `--issues`)
+    pub flow_log_details: Vec<FlowLog>,
+    /// PrivateLink services offered from the VPC (only collected for
+    /// `--endpoint-services`)
+    pub endpoint_services: Vec<EndpointService>,
+    /// Findings reported by `--issues`
+    pub issues: Vec<Issue>,
+    /// NAT and data-transfer spend (USD) over the `--with-spend` window
+    /// (`None` when no spend carries the VPC's `--spend-tag`)
+    pub spend: Option<f64>,
 }
 
 /// Result of a VPC scan operation
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ScanResult {
-    pub vpcs: BTreeMap<(String, String), VpcSummary>,
+    /// Keyed by (account, region, VPC-ID)
+    pub vpcs: BTreeMap<(String, String, String), VpcSummary>,
     pub regions_scanned: usize,
+    /// Non-fatal problems (failed scanners, unreadable metrics …)
+    pub warnings: Vec<String>,
+    /// Regions that could not be scanned
+    pub errors: Vec<ScanError>,
+    /// `--with-spend` spend (USD) per account not attributed to a scanned VPC
+    pub unattributed_spend: BTreeMap<String, f64>,
 }
 
 /// Headers for summary table output
 pub fn summary_headers() -> Vec<&'static str> {
-    vec!["REGION", "VIS", "CIDR", "VPC-ID", "PEERS", "NAME"]
+    vec!["ACCOUNT", "REGION", "VIS", "CIDR", "VPC-ID", "PEERS", "RESOURCES", "NAME"]
 }
 
 /// Create a row for summary table output
 pub fn summary_row(region: &str, vpc_id: &str, s: &VpcSummary) -> Vec<String> {
     let vis = if s.public { "public" } else { "private" };
     vec![
+        s.account.clone(),
         region.to_owned(),
         vis.to_owned(),
         s.cidrs.join(","),
         vpc_id.to_owned(),
         s.peers.join(","),
+        s.resource_count.to_string(),
         s.name.clone().unwrap_or_default(),
     ]
 }
 
+/// VPC-ID, owning account and Name tag of a listed VPC
+fn listed_vpc(v: &ec2::types::Vpc) -> (String, String, Option<String>) {
+    let name = v
+        .tags()
+        .iter()
+        .find(|t| t.key() == Some("Name"))
+        .and_then(|t| t.value())
+        .map(|s| s.to_owned());
+    (
+        v.vpc_id().unwrap_or_default().to_owned(),
+        v.owner_id().unwrap_or_default().to_owned(),
+        name,
+    )
+}
+
 /// List VPCs, optionally filtered by ID
 pub async fn list_filtered_vpcs(
     client: &ec2::Client,
+    calls: &CallCounter,
     filter: &[String],
-) -> Result<Vec<(String, Option<String>)>> {
+) -> Result<Vec<(String, String, Option<String>)>> {
     let mut out = Vec::new();
     for id in filter {
+        calls.record("ec2", "DescribeVpcs", client.config().region());
         match client.describe_vpcs().vpc_ids(id).send().await {
-            Ok(resp) => {
-                for v in resp.vpcs() {
-                    let name = v
-                        .tags()
-                        .iter()
-                        .find(|t| t.key() == Some("Name"))
-                        .and_then(|t| t.value())
-                        .map(|s| s.to_owned());
-                    out.push((v.vpc_id().unwrap_or_default().to_owned(), name));
-                }
-            }
+            Ok(resp) => out.extend(resp.vpcs().iter().map(listed_vpc)),
             Err(e) if e.code() == Some("InvalidVpcID.NotFound") => {
                 trace!("{id} absent in this region – skipped");
             }
@@ -87,37 +200,52 @@ pub async fn list_filtered_vpcs(
 }
 
 /// List all VPCs in the region
-pub async fn list_all_vpcs(client: &ec2::Client) -> Result<Vec<(String, Option<String>)>> {
+pub async fn list_all_vpcs(client: &ec2::Client, calls: &CallCounter) -> Result<Vec<(String, String, Option<String>)>> {
+    calls.record("ec2", "DescribeVpcs", client.config().region());
     Ok(client
         .describe_vpcs()
         .send()
         .await?
         .vpcs()
         .iter()
-        .map(|v| {
-            let name = v
-                .tags()
-                .iter()
-                .find(|t| t.key() == Some("Name"))
-                .and_then(|t| t.value())
-                .map(|s| s.to_owned());
-            (v.vpc_id().unwrap_or_default().to_owned(), name)
-        })
+        .map(listed_vpc)
         .collect())
 }
 
 /// List VPCs with optional filtering
-pub async fn list_vpcs(conf: &SdkConfig, filter: &[String]) -> Result<Vec<(String, Option<String>)>> {
+pub async fn list_vpcs(
+    conf: &SdkConfig,
+    calls: &CallCounter,
+    filter: &[String],
+) -> Result<Vec<(String, String, Option<String>)>> {
     let client = ec2::Client::new(conf);
     if filter.is_empty() {
-        return list_all_vpcs(&client).await;
+        return list_all_vpcs(&client, calls).await;
     }
-    list_filtered_vpcs(&client, filter).await
+    list_filtered_vpcs(&client, calls, filter).await
+}
+
+/// Count the network interfaces in a VPC, a cheap stand-in for its
+/// resources when the scanners don't run
+pub async fn count_network_interfaces(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<usize> {
+    let client = ec2::Client::new(conf);
+    let mut count = 0;
+    let mut pages = client
+        .describe_network_interfaces()
+        .filters(ec2::types::Filter::builder().name("vpc-id").values(vpc_id).build())
+        .into_paginator()
+        .send();
+    while let Some(page) = pages.next().await {
+        calls.record("ec2", "DescribeNetworkInterfaces", conf.region());
+        count += page?.network_interfaces().len();
+    }
+    Ok(count)
 }
 
 /// Check if a VPC has an internet gateway attached (making it "public")
-pub async fn is_public(conf: &SdkConfig, vpc_id: &str) -> Result<bool> {
+pub async fn is_public(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<bool> {
     let client = ec2::Client::new(conf);
+    calls.record("ec2", "DescribeInternetGateways", conf.region());
     Ok(!client
         .describe_internet_gateways()
         .filters(
@@ -132,9 +260,28 @@ pub async fn is_public(conf: &SdkConfig, vpc_id: &str) -> Result<bool> {
         .is_empty())
 }
 
+/// Check if a VPC has at least one flow log attached
+pub async fn has_flow_logs(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<bool> {
+    let client = ec2::Client::new(conf);
+    calls.record("ec2", "DescribeFlowLogs", conf.region());
+    Ok(!client
+        .describe_flow_logs()
+        .filter(
+            ec2::types::Filter::builder()
+                .name("resource-id")
+                .values(vpc_id)
+                .build(),
+        )
+        .send()
+        .await?
+        .flow_logs()
+        .is_empty())
+}
+
 /// Collect peer VPCs using a filter
 pub async fn collect_peers<F>(
     client: &ec2::Client,
+    calls: &CallCounter,
     vpc_id: &str,
     filter_name: &str,
     extract_other: F,
@@ -144,6 +291,7 @@ where
 {
     use aws_sdk_ec2::types::VpcPeeringConnectionStateReasonCode as State;
 
+    calls.record("ec2", "DescribeVpcPeeringConnections", client.config().region());
     let resp = client
         .describe_vpc_peering_connections()
         .filters(
@@ -167,11 +315,12 @@ where
 }
 
 /// Get all peer VPCs for a given VPC
-pub async fn get_peer_vpcs(conf: &SdkConfig, vpc_id: &str) -> Result<Vec<String>> {
+pub async fn get_peer_vpcs(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<String>> {
     let client = ec2::Client::new(conf);
 
     let mut peers = collect_peers(
         &client,
+        calls,
         vpc_id,
         "requester-vpc-info.vpc-id",
         |pc| pc.accepter_vpc_info().and_then(|i| i.vpc_id()),
@@ -181,6 +330,7 @@ pub async fn get_peer_vpcs(conf: &SdkConfig, vpc_id: &str) -> Result<Vec<String>
     peers.extend(
         collect_peers(
             &client,
+            calls,
             vpc_id,
             "accepter-vpc-info.vpc-id",
             |pc| pc.requester_vpc_info().and_then(|i| i.vpc_id()),
@@ -194,10 +344,11 @@ pub async fn get_peer_vpcs(conf: &SdkConfig, vpc_id: &str) -> Result<Vec<String>
 }
 
 /// Get all CIDR blocks for a VPC
-pub async fn get_cidrs(conf: &SdkConfig, vpc_id: &str) -> Result<Vec<String>> {
+pub async fn get_cidrs(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<String>> {
     let client = ec2::Client::new(conf);
     let mut cidrs = Vec::new();
 
+    calls.record("ec2", "DescribeVpcs", conf.region());
     let resp = client.describe_vpcs().vpc_ids(vpc_id).send().await?;
     if let Some(vpc) = resp.vpcs().first() {
         if let Some(primary) = vpc.cidr_block() {
@@ -221,28 +372,74 @@ pub async fn get_cidrs(conf: &SdkConfig, vpc_id: &str) -> Result<Vec<String>> {
 }
 
 /// Format summary table for terminal output
-pub fn format_summary_table(vpcs: &BTreeMap<(String, String), VpcSummary>) -> String {
+pub fn format_summary_table(vpcs: &BTreeMap<(String, String, String), VpcSummary>, format: OutputFormat) -> String {
     let mut table = Table::new();
     table.load_preset(ASCII_FULL_CONDENSED);
     table.set_header(summary_headers());
-    for ((region, vpc_id), s) in vpcs {
+    for ((_, region, vpc_id), s) in vpcs {
         table.add_row(summary_row(region, vpc_id, s));
     }
-    table.to_string()
+    render_table(&table, format)
 }
 
-/// Format detail table for terminal output
-pub fn format_detail_table(vpcs: &BTreeMap<(String, String), VpcSummary>) -> String {
-    use comfy_table::{ColumnConstraint, ContentArrangement, Width};
+/// Detail view as TSV: one line per resource, prefixed with its VPC, so it
+/// can be filtered without the per-VPC table framing.
+fn format_detail_tsv(vpcs: &BTreeMap<(String, String, String), VpcSummary>, config: &Config) -> String {
+    let now = unix_now();
+    let mut header = vec!["ACCOUNT", "REGION", "VPC-ID", "TYPE", "NAME", "AGE", "IDENTIFIER / ARN"];
+    if config.with_stacks {
+        header.push("STACK");
+    }
+    header.extend(config.show_tags.iter().map(String::as_str));
+    let mut output = header.join("\t");
+    output.push('\n');
+    for ((_, region, vpc_id), s) in vpcs {
+        for r in &s.resources {
+            let age = format_age(r.created, now);
+            let tags = tag_values(&r.tags, &config.show_tags);
+            let mut row = vec![
+                s.account.as_str(),
+                region.as_str(),
+                vpc_id.as_str(),
+                r.rtype,
+                r.name.as_str(),
+                age.as_str(),
+                r.arn.as_str(),
+            ];
+            if config.with_stacks {
+                row.push(r.stack.as_deref().unwrap_or("-"));
+            }
+            row.extend(tags.iter().map(String::as_str));
+            output.push_str(&row.join("\t"));
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Format detail table for terminal output.  Resources with an open issue
+/// (e.g. a subnet over `--ip-threshold`) are highlighted.
+pub fn format_detail_table(
+    vpcs: &BTreeMap<(String, String, String), VpcSummary>,
+    config: &Config,
+    format: OutputFormat,
+) -> String {
+    use comfy_table::{Cell, Color, ColumnConstraint, ContentArrangement, Width};
+
+    if format == OutputFormat::Tsv {
+        return format_detail_tsv(vpcs, config);
+    }
 
     let term_w = terminal_width();
-    let borders_and_padding = 10usize;
+    let borders_and_padding = (if config.with_stacks { 16usize } else { 13usize }) + 3 * config.show_tags.len();
+    let age_col_len = 4usize;
+    let now = unix_now();
     let min_arn_width = 20usize;
     let name_soft_cap = term_w / 3;
 
     let mut output = String::new();
 
-    for ((region, vpc_id), s) in vpcs {
+    for ((_, region, vpc_id), s) in vpcs {
         let mut summary = Table::new();
         summary.load_preset(ASCII_FULL);
         summary.set_header(summary_headers());
@@ -267,13 +464,51 @@ pub fn format_detail_table(vpcs: &BTreeMap<(String, String), VpcSummary>) -> Str
                 .unwrap_or(4)
                 .min(name_soft_cap);
 
+            let stack_col_len = if config.with_stacks {
+                s.resources
+                    .iter()
+                    .map(|r| r.stack.as_deref().unwrap_or_default().len())
+                    .max()
+                    .unwrap_or(5)
+                    .clamp(5, 30)
+            } else {
+                0
+            };
+
+            // One column per --show-tags key, wide enough for its header
+            let tag_col_lens: Vec<usize> = config
+                .show_tags
+                .iter()
+                .map(|key| {
+                    s.resources
+                        .iter()
+                        .filter_map(|r| r.tags.get(key).map(String::len))
+                        .max()
+                        .unwrap_or(1)
+                        .clamp(key.len(), 30)
+                })
+                .collect();
+
             let arn_col_len = term_w
-                .saturating_sub(type_col_len + name_col_len + borders_and_padding)
+                .saturating_sub(
+                    type_col_len
+                        + name_col_len
+                        + age_col_len
+                        + stack_col_len
+                        + tag_col_lens.iter().sum::<usize>()
+                        + borders_and_padding,
+                )
                 .max(min_arn_width);
 
+            let mut headers = vec!["TYPE", "NAME", "AGE", "IDENTIFIER / ARN"];
+            if config.with_stacks {
+                headers.push("STACK");
+            }
+            headers.extend(config.show_tags.iter().map(String::as_str));
+
             let mut detail = Table::new();
             detail.load_preset(ASCII_FULL_CONDENSED);
-            detail.set_header(vec!["TYPE", "NAME", "IDENTIFIER / ARN"]);
+            detail.set_header(headers);
             detail.set_content_arrangement(ContentArrangement::DynamicFullWidth);
 
             detail
@@ -291,18 +526,45 @@ pub fn format_detail_table(vpcs: &BTreeMap<(String, String), VpcSummary>) -> Str
                 )));
 
             detail
-                .column_mut(2)
+                .column_mut(3)
                 .expect("ARN column exists")
                 .set_constraint(ColumnConstraint::UpperBoundary(Width::Fixed(
                     arn_col_len as u16,
                 )));
 
+            if config.with_stacks {
+                detail
+                    .column_mut(4)
+                    .expect("STACK column exists")
+                    .set_constraint(ColumnConstraint::UpperBoundary(Width::Fixed(
+                        stack_col_len as u16,
+                    )));
+            }
+
+            let first_tag_col = if config.with_stacks { 5 } else { 4 };
+            for (i, len) in tag_col_lens.iter().enumerate() {
+                detail
+                    .column_mut(first_tag_col + i)
+                    .expect("tag column exists")
+                    .set_constraint(ColumnConstraint::UpperBoundary(Width::Fixed(*len as u16)));
+            }
+
             for r in &s.resources {
-                detail.add_row(vec![
+                let mut row = vec![
                     r.rtype.to_owned(),
                     r.name.clone(),
+                    format_age(r.created, now),
                     wrap_identifier(&r.arn, arn_col_len),
-                ]);
+                ];
+                if config.with_stacks {
+                    row.push(r.stack.clone().unwrap_or_else(|| "-".to_owned()));
+                }
+                row.extend(tag_values(&r.tags, &config.show_tags));
+                let flagged = s.issues.iter().any(|i| i.resource == r.arn);
+                detail.add_row(row.into_iter().map(|c| {
+                    let cell = Cell::new(c);
+                    if flagged { cell.fg(Color::Red) } else { cell }
+                }));
             }
 
             output.push_str(&detail.to_string());
@@ -314,45 +576,281 @@ pub fn format_detail_table(vpcs: &BTreeMap<(String, String), VpcSummary>) -> Str
     output
 }
 
-/// Run the VPC scan for given config
-pub async fn run(config: &Config) -> Result<ScanResult> {
-    let scanners: Vec<Box<dyn ServiceScanner>> =
-        vec![Box::new(Ec2Scanner), Box::new(ElbScanner), Box::new(RdsScanner)];
-
-    let mut vpcs: BTreeMap<(String, String), VpcSummary> = BTreeMap::new();
-
-    for region in &config.regions {
-        let conf = aws_config::defaults(BehaviorVersion::latest())
-            .region(Region::new(region.clone()))
-            .load()
-            .await;
-
-        for (vpc_id, vpc_name) in list_vpcs(&conf, &config.vpc_ids).await? {
-            let peers = get_peer_vpcs(&conf, &vpc_id).await?;
-            let mut summary = VpcSummary {
-                name: vpc_name,
-                public: is_public(&conf, &vpc_id).await?,
-                cidrs: get_cidrs(&conf, &vpc_id).await?,
-                peers,
-                resources: Vec::new(),
-            };
+/// SDK config loader with the `--max-retries` and timeout flags applied
+pub fn sdk_loader(config: &Config) -> ConfigLoader {
+    let mut loader = aws_config::defaults(BehaviorVersion::latest());
+    if let Some(retries) = config.max_retries {
+        loader = loader.retry_config(RetryConfig::standard().with_max_attempts(retries + 1));
+    }
+    if config.connect_timeout.is_some() || config.operation_timeout.is_some() {
+        let mut timeouts = TimeoutConfig::builder();
+        timeouts
+            .set_connect_timeout(config.connect_timeout)
+            .set_operation_timeout(config.operation_timeout);
+        loader = loader.timeout_config(timeouts.build());
+    }
+    loader
+}
+
+/// Identity of the base credentials (before any `--assume-role`), resolved
+/// in `--sts-region` or else the first region to scan
+pub async fn whoami(config: &Config, stats: &ApiStats) -> Result<CallerIdentity> {
+    let region = Region::new(
+        config
+            .sts_region
+            .clone()
+            .or_else(|| config.regions.first().cloned())
+            .unwrap_or_default(),
+    );
+    let conf = sdk_loader(config).region(region).load().await;
+    let identity = identity::caller_identity(&conf).await;
+    let account = identity.as_ref().map(|id| id.account.as_str()).unwrap_or_default();
+    stats.for_account(account).record("sts", "GetCallerIdentity", conf.region());
+    identity
+}
+
+/// Account the scan's calls are made in: the `--assume-role` ARN's, else
+/// the base credentials' (empty when they can't be resolved)
+pub async fn scan_account(config: &Config, stats: &ApiStats) -> String {
+    if let Some(arn) = &config.assume_role {
+        return identity::account_of(arn).to_owned();
+    }
+    match whoami(config, stats).await {
+        Ok(id) => id.account,
+        Err(e) => {
+            warn!("cannot resolve the caller account: {e:#}");
+            String::new()
+        }
+    }
+}
+
+/// Build the SDK config for one region, running under `--assume-role` (with
+/// its external ID) when one is given.  The role is assumed through the
+/// `--sts-region` endpoint when set, else through the region's own.  Each
+/// AssumeRole call is counted in `calls` when the SDK makes it.
+pub async fn region_conf(region: &str, config: &Config, calls: &CallCounter) -> SdkConfig {
+    let region = Region::new(region.to_owned());
+    let base = sdk_loader(config)
+        .region(region.clone())
+        .load()
+        .await;
+    let Some(arn) = &config.assume_role else {
+        return base;
+    };
+
+    let sts_region = config.sts_region.clone().map_or_else(|| region.clone(), Region::new);
+    let mut builder = AssumeRoleProvider::builder(arn.clone())
+        .session_name("ls-vpc")
+        .region(sts_region.clone());
+    if let Some(external_id) = &config.external_id {
+        builder = builder.external_id(external_id.clone());
+    }
+    let provider = builder.configure(&base).build().await;
+    let calls = calls.clone();
+    let provider = CountingProvider::new(provider, move || calls.record("sts", "AssumeRole", Some(&sts_region)));
+    sdk_loader(config)
+        .region(region)
+        .credentials_provider(provider)
+        .load()
+        .await
+}
+
+/// Scan one VPC, returning its summary and any non-fatal warnings.  Each
+/// scanner's resources are passed to `found` as soon as it returns them,
+/// before stacks and tags are resolved.
+async fn scan_vpc(
+    conf: &SdkConfig,
+    calls: &CallCounter,
+    vpc_id: &str,
+    vpc_name: Option<String>,
+    config: &Config,
+    scanners: &[Box<dyn ServiceScanner>],
+    found: &(dyn Fn(&[ResourceRecord]) + Sync),
+) -> Result<(VpcSummary, Vec<String>)> {
+    let region = conf.region().map(|r| r.to_string()).unwrap_or_default();
+    let mut warnings = Vec::new();
+    let peers = get_peer_vpcs(conf, calls, vpc_id).await?;
+    let mut summary = VpcSummary {
+        name: vpc_name,
+        public: is_public(conf, calls, vpc_id).await?,
+        cidrs: get_cidrs(conf, calls, vpc_id).await?,
+        peers,
+        resources: Vec::new(),
+        ..Default::default()
+    };
+
+    if config.flow_logs || config.issues {
+        summary.flow_log_details = flowlogs::list_flow_logs(conf, calls, vpc_id).await?;
+        summary.flow_logs = !summary.flow_log_details.is_empty();
+        if let Err(e) = flowlogs::resolve_retention(conf, calls, &mut summary.flow_log_details).await {
+            let message = format!("{region} {vpc_id}: cannot read flow log group retention: {e:#}");
+            warn!("{message}");
+            warnings.push(message);
+        }
+        if config.issues {
+            summary.issues.extend(issues::unretained_flow_logs(&summary.flow_log_details));
+        }
+    } else if config.fail_on.as_ref().is_some_and(|p| p.expr.uses("flow_logs")) {
+        summary.flow_logs = has_flow_logs(conf, calls, vpc_id).await?;
+    }
+
+    if config.sg_graph.is_some() || config.unused_sgs {
+        summary.security_groups = sg::list_security_groups(conf, calls, vpc_id).await?;
+    }
+    if config.unused_sgs {
+        sg::count_attachments(conf, calls, vpc_id, &mut summary.security_groups).await?;
+    }
+
+    if config.issues || config.egress || config.emit_tf_imports {
+        match routes::list_route_tables(conf, calls, vpc_id).await {
+            Ok(tables) => summary.route_tables = tables,
+            Err(e) => {
+                let message = format!("{region} {vpc_id}: cannot list route tables: {e:#}");
+                warn!("{message}");
+                warnings.push(message);
+            }
+        }
+    }
+    if config.issues {
+        summary.issues.extend(issues::blackhole_routes(&summary.route_tables));
+        summary.peerings = peering::list_peerings(conf, calls, vpc_id).await?;
+    }
 
-            if !config.summary_only {
-                for s in &scanners {
-                    if let Ok(mut res) = s.scan(&conf, &vpc_id).await {
-                        summary.resources.append(&mut res);
-                    }
+    if config.ip_threshold.is_some()
+        || config.egress
+        || config.format == Some(OutputFormat::Drawio)
+        || config.emit_tf_imports
+    {
+        summary.subnets = subnets::list_subnets(conf, calls, vpc_id).await?;
+    }
+    if let Some(threshold) = config.ip_threshold {
+        summary.issues.extend(issues::exhausted_subnets(&summary.subnets, threshold));
+    }
+
+    if config.with_cost || config.issues {
+        summary.endpoints = endpoints::list_vpc_endpoints(conf, calls, vpc_id).await?;
+    }
+
+    if config.issues {
+        match nat::list_nat_gateways(conf, calls, vpc_id).await {
+            Ok(gateways) => summary.nat_gateways = gateways,
+            Err(e) => {
+                let message = format!("{region} {vpc_id}: cannot read NAT gateway traffic: {e:#}");
+                warn!("{message}");
+                warnings.push(message);
+            }
+        }
+        let missing = issues::missing_gateway_endpoints(&summary);
+        summary.issues.extend(missing);
+        summary.db_subnet_groups = db_subnet_groups::list_db_subnet_groups(conf, calls, vpc_id).await?;
+        summary.issues.extend(issues::single_az_db_subnet_groups(&summary.db_subnet_groups));
+    }
+
+    if config.egress {
+        let nats = egress::nat_subnets(conf, calls, vpc_id).await?;
+        summary.egress = egress::classify(&summary.subnets, &summary.route_tables, &nats);
+    }
+
+    if config.endpoint_services {
+        summary.endpoint_services = endpoint_services::list_endpoint_services(conf, calls, vpc_id).await?;
+    }
+
+    if config.nau {
+        match nau::vpc_nau(conf, calls, vpc_id).await {
+            Ok(usage) => summary.nau = usage,
+            Err(e) => {
+                let message = format!("{region} {vpc_id}: cannot read NAU metrics: {e:#}");
+                warn!("{message}");
+                warnings.push(message);
+            }
+        }
+    }
+
+    if !config.summary_only {
+        for s in scanners {
+            match s.scan(conf, calls, vpc_id).await {
+                Ok(mut res) => {
+                    found(&res);
+                    summary.resources.append(&mut res);
                 }
+                Err(e) => {
+                    let message = format!("{region} {vpc_id}: {} scan failed: {e:#}", s.name());
+                    warn!("{message}");
+                    warnings.push(message);
+                }
+            }
+        }
+        if config.with_stacks {
+            if let Err(e) = resolve_stacks(conf, calls, &mut summary.resources).await {
+                let message = format!("{region} {vpc_id}: {e:#}");
+                warn!("{message}");
+                warnings.push(message);
             }
+        }
+        if !config.show_tags.is_empty() {
+            resolve_tags(conf, calls, &mut summary.resources).await?;
+        }
+        if config.sort_by == Some(SortBy::Age) {
+            sort_by_age(&mut summary.resources);
+        }
+        summary.resource_count = summary.resources.len();
+    } else {
+        summary.resource_count = count_network_interfaces(conf, calls, vpc_id).await?;
+    }
 
-            vpcs.insert((region.clone(), vpc_id), summary);
+    Ok((summary, warnings))
+}
+
+/// Run the VPC scan for given config: collect every event of
+/// [`scan_stream`] into one result, counting its calls in `stats`
+pub async fn run(config: &Config, stats: &ApiStats) -> Result<ScanResult> {
+    let mut result = ScanResult::default();
+    let mut events = pin!(scan_stream(config, stats));
+    while let Some(event) = events.next().await {
+        match event? {
+            VpcEvent::VpcScanned {
+                account,
+                region,
+                vpc_id,
+                summary,
+            } => {
+                result.vpcs.insert((account, region, vpc_id), *summary);
+            }
+            VpcEvent::Warning(warning) => result.warnings.push(warning),
+            VpcEvent::RegionFailed(error) => result.errors.push(error),
+            VpcEvent::RegionScanned { .. } => result.regions_scanned += 1,
+            VpcEvent::VpcDiscovered { .. } | VpcEvent::ResourceDiscovered { .. } => {}
         }
     }
 
-    Ok(ScanResult {
-        regions_scanned: config.regions.len(),
-        vpcs,
-    })
+    if let Some(days) = config.spend_days {
+        // Every VPC key carries the scan's account; resolve it only when no
+        // VPC was found
+        let account = match result.vpcs.keys().next() {
+            Some((account, _, _)) => account.clone(),
+            None => scan_account(config, stats).await,
+        };
+        let calls = stats.for_account(&account);
+        let conf = region_conf("us-east-1", config, &calls).await;
+        match spend::network_spend(&conf, &calls, days, &config.spend_tag).await {
+            Ok(tagged) => result.unattributed_spend = spend::join_spend(&mut result.vpcs, &tagged),
+            Err(e) => {
+                let message = format!("cannot read network spend from Cost Explorer: {e:#}");
+                warn!("{message}");
+                result.warnings.push(message);
+            }
+        }
+    }
+
+    if config.issues {
+        for (key, issue) in issues::asymmetric_peerings(&result.vpcs) {
+            if let Some(s) = result.vpcs.get_mut(&key) {
+                s.issues.push(issue);
+            }
+        }
+    }
+
+    Ok(result)
 }
 
 #[cfg(test)]
@@ -360,8 +858,8 @@ mod tests {
     use super::*;
 
     #[test]
-    fn summary_headers_has_six_columns() {
-        assert_eq!(summary_headers().len(), 6);
+    fn summary_headers_has_eight_columns() {
+        assert_eq!(summary_headers().len(), 8);
     }
 
     #[test]
@@ -370,16 +868,21 @@ mod tests {
             name: Some("my-vpc".to_string()),
             public: true,
             cidrs: vec!["10.0.0.0/16".to_string()],
+            account: "111111111111".to_string(),
             peers: vec!["vpc-peer1".to_string()],
             resources: vec![],
+            resource_count: 12,
+            ..Default::default()
         };
         let row = summary_row("us-west-2", "vpc-123", &summary);
-        assert_eq!(row[0], "us-west-2");
-        assert_eq!(row[1], "public");
-        assert_eq!(row[2], "10.0.0.0/16");
-        assert_eq!(row[3], "vpc-123");
-        assert_eq!(row[4], "vpc-peer1");
-        assert_eq!(row[5], "my-vpc");
+        assert_eq!(row[0], "111111111111");
+        assert_eq!(row[1], "us-west-2");
+        assert_eq!(row[2], "public");
+        assert_eq!(row[3], "10.0.0.0/16");
+        assert_eq!(row[4], "vpc-123");
+        assert_eq!(row[5], "vpc-peer1");
+        assert_eq!(row[6], "12");
+        assert_eq!(row[7], "my-vpc");
     }
 
     #[test]
@@ -390,27 +893,30 @@ mod tests {
             cidrs: vec!["10.0.0.0/16".to_string(), "10.1.0.0/16".to_string()],
             peers: vec![],
             resources: vec![],
+            ..Default::default()
         };
         let row = summary_row("us-east-1", "vpc-456", &summary);
-        assert_eq!(row[1], "private");
-        assert_eq!(row[2], "10.0.0.0/16,10.1.0.0/16");
-        assert_eq!(row[5], "");
+        assert_eq!(row[2], "private");
+        assert_eq!(row[3], "10.0.0.0/16,10.1.0.0/16");
+        assert_eq!(row[6], "0");
+        assert_eq!(row[7], "");
     }
 
     #[test]
     fn format_summary_table_creates_valid_table() {
         let mut vpcs = BTreeMap::new();
         vpcs.insert(
-            ("us-west-2".to_string(), "vpc-123".to_string()),
+            ("111111111111".to_string(), "us-west-2".to_string(), "vpc-123".to_string()),
             VpcSummary {
                 name: Some("test-vpc".to_string()),
                 public: true,
                 cidrs: vec!["10.0.0.0/16".to_string()],
                 peers: vec![],
                 resources: vec![],
+                ..Default::default()
             },
         );
-        let table = format_summary_table(&vpcs);
+        let table = format_summary_table(&vpcs, OutputFormat::Table);
         assert!(table.contains("us-west-2"));
         assert!(table.contains("vpc-123"));
         assert!(table.contains("test-vpc"));
@@ -421,7 +927,7 @@ mod tests {
     fn format_detail_table_includes_resources() {
         let mut vpcs = BTreeMap::new();
         vpcs.insert(
-            ("us-west-2".to_string(), "vpc-123".to_string()),
+            ("111111111111".to_string(), "us-west-2".to_string(), "vpc-123".to_string()),
             VpcSummary {
                 name: Some("test-vpc".to_string()),
                 public: true,
@@ -431,12 +937,96 @@ mod tests {
                     arn: "i-1234567890abcdef0".to_string(),
                     rtype: "ec2.instance",
                     name: "my-instance".to_string(),
+                    ..Default::default()
                 }],
+                ..Default::default()
             },
         );
-        let table = format_detail_table(&vpcs);
+        let table = format_detail_table(&vpcs, &Config::default(), OutputFormat::Table);
         assert!(table.contains("ec2.instance"));
         assert!(table.contains("my-instance"));
+        assert!(!table.contains("STACK"));
+    }
+
+    #[test]
+    fn format_detail_table_tsv_is_one_line_per_resource() {
+        let mut vpcs = BTreeMap::new();
+        vpcs.insert(
+            ("111111111111".to_string(), "us-west-2".to_string(), "vpc-123".to_string()),
+            VpcSummary {
+                resources: vec![ResourceRecord {
+                    arn: "i-1234567890abcdef0".to_string(),
+                    rtype: "ec2.instance",
+                    name: "my-instance".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+        let table = format_detail_table(&vpcs, &Config::default(), OutputFormat::Tsv);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "ACCOUNT\tREGION\tVPC-ID\tTYPE\tNAME\tAGE\tIDENTIFIER / ARN");
+        assert_eq!(lines[1], "111111111111\tus-west-2\tvpc-123\tec2.instance\tmy-instance\t-\ti-1234567890abcdef0");
+    }
+
+    #[test]
+    fn format_detail_table_shows_stack_column() {
+        let mut vpcs = BTreeMap::new();
+        vpcs.insert(
+            ("111111111111".to_string(), "us-west-2".to_string(), "vpc-123".to_string()),
+            VpcSummary {
+                account: "111111111111".to_string(),
+                name: None,
+                public: false,
+                cidrs: vec!["10.0.0.0/16".to_string()],
+                peers: vec![],
+                resources: vec![ResourceRecord {
+                    arn: "nat-0123456789abcdef0".to_string(),
+                    rtype: "ec2.nat-gateway",
+                    name: "nat-0123456789abcdef0".to_string(),
+                    stack: Some("network-stack".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+        let config = Config {
+            with_stacks: true,
+            ..Config::default()
+        };
+        let table = format_detail_table(&vpcs, &config, OutputFormat::Table);
+        assert!(table.contains("STACK"));
+        assert!(table.contains("network-stack"));
+    }
+
+    #[test]
+    fn format_detail_table_shows_requested_tags() {
+        let mut vpcs = BTreeMap::new();
+        vpcs.insert(
+            ("111111111111".to_string(), "us-west-2".to_string(), "vpc-123".to_string()),
+            VpcSummary {
+                account: "111111111111".to_string(),
+                resources: vec![ResourceRecord {
+                    arn: "i-0123456789abcdef0".to_string(),
+                    rtype: "ec2.instance",
+                    name: "web".to_string(),
+                    tags: BTreeMap::from([("Team".to_string(), "payments".to_string())]),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+        let config = Config {
+            show_tags: vec!["Team".to_string(), "Environment".to_string()],
+            ..Config::default()
+        };
+        let tsv = format_detail_table(&vpcs, &config, OutputFormat::Tsv);
+        let mut lines = tsv.lines();
+        assert!(lines.next().unwrap().ends_with("\tTeam\tEnvironment"));
+        assert!(lines.next().unwrap().ends_with("\ti-0123456789abcdef0\tpayments\t-"));
+
+        let table = format_detail_table(&vpcs, &config, OutputFormat::Table);
+        assert!(table.contains("Environment") && table.contains("payments"));
     }
 
     #[test]
@@ -447,6 +1037,7 @@ mod tests {
             cidrs: vec!["10.0.0.0/16".to_string()],
             peers: vec![],
             resources: vec![],
+            ..Default::default()
         };
         let cloned = summary.clone();
         assert_eq!(cloned.name, summary.name);
@@ -8,13 +8,69 @@
 use clap::Parser;
 use env_logger::Target;
 use eyre::Result;
-use ls_vpc::{format_detail_table, format_summary_table, get_or_create_log_dir, run, Cli, Config};
-use std::{fs::OpenOptions, io::Write, time::Instant};
+use ls_vpc::cli::SgGraphFormat;
+use ls_vpc::config::resolve_format;
+use ls_vpc::settings::Settings;
+use ls_vpc::{
+    api_stats, drawio, email, error_json_lines, find_duplicates, format_cost_table, format_detail_table,
+    format_duplicates_table, format_egress_table, format_endpoint_services_table, format_flow_logs_table,
+    format_identity, format_issues_table, format_nau_table, format_scan_error, format_sg_dot, format_sg_table,
+    format_spend_table, format_summary_table, format_tf_imports, format_unused_sgs_table, get_or_create_log_dir, json,
+    pick, plan, region_conf, run, scan_account, service_scanners, whoami, ApiStats, Cli, Config, OutputFormat,
+    Redactor, ScanResult,
+};
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io::{IsTerminal, Write},
+    time::Instant,
+};
+
+/// The `--format` in effect: an emailed report is read by people, so it
+/// keeps tables when piped
+fn output_format(config: &Config) -> OutputFormat {
+    resolve_format(config.format, std::io::stdout().is_terminal() || !config.email_to.is_empty())
+}
+
+/// Scan in the background while the fuzzy finder lists VPCs as they arrive.
+async fn pick_vpc(config: &Config) -> Result<()> {
+    let (feed, scan) = pick::spawn_feed(config.clone());
+
+    let picked = tokio::task::spawn_blocking(move || pick::pick(feed)).await??;
+    let Some((key, summary)) = picked else {
+        if scan.is_finished() {
+            scan.await??;
+        } else {
+            scan.abort();
+        }
+        return Ok(());
+    };
+    scan.abort();
+
+    let mut picked = ScanResult {
+        vpcs: BTreeMap::from([(key, summary)]),
+        ..Default::default()
+    };
+    if config.redact {
+        Redactor::default().redact_result(&mut picked);
+    }
+
+    if config.quiet {
+        for (_, _, vpc_id) in picked.vpcs.keys() {
+            println!("{}", vpc_id);
+        }
+    } else {
+        print!("{}", format_detail_table(&picked.vpcs, config, output_format(config)));
+    }
+    Ok(())
+}
+
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let config = Config::try_from(cli)?;
+    let settings = Settings::load()?;
+    let cli = Cli::parse_from(settings.expand_view("ls-vpc", std::env::args().collect())?);
+    let config = Config::try_from_with(cli, &settings)?;
 
     // Set up logging
     let log_file = get_or_create_log_dir().join("ls-vpc.log");
@@ -37,22 +93,187 @@ async fn main() -> Result<()> {
         .filter_level(log::LevelFilter::Trace)
         .init();
 
+    if config.schema {
+        print!("{}", json::SCHEMA);
+        return Ok(());
+    }
+
+    let stats = ApiStats::default();
+    if config.whoami {
+        print!("{}", format_identity(&whoami(&config, &stats).await?));
+        return Ok(());
+    }
+    if config.verbose {
+        eprint!("{}", format_identity(&whoami(&config, &stats).await?));
+    }
+
+    if config.dry_run {
+        let mut scan_plan = plan::plan(&config, &stats, &service_scanners()).await?;
+        if config.redact {
+            Redactor::default().redact_plan(&mut scan_plan);
+        }
+        print!("{}", plan::format_plan(&scan_plan));
+        return Ok(());
+    }
+
+    if config.pick {
+        return pick_vpc(&config).await;
+    }
+
     let start = Instant::now();
-    let result = run(&config).await?;
+    let mut result = run(&config, &stats).await?;
+    if config.redact {
+        Redactor::default().redact_result(&mut result);
+    }
+
+    // --limit/--page only trim what is printed; the exit checks see every VPC
+    let all_vpcs = std::mem::take(&mut result.vpcs);
+    result.vpcs = config
+        .page
+        .window(all_vpcs.iter())
+        .map(|(key, s)| (key.clone(), s.clone()))
+        .collect();
 
-    // Output results
-    if config.summary_only {
-        print!("{}", format_summary_table(&result.vpcs));
+    // Output results, kept as one report so --email-to can send it too
+    let format = output_format(&config);
+    let mut report = String::new();
+    if config.tui {
+        ls_vpc::tui::run(&result.vpcs)?;
+    } else if let Some(fmt) = config.sg_graph {
+        let groups: Vec<_> = result
+            .vpcs
+            .values()
+            .flat_map(|s| s.security_groups.iter().cloned())
+            .collect();
+        match fmt {
+            SgGraphFormat::Table => report.push_str(&format!("{}\n", format_sg_table(&groups, format))),
+            SgGraphFormat::Dot => report.push_str(&format_sg_dot(&groups)),
+        }
+    } else if config.unused_sgs {
+        let groups: Vec<_> = result
+            .vpcs
+            .values()
+            .flat_map(|s| s.security_groups.iter().cloned())
+            .collect();
+        report.push_str(&format!("{}\n", format_unused_sgs_table(&groups, format)));
+    } else if config.emit_tf_imports {
+        report.push_str(&format_tf_imports(&result.vpcs));
+    } else if format == OutputFormat::Json {
+        report.push_str(&json::to_json(&result)?);
+    } else if format == OutputFormat::Drawio {
+        report.push_str(&drawio::to_drawio(&result.vpcs));
+    } else if config.summary_only {
+        report.push_str(&format!("{}\n", format_summary_table(&result.vpcs, format)));
     } else {
-        print!("{}", format_detail_table(&result.vpcs));
+        report.push_str(&format_detail_table(&result.vpcs, &config, format));
+    }
+
+    // JSON already carries the issues; extra tables would break the document
+    let has_issues = result.vpcs.values().any(|s| !s.issues.is_empty());
+    let extra_tables = format != OutputFormat::Json;
+    if extra_tables && (config.issues || config.ip_threshold.is_some()) {
+        if has_issues {
+            report.push_str(&format!("{}\n", format_issues_table(&result.vpcs, format)));
+        } else {
+            report.push_str("No issues found\n");
+        }
+    }
+
+    if extra_tables && config.with_cost {
+        report.push_str(&format!("{}\n", format_cost_table(&result.vpcs, format)));
     }
 
-    println!(
+    if extra_tables && config.spend_days.is_some() {
+        report.push_str(&format!("{}\n", format_spend_table(&result.vpcs, &result.unattributed_spend)));
+    }
+
+    if extra_tables && config.nau {
+        report.push_str(&format!("{}\n", format_nau_table(&result.vpcs, format)));
+    }
+
+    if extra_tables && config.egress {
+        report.push_str(&format!("{}\n", format_egress_table(&result.vpcs, format)));
+    }
+
+    if extra_tables && config.duplicates {
+        let duplicates = find_duplicates(&result.vpcs);
+        if duplicates.is_empty() {
+            report.push_str("No duplicate names found\n");
+        } else {
+            report.push_str(&format!("{}\n", format_duplicates_table(&duplicates, format)));
+        }
+    }
+
+    if extra_tables && config.flow_logs {
+        report.push_str(&format!("{}\n", format_flow_logs_table(&result.vpcs, format)));
+    }
+
+    if extra_tables && config.endpoint_services {
+        report.push_str(&format!("{}\n", format_endpoint_services_table(&result.vpcs, format)));
+    }
+    print!("{report}");
+
+    // Keep TSV and JSON output machine-readable: the run summary goes to stderr
+    let mut finished = format!(
         "Finished in {:.2?} – {} VPC(s) across {} Region(s)",
         start.elapsed(),
-        result.vpcs.len(),
+        all_vpcs.len(),
         result.regions_scanned
     );
+    if config.page.trims(all_vpcs.len()) {
+        finished.push_str(&format!(" (showing {} on page {})", result.vpcs.len(), config.page.page));
+    }
+    if matches!(format, OutputFormat::Tsv | OutputFormat::Json) {
+        eprintln!("{finished}");
+    } else {
+        println!("{finished}");
+    }
+
+    if let (Some(from), Some(region)) = (&config.email_from, config.regions.first()) {
+        let subject = format!(
+            "ls-vpc report: {} VPC(s) across {} Region(s)",
+            all_vpcs.len(),
+            result.regions_scanned
+        );
+        let calls = stats.for_account(&scan_account(&config, &stats).await);
+        let conf = region_conf(region, &config, &calls).await;
+        email::send_report(&conf, &calls, from, &config.email_to, &subject, &report, config.email_format).await?;
+        eprintln!("Emailed report to {}", config.email_to.join(", "));
+    }
+
+    for warning in &result.warnings {
+        eprintln!("warning: {warning}");
+    }
+    if format == OutputFormat::Json {
+        eprint!("{}", error_json_lines(&result.errors)?);
+    } else {
+        for error in &result.errors {
+            eprintln!("error: {}", format_scan_error(error));
+        }
+    }
+
+    if config.api_stats {
+        eprintln!("{}", api_stats::format_api_stats(&stats.snapshot()));
+    }
+
+    if let Some(policy) = &config.fail_on {
+        let failing: Vec<_> = all_vpcs.iter().filter(|(_, s)| policy.matches(s)).collect();
+        if !failing.is_empty() {
+            eprintln!("{} VPC(s) match --fail-on '{}':", failing.len(), policy.source);
+            for ((account, region, vpc_id), s) in failing {
+                eprintln!("  {account}\t{region}\t{vpc_id}\t{}", s.name.as_deref().unwrap_or("-"));
+            }
+            std::process::exit(1);
+        }
+    }
+
+    let exhausted = all_vpcs
+        .values()
+        .flat_map(|s| &s.issues)
+        .any(|i| i.kind == "subnet-ip-exhaustion");
+    if exhausted {
+        std::process::exit(1);
+    }
 
     Ok(())
 }
@@ -0,0 +1,115 @@
+//! nat.rs
+//! ---------------------------------------------------------------------------
+//! NAT gateways and the traffic they processed, used by the `--issues` check
+//! for VPCs that send S3/DynamoDB traffic through NAT instead of a (free)
+//! gateway endpoint.
+
+use crate::api_stats::CallCounter;
+use aws_sdk_cloudwatch as cloudwatch;
+use aws_sdk_cloudwatch::primitives::DateTime;
+use aws_sdk_cloudwatch::types::{Dimension, Statistic};
+use aws_sdk_ec2 as ec2;
+use aws_sdk_ec2::types::NatGatewayState;
+use aws_types::SdkConfig;
+use eyre::Result;
+use std::time::{Duration, SystemTime};
+
+/// NAT gateway data-processing charge per GB (USD, us-east-1).
+pub const NAT_PROCESSING_USD_PER_GB: f64 = 0.045;
+
+/// Days of CloudWatch traffic summed for the estimate.
+pub const TRAFFIC_WINDOW_DAYS: u64 = 30;
+
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// An available NAT gateway and the bytes it processed over the window.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NatGateway {
+    pub id: String,
+    /// Bytes to and from destinations over [`TRAFFIC_WINDOW_DAYS`]
+    /// (`None` = no metrics)
+    pub processed_bytes: Option<f64>,
+}
+
+impl NatGateway {
+    /// Data-processing cost of the traffic over the window.
+    pub fn processing_cost(&self) -> Option<f64> {
+        self.processed_bytes.map(|b| b / BYTES_PER_GB * NAT_PROCESSING_USD_PER_GB)
+    }
+}
+
+/// Sum of one NAT gateway metric over the traffic window.
+async fn metric_sum(
+    client: &cloudwatch::Client,
+    conf: &SdkConfig,
+    calls: &CallCounter,
+    metric: &str,
+    nat_id: &str,
+) -> Result<Option<f64>> {
+    let now = SystemTime::now();
+    let window = Duration::from_secs(TRAFFIC_WINDOW_DAYS * 86_400);
+    calls.record("cloudwatch", "GetMetricStatistics", conf.region());
+    let resp = client
+        .get_metric_statistics()
+        .namespace("AWS/NATGateway")
+        .metric_name(metric)
+        .dimensions(Dimension::builder().name("NatGatewayId").value(nat_id).build())
+        .start_time(DateTime::from(now - window))
+        .end_time(DateTime::from(now))
+        .period(86_400)
+        .statistics(Statistic::Sum)
+        .send()
+        .await?;
+    let points: Vec<f64> = resp.datapoints().iter().filter_map(|d| d.sum()).collect();
+    Ok((!points.is_empty()).then(|| points.iter().sum()))
+}
+
+/// List the available NAT gateways in a VPC with their processed traffic.
+pub async fn list_nat_gateways(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<NatGateway>> {
+    let client = ec2::Client::new(conf);
+    let metrics = cloudwatch::Client::new(conf);
+    let mut gateways = Vec::new();
+
+    calls.record("ec2", "DescribeNatGateways", conf.region());
+    let mut pages = client
+        .describe_nat_gateways()
+        .filter(
+            ec2::types::Filter::builder()
+                .name("vpc-id")
+                .values(vpc_id)
+                .build(),
+        )
+        .into_paginator()
+        .items()
+        .send();
+    while let Some(ngw) = pages.next().await {
+        let ngw = ngw?;
+        if ngw.state() != Some(&NatGatewayState::Available) {
+            continue;
+        }
+        let id = ngw.nat_gateway_id().unwrap_or_default().to_owned();
+        let out = metric_sum(&metrics, conf, calls, "BytesOutToDestination", &id).await?;
+        let back = metric_sum(&metrics, conf, calls, "BytesInFromDestination", &id).await?;
+        let processed_bytes = match (out, back) {
+            (None, None) => None,
+            (out, back) => Some(out.unwrap_or(0.0) + back.unwrap_or(0.0)),
+        };
+        gateways.push(NatGateway { id, processed_bytes });
+    }
+    Ok(gateways)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn processing_cost_per_gb() {
+        let ngw = NatGateway {
+            id: "nat-1".to_string(),
+            processed_bytes: Some(100.0 * BYTES_PER_GB),
+        };
+        assert!((ngw.processing_cost().unwrap() - 4.5).abs() < 1e-9);
+        assert_eq!(NatGateway::default().processing_cost(), None);
+    }
+}
@@ -0,0 +1,155 @@
+//! nau.rs
+//! ---------------------------------------------------------------------------
+//! `--nau`: Network Address Usage per VPC, read from the CloudWatch
+//! `NetworkAddressUsage` / `NetworkAddressUsagePeered` metrics and compared
+//! with the default per-VPC quotas.  The metrics only exist once NAU metrics
+//! are enabled on the VPC; until then the usage is unknown.
+
+use crate::VpcSummary;
+use crate::api_stats::CallCounter;
+use crate::cli::OutputFormat;
+use crate::utils::render_table;
+use aws_sdk_cloudwatch as cloudwatch;
+use aws_sdk_cloudwatch::primitives::DateTime;
+use aws_sdk_cloudwatch::types::{Dimension, Statistic};
+use aws_types::SdkConfig;
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use eyre::Result;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+/// Default NAU quota for a single VPC.
+pub const NAU_LIMIT: f64 = 64_000.0;
+
+/// Default NAU quota for a VPC together with the VPCs peered to it.
+pub const NAU_PEERED_LIMIT: f64 = 128_000.0;
+
+/// Latest NAU readings for a VPC (`None` = metrics not enabled).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NauUsage {
+    pub nau: Option<f64>,
+    pub peered: Option<f64>,
+}
+
+impl NauUsage {
+    /// Percentage of the per-VPC quota in use.
+    pub fn used_pct(&self) -> Option<f64> {
+        self.nau.map(|n| n * 100.0 / NAU_LIMIT)
+    }
+
+    /// Percentage of the peered quota in use.
+    pub fn peered_pct(&self) -> Option<f64> {
+        self.peered.map(|n| n * 100.0 / NAU_PEERED_LIMIT)
+    }
+}
+
+/// Most recent hourly maximum of one NAU metric over the last day.
+async fn latest_metric(
+    client: &cloudwatch::Client,
+    conf: &SdkConfig,
+    calls: &CallCounter,
+    metric: &str,
+    vpc_id: &str,
+) -> Result<Option<f64>> {
+    let now = SystemTime::now();
+    calls.record("cloudwatch", "GetMetricStatistics", conf.region());
+    let resp = client
+        .get_metric_statistics()
+        .namespace("AWS/EC2")
+        .metric_name(metric)
+        .dimensions(Dimension::builder().name("VpcId").value(vpc_id).build())
+        .start_time(DateTime::from(now - Duration::from_secs(24 * 3600)))
+        .end_time(DateTime::from(now))
+        .period(3600)
+        .statistics(Statistic::Maximum)
+        .send()
+        .await?;
+    Ok(resp
+        .datapoints()
+        .iter()
+        .filter_map(|d| Some((d.timestamp()?.secs(), d.maximum()?)))
+        .max_by_key(|(ts, _)| *ts)
+        .map(|(_, v)| v))
+}
+
+/// Read the current NAU and peered NAU of a VPC.
+pub async fn vpc_nau(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<NauUsage> {
+    let client = cloudwatch::Client::new(conf);
+    Ok(NauUsage {
+        nau: latest_metric(&client, conf, calls, "NetworkAddressUsage", vpc_id).await?,
+        peered: latest_metric(&client, conf, calls, "NetworkAddressUsagePeered", vpc_id).await?,
+    })
+}
+
+/// Render NAU against the quotas per VPC.
+pub fn format_nau_table(vpcs: &BTreeMap<(String, String, String), VpcSummary>, format: OutputFormat) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec![
+        "ACCOUNT",
+        "REGION",
+        "VPC-ID",
+        "NAU",
+        "NAU-LIMIT",
+        "USED",
+        "PEERED-NAU",
+        "PEERED-LIMIT",
+        "PEERED-USED",
+    ]);
+
+    let count = |v: Option<f64>| v.map(|n| format!("{n:.0}")).unwrap_or_else(|| "-".to_owned());
+    let pct = |v: Option<f64>| v.map(|p| format!("{p:.1}%")).unwrap_or_else(|| "-".to_owned());
+    for ((account, region, vpc_id), s) in vpcs {
+        table.add_row(vec![
+            account.clone(),
+            region.clone(),
+            vpc_id.clone(),
+            count(s.nau.nau),
+            format!("{NAU_LIMIT:.0}"),
+            pct(s.nau.used_pct()),
+            count(s.nau.peered),
+            format!("{NAU_PEERED_LIMIT:.0}"),
+            pct(s.nau.peered_pct()),
+        ]);
+    }
+    render_table(&table, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn used_pct_against_default_quotas() {
+        let usage = NauUsage {
+            nau: Some(48_000.0),
+            peered: Some(32_000.0),
+        };
+        assert_eq!(usage.used_pct(), Some(75.0));
+        assert_eq!(usage.peered_pct(), Some(25.0));
+        assert_eq!(NauUsage::default().used_pct(), None);
+    }
+
+    #[test]
+    fn format_nau_table_marks_missing_metrics() {
+        let mut vpcs = BTreeMap::new();
+        vpcs.insert(
+            ("111111111111".to_string(), "us-east-1".to_string(), "vpc-a".to_string()),
+            VpcSummary {
+                nau: NauUsage {
+                    nau: Some(51_200.0),
+                    peered: Some(64_000.0),
+                },
+                ..Default::default()
+            },
+        );
+        vpcs.insert(("111111111111".to_string(), "us-east-1".to_string(), "vpc-b".to_string()), VpcSummary::default());
+        let table = format_nau_table(&vpcs, OutputFormat::Table);
+        assert!(table.contains("51200"));
+        assert!(table.contains("80.0%"));
+        assert!(table.contains("50.0%"));
+        let row_b = table.lines().find(|l| l.contains("vpc-b")).unwrap();
+        assert!(row_b.contains(" - "));
+    }
+}
@@ -0,0 +1,179 @@
+//! peering.rs
+//! ---------------------------------------------------------------------------
+//! Active VPC peering connections with the CIDRs on both sides, plus the
+//! CIDR arithmetic needed to decide whether a route actually covers a peer.
+
+use crate::api_stats::CallCounter;
+use crate::routes::RouteTable;
+use aws_sdk_ec2 as ec2;
+use aws_sdk_ec2::types::{VpcPeeringConnectionStateReasonCode as State, VpcPeeringConnectionVpcInfo};
+use aws_types::SdkConfig;
+use eyre::Result;
+
+/// One side of a peering connection.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PeeringSide {
+    pub vpc_id: String,
+    pub region: String,
+    pub owner: String,
+    pub cidrs: Vec<String>,
+}
+
+/// An active peering connection.
+#[derive(Debug, Clone, Default)]
+pub struct Peering {
+    pub id: String,
+    pub requester: PeeringSide,
+    pub accepter: PeeringSide,
+}
+
+impl Peering {
+    /// The side of the connection that isn't `vpc_id`.
+    pub fn other_side(&self, vpc_id: &str) -> &PeeringSide {
+        if self.requester.vpc_id == vpc_id { &self.accepter } else { &self.requester }
+    }
+}
+
+fn side_from(info: Option<&VpcPeeringConnectionVpcInfo>) -> PeeringSide {
+    let Some(info) = info else { return PeeringSide::default() };
+    let mut cidrs: Vec<String> = info
+        .cidr_block_set()
+        .iter()
+        .filter_map(|c| c.cidr_block().map(str::to_owned))
+        .collect();
+    if let Some(primary) = info.cidr_block() {
+        cidrs.push(primary.to_owned());
+    }
+    cidrs.sort();
+    cidrs.dedup();
+    PeeringSide {
+        vpc_id: info.vpc_id().unwrap_or_default().to_owned(),
+        region: info.region().unwrap_or_default().to_owned(),
+        owner: info.owner_id().unwrap_or_default().to_owned(),
+        cidrs,
+    }
+}
+
+/// List the active peering connections a VPC takes part in (either side).
+pub async fn list_peerings(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<Peering>> {
+    let client = ec2::Client::new(conf);
+    let mut peerings = Vec::new();
+
+    for filter_name in ["requester-vpc-info.vpc-id", "accepter-vpc-info.vpc-id"] {
+        calls.record("ec2", "DescribeVpcPeeringConnections", conf.region());
+        let resp = client
+            .describe_vpc_peering_connections()
+            .filters(
+                ec2::types::Filter::builder()
+                    .name(filter_name)
+                    .values(vpc_id)
+                    .build(),
+            )
+            .send()
+            .await?;
+        for pc in resp.vpc_peering_connections() {
+            if matches!(pc.status().and_then(|s| s.code()), Some(State::Active)) {
+                peerings.push(Peering {
+                    id: pc.vpc_peering_connection_id().unwrap_or_default().to_owned(),
+                    requester: side_from(pc.requester_vpc_info()),
+                    accepter: side_from(pc.accepter_vpc_info()),
+                });
+            }
+        }
+    }
+    Ok(peerings)
+}
+
+fn parse_v4(cidr: &str) -> Option<(u32, u32)> {
+    let (addr, prefix) = cidr.split_once('/')?;
+    let addr: std::net::Ipv4Addr = addr.parse().ok()?;
+    let prefix: u32 = prefix.parse().ok()?;
+    (prefix <= 32).then_some((u32::from(addr), prefix))
+}
+
+/// Whether two CIDR blocks share any address.  Non-IPv4 blocks only match
+/// themselves.
+pub fn cidrs_overlap(a: &str, b: &str) -> bool {
+    match (parse_v4(a), parse_v4(b)) {
+        (Some((a, pa)), Some((b, pb))) => {
+            let prefix = pa.min(pb);
+            let mask = if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) };
+            a & mask == b & mask
+        }
+        _ => a == b,
+    }
+}
+
+/// Peer CIDRs not covered by any route through `pcx_id`.
+pub fn unrouted_cidrs<'a>(tables: &[RouteTable], pcx_id: &str, cidrs: &'a [String]) -> Vec<&'a str> {
+    cidrs
+        .iter()
+        .filter(|cidr| {
+            !tables
+                .iter()
+                .flat_map(|rt| &rt.routes)
+                .any(|r| r.target == pcx_id && !r.blackhole && cidrs_overlap(&r.destination, cidr))
+        })
+        .map(String::as_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::Route;
+
+    #[test]
+    fn cidrs_overlap_handles_containment() {
+        assert!(cidrs_overlap("10.0.0.0/16", "10.0.5.0/24"));
+        assert!(cidrs_overlap("10.0.5.0/24", "10.0.0.0/16"));
+        assert!(!cidrs_overlap("10.0.0.0/16", "10.1.0.0/16"));
+        assert!(cidrs_overlap("0.0.0.0/0", "192.168.0.0/24"));
+        assert!(cidrs_overlap("2600:1f14::/56", "2600:1f14::/56"));
+        assert!(!cidrs_overlap("pl-123", "10.0.0.0/16"));
+    }
+
+    #[test]
+    fn other_side_picks_opposite_vpc() {
+        let pc = Peering {
+            id: "pcx-1".to_string(),
+            requester: PeeringSide {
+                vpc_id: "vpc-a".to_string(),
+                ..Default::default()
+            },
+            accepter: PeeringSide {
+                vpc_id: "vpc-b".to_string(),
+                ..Default::default()
+            },
+        };
+        assert_eq!(pc.other_side("vpc-a").vpc_id, "vpc-b");
+        assert_eq!(pc.other_side("vpc-b").vpc_id, "vpc-a");
+    }
+
+    #[test]
+    fn unrouted_cidrs_ignores_other_targets_and_blackholes() {
+        let tables = vec![RouteTable {
+            id: "rtb-1".to_string(),
+            routes: vec![
+                Route {
+                    destination: "10.1.0.0/16".to_string(),
+                    target: "pcx-1".to_string(),
+                    blackhole: false,
+                },
+                Route {
+                    destination: "10.2.0.0/16".to_string(),
+                    target: "tgw-1".to_string(),
+                    blackhole: false,
+                },
+                Route {
+                    destination: "10.3.0.0/16".to_string(),
+                    target: "pcx-1".to_string(),
+                    blackhole: true,
+                },
+            ],
+            ..Default::default()
+        }];
+        let cidrs = vec!["10.1.0.0/16".to_string(), "10.2.0.0/16".to_string(), "10.3.0.0/16".to_string()];
+        assert_eq!(unrouted_cidrs(&tables, "pcx-1", &cidrs), vec!["10.2.0.0/16", "10.3.0.0/16"]);
+    }
+}
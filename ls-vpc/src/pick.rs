@@ -0,0 +1,238 @@
+//! pick.rs
+//! ---------------------------------------------------------------------------
+//! `--pick`: a built-in fuzzy finder.  A background scan feeds each VPC from
+//! [`scan_stream`] to the picker as soon as it is complete, and the picker
+//! lists them while the remaining regions are still being scanned.
+
+use crate::{ApiStats, Config, VpcEvent, VpcSummary, scan_stream};
+use eyre::Result;
+use futures::StreamExt;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use std::pin::pin;
+use std::sync::mpsc::{Receiver, TryRecvError, channel};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// A VPC as sent to the feed, keyed by (account, region, vpc-id)
+pub type PickItem = ((String, String, String), VpcSummary);
+
+/// Start scanning with `config` in the background, sending every VPC to the
+/// returned feed as it completes.  The feed closes when the scan ends; the
+/// task yields the scan's error, if any.
+pub fn spawn_feed(config: Config) -> (Receiver<PickItem>, JoinHandle<Result<()>>) {
+    let (tx, rx) = channel();
+    let scan = tokio::spawn(async move {
+        let stats = ApiStats::default();
+        let mut events = pin!(scan_stream(&config, &stats));
+        while let Some(event) = events.next().await {
+            if let VpcEvent::VpcScanned {
+                account,
+                region,
+                vpc_id,
+                summary,
+            } = event?
+            {
+                // The picker has gone away once something was picked
+                if tx.send(((account, region, vpc_id), *summary)).is_err() {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    });
+    (rx, scan)
+}
+
+/// Fuzzy-match `query` against `candidate` (case-insensitive subsequence).
+/// Higher scores are better matches; `None` means no match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0i64;
+    let mut pos = 0usize;
+    let mut last: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = pos + candidate[pos..].iter().position(|&c| c == q)?;
+        score += 1;
+        match last {
+            Some(l) if l + 1 == found => score += 5,
+            Some(l) => score -= (found - l - 1).min(5) as i64,
+            None => {}
+        }
+        if found == 0 || matches!(candidate[found - 1], ' ' | '-' | '_' | '/' | '.' | ':') {
+            score += 3;
+        }
+        last = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+/// Text the picker matches against and displays for a VPC
+pub fn label(item: &PickItem) -> String {
+    let ((account, region, vpc_id), s) = item;
+    format!(
+        "{} {} {} {} {}",
+        account,
+        region,
+        vpc_id,
+        s.cidrs.join(","),
+        s.name.as_deref().unwrap_or_default()
+    )
+}
+
+/// Indexes of `items` matching `query`, best match first
+pub fn ranked(query: &str, labels: &[String]) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = labels
+        .iter()
+        .enumerate()
+        .filter_map(|(i, l)| fuzzy_score(query, l).map(|s| (s, i)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Run the picker over the feed until the user picks a VPC (`Some`) or
+/// cancels with Esc / Ctrl-C (`None`).
+pub fn pick(feed: Receiver<PickItem>) -> Result<Option<PickItem>> {
+    let mut terminal = ratatui::init();
+    let outcome = pick_loop(&mut terminal, feed);
+    ratatui::restore();
+    outcome
+}
+
+fn pick_loop(terminal: &mut ratatui::DefaultTerminal, feed: Receiver<PickItem>) -> Result<Option<PickItem>> {
+    let mut items: Vec<PickItem> = Vec::new();
+    let mut labels: Vec<String> = Vec::new();
+    let mut query = String::new();
+    let mut selected = 0usize;
+    let mut scanning = true;
+
+    loop {
+        while scanning {
+            match feed.try_recv() {
+                Ok(item) => {
+                    labels.push(label(&item));
+                    items.push(item);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => scanning = false,
+            }
+        }
+
+        let matches = ranked(&query, &labels);
+        selected = selected.min(matches.len().saturating_sub(1));
+
+        terminal.draw(|frame| {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(1)])
+                .split(frame.area());
+            let progress = if scanning { "scanning…" } else { "done" };
+            frame.render_widget(
+                Paragraph::new(format!("> {query}")).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!("{}/{} VPCs ({progress})", matches.len(), labels.len())),
+                ),
+                rows[0],
+            );
+            let list: Vec<ListItem> = matches.iter().map(|&i| ListItem::new(labels[i].clone())).collect();
+            let mut state = ListState::default();
+            if !matches.is_empty() {
+                state.select(Some(selected));
+            }
+            frame.render_stateful_widget(
+                List::new(list).highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+                rows[1],
+                &mut state,
+            );
+        })?;
+
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if ctrl => return Ok(None),
+            KeyCode::Enter => {
+                return Ok(matches.get(selected).map(|&i| items.swap_remove(i)));
+            }
+            KeyCode::Down => selected += 1,
+            KeyCode::Char('n') if ctrl => selected += 1,
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => selected = selected.saturating_sub(1),
+            KeyCode::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_requires_subsequence() {
+        assert!(fuzzy_score("pv", "prod-vpc").is_some());
+        assert!(fuzzy_score("PRD", "prod-vpc").is_some());
+        assert!(fuzzy_score("vp", "prod").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_contiguous_and_word_starts() {
+        let contiguous = fuzzy_score("prod", "us-east-1 vpc-1 prod").unwrap();
+        let scattered = fuzzy_score("prod", "us-east-1 vpc-1 p-r-o-d").unwrap();
+        assert!(contiguous > scattered);
+    }
+
+    #[test]
+    fn ranked_orders_best_first_and_drops_misses() {
+        let labels = vec![
+            "us-east-1 vpc-111 10.0.0.0/16 staging".to_string(),
+            "us-west-2 vpc-222 10.1.0.0/16 prod".to_string(),
+            "us-east-1 vpc-333 10.2.0.0/16 shared".to_string(),
+        ];
+        assert_eq!(ranked("prod", &labels), vec![1]);
+        assert_eq!(ranked("", &labels), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn label_includes_account_region_id_cidrs_and_name() {
+        let item = (
+            ("111111111111".to_string(), "us-east-1".to_string(), "vpc-1".to_string()),
+            VpcSummary {
+                name: Some("prod".to_string()),
+                cidrs: vec!["10.0.0.0/16".to_string()],
+                ..Default::default()
+            },
+        );
+        assert_eq!(label(&item), "111111111111 us-east-1 vpc-1 10.0.0.0/16 prod");
+    }
+
+    #[tokio::test]
+    async fn feed_closes_when_the_scan_ends() {
+        let config = Config {
+            regions: Vec::new(),
+            ..Config::default()
+        };
+        let (feed, scan) = spawn_feed(config);
+        scan.await.unwrap().unwrap();
+        assert!(feed.recv().is_err());
+    }
+}
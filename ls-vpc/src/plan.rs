@@ -0,0 +1,226 @@
+//! plan.rs
+//! ---------------------------------------------------------------------------
+//! `--dry-run`: resolve the account, role, regions and scanners a scan would
+//! use and print the calls it would make, without a single Describe call.
+//! The per-VPC operations mirror `scan_vpc`; the estimate counts one call
+//! per operation (paginated listings come on top) and can only be totalled
+//! when `--vpc-ids` names the VPCs, since listing them is a Describe call.
+
+use crate::scanner::ServiceScanner;
+use crate::{ApiStats, Config, OutputFormat, whoami};
+use eyre::Result;
+
+/// Everything a scan would do, resolved without scanning
+#[derive(Debug, Clone, Default)]
+pub struct ScanPlan {
+    /// Account the VPCs are listed in
+    pub account: String,
+    /// Role the scan runs under (`None` = the caller's own credentials)
+    pub role: Option<String>,
+    pub regions: Vec<String>,
+    /// VPCs named with `--vpc-ids` (empty = every VPC)
+    pub vpc_ids: Vec<String>,
+    /// Service scanners run against each VPC
+    pub scanners: Vec<&'static str>,
+    /// Calls made once per region
+    pub region_operations: Vec<&'static str>,
+    /// Calls made for every VPC
+    pub vpc_operations: Vec<&'static str>,
+    /// Calls made once for the whole run
+    pub run_operations: Vec<&'static str>,
+}
+
+impl ScanPlan {
+    /// Calls made before any VPC is scanned
+    pub fn fixed_calls(&self) -> usize {
+        self.run_operations.len() + self.regions.len() * self.region_operations.len()
+    }
+
+    /// Estimated total, known only when the VPCs are named (a VPC ID lives
+    /// in exactly one region)
+    pub fn estimated_calls(&self) -> Option<usize> {
+        (!self.vpc_ids.is_empty()).then(|| self.fixed_calls() + self.vpc_ids.len() * self.vpc_operations.len())
+    }
+}
+
+/// Operations each region needs before its VPCs are scanned
+pub fn region_operations(config: &Config) -> Vec<&'static str> {
+    let mut ops = Vec::new();
+    if config.assume_role.is_some() {
+        ops.push("sts:AssumeRole");
+    }
+    ops.push("ec2:DescribeVpcs");
+    ops
+}
+
+/// Operations `scan_vpc` runs for each VPC with `config` and `scanners`
+pub fn vpc_operations(config: &Config, scanners: &[Box<dyn ServiceScanner>]) -> Vec<&'static str> {
+    let mut ops = vec![
+        "ec2:DescribeVpcPeeringConnections",
+        "ec2:DescribeVpcPeeringConnections",
+        "ec2:DescribeInternetGateways",
+        "ec2:DescribeVpcs",
+    ];
+    if config.flow_logs || config.issues {
+        ops.extend(["ec2:DescribeFlowLogs", "logs:DescribeLogGroups"]);
+    } else if config.fail_on.as_ref().is_some_and(|p| p.expr.uses("flow_logs")) {
+        ops.push("ec2:DescribeFlowLogs");
+    }
+    if config.sg_graph.is_some() || config.unused_sgs {
+        ops.push("ec2:DescribeSecurityGroups");
+    }
+    if config.unused_sgs {
+        ops.push("ec2:DescribeNetworkInterfaces");
+    }
+    if config.issues || config.egress || config.emit_tf_imports {
+        ops.push("ec2:DescribeRouteTables");
+    }
+    if config.issues {
+        ops.push("ec2:DescribeVpcPeeringConnections");
+    }
+    if config.ip_threshold.is_some()
+        || config.egress
+        || config.format == Some(OutputFormat::Drawio)
+        || config.emit_tf_imports
+    {
+        ops.push("ec2:DescribeSubnets");
+    }
+    if config.egress {
+        ops.push("ec2:DescribeNatGateways");
+    }
+    if config.with_cost || config.issues {
+        ops.push("ec2:DescribeVpcEndpoints");
+    }
+    if config.issues {
+        ops.extend([
+            "ec2:DescribeNatGateways",
+            "cloudwatch:GetMetricStatistics",
+            "rds:DescribeDBSubnetGroups",
+        ]);
+    }
+    if config.endpoint_services {
+        ops.extend([
+            "ec2:DescribeVpcEndpointServiceConfigurations",
+            "elbv2:DescribeLoadBalancers",
+        ]);
+    }
+    if config.nau {
+        ops.push("cloudwatch:GetMetricStatistics");
+    }
+    if config.summary_only {
+        ops.push("ec2:DescribeNetworkInterfaces");
+    } else {
+        for s in scanners {
+            ops.extend_from_slice(s.operations());
+        }
+        if config.with_stacks {
+            ops.push("cloudformation:DescribeStackResources");
+        }
+        if !config.show_tags.is_empty() {
+            ops.extend(["rds:ListTagsForResource", "docdb:ListTagsForResource"]);
+        }
+    }
+    ops
+}
+
+/// Operations run once per scan
+pub fn run_operations(config: &Config) -> Vec<&'static str> {
+    let mut ops = Vec::new();
+    if config.spend_days.is_some() {
+        ops.push("ce:GetCostAndUsage");
+    }
+    ops
+}
+
+/// Resolve the plan: the caller's identity (or the role's account) and the
+/// operations for `config`.  Only GetCallerIdentity is called.
+pub async fn plan(config: &Config, stats: &ApiStats, scanners: &[Box<dyn ServiceScanner>]) -> Result<ScanPlan> {
+    let identity = whoami(config, stats).await?;
+    let account = match config.assume_role.as_deref().and_then(|arn| arn.split(':').nth(4)) {
+        Some(account) => account.to_owned(),
+        None => identity.account,
+    };
+    Ok(ScanPlan {
+        account,
+        role: config.assume_role.clone(),
+        regions: config.regions.clone(),
+        vpc_ids: config.vpc_ids.clone(),
+        scanners: if config.summary_only { Vec::new() } else { scanners.iter().map(|s| s.name()).collect() },
+        region_operations: region_operations(config),
+        vpc_operations: vpc_operations(config, scanners),
+        run_operations: run_operations(config),
+    })
+}
+
+/// Render the plan: a header with the estimate, then one line per setting
+pub fn format_plan(plan: &ScanPlan) -> String {
+    let list = |items: &[&str]| if items.is_empty() { "-".to_owned() } else { items.join(", ") };
+    let estimate = match plan.estimated_calls() {
+        Some(total) => format!("~{} API calls", total),
+        None => format!(
+            "~{} API calls + {} per VPC found",
+            plan.fixed_calls(),
+            plan.vpc_operations.len()
+        ),
+    };
+    let vpcs: Vec<&str> = plan.vpc_ids.iter().map(String::as_str).collect();
+    let regions: Vec<&str> = plan.regions.iter().map(String::as_str).collect();
+    let mut out = format!(
+        "Dry run for account {}: {} regions, {}\n",
+        plan.account,
+        plan.regions.len(),
+        estimate
+    );
+    out.push_str(&format!("Role:\t{}\n", plan.role.as_deref().unwrap_or("(caller credentials)")));
+    out.push_str(&format!("Regions:\t{}\n", list(&regions)));
+    out.push_str(&format!("VPCs:\t{}\n", if vpcs.is_empty() { "all".to_owned() } else { vpcs.join(", ") }));
+    out.push_str(&format!("Scanners:\t{}\n", list(&plan.scanners)));
+    out.push_str(&format!("Per region:\t{}\n", list(&plan.region_operations)));
+    out.push_str(&format!("Per VPC:\t{}\n", list(&plan.vpc_operations)));
+    out.push_str(&format!("Once:\t{}\n", list(&plan.run_operations)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service_scanners;
+
+    #[test]
+    fn vpc_operations_include_scanners_only_in_detail_mode() {
+        let summary = vpc_operations(&Config::default(), &service_scanners());
+        assert_eq!(summary.len(), 5);
+        let detail = Config {
+            summary_only: false,
+            ..Config::default()
+        };
+        let ops = vpc_operations(&detail, &service_scanners());
+        assert!(ops.contains(&"ec2:DescribeInstances") && ops.contains(&"elbv2:DescribeTargetGroups"));
+    }
+
+    #[test]
+    fn vpc_operations_list_tag_lookups_of_both_services() {
+        let config = Config {
+            summary_only: false,
+            show_tags: vec!["team".to_string()],
+            ..Config::default()
+        };
+        let ops = vpc_operations(&config, &service_scanners());
+        assert!(ops.contains(&"rds:ListTagsForResource") && ops.contains(&"docdb:ListTagsForResource"));
+    }
+
+    #[test]
+    fn estimated_calls_need_named_vpcs() {
+        let mut plan = ScanPlan {
+            regions: vec!["us-east-1".to_string(), "us-west-2".to_string()],
+            region_operations: vec!["sts:AssumeRole", "ec2:DescribeVpcs"],
+            vpc_operations: vec!["ec2:DescribeVpcs"; 4],
+            ..Default::default()
+        };
+        assert_eq!(plan.estimated_calls(), None);
+        assert!(format_plan(&plan).contains("~4 API calls + 4 per VPC found"));
+
+        plan.vpc_ids = vec!["vpc-a".to_string(), "vpc-b".to_string(), "vpc-c".to_string()];
+        assert_eq!(plan.estimated_calls(), Some(2 * 2 + 3 * 4));
+    }
+}
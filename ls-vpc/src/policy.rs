@@ -0,0 +1,285 @@
+//! policy.rs
+//! ---------------------------------------------------------------------------
+//! `--fail-on`: a tiny expression language over VPC summary fields, so ls-vpc
+//! can gate a pipeline (`--fail-on 'public && !flow_logs'`).  Expressions
+//! combine fields and integers with `!`, `&&`, `||`, parentheses and the
+//! comparisons `== != < <= > >=`.  Booleans count as 0/1 and a number on its
+//! own is true when non-zero.
+
+use crate::VpcSummary;
+use eyre::{Result, bail};
+
+/// Fields an expression may reference, with what they mean.
+pub const FIELDS: &[(&str, &str)] = &[
+    ("public", "the VPC has an internet gateway"),
+    ("flow_logs", "at least one flow log is attached to the VPC"),
+    ("named", "the VPC has a Name tag"),
+    ("peers", "number of peered VPCs"),
+    ("cidrs", "number of CIDR blocks"),
+    ("issues", "number of issues found (runs the issue checks)"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Field(&'static str),
+    Number(i64),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Cmp(Box<Expr>, CmpOp, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(i64),
+    Op(&'static str),
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(digits.parse()?));
+        } else {
+            let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            let op = ["&&", "||", "==", "!=", "<=", ">="]
+                .into_iter()
+                .find(|op| *op == two)
+                .or_else(|| ["!", "<", ">", "(", ")"].into_iter().find(|op| op.starts_with(c)));
+            let Some(op) = op else {
+                bail!("Unexpected character '{}' in --fail-on expression", c);
+            };
+            i += op.len();
+            tokens.push(Token::Op(op));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if self.peek_op() == Some(op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut lhs = self.and()?;
+        while self.eat("||") {
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut lhs = self.unary()?;
+        while self.eat("&&") {
+            lhs = Expr::And(Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let lhs = self.primary()?;
+        let op = match self.peek_op() {
+            Some("==") => CmpOp::Eq,
+            Some("!=") => CmpOp::Ne,
+            Some("<") => CmpOp::Lt,
+            Some("<=") => CmpOp::Le,
+            Some(">") => CmpOp::Gt,
+            Some(">=") => CmpOp::Ge,
+            _ => return Ok(lhs),
+        };
+        self.pos += 1;
+        Ok(Expr::Cmp(Box::new(lhs), op, Box::new(self.primary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Ident(name)) => match FIELDS.iter().find(|(f, _)| *f == name) {
+                Some((field, _)) => Ok(Expr::Field(field)),
+                None => {
+                    let known: Vec<&str> = FIELDS.iter().map(|(f, _)| *f).collect();
+                    bail!("Unknown field '{}' in --fail-on. Known fields: {}", name, known.join(", "))
+                }
+            },
+            Some(Token::Op("(")) => {
+                let inner = self.or()?;
+                if !self.eat(")") {
+                    bail!("Missing ')' in --fail-on expression");
+                }
+                Ok(inner)
+            }
+            Some(Token::Op(op)) => bail!("Unexpected '{}' in --fail-on expression", op),
+            None => bail!("Incomplete --fail-on expression"),
+        }
+    }
+}
+
+impl Expr {
+    /// Whether the expression references `field`
+    pub fn uses(&self, field: &str) -> bool {
+        match self {
+            Expr::Field(f) => *f == field,
+            Expr::Number(_) => false,
+            Expr::Not(e) => e.uses(field),
+            Expr::And(a, b) | Expr::Or(a, b) | Expr::Cmp(a, _, b) => a.uses(field) || b.uses(field),
+        }
+    }
+
+    fn value(&self, s: &VpcSummary) -> i64 {
+        let flag = |b: bool| i64::from(b);
+        match self {
+            Expr::Field(f) => match *f {
+                "public" => flag(s.public),
+                "flow_logs" => flag(s.flow_logs),
+                "named" => flag(s.name.is_some()),
+                "peers" => s.peers.len() as i64,
+                "cidrs" => s.cidrs.len() as i64,
+                "issues" => s.issues.len() as i64,
+                _ => 0,
+            },
+            Expr::Number(n) => *n,
+            Expr::Not(e) => flag(e.value(s) == 0),
+            Expr::And(a, b) => flag(a.value(s) != 0 && b.value(s) != 0),
+            Expr::Or(a, b) => flag(a.value(s) != 0 || b.value(s) != 0),
+            Expr::Cmp(a, op, b) => {
+                let (a, b) = (a.value(s), b.value(s));
+                flag(match op {
+                    CmpOp::Eq => a == b,
+                    CmpOp::Ne => a != b,
+                    CmpOp::Lt => a < b,
+                    CmpOp::Le => a <= b,
+                    CmpOp::Gt => a > b,
+                    CmpOp::Ge => a >= b,
+                })
+            }
+        }
+    }
+}
+
+/// A parsed `--fail-on` condition
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    /// The expression as written
+    pub source: String,
+    pub expr: Expr,
+}
+
+impl Policy {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+        };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            bail!("Unexpected trailing input in --fail-on expression '{}'", source);
+        }
+        Ok(Policy {
+            source: source.to_owned(),
+            expr,
+        })
+    }
+
+    /// Whether a VPC violates the policy
+    pub fn matches(&self, s: &VpcSummary) -> bool {
+        self.expr.value(s) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vpc(public: bool, flow_logs: bool, peers: usize) -> VpcSummary {
+        VpcSummary {
+            public,
+            flow_logs,
+            peers: (0..peers).map(|n| format!("vpc-{n}")).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn public_without_flow_logs() {
+        let policy = Policy::parse("public && !flow_logs").unwrap();
+        assert!(policy.matches(&vpc(true, false, 0)));
+        assert!(!policy.matches(&vpc(true, true, 0)));
+        assert!(!policy.matches(&vpc(false, false, 0)));
+        assert!(policy.expr.uses("flow_logs"));
+    }
+
+    #[test]
+    fn comparisons_and_precedence() {
+        let policy = Policy::parse("!named || peers >= 2 && public").unwrap();
+        let mut named = vpc(false, false, 3);
+        named.name = Some("prod".to_string());
+        assert!(!policy.matches(&named));
+        named.public = true;
+        assert!(policy.matches(&named));
+        assert!(policy.matches(&vpc(false, false, 0)));
+        assert!(Policy::parse("(public || flow_logs) && cidrs != 1").unwrap().matches(&vpc(true, false, 0)));
+    }
+
+    #[test]
+    fn parse_rejects_bad_expressions() {
+        assert!(Policy::parse("public && ").is_err());
+        assert!(Policy::parse("encrypted").is_err());
+        assert!(Policy::parse("(public").is_err());
+        assert!(Policy::parse("public flow_logs").is_err());
+        assert!(Policy::parse("public & flow_logs").is_err());
+    }
+}
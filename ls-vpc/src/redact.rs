@@ -0,0 +1,413 @@
+//! redact.rs
+//! ---------------------------------------------------------------------------
+//! `--redact`: anonymize a scan before it is printed, so topology exports
+//! (tables, DOT, draw.io, TUI) can be shared outside the account.  Account
+//! IDs, resource IDs, names and tag values become stable aliases (`vpc-1`,
+//! `000000000001` …) and CIDRs get their network bits permuted prefix by
+//! prefix, starting from `10.0.0.0` / `fd00::`, so peerings, containment,
+//! overlaps and subnet sizes survive.
+
+use crate::plan::ScanPlan;
+use crate::{ResourceRecord, ScanResult, VpcSummary};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Consistent value → alias mapping for one run, per namespace; shared by
+/// the ls-vpc and ls-rds redactors
+#[derive(Debug, Default)]
+pub struct Aliases {
+    aliases: HashMap<(String, String), String>,
+    counts: HashMap<String, usize>,
+}
+
+impl Aliases {
+    /// Alias for `value` in the `kind` namespace, e.g. `name-3`; account
+    /// aliases stay 12 digits (`000000000001` …)
+    pub fn alias(&mut self, kind: &str, value: &str) -> String {
+        if value.is_empty() || value == "-" {
+            return value.to_owned();
+        }
+        self.alias_with(kind, value, |kind, n| {
+            if kind == "account" { format!("{:012}", n) } else { format!("{}-{}", kind, n) }
+        })
+    }
+
+    /// Alias for `value` in the `kind` namespace, built by `make` from the
+    /// next counter of the namespace when `value` is new
+    pub fn alias_with(&mut self, kind: &str, value: &str, make: impl FnOnce(&str, usize) -> String) -> String {
+        if let Some(alias) = self.aliases.get(&(kind.to_owned(), value.to_owned())) {
+            return alias.clone();
+        }
+        let n = self.counts.entry(kind.to_owned()).or_default();
+        *n += 1;
+        let alias = make(kind, *n);
+        self.aliases.insert((kind.to_owned(), value.to_owned()), alias.clone());
+        alias
+    }
+
+    pub fn account(&mut self, account_id: &str) -> String {
+        self.alias("account", account_id)
+    }
+
+    /// Replace every value aliased so far inside free text (issues, warnings)
+    pub fn text(&self, text: &str) -> String {
+        let mut known: Vec<(&String, &String)> = self.aliases.iter().map(|((_, v), a)| (v, a)).collect();
+        known.sort_by_key(|(value, _)| std::cmp::Reverse(value.len()));
+        known
+            .into_iter()
+            .fold(text.to_owned(), |out, (value, alias)| out.replace(value.as_str(), alias))
+    }
+}
+
+/// Redacts a VPC scan with one set of aliases for the whole run
+#[derive(Debug, Default)]
+pub struct Redactor {
+    aliases: Aliases,
+    /// (address width, depth, network bits above it) → the branch seen
+    /// first there, which is mapped onto the base address
+    branches: HashMap<(u32, u32, u128), bool>,
+}
+
+/// Whether `value` looks like an EC2-style resource ID (`vpc-0a1b2c3d`)
+fn is_resource_id(value: &str) -> bool {
+    match value.split_once('-') {
+        Some((prefix, suffix)) => {
+            !prefix.is_empty()
+                && prefix.chars().all(|c| c.is_ascii_lowercase())
+                && suffix.len() >= 8
+                && suffix.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+/// `bits` (an address `width` bits wide) with its first `len` bits
+/// permuted: at every depth the branch seen first takes the bit of `base`
+/// and the other branch its complement.  CIDRs sharing a prefix share its
+/// image and different prefixes never collide, so containment survives
+fn permute(branches: &mut HashMap<(u32, u32, u128), bool>, width: u32, base: u128, bits: u128, len: u32) -> u128 {
+    let mut out = bits;
+    for depth in 0..len.min(width) {
+        let shift = width - 1 - depth;
+        let bit = (bits >> shift) & 1 == 1;
+        let above = if depth == 0 { 0 } else { bits >> (shift + 1) };
+        let first = *branches.entry((width, depth, above)).or_insert(bit);
+        let mapped = (bit != first) != ((base >> shift) & 1 == 1);
+        out = (out & !(1 << shift)) | (u128::from(mapped) << shift);
+    }
+    out
+}
+
+impl Redactor {
+    /// Alias for `value` in the `kind` namespace, e.g. `name-3`
+    pub fn alias(&mut self, kind: &str, value: &str) -> String {
+        self.aliases.alias(kind, value)
+    }
+
+    pub fn account(&mut self, account_id: &str) -> String {
+        self.aliases.account(account_id)
+    }
+
+    /// Resource ID aliased within its own prefix (`subnet-0abc…` → `subnet-2`);
+    /// anything else that is not an ID (e.g. `local`) is kept
+    pub fn id(&mut self, id: &str) -> String {
+        match id.split_once('-') {
+            Some((prefix, _)) if is_resource_id(id) => self.alias(prefix, id),
+            _ => id.to_owned(),
+        }
+    }
+
+    /// A display name, which is often the resource ID itself
+    pub fn name(&mut self, name: &str) -> String {
+        if is_resource_id(name) { self.id(name) } else { self.alias("name", name) }
+    }
+
+    /// CIDR with its network bits permuted into `10.0.0.0`-space (IPv4) or
+    /// `fd00::`-space (IPv6) and its prefix length kept; CIDRs that contain
+    /// or overlap each other still do, and distinct CIDRs stay distinct
+    pub fn cidr(&mut self, cidr: &str) -> String {
+        let Some((addr, len)) = cidr.split_once('/') else {
+            return self.alias("cidr", cidr);
+        };
+        let Ok(bits) = len.parse::<u32>() else {
+            return self.alias("cidr", cidr);
+        };
+        let branches = &mut self.branches;
+        if let Ok(ip) = addr.parse::<Ipv4Addr>() {
+            return self.aliases.alias_with("cidr", cidr, |_, _| {
+                let base = u128::from(u32::from(Ipv4Addr::new(10, 0, 0, 0)));
+                let net = permute(branches, 32, base, u128::from(u32::from(ip)), bits);
+                format!("{}/{}", Ipv4Addr::from(net as u32), len)
+            });
+        }
+        match addr.parse::<Ipv6Addr>() {
+            Ok(ip) => self.aliases.alias_with("cidr", cidr, |_, _| {
+                let base = u128::from(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 0));
+                format!("{}/{}", Ipv6Addr::from(permute(branches, 128, base, u128::from(ip), bits)), len)
+            }),
+            Err(_) => self.alias("cidr", cidr),
+        }
+    }
+
+    /// ARN with its account and resource aliased; the partition, service,
+    /// region and resource type are kept
+    pub fn arn(&mut self, arn: &str) -> String {
+        let parts: Vec<&str> = arn.splitn(6, ':').collect();
+        if parts.len() < 6 || parts[0] != "arn" {
+            return self.name(arn);
+        }
+        let account = self.account(parts[4]);
+        let (rtype, resource) = match parts[5].find(['/', ':']) {
+            Some(i) => parts[5].split_at(i + 1),
+            None => ("", parts[5]),
+        };
+        let resource = if is_resource_id(resource) { self.id(resource) } else { self.alias("resource", resource) };
+        format!("arn:{}:{}:{}:{}:{}{}", parts[1], parts[2], parts[3], account, rtype, resource)
+    }
+
+    /// Tag values aliased; the `Name` tag shares the name namespace
+    pub fn tags(&mut self, tags: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        tags.iter()
+            .map(|(k, v)| {
+                let v = if k == "Name" { self.name(v) } else { self.alias("tag", v) };
+                (k.clone(), v)
+            })
+            .collect()
+    }
+
+    /// Replace every value aliased so far inside free text (issues, warnings)
+    pub fn text(&self, text: &str) -> String {
+        self.aliases.text(text)
+    }
+
+    fn redact_resource(&mut self, res: &mut ResourceRecord) {
+        res.arn = self.arn(&res.arn);
+        res.name = self.name(&res.name);
+        res.tags = self.tags(&res.tags);
+        res.stack = res.stack.as_deref().map(|s| self.alias("stack", s));
+    }
+
+    /// Redact one VPC summary in place (everything but its issues, which are
+    /// rewritten once every ID is known)
+    pub fn redact_vpc(&mut self, s: &mut VpcSummary) {
+        s.account = self.account(&s.account);
+        s.name = s.name.as_deref().map(|n| self.name(n));
+        s.cidrs = s.cidrs.iter().map(|c| self.cidr(c)).collect();
+        s.peers = s.peers.iter().map(|p| self.id(p)).collect();
+        for res in &mut s.resources {
+            self.redact_resource(res);
+        }
+        for g in &mut s.security_groups {
+            g.id = self.id(&g.id);
+            g.name = self.name(&g.name);
+            g.vpc_id = self.id(&g.vpc_id);
+            for rule in &mut g.rules {
+                rule.peer_group = self.id(&rule.peer_group);
+            }
+        }
+        for rt in &mut s.route_tables {
+            rt.id = self.id(&rt.id);
+            rt.name = rt.name.as_deref().map(|n| self.name(n));
+            rt.subnets = rt.subnets.iter().map(|id| self.id(id)).collect();
+            for route in &mut rt.routes {
+                route.destination = if route.destination.contains('/') {
+                    self.cidr(&route.destination)
+                } else {
+                    self.id(&route.destination)
+                };
+                route.target = self.id(&route.target);
+            }
+            rt.tags = self.tags(&rt.tags);
+        }
+        for p in &mut s.peerings {
+            p.id = self.id(&p.id);
+            for side in [&mut p.requester, &mut p.accepter] {
+                side.vpc_id = self.id(&side.vpc_id);
+                side.owner = self.account(&side.owner);
+                side.cidrs = side.cidrs.iter().map(|c| self.cidr(c)).collect();
+            }
+        }
+        for subnet in &mut s.subnets {
+            subnet.id = self.id(&subnet.id);
+            subnet.name = subnet.name.as_deref().map(|n| self.name(n));
+            subnet.cidr = self.cidr(&subnet.cidr);
+            subnet.tags = self.tags(&subnet.tags);
+        }
+        for ep in &mut s.endpoints {
+            ep.id = self.id(&ep.id);
+            if ep.service.contains("vpce-svc") {
+                ep.service = self.alias("service", &ep.service);
+            }
+            ep.subnets = ep.subnets.iter().map(|id| self.id(id)).collect();
+            ep.tags = self.tags(&ep.tags);
+        }
+        for ngw in &mut s.nat_gateways {
+            ngw.id = self.id(&ngw.id);
+        }
+        for g in &mut s.db_subnet_groups {
+            g.name = self.name(&g.name);
+            g.arn = self.arn(&g.arn);
+            g.subnets = g.subnets.iter().map(|id| self.id(id)).collect();
+        }
+        for svc in &mut s.endpoint_services {
+            svc.id = self.id(&svc.id);
+            svc.name = self.alias("service", &svc.name);
+            svc.allowed_principals = svc
+                .allowed_principals
+                .iter()
+                .map(|p| if p == "*" { p.clone() } else { self.arn(p) })
+                .collect();
+            svc.load_balancers = svc.load_balancers.iter().map(|lb| self.arn(lb)).collect();
+        }
+        for fl in &mut s.flow_log_details {
+            fl.id = self.id(&fl.id);
+            fl.destination = if fl.destination.starts_with("arn:") {
+                self.arn(&fl.destination)
+            } else {
+                self.alias("log-group", &fl.destination)
+            };
+        }
+    }
+
+    /// Redact a whole scan in place, re-keying the VPC map by aliased
+    /// account and ID
+    pub fn redact_result(&mut self, result: &mut ScanResult) {
+        let mut vpcs = BTreeMap::new();
+        for ((account, region, vpc_id), mut summary) in std::mem::take(&mut result.vpcs) {
+            let account = self.account(&account);
+            let vpc_id = self.id(&vpc_id);
+            self.redact_vpc(&mut summary);
+            vpcs.insert((account, region, vpc_id), summary);
+        }
+        for summary in vpcs.values_mut() {
+            for issue in &mut summary.issues {
+                issue.resource = self.text(&issue.resource);
+                issue.detail = self.text(&issue.detail);
+            }
+        }
+        result.vpcs = vpcs;
+        result.unattributed_spend = std::mem::take(&mut result.unattributed_spend)
+            .into_iter()
+            .map(|(account, usd)| (self.account(&account), usd))
+            .collect();
+        result.warnings = result.warnings.iter().map(|w| self.text(w)).collect();
+        for error in &mut result.errors {
+            error.account_id = error.account_id.as_deref().map(|a| self.account(a));
+            error.message = self.text(&error.message);
+        }
+    }
+
+    /// Redact a `--dry-run` plan in place
+    pub fn redact_plan(&mut self, plan: &mut ScanPlan) {
+        plan.account = self.account(&plan.account);
+        plan.role = plan.role.as_deref().map(|r| self.arn(r));
+        plan.vpc_ids = plan.vpc_ids.iter().map(|v| self.id(v)).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peering::{Peering, PeeringSide};
+    use crate::{Issue, Subnet};
+
+    #[test]
+    fn id_aliases_within_prefix() {
+        let mut r = Redactor::default();
+        assert_eq!(r.id("vpc-0a1b2c3d4e5f60718"), "vpc-1");
+        assert_eq!(r.id("subnet-0a1b2c3d"), "subnet-1");
+        assert_eq!(r.id("vpc-0a1b2c3d4e5f60718"), "vpc-1");
+        assert_eq!(r.id("local"), "local");
+        assert_eq!(r.name("prod-vpc"), "name-1");
+        assert_eq!(r.account("123456789012"), "000000000001");
+    }
+
+    #[test]
+    fn cidr_keeps_prefix_length_and_containment() {
+        let mut r = Redactor::default();
+        assert_eq!(r.cidr("172.31.0.0/16"), "10.0.0.0/16");
+        assert_eq!(r.cidr("172.31.16.0/20"), "10.0.0.0/20");
+        assert_eq!(r.cidr("172.31.0.0/20"), "10.0.16.0/20");
+        assert_eq!(r.cidr("192.168.4.0/24"), "74.0.0.0/24");
+        assert_eq!(r.cidr("2600:1f18::/56"), "fd00::/56");
+
+        let mut r = Redactor::default();
+        assert_eq!(r.cidr("10.0.0.0/8"), "10.0.0.0/8");
+        assert_eq!(r.cidr("172.16.0.0/12"), "138.0.0.0/12");
+        assert_eq!(r.cidr("172.20.0.0/16"), "138.0.0.0/16");
+        assert_eq!(r.cidr("10.1.0.0/16"), "10.0.0.0/16");
+    }
+
+    #[test]
+    fn cidr_never_collides_past_256_blocks() {
+        let mut r = Redactor::default();
+        let cidrs: Vec<String> = (0..300).map(|i| format!("{}.{}.0.0/16", 100 + i / 256, i % 256)).collect();
+        let redacted: std::collections::HashSet<String> = cidrs.iter().map(|c| r.cidr(c)).collect();
+        assert_eq!(redacted.len(), cidrs.len());
+    }
+
+    #[test]
+    fn arn_keeps_shape() {
+        let mut r = Redactor::default();
+        assert_eq!(
+            r.arn("arn:aws:ec2:us-east-1:123456789012:instance/i-0123456789abcdef0"),
+            "arn:aws:ec2:us-east-1:000000000001:instance/i-1"
+        );
+        assert_eq!(
+            r.arn("arn:aws:elasticloadbalancing:us-east-1:123456789012:loadbalancer/app/web/50dc6c495c0c9188"),
+            "arn:aws:elasticloadbalancing:us-east-1:000000000001:loadbalancer/resource-1"
+        );
+    }
+
+    #[test]
+    fn redact_result_preserves_peerings_and_rewrites_issues() {
+        let side = |vpc: &str, owner: &str, cidr: &str| PeeringSide {
+            vpc_id: vpc.to_string(),
+            region: "us-east-1".to_string(),
+            owner: owner.to_string(),
+            cidrs: vec![cidr.to_string()],
+        };
+        let summary = VpcSummary {
+            account: "111111111111".to_string(),
+            name: Some("prod".to_string()),
+            cidrs: vec!["172.31.0.0/16".to_string()],
+            peerings: vec![Peering {
+                id: "pcx-11112222".to_string(),
+                requester: side("vpc-aaaa1111", "111111111111", "172.31.0.0/16"),
+                accepter: side("vpc-bbbb2222", "222222222222", "10.20.0.0/16"),
+            }],
+            subnets: vec![Subnet {
+                id: "subnet-cccc3333".to_string(),
+                cidr: "172.31.0.0/20".to_string(),
+                ..Default::default()
+            }],
+            issues: vec![Issue {
+                kind: "ip-exhaustion",
+                resource: "subnet-cccc3333".to_string(),
+                detail: "172.31.0.0/20 is 95% used".to_string(),
+            }],
+            ..Default::default()
+        };
+        let mut result = ScanResult {
+            vpcs: BTreeMap::from([(
+                ("111111111111".to_string(), "us-east-1".to_string(), "vpc-aaaa1111".to_string()),
+                summary,
+            )]),
+            ..Default::default()
+        };
+        Redactor::default().redact_result(&mut result);
+
+        let ((account, _, vpc_id), s) = result.vpcs.iter().next().unwrap();
+        assert_eq!(account, "000000000001");
+        assert_eq!(s.account, "000000000001");
+        assert_eq!(vpc_id, "vpc-1");
+        assert_eq!(s.name.as_deref(), Some("name-1"));
+        assert_eq!(s.peerings[0].requester.vpc_id, "vpc-1");
+        assert_eq!(s.peerings[0].accepter.vpc_id, "vpc-2");
+        assert_eq!(s.peerings[0].requester.owner, "000000000001");
+        assert_eq!(s.peerings[0].requester.cidrs, s.cidrs);
+        assert_eq!(s.subnets[0].cidr, "10.0.0.0/20");
+        assert_eq!(s.issues[0].resource, "subnet-1");
+        assert_eq!(s.issues[0].detail, "10.0.0.0/20 is 95% used");
+    }
+}
@@ -0,0 +1,126 @@
+//! routes.rs
+//! ---------------------------------------------------------------------------
+//! Route-table collection.  Routes are flattened to a destination / target
+//! pair so the analysis passes in `issues.rs` can work on plain data.
+
+use crate::api_stats::CallCounter;
+use crate::scanner::ec2_tags;
+use aws_sdk_ec2 as ec2;
+use aws_sdk_ec2::types::RouteState;
+use aws_types::SdkConfig;
+use eyre::Result;
+use std::collections::BTreeMap;
+
+/// A single route inside a route table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Route {
+    /// Destination CIDR (v4 or v6) or prefix-list ID
+    pub destination: String,
+    /// Target ID (`igw-…`, `nat-…`, `pcx-…`, `tgw-…`, `local` …)
+    pub target: String,
+    /// Route points at a resource that no longer exists
+    pub blackhole: bool,
+}
+
+/// A route table with its associations.
+#[derive(Debug, Clone, Default)]
+pub struct RouteTable {
+    pub id: String,
+    pub name: Option<String>,
+    pub main: bool,
+    pub subnets: Vec<String>,
+    pub routes: Vec<Route>,
+    pub tags: BTreeMap<String, String>,
+}
+
+fn route_destination(r: &ec2::types::Route) -> String {
+    r.destination_cidr_block()
+        .or(r.destination_ipv6_cidr_block())
+        .or(r.destination_prefix_list_id())
+        .unwrap_or_default()
+        .to_owned()
+}
+
+fn route_target(r: &ec2::types::Route) -> String {
+    r.gateway_id()
+        .or(r.nat_gateway_id())
+        .or(r.transit_gateway_id())
+        .or(r.vpc_peering_connection_id())
+        .or(r.egress_only_internet_gateway_id())
+        .or(r.network_interface_id())
+        .or(r.instance_id())
+        .or(r.local_gateway_id())
+        .or(r.carrier_gateway_id())
+        .or(r.core_network_arn())
+        .unwrap_or("-")
+        .to_owned()
+}
+
+/// List every route table in a VPC.
+pub async fn list_route_tables(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<RouteTable>> {
+    let client = ec2::Client::new(conf);
+    let mut tables = Vec::new();
+
+    calls.record("ec2", "DescribeRouteTables", conf.region());
+    let mut pages = client
+        .describe_route_tables()
+        .filters(
+            ec2::types::Filter::builder()
+                .name("vpc-id")
+                .values(vpc_id)
+                .build(),
+        )
+        .into_paginator()
+        .items()
+        .send();
+    while let Some(rt) = pages.next().await {
+        let rt = rt?;
+        let tags = ec2_tags(rt.tags());
+        tables.push(RouteTable {
+            id: rt.route_table_id().unwrap_or_default().to_owned(),
+            name: tags.get("Name").cloned(),
+            main: rt.associations().iter().any(|a| a.main() == Some(true)),
+            subnets: rt
+                .associations()
+                .iter()
+                .filter_map(|a| a.subnet_id().map(str::to_owned))
+                .collect(),
+            routes: rt
+                .routes()
+                .iter()
+                .map(|r| Route {
+                    destination: route_destination(r),
+                    target: route_target(r),
+                    blackhole: r.state() == Some(&RouteState::Blackhole),
+                })
+                .collect(),
+            tags,
+        });
+    }
+    Ok(tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_destination_prefers_ipv4_then_ipv6_then_prefix_list() {
+        let r = ec2::types::Route::builder().destination_cidr_block("0.0.0.0/0").build();
+        assert_eq!(route_destination(&r), "0.0.0.0/0");
+        let r = ec2::types::Route::builder().destination_ipv6_cidr_block("::/0").build();
+        assert_eq!(route_destination(&r), "::/0");
+        let r = ec2::types::Route::builder().destination_prefix_list_id("pl-63a5400a").build();
+        assert_eq!(route_destination(&r), "pl-63a5400a");
+    }
+
+    #[test]
+    fn route_target_picks_populated_target() {
+        let r = ec2::types::Route::builder().nat_gateway_id("nat-0abc").build();
+        assert_eq!(route_target(&r), "nat-0abc");
+        let r = ec2::types::Route::builder().vpc_peering_connection_id("pcx-0abc").build();
+        assert_eq!(route_target(&r), "pcx-0abc");
+        let r = ec2::types::Route::builder().build();
+        assert_eq!(route_target(&r), "-");
+    }
+}
@@ -4,32 +4,280 @@
 //! implements the [`ServiceScanner`] trait and returns a list of
 //! [`ResourceRecord`] items discovered inside a single VPC.
 
+use crate::api_stats::CallCounter;
+use crate::db_subnet_groups::list_db_subnet_groups;
+use crate::endpoints::list_vpc_endpoints;
+use crate::routes::list_route_tables;
+use crate::subnets::list_subnets;
 use async_trait::async_trait;
+use aws_sdk_cloudformation as cfn;
+use aws_sdk_cloudformation::error::{DisplayErrorContext, ProvideErrorMetadata};
 use aws_sdk_docdb as docdb;
 use aws_sdk_ec2 as ec2;
 use aws_sdk_elasticloadbalancingv2 as elbv2;
 use aws_sdk_rds as rds;
 use aws_types::SdkConfig;
-use eyre::Result;
+use eyre::{Result, eyre};
+use futures::stream::{self, StreamExt};
+use log::trace;
+use std::collections::BTreeMap;
+
+/// Tag CloudFormation stamps on every resource it creates.
+pub const STACK_NAME_TAG: &str = "aws:cloudformation:stack-name";
 
 /// A single AWS resource that lives inside a VPC (instance, ENI, DB cluster…).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ResourceRecord {
     pub arn:  String,
     pub rtype: &'static str,
     pub name: String,
+    pub tags: BTreeMap<String, String>,
+    /// Owning CloudFormation stack (only resolved with `--with-stacks`).
+    pub stack: Option<String>,
+    /// Creation time in Unix seconds, for types that expose one (launch time
+    /// for instances, attach time for ENIs).
+    pub created: Option<i64>,
+}
+
+impl ResourceRecord {
+    /// Identifier CloudFormation uses as the PhysicalResourceId for this type.
+    pub fn physical_id(&self) -> &str {
+        match self.rtype {
+            "rds.instance" | "rds.cluster" | "docdb.cluster" => &self.name,
+            // arn:aws:rds:<region>:<account>:subgrp:<name>
+            "rds.db-subnet-group" => self.arn.rsplit(':').next().unwrap_or_default(),
+            _ => &self.arn,
+        }
+    }
+}
+
+/// Order records oldest first; records without a creation time go last.
+pub fn sort_by_age(recs: &mut [ResourceRecord]) {
+    recs.sort_by_key(|r| (r.created.is_none(), r.created));
+}
+
+/// Attribute an ENI to the service that owns it, e.g. `lambda: my-func` or
+/// `ec2: i-0abc…`.  Interface type and requester ID are the most reliable
+/// signals; the description fills in the rest.
+pub fn eni_owner(interface_type: &str, requester_id: &str, description: &str, instance_id: Option<&str>) -> String {
+    let strip = |prefix: &str| description.strip_prefix(prefix).unwrap_or(description).to_owned();
+
+    let (service, detail) = if interface_type == "lambda" || description.starts_with("AWS Lambda VPC ENI") {
+        ("lambda", strip("AWS Lambda VPC ENI-"))
+    } else if interface_type == "natGateway" || description.starts_with("Interface for NAT Gateway ") {
+        ("nat", strip("Interface for NAT Gateway "))
+    } else if matches!(interface_type, "vpc_endpoint" | "gateway_load_balancer_endpoint")
+        || description.starts_with("VPC Endpoint Interface ")
+    {
+        ("vpc-endpoint", strip("VPC Endpoint Interface "))
+    } else if matches!(interface_type, "load_balancer" | "network_load_balancer" | "gateway_load_balancer")
+        || description.starts_with("ELB ")
+    {
+        ("elb", strip("ELB "))
+    } else if requester_id == "amazon-rds" || description == "RDSNetworkInterface" {
+        ("rds", description.to_owned())
+    } else if description.starts_with("EFS mount target for ") {
+        ("efs", strip("EFS mount target for "))
+    } else if let Some(instance) = instance_id {
+        ("ec2", instance.to_owned())
+    } else if description.is_empty() {
+        return "unattached".to_owned();
+    } else {
+        return description.to_owned();
+    };
+
+    format!("{service}: {detail}")
+}
+
+/// Collect EC2-style tags into a map.
+pub(crate) fn ec2_tags(tags: &[ec2::types::Tag]) -> BTreeMap<String, String> {
+    tags.iter()
+        .filter_map(|t| Some((t.key()?.to_owned(), t.value().unwrap_or_default().to_owned())))
+        .collect()
+}
+
+/// State of a gateway's attachment to `vpc_id` (`None` = not attached to it).
+fn attachment_state<'a>(attachments: &'a [ec2::types::InternetGatewayAttachment], vpc_id: &str) -> Option<&'a str> {
+    attachments
+        .iter()
+        .find(|a| a.vpc_id() == Some(vpc_id))
+        .map(|a| a.state().map(|s| s.as_str()).unwrap_or("unknown"))
+}
+
+/// Display name of a gateway: its Name tag followed by the attachment state,
+/// e.g. `edge (available)`.
+fn gateway_name(tags: &BTreeMap<String, String>, state: Option<&str>) -> String {
+    let state = format!("({})", state.unwrap_or("detached"));
+    match tags.get("Name").filter(|n| !n.is_empty()) {
+        Some(name) => format!("{name} {state}"),
+        None => state,
+    }
+}
+
+/// Collect RDS-style tags into a map.
+fn rds_tags(tags: &[rds::types::Tag]) -> BTreeMap<String, String> {
+    tags.iter()
+        .filter_map(|t| Some((t.key()?.to_owned(), t.value().unwrap_or_default().to_owned())))
+        .collect()
+}
+
+/// Fetch ELBv2 tags for the given ARNs (DescribeTags takes at most 20 per call).
+async fn elb_tags(
+    client: &elbv2::Client,
+    calls: &CallCounter,
+    arns: &[String],
+) -> Result<BTreeMap<String, BTreeMap<String, String>>> {
+    let mut out = BTreeMap::new();
+    for chunk in arns.chunks(20) {
+        calls.record("elbv2", "DescribeTags", client.config().region());
+        let resp = client
+            .describe_tags()
+            .set_resource_arns(Some(chunk.to_vec()))
+            .send()
+            .await?;
+        for desc in resp.tag_descriptions() {
+            let tags = desc
+                .tags()
+                .iter()
+                .filter_map(|t| Some((t.key()?.to_owned(), t.value().unwrap_or_default().to_owned())))
+                .collect();
+            out.insert(desc.resource_arn().unwrap_or_default().to_owned(), tags);
+        }
+    }
+    Ok(out)
+}
+
+/// Values of the `--show-tags` keys on a record, `-` where a tag is unset.
+pub fn tag_values(tags: &BTreeMap<String, String>, keys: &[String]) -> Vec<String> {
+    keys.iter()
+        .map(|k| tags.get(k).cloned().unwrap_or_else(|| "-".to_owned()))
+        .collect()
+}
+
+/// Fill in the tags of record types whose listing call does not return
+/// them (DB subnet groups and DocumentDB clusters).
+pub async fn resolve_tags(sdk: &SdkConfig, calls: &CallCounter, recs: &mut [ResourceRecord]) -> Result<()> {
+    let rds_client = rds::Client::new(sdk);
+    let docdb_client = docdb::Client::new(sdk);
+    for rec in recs.iter_mut().filter(|r| r.tags.is_empty()) {
+        match rec.rtype {
+            "rds.db-subnet-group" => {
+                calls.record("rds", "ListTagsForResource", sdk.region());
+                let resp = rds_client.list_tags_for_resource().resource_name(&rec.arn).send().await?;
+                rec.tags = rds_tags(resp.tag_list());
+            }
+            "docdb.cluster" => {
+                calls.record("docdb", "ListTagsForResource", sdk.region());
+                let resp = docdb_client.list_tags_for_resource().resource_name(&rec.arn).send().await?;
+                rec.tags = resp
+                    .tag_list()
+                    .iter()
+                    .filter_map(|t| Some((t.key()?.to_owned(), t.value().unwrap_or_default().to_owned())))
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// DescribeStackResources lookups in flight at once; CloudFormation
+/// throttles describe calls well below the EC2 rate.
+const STACK_LOOKUPS: usize = 4;
+
+/// Annotate each record with its owning CloudFormation stack.
+///
+/// The `aws:cloudformation:stack-name` tag is used when present; otherwise we
+/// fall back to DescribeStackResources on the record's physical ID, a few
+/// lookups at a time.  Lookups that fail (rather than finding no stack) leave
+/// the record unattributed and are reported in the returned error.
+pub async fn resolve_stacks(sdk: &SdkConfig, calls: &CallCounter, recs: &mut [ResourceRecord]) -> Result<()> {
+    for rec in recs.iter_mut() {
+        rec.stack = rec.tags.get(STACK_NAME_TAG).cloned();
+    }
+
+    let client = cfn::Client::new(sdk);
+    let failures: Vec<String> = stream::iter(recs.iter_mut().filter(|r| r.stack.is_none()))
+        .map(|rec| {
+            let client = &client;
+            async move {
+                calls.record("cloudformation", "DescribeStackResources", sdk.region());
+                match client
+                    .describe_stack_resources()
+                    .physical_resource_id(rec.physical_id())
+                    .send()
+                    .await
+                {
+                    Ok(resp) => {
+                        rec.stack = resp
+                            .stack_resources()
+                            .first()
+                            .and_then(|r| r.stack_name())
+                            .map(|s| s.to_owned());
+                        None
+                    }
+                    // CloudFormation answers ValidationError when no stack owns the resource.
+                    Err(e) if e.code() == Some("ValidationError") => {
+                        trace!("{} not owned by any stack", rec.physical_id());
+                        None
+                    }
+                    Err(e) => Some(format!("{}: {}", rec.physical_id(), DisplayErrorContext(&e))),
+                }
+            }
+        })
+        .buffer_unordered(STACK_LOOKUPS)
+        .filter_map(|failure| async move { failure })
+        .collect()
+        .await;
+
+    match failures.first() {
+        None => Ok(()),
+        Some(first) => Err(eyre!(
+            "cannot look up the owning stack of {} resource(s), e.g. {}",
+            failures.len(),
+            first
+        )),
+    }
+}
+
+/// The scanners run against every VPC in detail mode
+pub fn service_scanners() -> Vec<Box<dyn ServiceScanner>> {
+    vec![Box::new(Ec2Scanner), Box::new(ElbScanner), Box::new(RdsScanner)]
 }
 
 #[async_trait]
 pub trait ServiceScanner: Send + Sync {
-    async fn scan(&self, sdk: &SdkConfig, vpc_id: &str) -> Result<Vec<ResourceRecord>>;
+    /// Short service name used in warnings, e.g. `ec2`
+    fn name(&self) -> &'static str;
+    /// API operations one `scan` makes, as `service:Operation` (for
+    /// `--dry-run`)
+    fn operations(&self) -> &'static [&'static str];
+    async fn scan(&self, sdk: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<ResourceRecord>>;
 }
 
 pub struct Ec2Scanner;
 
 #[async_trait]
 impl ServiceScanner for Ec2Scanner {
-    async fn scan(&self, sdk: &SdkConfig, vpc_id: &str) -> Result<Vec<ResourceRecord>> {
+    fn name(&self) -> &'static str {
+        "ec2"
+    }
+
+    fn operations(&self) -> &'static [&'static str] {
+        &[
+            "ec2:DescribeInstances",
+            "ec2:DescribeNetworkInterfaces",
+            "ec2:DescribeNatGateways",
+            "ec2:DescribeInternetGateways",
+            "ec2:DescribeEgressOnlyInternetGateways",
+            "ec2:DescribeFlowLogs",
+            "ec2:DescribeSubnets",
+            "ec2:DescribeRouteTables",
+            "ec2:DescribeVpcEndpoints",
+        ]
+    }
+
+    async fn scan(&self, sdk: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<ResourceRecord>> {
         let client = ec2::Client::new(sdk);
         let mut recs = Vec::new();
 
@@ -42,10 +290,10 @@ impl ServiceScanner for Ec2Scanner {
                     .build(),
             )
             .into_paginator()
-            .items()
             .send();
-        while let Some(res) = pages.next().await {
-            for inst in res?.instances() {
+        while let Some(page) = pages.next().await {
+            calls.record("ec2", "DescribeInstances", sdk.region());
+            for inst in page?.reservations().iter().flat_map(|r| r.instances()) {
                 recs.push(ResourceRecord {
                     arn: inst.instance_id().unwrap_or_default().to_owned(),
                     rtype: "ec2.instance",
@@ -56,10 +304,14 @@ impl ServiceScanner for Ec2Scanner {
                         .and_then(|t| t.value())
                         .unwrap_or_default()
                         .to_owned(),
+                    tags: ec2_tags(inst.tags()),
+                    created: inst.launch_time().map(|t| t.secs()),
+                    ..Default::default()
                 });
             }
         }
 
+        calls.record("ec2", "DescribeNetworkInterfaces", sdk.region());
         for eni in client
             .describe_network_interfaces()
             .filters(
@@ -75,10 +327,19 @@ impl ServiceScanner for Ec2Scanner {
             recs.push(ResourceRecord {
                 arn: eni.network_interface_id().unwrap_or_default().to_owned(),
                 rtype: "ec2.eni",
-                name: eni.description().unwrap_or_default().to_owned(),
+                name: eni_owner(
+                    eni.interface_type().map(|t| t.as_str()).unwrap_or_default(),
+                    eni.requester_id().unwrap_or_default(),
+                    eni.description().unwrap_or_default(),
+                    eni.attachment().and_then(|a| a.instance_id()),
+                ),
+                tags: ec2_tags(eni.tag_set()),
+                created: eni.attachment().and_then(|a| a.attach_time()).map(|t| t.secs()),
+                ..Default::default()
             });
         }
 
+        calls.record("ec2", "DescribeNatGateways", sdk.region());
         for ngw in client
             .describe_nat_gateways()
             .filter(
@@ -95,9 +356,56 @@ impl ServiceScanner for Ec2Scanner {
                 arn: ngw.nat_gateway_id().unwrap_or_default().to_owned(),
                 rtype: "ec2.nat-gateway",
                 name: ngw.nat_gateway_id().unwrap_or_default().to_owned(),
+                tags: ec2_tags(ngw.tags()),
+                created: ngw.create_time().map(|t| t.secs()),
+                ..Default::default()
+            });
+        }
+
+        calls.record("ec2", "DescribeInternetGateways", sdk.region());
+        for igw in client
+            .describe_internet_gateways()
+            .filters(
+                ec2::types::Filter::builder()
+                    .name("attachment.vpc-id")
+                    .values(vpc_id)
+                    .build(),
+            )
+            .send()
+            .await?
+            .internet_gateways()
+        {
+            let tags = ec2_tags(igw.tags());
+            recs.push(ResourceRecord {
+                arn: igw.internet_gateway_id().unwrap_or_default().to_owned(),
+                rtype: "ec2.internet-gateway",
+                name: gateway_name(&tags, attachment_state(igw.attachments(), vpc_id)),
+                tags,
+                ..Default::default()
             });
         }
 
+        // Egress-only gateways can't be filtered by VPC server-side
+        calls.record("ec2", "DescribeEgressOnlyInternetGateways", sdk.region());
+        let mut eigws = client
+            .describe_egress_only_internet_gateways()
+            .into_paginator()
+            .items()
+            .send();
+        while let Some(eigw) = eigws.next().await {
+            let eigw = eigw?;
+            let Some(state) = attachment_state(eigw.attachments(), vpc_id) else { continue };
+            let tags = ec2_tags(eigw.tags());
+            recs.push(ResourceRecord {
+                arn: eigw.egress_only_internet_gateway_id().unwrap_or_default().to_owned(),
+                rtype: "ec2.egress-only-internet-gateway",
+                name: gateway_name(&tags, Some(state)),
+                tags,
+                ..Default::default()
+            });
+        }
+
+        calls.record("ec2", "DescribeFlowLogs", sdk.region());
         for fl in client
             .describe_flow_logs()
             .filter(
@@ -114,6 +422,53 @@ impl ServiceScanner for Ec2Scanner {
                 arn: fl.flow_log_id().unwrap_or_default().to_owned(),
                 rtype: "ec2.flow-log",
                 name: fl.log_group_name().unwrap_or_default().to_owned(),
+                tags: ec2_tags(fl.tags()),
+                created: fl.creation_time().map(|t| t.secs()),
+                ..Default::default()
+            });
+        }
+
+        for sn in list_subnets(sdk, calls, vpc_id).await? {
+            recs.push(ResourceRecord {
+                name: format!(
+                    "{} {} {} {:.0}% used",
+                    sn.name.as_deref().unwrap_or_default(),
+                    sn.cidr,
+                    sn.zone(),
+                    sn.used_pct()
+                )
+                .trim_start()
+                .to_owned(),
+                arn: sn.id,
+                rtype: "ec2.subnet",
+                tags: sn.tags,
+                ..Default::default()
+            });
+        }
+
+        for rt in list_route_tables(sdk, calls, vpc_id).await? {
+            let name = match (&rt.name, rt.main) {
+                (Some(n), true) => format!("{n} (main)"),
+                (Some(n), false) => n.clone(),
+                (None, true) => "(main)".to_owned(),
+                (None, false) => String::new(),
+            };
+            recs.push(ResourceRecord {
+                arn: rt.id,
+                rtype: "ec2.route-table",
+                name,
+                tags: rt.tags,
+                ..Default::default()
+            });
+        }
+
+        for ep in list_vpc_endpoints(sdk, calls, vpc_id).await? {
+            recs.push(ResourceRecord {
+                name: format!("{} ({})", ep.service, ep.kind),
+                arn: ep.id,
+                rtype: "ec2.vpc-endpoint",
+                tags: ep.tags,
+                ..Default::default()
             });
         }
 
@@ -125,30 +480,51 @@ pub struct ElbScanner;
 
 #[async_trait]
 impl ServiceScanner for ElbScanner {
-    async fn scan(&self, sdk: &SdkConfig, vpc_id: &str) -> Result<Vec<ResourceRecord>> {
+    fn name(&self) -> &'static str {
+        "elbv2"
+    }
+
+    fn operations(&self) -> &'static [&'static str] {
+        &["elbv2:DescribeLoadBalancers", "elbv2:DescribeTargetGroups", "elbv2:DescribeTags"]
+    }
+
+    async fn scan(&self, sdk: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<ResourceRecord>> {
         let client = elbv2::Client::new(sdk);
         let mut recs = Vec::new();
 
+        calls.record("elbv2", "DescribeLoadBalancers", sdk.region());
         for lb in client.describe_load_balancers().send().await?.load_balancers() {
             if lb.vpc_id() == Some(vpc_id) {
                 recs.push(ResourceRecord {
                     arn: lb.load_balancer_arn().unwrap_or_default().to_owned(),
                     rtype: "elbv2.load-balancer",
                     name: lb.load_balancer_name().unwrap_or_default().to_owned(),
+                    created: lb.created_time().map(|t| t.secs()),
+                    ..Default::default()
                 });
             }
         }
 
+        calls.record("elbv2", "DescribeTargetGroups", sdk.region());
         for tg in client.describe_target_groups().send().await?.target_groups() {
             if tg.vpc_id() == Some(vpc_id) {
                 recs.push(ResourceRecord {
                     arn: tg.target_group_arn().unwrap_or_default().to_owned(),
                     rtype: "elbv2.target-group",
                     name: tg.target_group_name().unwrap_or_default().to_owned(),
+                    ..Default::default()
                 });
             }
         }
 
+        if !recs.is_empty() {
+            let arns: Vec<String> = recs.iter().map(|r| r.arn.clone()).collect();
+            let mut tags = elb_tags(&client, calls, &arns).await?;
+            for r in &mut recs {
+                r.tags = tags.remove(&r.arn).unwrap_or_default();
+            }
+        }
+
         Ok(recs)
     }
 }
@@ -157,10 +533,24 @@ pub struct RdsScanner;
 
 #[async_trait]
 impl ServiceScanner for RdsScanner {
-    async fn scan(&self, sdk: &SdkConfig, vpc_id: &str) -> Result<Vec<ResourceRecord>> {
+    fn name(&self) -> &'static str {
+        "rds"
+    }
+
+    fn operations(&self) -> &'static [&'static str] {
+        &[
+            "rds:DescribeDBInstances",
+            "rds:DescribeDBClusters",
+            "rds:DescribeDBSubnetGroups",
+            "docdb:DescribeDBClusters",
+        ]
+    }
+
+    async fn scan(&self, sdk: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<ResourceRecord>> {
         let client = rds::Client::new(sdk);
         let mut recs = Vec::new();
 
+        calls.record("rds", "DescribeDBInstances", sdk.region());
         for db in client.describe_db_instances().send().await?.db_instances() {
             if db
                 .db_subnet_group()
@@ -171,27 +561,227 @@ impl ServiceScanner for RdsScanner {
                     arn: db.db_instance_arn().unwrap_or_default().to_owned(),
                     rtype: "rds.instance",
                     name: db.db_instance_identifier().unwrap_or_default().to_owned(),
+                    tags: rds_tags(db.tag_list()),
+                    created: db.instance_create_time().map(|t| t.secs()),
+                    ..Default::default()
                 });
             }
         }
 
+        calls.record("rds", "DescribeDBClusters", sdk.region());
         for cl in client.describe_db_clusters().send().await?.db_clusters() {
             recs.push(ResourceRecord {
                 arn: cl.db_cluster_arn().unwrap_or_default().to_owned(),
                 rtype: "rds.cluster",
                 name: cl.db_cluster_identifier().unwrap_or_default().to_owned(),
+                tags: rds_tags(cl.tag_list()),
+                created: cl.cluster_create_time().map(|t| t.secs()),
+                ..Default::default()
+            });
+        }
+
+        for g in list_db_subnet_groups(sdk, calls, vpc_id).await? {
+            recs.push(ResourceRecord {
+                name: format!("{} ({} subnets in {} AZs)", g.name, g.subnets.len(), g.azs.len()),
+                arn: g.arn,
+                rtype: "rds.db-subnet-group",
+                ..Default::default()
             });
         }
 
         let dclient = docdb::Client::new(sdk);
+        calls.record("docdb", "DescribeDBClusters", sdk.region());
         for cl in dclient.describe_db_clusters().send().await?.db_clusters() {
             recs.push(ResourceRecord {
                 arn: cl.db_cluster_arn().unwrap_or_default().to_owned(),
                 rtype: "docdb.cluster",
                 name: cl.db_cluster_identifier().unwrap_or_default().to_owned(),
+                created: cl.cluster_create_time().map(|t| t.secs()),
+                ..Default::default()
             });
         }
 
         Ok(recs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// Sources of this module and of every helper a scanner hands `calls`
+    const SOURCES: &[&str] = &[
+        include_str!("scanner.rs"),
+        include_str!("subnets.rs"),
+        include_str!("routes.rs"),
+        include_str!("endpoints.rs"),
+        include_str!("db_subnet_groups.rs"),
+    ];
+
+    /// Body of `fn name(…)` in `src`, up to the end of the item
+    fn fn_body<'a>(src: &'a str, name: &str) -> Option<&'a str> {
+        let start = src.find(&format!("fn {name}("))?;
+        let end = src[start..].find("\n}\n").map_or(src.len(), |end| start + end);
+        Some(&src[start..end])
+    }
+
+    /// `service:Operation` recorded in `code`, following the helpers it
+    /// passes `calls` to
+    fn recorded_operations(code: &str) -> BTreeSet<String> {
+        let mut ops = BTreeSet::new();
+        for (i, _) in code.match_indices("calls.record(") {
+            // calls.record("svc", "Op", …) → ["calls.record(", "svc", ", ", "Op", …]
+            let args: Vec<&str> = code[i..].splitn(5, '"').collect();
+            ops.insert(format!("{}:{}", args[1], args[3]));
+        }
+        for pattern in [", calls,", ", calls)"] {
+            for (i, _) in code.match_indices(pattern) {
+                let call = &code[..code[..i].rfind('(').unwrap()];
+                let helper = call.rsplit(|c: char| !(c.is_alphanumeric() || c == '_')).next().unwrap();
+                let body = SOURCES.iter().find_map(|src| fn_body(src, helper));
+                let body = body.unwrap_or_else(|| panic!("add the module of {helper} to SOURCES"));
+                ops.extend(recorded_operations(body));
+            }
+        }
+        ops
+    }
+
+    #[test]
+    fn tag_values_follow_requested_keys() {
+        let tags = BTreeMap::from([("Team".to_string(), "payments".to_string())]);
+        let keys = vec!["Team".to_string(), "Environment".to_string()];
+        assert_eq!(tag_values(&tags, &keys), vec!["payments", "-"]);
+        assert!(tag_values(&tags, &[]).is_empty());
+    }
+
+    #[test]
+    fn physical_id_uses_identifier_for_databases() {
+        let rec = ResourceRecord {
+            arn: "arn:aws:rds:us-west-2:123456789012:db:my-db".to_string(),
+            rtype: "rds.instance",
+            name: "my-db".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(rec.physical_id(), "my-db");
+    }
+
+    #[test]
+    fn physical_id_uses_name_from_db_subnet_group_arn() {
+        let rec = ResourceRecord {
+            arn: "arn:aws:rds:us-west-2:123456789012:subgrp:db-private".to_string(),
+            rtype: "rds.db-subnet-group",
+            name: "db-private (3 subnets in 3 AZs)".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(rec.physical_id(), "db-private");
+    }
+
+    #[test]
+    fn physical_id_uses_arn_field_for_ec2() {
+        let rec = ResourceRecord {
+            arn: "i-1234567890abcdef0".to_string(),
+            rtype: "ec2.instance",
+            name: "web".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(rec.physical_id(), "i-1234567890abcdef0");
+    }
+
+    #[test]
+    fn gateway_name_shows_name_tag_and_attachment_state() {
+        let named = BTreeMap::from([("Name".to_string(), "edge".to_string())]);
+        assert_eq!(gateway_name(&named, Some("available")), "edge (available)");
+        assert_eq!(gateway_name(&BTreeMap::new(), Some("attached")), "(attached)");
+        assert_eq!(gateway_name(&BTreeMap::new(), None), "(detached)");
+    }
+
+    #[test]
+    fn sort_by_age_puts_oldest_first_and_unknown_last() {
+        let rec = |arn: &str, created: Option<i64>| ResourceRecord {
+            arn: arn.to_string(),
+            created,
+            ..Default::default()
+        };
+        let mut recs = vec![rec("new", Some(200)), rec("unknown", None), rec("old", Some(100))];
+        sort_by_age(&mut recs);
+        let order: Vec<&str> = recs.iter().map(|r| r.arn.as_str()).collect();
+        assert_eq!(order, vec!["old", "new", "unknown"]);
+    }
+
+    #[test]
+    fn ec2_tags_skips_keyless_tags() {
+        let tags = vec![
+            ec2::types::Tag::builder().key(STACK_NAME_TAG).value("net-stack").build(),
+            ec2::types::Tag::builder().value("orphan").build(),
+        ];
+        let map = ec2_tags(&tags);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(STACK_NAME_TAG).map(String::as_str), Some("net-stack"));
+    }
+
+    #[test]
+    fn eni_owner_detects_lambda() {
+        let name = eni_owner("lambda", "", "AWS Lambda VPC ENI-orders-handler-1a2b3c", None);
+        assert_eq!(name, "lambda: orders-handler-1a2b3c");
+    }
+
+    #[test]
+    fn eni_owner_detects_nat_gateway() {
+        let name = eni_owner("natGateway", "", "Interface for NAT Gateway nat-0abc", None);
+        assert_eq!(name, "nat: nat-0abc");
+    }
+
+    #[test]
+    fn eni_owner_detects_vpc_endpoint() {
+        let name = eni_owner("vpc_endpoint", "", "VPC Endpoint Interface vpce-0abc", None);
+        assert_eq!(name, "vpc-endpoint: vpce-0abc");
+    }
+
+    #[test]
+    fn eni_owner_detects_load_balancers() {
+        assert_eq!(eni_owner("interface", "amazon-elb", "ELB app/web/123", None), "elb: app/web/123");
+        assert_eq!(
+            eni_owner("network_load_balancer", "", "ELB net/api/456", None),
+            "elb: net/api/456"
+        );
+    }
+
+    #[test]
+    fn eni_owner_detects_rds_and_efs() {
+        assert_eq!(eni_owner("interface", "amazon-rds", "RDSNetworkInterface", None), "rds: RDSNetworkInterface");
+        assert_eq!(
+            eni_owner("interface", "", "EFS mount target for fs-0abc (fsmt-0def)", None),
+            "efs: fs-0abc (fsmt-0def)"
+        );
+    }
+
+    #[test]
+    fn eni_owner_falls_back_to_instance_then_description() {
+        assert_eq!(eni_owner("interface", "", "", Some("i-0abc")), "ec2: i-0abc");
+        assert_eq!(eni_owner("interface", "", "custom eni", None), "custom eni");
+        assert_eq!(eni_owner("interface", "", "", None), "unattached");
+    }
+
+    #[test]
+    fn scanner_names_match_record_type_prefixes() {
+        assert_eq!(Ec2Scanner.name(), "ec2");
+        assert_eq!(ElbScanner.name(), "elbv2");
+        assert_eq!(RdsScanner.name(), "rds");
+    }
+
+    #[test]
+    fn scanners_declare_every_operation_they_call() {
+        let src = &SOURCES[0][..SOURCES[0].find("#[cfg(test)]").unwrap()];
+        let scanners = service_scanners();
+        let impls: Vec<&str> = src.split("impl ServiceScanner for ").skip(1).collect();
+        assert_eq!(impls.len(), scanners.len());
+        for block in impls {
+            let block = &block[..block.find("\n}\n").unwrap()];
+            let name = block.split('"').nth(1).unwrap();
+            let scanner = scanners.iter().find(|s| s.name() == name).unwrap();
+            let declared: BTreeSet<String> = scanner.operations().iter().map(|op| op.to_string()).collect();
+            assert_eq!(declared, recorded_operations(block), "{name} scanner");
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! settings.rs
+//! ---------------------------------------------------------------------------
+//! Shared aws-tools settings file.  Both tools read
+//! `$XDG_CONFIG_HOME/aws-tools/config.yml` (falling back to
+//! `~/.config/aws-tools/config.yml`); a missing file means no settings.
+//! Region groups defined there can be passed to `--regions` by name:
+//!
+//! ```yaml
+//! region-groups:
+//!   us: [us-east-1, us-west-2]
+//!   eu: [eu-west-1, eu-central-1]
+//! ```
+//!
+//! Views are named reports: per tool, the arguments `--view NAME` stands
+//! for, so a team can share a standard report without long command lines:
+//!
+//! ```yaml
+//! views:
+//!   security-audit:
+//!     ls-rds: [--public-only, --with-specs, --fail-on-unencrypted]
+//!     ls-vpc: [--issues, --flow-logs]
+//!   capacity:
+//!     ls-vpc: [--ip-threshold, "80", --format, table]
+//! ```
+
+use eyre::{Result, WrapErr, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+/// Settings shared by ls-vpc and ls-rds
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Settings {
+    /// Group name → regions, usable in place of a region name
+    #[serde(default)]
+    pub region_groups: BTreeMap<String, Vec<String>>,
+    /// View name → tool name → the arguments the view adds
+    #[serde(default)]
+    pub views: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+impl Settings {
+    /// Location of the settings file, if a config directory can be found
+    pub fn path() -> Option<PathBuf> {
+        let base = match env::var("XDG_CONFIG_HOME") {
+            Ok(xdg) if !xdg.is_empty() => PathBuf::from(xdg),
+            _ => PathBuf::from(env::var("HOME").ok()?).join(".config"),
+        };
+        Some(base.join("aws-tools").join("config.yml"))
+    }
+
+    /// Parse settings from YAML text
+    pub fn parse(yaml: &str) -> Result<Self> {
+        if yaml.trim().is_empty() {
+            return Ok(Settings::default());
+        }
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Read a settings file; a missing file yields the defaults
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+        let text = fs::read_to_string(path).wrap_err_with(|| format!("Cannot read settings {}", path.display()))?;
+        Self::parse(&text).wrap_err_with(|| format!("Invalid settings {}", path.display()))
+    }
+
+    /// Read the settings file from its default location
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Settings::default()),
+        }
+    }
+
+    /// Replace region-group names with their regions, dropping duplicates
+    /// (keeping order).  Anything that is neither a group nor shaped like a
+    /// region name (e.g. `us-east-1`) is rejected.
+    pub fn expand_regions(&self, regions: &[String]) -> Result<Vec<String>> {
+        let mut out: Vec<String> = Vec::new();
+        for name in regions.iter().map(|r| r.trim()) {
+            let expanded = match self.region_groups.get(name) {
+                Some(group) => group.clone(),
+                None => vec![name.to_owned()],
+            };
+            for region in expanded {
+                if !is_region_name(&region) {
+                    bail!("Unknown region or region group '{}'", region);
+                }
+                if !out.contains(&region) {
+                    out.push(region);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Append the arguments of the `--view NAME` (or `--view=NAME`) in
+    /// `args` for `tool`; arguments without a view are returned unchanged.
+    /// The view's arguments go last so a multi-value option on the command
+    /// line cannot swallow them, and they cannot be repeated there.
+    pub fn expand_view(&self, tool: &str, mut args: Vec<String>) -> Result<Vec<String>> {
+        let name = args.iter().enumerate().find_map(|(i, arg)| match arg.strip_prefix("--view") {
+            Some("") => args.get(i + 1).cloned(),
+            Some(value) => value.strip_prefix('=').map(str::to_owned),
+            None => None,
+        });
+        let Some(name) = name else { return Ok(args) };
+
+        let Some(view) = self.views.get(&name) else {
+            let known: Vec<&str> = self.views.keys().map(String::as_str).collect();
+            bail!(
+                "Unknown view '{}'. Views in the settings file: {}",
+                name,
+                if known.is_empty() { "none".to_owned() } else { known.join(", ") }
+            );
+        };
+        let Some(extra) = view.get(tool) else {
+            bail!("View '{}' has no {} arguments", name, tool);
+        };
+        if extra.first().is_some_and(|a| !a.starts_with('-')) || extra.iter().any(|a| a.starts_with("--view")) {
+            bail!("View '{}' must list {} options, starting with one and without --view", name, tool);
+        }
+        args.extend(extra.iter().cloned());
+        Ok(args)
+    }
+}
+
+/// Whether `s` looks like an AWS region name (`us-east-1`, `us-gov-west-1` …)
+pub fn is_region_name(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    parts.len() >= 3
+        && parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()))
+        && parts[parts.len() - 1].chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_reads_region_groups() {
+        let settings = Settings::parse("region-groups:\n  us: [us-east-1, us-west-2]\n").unwrap();
+        assert_eq!(settings.region_groups["us"], regions(&["us-east-1", "us-west-2"]));
+        assert_eq!(Settings::parse("").unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn expand_regions_replaces_groups_and_dedupes() {
+        let settings = Settings::parse("region-groups:\n  us: [us-east-1, us-west-2]\n  eu: [eu-west-1]\n").unwrap();
+        assert_eq!(
+            settings.expand_regions(&regions(&["us", "eu", "us-east-1"])).unwrap(),
+            regions(&["us-east-1", "us-west-2", "eu-west-1"])
+        );
+    }
+
+    #[test]
+    fn expand_regions_rejects_unknown_names() {
+        let err = Settings::default().expand_regions(&regions(&["emea"])).unwrap_err();
+        assert!(err.to_string().contains("emea"));
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    const VIEWS: &str = "views:\n  audit:\n    ls-rds: [--public-only, --with-specs]\n    ls-vpc: [--issues]\n";
+
+    #[test]
+    fn expand_view_appends_the_tools_arguments() {
+        let settings = Settings::parse(VIEWS).unwrap();
+        assert_eq!(
+            settings.expand_view("ls-rds", args(&["ls-rds", "--view", "audit", "--json"])).unwrap(),
+            args(&["ls-rds", "--view", "audit", "--json", "--public-only", "--with-specs"])
+        );
+        assert_eq!(
+            settings.expand_view("ls-vpc", args(&["ls-vpc", "--view=audit"])).unwrap(),
+            args(&["ls-vpc", "--view=audit", "--issues"])
+        );
+        let plain = args(&["ls-rds", "--json"]);
+        assert_eq!(settings.expand_view("ls-rds", plain.clone()).unwrap(), plain);
+    }
+
+    #[test]
+    fn expand_view_rejects_unknown_views() {
+        let settings = Settings::parse(VIEWS).unwrap();
+        let err = settings.expand_view("ls-rds", args(&["ls-rds", "--view", "capacity"])).unwrap_err();
+        assert!(err.to_string().contains("Unknown view 'capacity'. Views in the settings file: audit"));
+        let settings = Settings::parse("views:\n  audit:\n    ls-rds: [--public-only]\n").unwrap();
+        let err = settings.expand_view("ls-vpc", args(&["ls-vpc", "--view", "audit"])).unwrap_err();
+        assert!(err.to_string().contains("no ls-vpc arguments"));
+    }
+
+    #[test]
+    fn is_region_name_accepts_region_shapes() {
+        assert!(is_region_name("us-east-1"));
+        assert!(is_region_name("us-gov-west-1"));
+        assert!(!is_region_name("us"));
+        assert!(!is_region_name("US-EAST-1"));
+    }
+
+    #[test]
+    fn load_from_missing_file_is_default() {
+        let settings = Settings::load_from(Path::new("/nonexistent/aws-tools/config.yml")).unwrap();
+        assert_eq!(settings, Settings::default());
+    }
+}
@@ -0,0 +1,369 @@
+//! sg.rs
+//! ---------------------------------------------------------------------------
+//! Security-group reference graph.  Collects the groups in each scanned VPC
+//! and turns rules that reference *other* security groups into edges, which
+//! can be rendered as a table or as Graphviz DOT.  `--unused-sgs` also counts
+//! the ENIs using each group to list groups attached to nothing.
+
+use crate::api_stats::CallCounter;
+use crate::cli::OutputFormat;
+use crate::utils::render_table;
+use aws_sdk_ec2 as ec2;
+use aws_types::SdkConfig;
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use eyre::Result;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A rule that references another security group.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SgRule {
+    /// `ingress` or `egress`
+    pub direction: &'static str,
+    pub peer_group: String,
+    pub ports: String,
+}
+
+/// A security group and the group-to-group rules it carries.
+#[derive(Debug, Clone, Default)]
+pub struct SecurityGroup {
+    pub id: String,
+    pub name: String,
+    pub vpc_id: String,
+    pub rules: Vec<SgRule>,
+    /// Network interfaces using the group (only counted for `--unused-sgs`)
+    pub attachments: Option<usize>,
+}
+
+/// Directed edge: traffic from `source` is allowed to reach `target`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SgEdge {
+    pub source: String,
+    pub target: String,
+    pub ports: String,
+    /// Rule that created the edge (`ingress` on target / `egress` on source)
+    pub rule: &'static str,
+}
+
+/// Render protocol and port range the way the console does (`tcp/443`, `all`).
+pub fn format_ports(protocol: &str, from: Option<i32>, to: Option<i32>) -> String {
+    match (protocol, from, to) {
+        ("-1", _, _) => "all".to_owned(),
+        (p, Some(f), Some(t)) if f == t => format!("{p}/{f}"),
+        (p, Some(f), Some(t)) => format!("{p}/{f}-{t}"),
+        (p, _, _) => p.to_owned(),
+    }
+}
+
+fn group_rules(perms: &[ec2::types::IpPermission], direction: &'static str) -> Vec<SgRule> {
+    let mut rules = Vec::new();
+    for perm in perms {
+        let ports = format_ports(perm.ip_protocol().unwrap_or("-1"), perm.from_port(), perm.to_port());
+        for pair in perm.user_id_group_pairs() {
+            if let Some(peer) = pair.group_id() {
+                rules.push(SgRule {
+                    direction,
+                    peer_group: peer.to_owned(),
+                    ports: ports.clone(),
+                });
+            }
+        }
+    }
+    rules
+}
+
+/// List the security groups in a VPC with their group-referencing rules.
+pub async fn list_security_groups(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<SecurityGroup>> {
+    let client = ec2::Client::new(conf);
+    let mut groups = Vec::new();
+
+    calls.record("ec2", "DescribeSecurityGroups", conf.region());
+    let mut pages = client
+        .describe_security_groups()
+        .filters(
+            ec2::types::Filter::builder()
+                .name("vpc-id")
+                .values(vpc_id)
+                .build(),
+        )
+        .into_paginator()
+        .items()
+        .send();
+    while let Some(sg) = pages.next().await {
+        let sg = sg?;
+        let mut rules = group_rules(sg.ip_permissions(), "ingress");
+        rules.extend(group_rules(sg.ip_permissions_egress(), "egress"));
+        groups.push(SecurityGroup {
+            id: sg.group_id().unwrap_or_default().to_owned(),
+            name: sg.group_name().unwrap_or_default().to_owned(),
+            vpc_id: sg.vpc_id().unwrap_or_default().to_owned(),
+            rules,
+            attachments: None,
+        });
+    }
+    Ok(groups)
+}
+
+/// Count the network interfaces in a VPC using each security group.
+pub async fn count_attachments(
+    conf: &SdkConfig,
+    calls: &CallCounter,
+    vpc_id: &str,
+    groups: &mut [SecurityGroup],
+) -> Result<()> {
+    let client = ec2::Client::new(conf);
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+
+    calls.record("ec2", "DescribeNetworkInterfaces", conf.region());
+    let mut pages = client
+        .describe_network_interfaces()
+        .filters(
+            ec2::types::Filter::builder()
+                .name("vpc-id")
+                .values(vpc_id)
+                .build(),
+        )
+        .into_paginator()
+        .items()
+        .send();
+    while let Some(eni) = pages.next().await {
+        for group in eni?.groups() {
+            if let Some(id) = group.group_id() {
+                *counts.entry(id.to_owned()).or_default() += 1;
+            }
+        }
+    }
+    for g in groups.iter_mut() {
+        g.attachments = Some(counts.get(&g.id).copied().unwrap_or(0));
+    }
+    Ok(())
+}
+
+/// Groups attached to no network interface, excluding each VPC's `default`
+/// group (which cannot be deleted).  Groups with unknown attachments are
+/// never reported.
+pub fn unused_groups(groups: &[SecurityGroup]) -> Vec<&SecurityGroup> {
+    groups
+        .iter()
+        .filter(|g| g.attachments == Some(0) && g.name != "default")
+        .collect()
+}
+
+/// Render unused groups per VPC.  REFERENCED-BY lists groups whose rules
+/// point at the unused group; those rules must be removed before deleting it.
+pub fn format_unused_sgs_table(groups: &[SecurityGroup], format: OutputFormat) -> String {
+    let mut referenced_by: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for g in groups {
+        for r in &g.rules {
+            if r.peer_group != g.id {
+                referenced_by.entry(r.peer_group.as_str()).or_default().insert(g.id.as_str());
+            }
+        }
+    }
+
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec!["VPC-ID", "GROUP-ID", "NAME", "REFERENCED-BY"]);
+    let mut unused = unused_groups(groups);
+    unused.sort_by(|a, b| (&a.vpc_id, &a.id).cmp(&(&b.vpc_id, &b.id)));
+    for g in unused {
+        let refs: Vec<&str> = referenced_by.get(g.id.as_str()).into_iter().flatten().copied().collect();
+        table.add_row(vec![
+            g.vpc_id.clone(),
+            g.id.clone(),
+            g.name.clone(),
+            if refs.is_empty() { "-".to_owned() } else { refs.join(",") },
+        ]);
+    }
+    render_table(&table, format)
+}
+
+/// Turn group rules into directed edges (source may reach target).
+pub fn sg_edges(groups: &[SecurityGroup]) -> Vec<SgEdge> {
+    let mut edges = Vec::new();
+    for g in groups {
+        for r in &g.rules {
+            let (source, target) = match r.direction {
+                "ingress" => (r.peer_group.clone(), g.id.clone()),
+                _ => (g.id.clone(), r.peer_group.clone()),
+            };
+            edges.push(SgEdge {
+                source,
+                target,
+                ports: r.ports.clone(),
+                rule: r.direction,
+            });
+        }
+    }
+    edges.sort_by(|a, b| (&a.source, &a.target, &a.ports).cmp(&(&b.source, &b.target, &b.ports)));
+    edges.dedup();
+    edges
+}
+
+/// Render the graph as a table (groups outside the scanned VPCs show `-` for name).
+pub fn format_sg_table(groups: &[SecurityGroup], format: OutputFormat) -> String {
+    let names: BTreeMap<&str, &str> = groups.iter().map(|g| (g.id.as_str(), g.name.as_str())).collect();
+    let name_of = |id: &str| names.get(id).copied().unwrap_or("-").to_owned();
+
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec!["SOURCE-SG", "SOURCE-NAME", "TARGET-SG", "TARGET-NAME", "PORTS", "RULE"]);
+    for e in sg_edges(groups) {
+        table.add_row(vec![
+            e.source.clone(),
+            name_of(&e.source),
+            e.target.clone(),
+            name_of(&e.target),
+            e.ports,
+            e.rule.to_owned(),
+        ]);
+    }
+    render_table(&table, format)
+}
+
+/// Quote-safe text for a DOT string: group names may hold `"` and `\`.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Render the graph as Graphviz DOT, clustering groups by VPC.
+pub fn format_sg_dot(groups: &[SecurityGroup]) -> String {
+    let mut by_vpc: BTreeMap<&str, Vec<&SecurityGroup>> = BTreeMap::new();
+    for g in groups {
+        by_vpc.entry(g.vpc_id.as_str()).or_default().push(g);
+    }
+
+    let mut out = String::from("digraph sg {\n  rankdir=LR;\n  node [shape=box];\n");
+    for (vpc, members) in &by_vpc {
+        let vpc = dot_escape(vpc);
+        out.push_str(&format!("  subgraph \"cluster_{vpc}\" {{\n    label=\"{vpc}\";\n"));
+        for g in members {
+            let (id, name) = (dot_escape(&g.id), dot_escape(&g.name));
+            out.push_str(&format!("    \"{id}\" [label=\"{name}\\n{id}\"];\n"));
+        }
+        out.push_str("  }\n");
+    }
+    for e in sg_edges(groups) {
+        out.push_str(&format!(
+            "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
+            dot_escape(&e.source),
+            dot_escape(&e.target),
+            dot_escape(&e.ports)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> Vec<SecurityGroup> {
+        vec![
+            SecurityGroup {
+                id: "sg-web".to_string(),
+                name: "web".to_string(),
+                vpc_id: "vpc-1".to_string(),
+                rules: vec![SgRule {
+                    direction: "egress",
+                    peer_group: "sg-db".to_string(),
+                    ports: "tcp/5432".to_string(),
+                }],
+                attachments: Some(2),
+            },
+            SecurityGroup {
+                id: "sg-db".to_string(),
+                name: "db".to_string(),
+                vpc_id: "vpc-1".to_string(),
+                rules: vec![
+                    SgRule {
+                        direction: "ingress",
+                        peer_group: "sg-web".to_string(),
+                        ports: "tcp/5432".to_string(),
+                    },
+                    SgRule {
+                        direction: "ingress",
+                        peer_group: "sg-bastion".to_string(),
+                        ports: "all".to_string(),
+                    },
+                ],
+                attachments: Some(0),
+            },
+        ]
+    }
+
+    #[test]
+    fn format_ports_variants() {
+        assert_eq!(format_ports("-1", None, None), "all");
+        assert_eq!(format_ports("tcp", Some(443), Some(443)), "tcp/443");
+        assert_eq!(format_ports("udp", Some(1000), Some(2000)), "udp/1000-2000");
+        assert_eq!(format_ports("icmp", None, None), "icmp");
+    }
+
+    #[test]
+    fn sg_edges_orients_ingress_and_egress() {
+        let edges = sg_edges(&fixture());
+        assert!(edges.iter().any(|e| e.source == "sg-bastion" && e.target == "sg-db" && e.rule == "ingress"));
+        assert!(edges.iter().any(|e| e.source == "sg-web" && e.target == "sg-db" && e.rule == "egress"));
+    }
+
+    #[test]
+    fn sg_edges_keeps_both_sides_of_symmetric_rules() {
+        let edges = sg_edges(&fixture());
+        let web_to_db = edges.iter().filter(|e| e.source == "sg-web" && e.target == "sg-db").count();
+        assert_eq!(web_to_db, 2);
+    }
+
+    #[test]
+    fn format_sg_table_marks_unknown_groups() {
+        let table = format_sg_table(&fixture(), OutputFormat::Table);
+        assert!(table.contains("SOURCE-SG"));
+        assert!(table.contains("sg-bastion"));
+        assert!(table.contains(" - "));
+    }
+
+    #[test]
+    fn unused_groups_skips_default_and_unknown() {
+        let mut groups = fixture();
+        groups.push(SecurityGroup {
+            id: "sg-default".to_string(),
+            name: "default".to_string(),
+            vpc_id: "vpc-1".to_string(),
+            attachments: Some(0),
+            ..Default::default()
+        });
+        groups.push(SecurityGroup {
+            id: "sg-unknown".to_string(),
+            name: "unknown".to_string(),
+            vpc_id: "vpc-1".to_string(),
+            ..Default::default()
+        });
+        let unused: Vec<&str> = unused_groups(&groups).iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(unused, vec!["sg-db"]);
+    }
+
+    #[test]
+    fn format_unused_sgs_table_lists_referencing_groups() {
+        let table = format_unused_sgs_table(&fixture(), OutputFormat::Tsv);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines, vec!["VPC-ID\tGROUP-ID\tNAME\tREFERENCED-BY", "vpc-1\tsg-db\tdb\tsg-web"]);
+    }
+
+    #[test]
+    fn format_sg_dot_clusters_by_vpc() {
+        let dot = format_sg_dot(&fixture());
+        assert!(dot.starts_with("digraph sg {"));
+        assert!(dot.contains("subgraph \"cluster_vpc-1\""));
+        assert!(dot.contains("\"sg-web\" -> \"sg-db\" [label=\"tcp/5432\"];"));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn format_sg_dot_escapes_quotes_and_backslashes() {
+        let mut groups = fixture();
+        groups[0].name = r#"web "edge" \ tier"#.to_string();
+        let dot = format_sg_dot(&groups);
+        assert!(dot.contains(r#""sg-web" [label="web \"edge\" \\ tier\nsg-web"];"#));
+    }
+}
@@ -0,0 +1,268 @@
+//! spend.rs
+//! ---------------------------------------------------------------------------
+//! `--with-spend`: NAT gateway and data-transfer spend per VPC from Cost
+//! Explorer.  Cost Explorer has no VPC dimension, so the spend is grouped by
+//! a cost allocation tag (`--spend-tag`) whose value names the VPC, by ID or
+//! Name, that the NAT gateway or instance belongs to.  Spend without the tag,
+//! or tagged for a VPC the scan did not list, stays unattributed per account.
+
+use crate::{CallCounter, VpcSummary};
+use aws_sdk_costexplorer as ce;
+use aws_sdk_costexplorer::types::{
+    DateInterval, Dimension, DimensionValues, Expression, Granularity, GroupDefinition, GroupDefinitionType,
+    ResultByTime,
+};
+use aws_types::{SdkConfig, region::Region};
+use chrono::{Duration, NaiveDate, Utc};
+use comfy_table::Table;
+use comfy_table::presets::ASCII_FULL_CONDENSED;
+use eyre::Result;
+use std::collections::BTreeMap;
+
+/// Cost Explorer usage type groups counted as VPC network spend.
+pub const NETWORK_USAGE_TYPE_GROUPS: &[&str] = &[
+    "EC2: NAT Gateway - Data Processed",
+    "EC2: NAT Gateway - Running Hours",
+    "EC2: Data Transfer - Inter AZ",
+    "EC2: Data Transfer - Internet (Out)",
+    "EC2: Data Transfer - Region to Region (Out)",
+];
+
+/// Cost Explorer metric summed into the report.
+pub const SPEND_METRIC: &str = "UnblendedCost";
+
+/// Spend keyed by (account, tag value); an empty value is untagged spend.
+pub type TaggedSpend = BTreeMap<(String, String), f64>;
+
+/// Return the `[start, end)` date strings covering the last `days` days.
+pub fn spend_window(today: NaiveDate, days: u32) -> (String, String) {
+    let start = today - Duration::days(i64::from(days));
+    (start.format("%Y-%m-%d").to_string(), today.format("%Y-%m-%d").to_string())
+}
+
+/// Add every (linked account, tag) group in `results` to the running totals.
+/// Cost Explorer returns tag groups as `KEY$VALUE`.
+pub fn accumulate_spend(results: &[ResultByTime], totals: &mut TaggedSpend) {
+    for period in results {
+        for group in period.groups() {
+            let [account, tag] = group.keys() else {
+                continue;
+            };
+            let value = tag.split_once('$').map_or("", |(_, v)| v);
+            let amount = group
+                .metrics()
+                .and_then(|m| m.get(SPEND_METRIC))
+                .and_then(|v| v.amount())
+                .and_then(|a| a.parse::<f64>().ok())
+                .unwrap_or_default();
+            *totals.entry((account.clone(), value.to_owned())).or_default() += amount;
+        }
+    }
+}
+
+/// Query Cost Explorer for NAT and data-transfer spend over the last `days`,
+/// grouped by linked account and the `tag` cost allocation tag.  Each page
+/// is counted in `calls`.
+pub async fn network_spend(base_conf: &SdkConfig, calls: &CallCounter, days: u32, tag: &str) -> Result<TaggedSpend> {
+    // Cost Explorer is served from us-east-1 only.
+    let region = Region::new("us-east-1");
+    let conf = ce::config::Builder::from(base_conf).region(region.clone()).build();
+    let client = ce::Client::from_conf(conf);
+
+    let (start, end) = spend_window(Utc::now().date_naive(), days);
+    let mut totals = BTreeMap::new();
+    let mut token: Option<String> = None;
+    loop {
+        calls.record("ce", "GetCostAndUsage", Some(&region));
+        let resp = client
+            .get_cost_and_usage()
+            .time_period(DateInterval::builder().start(&start).end(&end).build()?)
+            .granularity(Granularity::Monthly)
+            .metrics(SPEND_METRIC)
+            .filter(
+                Expression::builder()
+                    .dimensions(
+                        DimensionValues::builder()
+                            .key(Dimension::UsageTypeGroup)
+                            .set_values(Some(NETWORK_USAGE_TYPE_GROUPS.iter().map(|g| g.to_string()).collect()))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .group_by(
+                GroupDefinition::builder()
+                    .r#type(GroupDefinitionType::Dimension)
+                    .key("LINKED_ACCOUNT")
+                    .build(),
+            )
+            .group_by(GroupDefinition::builder().r#type(GroupDefinitionType::Tag).key(tag).build())
+            .set_next_page_token(token.take())
+            .send()
+            .await?;
+
+        accumulate_spend(resp.results_by_time(), &mut totals);
+
+        match resp.next_page_token() {
+            Some(next) => token = Some(next.to_owned()),
+            None => break,
+        }
+    }
+    Ok(totals)
+}
+
+/// Set each VPC's spend from the tag values matching its ID or, when no other
+/// VPC in the account shares it, its Name.  Returns the spend left over per
+/// account: untagged, or tagged for a VPC that was not scanned.
+pub fn join_spend(
+    vpcs: &mut BTreeMap<(String, String, String), VpcSummary>,
+    spend: &TaggedSpend,
+) -> BTreeMap<String, f64> {
+    let mut unattributed = BTreeMap::new();
+    for ((account, value), amount) in spend {
+        let matching = |by_name: bool| -> Vec<(String, String, String)> {
+            vpcs.iter()
+                .filter(|((a, _, id), s)| {
+                    a == account && if by_name { s.name.as_deref() == Some(value.as_str()) } else { id == value }
+                })
+                .map(|(key, _)| key.clone())
+                .collect()
+        };
+        let mut found = matching(false);
+        if found.is_empty() && !value.is_empty() {
+            found = matching(true);
+        }
+        match found.as_slice() {
+            [key] => {
+                if let Some(s) = vpcs.get_mut(key) {
+                    *s.spend.get_or_insert(0.0) += amount;
+                }
+            }
+            _ => *unattributed.entry(account.clone()).or_default() += amount,
+        }
+    }
+    unattributed
+}
+
+/// Render the per-VPC spend, each account's unattributed spend and a total.
+pub fn format_spend_table(
+    vpcs: &BTreeMap<(String, String, String), VpcSummary>,
+    unattributed: &BTreeMap<String, f64>,
+) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_FULL_CONDENSED);
+    table.set_header(vec!["ACCOUNT", "REGION", "VPC-ID", "NAME", "NETWORK-USD"]);
+
+    let mut total = 0.0;
+    for ((account, region, vpc_id), s) in vpcs {
+        total += s.spend.unwrap_or_default();
+        table.add_row(vec![
+            account.clone(),
+            region.clone(),
+            vpc_id.clone(),
+            s.name.clone().unwrap_or_default(),
+            s.spend.map_or_else(|| "-".to_owned(), |usd| format!("{usd:.2}")),
+        ]);
+    }
+    for (account, usd) in unattributed {
+        total += usd;
+        table.add_row(vec![
+            account.clone(),
+            String::new(),
+            "(unattributed)".to_owned(),
+            String::new(),
+            format!("{usd:.2}"),
+        ]);
+    }
+    table.add_row(vec![
+        "TOTAL".to_owned(),
+        String::new(),
+        String::new(),
+        String::new(),
+        format!("{total:.2}"),
+    ]);
+    table.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_costexplorer::types::{Group, MetricValue};
+
+    fn group(account: &str, tag: &str, amount: &str) -> Group {
+        Group::builder()
+            .keys(account)
+            .keys(tag)
+            .metrics(SPEND_METRIC, MetricValue::builder().amount(amount).unit("USD").build())
+            .build()
+    }
+
+    fn vpc(name: Option<&str>) -> VpcSummary {
+        VpcSummary {
+            account: "111111111111".to_string(),
+            name: name.map(str::to_owned),
+            ..Default::default()
+        }
+    }
+
+    fn key(vpc_id: &str) -> (String, String, String) {
+        ("111111111111".to_string(), "us-east-1".to_string(), vpc_id.to_string())
+    }
+
+    #[test]
+    fn spend_window_spans_requested_days() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        assert_eq!(spend_window(today, 30), ("2025-03-01".to_string(), "2025-03-31".to_string()));
+    }
+
+    #[test]
+    fn accumulate_spend_splits_tag_values() {
+        let results = vec![
+            ResultByTime::builder()
+                .groups(group("111111111111", "vpc-id$vpc-a", "10.25"))
+                .groups(group("111111111111", "vpc-id$", "3"))
+                .build(),
+            ResultByTime::builder().groups(group("111111111111", "vpc-id$vpc-a", "4.75")).build(),
+        ];
+        let mut totals = BTreeMap::new();
+        accumulate_spend(&results, &mut totals);
+        assert_eq!(totals.get(&("111111111111".to_string(), "vpc-a".to_string())), Some(&15.0));
+        assert_eq!(totals.get(&("111111111111".to_string(), String::new())), Some(&3.0));
+    }
+
+    #[test]
+    fn join_spend_matches_ids_then_unique_names() {
+        let mut vpcs = BTreeMap::from([
+            (key("vpc-a"), vpc(Some("prod"))),
+            (key("vpc-b"), vpc(Some("shared"))),
+            (key("vpc-c"), vpc(Some("shared"))),
+        ]);
+        let spend = TaggedSpend::from([
+            (("111111111111".to_string(), "vpc-a".to_string()), 10.0),
+            (("111111111111".to_string(), "prod".to_string()), 5.0),
+            (("111111111111".to_string(), "shared".to_string()), 7.0),
+            (("111111111111".to_string(), String::new()), 2.0),
+            (("222222222222".to_string(), "vpc-a".to_string()), 1.0),
+        ]);
+        let unattributed = join_spend(&mut vpcs, &spend);
+        assert_eq!(vpcs[&key("vpc-a")].spend, Some(15.0));
+        assert_eq!(vpcs[&key("vpc-b")].spend, None);
+        assert_eq!(unattributed.get("111111111111"), Some(&9.0));
+        assert_eq!(unattributed.get("222222222222"), Some(&1.0));
+    }
+
+    #[test]
+    fn format_spend_table_totals_vpcs_and_unattributed() {
+        let vpcs = BTreeMap::from([(
+            key("vpc-a"),
+            VpcSummary {
+                spend: Some(12.5),
+                ..vpc(Some("prod"))
+            },
+        )]);
+        let unattributed = BTreeMap::from([("111111111111".to_string(), 2.5)]);
+        let table = format_spend_table(&vpcs, &unattributed);
+        assert!(table.contains("12.50"));
+        assert!(table.contains("(unattributed)"));
+        assert!(table.contains("15.00"));
+    }
+}
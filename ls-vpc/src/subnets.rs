@@ -0,0 +1,135 @@
+//! subnets.rs
+//! ---------------------------------------------------------------------------
+//! Subnet collection and IP-usage arithmetic.
+
+use crate::api_stats::CallCounter;
+use crate::scanner::ec2_tags;
+use aws_sdk_ec2 as ec2;
+use aws_types::SdkConfig;
+use eyre::Result;
+use std::collections::BTreeMap;
+
+/// AWS reserves the first four and the last address of every subnet.
+pub const RESERVED_IPS: u64 = 5;
+
+/// A subnet and its current address usage.
+#[derive(Debug, Clone, Default)]
+pub struct Subnet {
+    pub id: String,
+    pub name: Option<String>,
+    pub cidr: String,
+    pub az: String,
+    /// Zone ID, e.g. `use1-az4`: zone names map to different physical zones
+    /// in each account, zone IDs do not
+    pub az_id: String,
+    pub available_ips: u64,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl Subnet {
+    /// Zone name with its ID, e.g. `us-east-1a (use1-az4)`.
+    pub fn zone(&self) -> String {
+        if self.az_id.is_empty() {
+            self.az.clone()
+        } else {
+            format!("{} ({})", self.az, self.az_id)
+        }
+    }
+
+    /// Addresses that can actually be handed out (total minus AWS-reserved).
+    pub fn usable_ips(&self) -> u64 {
+        let prefix: u32 = self
+            .cidr
+            .rsplit_once('/')
+            .and_then(|(_, p)| p.parse().ok())
+            .unwrap_or(32);
+        (1u64 << (32 - prefix.min(32))).saturating_sub(RESERVED_IPS)
+    }
+
+    /// Percentage of usable addresses currently in use.
+    pub fn used_pct(&self) -> f64 {
+        let usable = self.usable_ips();
+        if usable == 0 {
+            return 0.0;
+        }
+        usable.saturating_sub(self.available_ips) as f64 * 100.0 / usable as f64
+    }
+}
+
+/// List every subnet in a VPC.
+pub async fn list_subnets(conf: &SdkConfig, calls: &CallCounter, vpc_id: &str) -> Result<Vec<Subnet>> {
+    let client = ec2::Client::new(conf);
+    let mut subnets = Vec::new();
+
+    calls.record("ec2", "DescribeSubnets", conf.region());
+    let mut pages = client
+        .describe_subnets()
+        .filters(
+            ec2::types::Filter::builder()
+                .name("vpc-id")
+                .values(vpc_id)
+                .build(),
+        )
+        .into_paginator()
+        .items()
+        .send();
+    while let Some(sn) = pages.next().await {
+        let sn = sn?;
+        let tags = ec2_tags(sn.tags());
+        subnets.push(Subnet {
+            id: sn.subnet_id().unwrap_or_default().to_owned(),
+            name: tags.get("Name").cloned(),
+            cidr: sn.cidr_block().unwrap_or_default().to_owned(),
+            az: sn.availability_zone().unwrap_or_default().to_owned(),
+            az_id: sn.availability_zone_id().unwrap_or_default().to_owned(),
+            available_ips: sn.available_ip_address_count().unwrap_or_default().max(0) as u64,
+            tags,
+        });
+    }
+    Ok(subnets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subnet(cidr: &str, available: u64) -> Subnet {
+        Subnet {
+            id: "subnet-1".to_string(),
+            cidr: cidr.to_string(),
+            available_ips: available,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn usable_ips_excludes_reserved() {
+        assert_eq!(subnet("10.0.0.0/24", 0).usable_ips(), 251);
+        assert_eq!(subnet("10.0.0.0/28", 0).usable_ips(), 11);
+        assert_eq!(subnet("10.0.0.0/16", 0).usable_ips(), 65_531);
+    }
+
+    #[test]
+    fn used_pct_computes_share_in_use() {
+        assert_eq!(subnet("10.0.0.0/24", 251).used_pct(), 0.0);
+        assert_eq!(subnet("10.0.0.0/24", 0).used_pct(), 100.0);
+        let pct = subnet("10.0.0.0/28", 1).used_pct();
+        assert!((pct - 90.909).abs() < 0.01);
+    }
+
+    #[test]
+    fn zone_includes_id_when_known() {
+        let mut sn = Subnet {
+            az: "us-east-1a".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(sn.zone(), "us-east-1a");
+        sn.az_id = "use1-az4".to_string();
+        assert_eq!(sn.zone(), "us-east-1a (use1-az4)");
+    }
+
+    #[test]
+    fn used_pct_handles_bad_cidr() {
+        assert_eq!(subnet("garbage", 0).used_pct(), 0.0);
+    }
+}
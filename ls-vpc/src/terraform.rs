@@ -0,0 +1,162 @@
+//! terraform.rs
+//! ---------------------------------------------------------------------------
+//! `--emit-tf-imports`: Terraform 1.5+ `import` blocks for the scanned VPCs,
+//! their subnets, route tables and gateways, so existing networks can be
+//! brought under IaC with `terraform plan -generate-config-out=…`.
+
+use crate::VpcSummary;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Terraform resource type for a gateway ID found as a route target
+fn gateway_type(id: &str) -> Option<&'static str> {
+    match id.split_once('-')?.0 {
+        "igw" => Some("aws_internet_gateway"),
+        "nat" => Some("aws_nat_gateway"),
+        "eigw" => Some("aws_egress_only_internet_gateway"),
+        "vgw" => Some("aws_vpn_gateway"),
+        _ => None,
+    }
+}
+
+/// Turn a Name tag or resource ID into a valid Terraform identifier.
+pub fn tf_name(s: &str) -> String {
+    let mut name: String = s
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Hands out resource addresses, suffixing repeated names with `_2`, `_3` …
+#[derive(Default)]
+struct Addresses {
+    used: BTreeSet<String>,
+}
+
+impl Addresses {
+    fn next(&mut self, rtype: &str, label: &str) -> String {
+        let base = format!("{rtype}.{}", tf_name(label));
+        let mut address = base.clone();
+        let mut n = 1;
+        while !self.used.insert(address.clone()) {
+            n += 1;
+            address = format!("{base}_{n}");
+        }
+        address
+    }
+}
+
+fn import_block(out: &mut String, address: &str, id: &str) {
+    out.push_str(&format!("import {{\n  to = {address}\n  id = \"{id}\"\n}}\n\n"));
+}
+
+/// Render `import` blocks for every VPC, subnet, route table and gateway.
+pub fn format_tf_imports(vpcs: &BTreeMap<(String, String, String), VpcSummary>) -> String {
+    let mut out = String::new();
+    let mut addresses = Addresses::default();
+
+    for ((account, region, vpc_id), s) in vpcs {
+        out.push_str(&format!("# {vpc_id} ({account} {region})\n"));
+        import_block(&mut out, &addresses.next("aws_vpc", s.name.as_deref().unwrap_or(vpc_id)), vpc_id);
+
+        for sn in &s.subnets {
+            let label = sn.name.as_deref().unwrap_or(&sn.id);
+            import_block(&mut out, &addresses.next("aws_subnet", label), &sn.id);
+        }
+
+        for rt in &s.route_tables {
+            let label = rt.name.as_deref().unwrap_or(&rt.id);
+            import_block(&mut out, &addresses.next("aws_route_table", label), &rt.id);
+        }
+
+        // Gateways: route targets plus NAT gateways the scanner found, once each
+        let gateways: BTreeSet<&str> = s
+            .route_tables
+            .iter()
+            .flat_map(|rt| &rt.routes)
+            .map(|r| r.target.as_str())
+            .chain(
+                s.resources
+                    .iter()
+                    .filter(|r| r.rtype == "ec2.nat-gateway")
+                    .map(|r| r.name.as_str()),
+            )
+            .filter(|id| gateway_type(id).is_some())
+            .collect();
+        for id in gateways {
+            if let Some(rtype) = gateway_type(id) {
+                import_block(&mut out, &addresses.next(rtype, id), id);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResourceRecord;
+    use crate::routes::{Route, RouteTable};
+    use crate::subnets::Subnet;
+
+    fn route(target: &str) -> Route {
+        Route {
+            destination: "0.0.0.0/0".to_string(),
+            target: target.to_string(),
+            blackhole: false,
+        }
+    }
+
+    #[test]
+    fn tf_name_sanitizes_identifiers() {
+        assert_eq!(tf_name("Prod VPC"), "prod_vpc");
+        assert_eq!(tf_name("vpc-0abc"), "vpc_0abc");
+        assert_eq!(tf_name("10-net"), "_10_net");
+    }
+
+    #[test]
+    fn addresses_suffix_duplicates() {
+        let mut a = Addresses::default();
+        assert_eq!(a.next("aws_subnet", "private"), "aws_subnet.private");
+        assert_eq!(a.next("aws_subnet", "private"), "aws_subnet.private_2");
+        assert_eq!(a.next("aws_route_table", "private"), "aws_route_table.private");
+    }
+
+    #[test]
+    fn format_tf_imports_covers_vpc_subnets_tables_and_gateways() {
+        let vpcs = BTreeMap::from([(
+            ("111111111111".to_string(), "us-east-1".to_string(), "vpc-1".to_string()),
+            VpcSummary {
+                name: Some("prod".to_string()),
+                subnets: vec![Subnet {
+                    id: "subnet-1".to_string(),
+                    name: Some("private-a".to_string()),
+                    ..Default::default()
+                }],
+                route_tables: vec![RouteTable {
+                    id: "rtb-1".to_string(),
+                    routes: vec![route("local"), route("igw-1"), route("pcx-1"), route("nat-1")],
+                    ..Default::default()
+                }],
+                resources: vec![ResourceRecord {
+                    arn: "nat-1".to_string(),
+                    rtype: "ec2.nat-gateway",
+                    name: "nat-1".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        )]);
+        let out = format_tf_imports(&vpcs);
+        assert!(out.contains("import {\n  to = aws_vpc.prod\n  id = \"vpc-1\"\n}\n"));
+        assert!(out.contains("to = aws_subnet.private_a\n  id = \"subnet-1\""));
+        assert!(out.contains("to = aws_route_table.rtb_1\n  id = \"rtb-1\""));
+        assert!(out.contains("to = aws_internet_gateway.igw_1\n  id = \"igw-1\""));
+        assert_eq!(out.matches("aws_nat_gateway.nat_1").count(), 1);
+        assert!(!out.contains("pcx-1"));
+        assert!(!out.contains("\"local\""));
+    }
+}
@@ -0,0 +1,387 @@
+//! tui.rs
+//! ---------------------------------------------------------------------------
+//! `--tui`: an interactive browser over a finished scan.  The left pane lists
+//! VPCs (live-filtered with `/`), the right pane shows the selected VPC's
+//! resources grouped by type.  `y` copies the selected ARN / VPC-ID and `Y`
+//! every ARN of the VPC to the clipboard via OSC 52, which also works over
+//! SSH and inside tmux.
+
+use crate::{ResourceRecord, VpcSummary};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use eyre::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Which pane has keyboard focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Vpcs,
+    Resources,
+}
+
+/// Everything the TUI renders, independent of the terminal
+pub struct App<'a> {
+    vpcs: Vec<(&'a (String, String, String), &'a VpcSummary)>,
+    pub filter: String,
+    pub filtering: bool,
+    pub focus: Pane,
+    /// Index into [`App::visible`]
+    pub selected: usize,
+    /// Index into the selected VPC's [`App::resources`]
+    pub resource: usize,
+    pub status: String,
+    pub quit: bool,
+}
+
+impl<'a> App<'a> {
+    pub fn new(vpcs: &'a BTreeMap<(String, String, String), VpcSummary>) -> Self {
+        App {
+            vpcs: vpcs.iter().collect(),
+            filter: String::new(),
+            filtering: false,
+            focus: Pane::Vpcs,
+            selected: 0,
+            resource: 0,
+            status: "/ filter  tab switch pane  j/k move  y copy  Y copy all ARNs  q quit".to_owned(),
+            quit: false,
+        }
+    }
+
+    fn matches(&self, key: &(String, String, String), s: &VpcSummary) -> bool {
+        let needle = self.filter.to_lowercase();
+        needle.is_empty()
+            || key.0.contains(&needle)
+            || key.1.contains(&needle)
+            || key.2.to_lowercase().contains(&needle)
+            || s.name.as_deref().unwrap_or_default().to_lowercase().contains(&needle)
+            || s.cidrs.iter().any(|c| c.contains(&needle))
+    }
+
+    /// VPCs matching the current filter
+    pub fn visible(&self) -> Vec<(&'a (String, String, String), &'a VpcSummary)> {
+        self.vpcs
+            .iter()
+            .filter(|(key, s)| self.matches(key, s))
+            .copied()
+            .collect()
+    }
+
+    /// Selected VPC, if any match the filter
+    pub fn current(&self) -> Option<(&'a (String, String, String), &'a VpcSummary)> {
+        self.visible().get(self.selected).copied()
+    }
+
+    /// Resources of the selected VPC in display order (grouped by type)
+    pub fn resources(&self) -> Vec<&'a ResourceRecord> {
+        self.current()
+            .map(|(_, s)| grouped(&s.resources).into_values().flatten().collect())
+            .unwrap_or_default()
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let len = match self.focus {
+            Pane::Vpcs => self.visible().len(),
+            Pane::Resources => self.resources().len(),
+        };
+        let index = match self.focus {
+            Pane::Vpcs => &mut self.selected,
+            Pane::Resources => &mut self.resource,
+        };
+        if len == 0 {
+            *index = 0;
+            return;
+        }
+        *index = (*index as isize + delta).clamp(0, len as isize - 1) as usize;
+        if self.focus == Pane::Vpcs {
+            self.resource = 0;
+        }
+    }
+
+    fn filter_changed(&mut self) {
+        self.selected = 0;
+        self.resource = 0;
+    }
+
+    /// Text `y` copies: the selected resource's ARN, or the VPC-ID
+    pub fn copy_target(&self) -> Option<String> {
+        match self.focus {
+            Pane::Resources => self.resources().get(self.resource).map(|r| r.arn.clone()),
+            Pane::Vpcs => self.current().map(|(key, _)| key.2.clone()),
+        }
+    }
+
+    /// Apply one key press.  Returns text to copy to the clipboard, if any.
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<String> {
+        if self.filtering {
+            match code {
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.filtering = false;
+                    self.filter_changed();
+                }
+                KeyCode::Enter => self.filtering = false,
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                    self.filter_changed();
+                }
+                KeyCode::Char(c) => {
+                    self.filter.push(c);
+                    self.filter_changed();
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('/') => {
+                self.filtering = true;
+                self.focus = Pane::Vpcs;
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Pane::Vpcs => Pane::Resources,
+                    Pane::Resources => Pane::Vpcs,
+                };
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown => self.move_by(10),
+            KeyCode::PageUp => self.move_by(-10),
+            KeyCode::Char('y') => {
+                let text = self.copy_target();
+                if let Some(t) = &text {
+                    self.status = format!("copied {t}");
+                }
+                return text;
+            }
+            KeyCode::Char('Y') => {
+                let arns: Vec<String> = self.resources().iter().map(|r| r.arn.clone()).collect();
+                if !arns.is_empty() {
+                    self.status = format!("copied {} ARNs", arns.len());
+                    return Some(arns.join("\n"));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Resources keyed by type, in the order the detail pane shows them
+pub fn grouped(resources: &[ResourceRecord]) -> BTreeMap<&'static str, Vec<&ResourceRecord>> {
+    let mut groups: BTreeMap<&'static str, Vec<&ResourceRecord>> = BTreeMap::new();
+    for r in resources {
+        groups.entry(r.rtype).or_default().push(r);
+    }
+    groups
+}
+
+/// Put `text` on the clipboard with an OSC 52 escape sequence.
+fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut out = std::io::stdout();
+    write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    out.flush()?;
+    Ok(())
+}
+
+fn pane_block(title: String, focused: bool) -> Block<'static> {
+    let style = if focused { Style::default().fg(Color::Cyan) } else { Style::default() };
+    Block::default().borders(Borders::ALL).border_style(style).title(title)
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(frame.area());
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(rows[0]);
+    let highlight = Style::default().add_modifier(Modifier::REVERSED);
+
+    let visible = app.visible();
+    let items: Vec<ListItem> = visible
+        .iter()
+        .map(|((account, region, vpc_id), s)| {
+            let issues = if s.issues.is_empty() { String::new() } else { format!(" !{}", s.issues.len()) };
+            ListItem::new(format!(
+                "{account} {region} {vpc_id} {}{issues}",
+                s.name.as_deref().unwrap_or_default()
+            ))
+        })
+        .collect();
+    let mut vpc_state = ListState::default();
+    if !visible.is_empty() {
+        vpc_state.select(Some(app.selected));
+    }
+    frame.render_stateful_widget(
+        List::new(items)
+            .block(pane_block(format!("VPCs ({})", visible.len()), app.focus == Pane::Vpcs))
+            .highlight_style(highlight),
+        panes[0],
+        &mut vpc_state,
+    );
+
+    let mut lines: Vec<ListItem> = Vec::new();
+    let mut selected_line = None;
+    let mut title = "Resources".to_owned();
+    if let Some(((_, _, vpc_id), s)) = app.current() {
+        title = format!("{vpc_id} – {}", s.cidrs.join(","));
+        let mut n = 0;
+        for (rtype, records) in grouped(&s.resources) {
+            lines.push(ListItem::new(Line::styled(
+                format!("{rtype} ({})", records.len()),
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            for r in records {
+                if n == app.resource {
+                    selected_line = Some(lines.len());
+                }
+                lines.push(ListItem::new(format!("  {}  {}", r.name, r.arn)));
+                n += 1;
+            }
+        }
+    }
+    let mut resource_state = ListState::default();
+    if app.focus == Pane::Resources {
+        resource_state.select(selected_line);
+    }
+    frame.render_stateful_widget(
+        List::new(lines)
+            .block(pane_block(title, app.focus == Pane::Resources))
+            .highlight_style(highlight),
+        panes[1],
+        &mut resource_state,
+    );
+
+    let status = if app.filtering || !app.filter.is_empty() {
+        format!("/{}", app.filter)
+    } else {
+        app.status.clone()
+    };
+    frame.render_widget(Paragraph::new(status), rows[1]);
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| draw(frame, app))?;
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                if let Some(text) = app.handle_key(key.code) {
+                    copy_to_clipboard(&text)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Browse the scanned VPCs until the user quits.
+pub fn run(vpcs: &BTreeMap<(String, String, String), VpcSummary>) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let mut app = App::new(vpcs);
+    let outcome = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(rtype: &'static str, arn: &str) -> ResourceRecord {
+        ResourceRecord {
+            arn: arn.to_string(),
+            rtype,
+            name: arn.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn vpcs() -> BTreeMap<(String, String, String), VpcSummary> {
+        BTreeMap::from([
+            (
+                ("111111111111".to_string(), "us-east-1".to_string(), "vpc-aaa".to_string()),
+                VpcSummary {
+                    name: Some("prod".to_string()),
+                    cidrs: vec!["10.0.0.0/16".to_string()],
+                    resources: vec![record("ec2.subnet", "subnet-1"), record("ec2.instance", "i-1")],
+                    ..Default::default()
+                },
+            ),
+            (
+                ("222222222222".to_string(), "us-west-2".to_string(), "vpc-bbb".to_string()),
+                VpcSummary {
+                    name: Some("staging".to_string()),
+                    cidrs: vec!["10.1.0.0/16".to_string()],
+                    ..Default::default()
+                },
+            ),
+        ])
+    }
+
+    #[test]
+    fn filter_matches_name_id_account_region_and_cidr() {
+        let vpcs = vpcs();
+        let mut app = App::new(&vpcs);
+        for (text, expected) in [
+            ("STAG", "vpc-bbb"),
+            ("aaa", "vpc-aaa"),
+            ("west", "vpc-bbb"),
+            ("10.0.", "vpc-aaa"),
+            ("2222", "vpc-bbb"),
+        ] {
+            app.filter = text.to_string();
+            let visible = app.visible();
+            assert_eq!(visible.len(), 1, "filter {text}");
+            assert_eq!(visible[0].0.2, expected);
+        }
+    }
+
+    #[test]
+    fn resources_are_grouped_by_type() {
+        let vpcs = vpcs();
+        let app = App::new(&vpcs);
+        let arns: Vec<&str> = app.resources().iter().map(|r| r.arn.as_str()).collect();
+        assert_eq!(arns, vec!["i-1", "subnet-1"]);
+    }
+
+    #[test]
+    fn typing_a_filter_resets_selection() {
+        let vpcs = vpcs();
+        let mut app = App::new(&vpcs);
+        app.handle_key(KeyCode::Char('j'));
+        assert_eq!(app.selected, 1);
+        app.handle_key(KeyCode::Char('/'));
+        app.handle_key(KeyCode::Char('p'));
+        assert_eq!(app.selected, 0);
+        assert_eq!(app.filter, "p");
+        app.handle_key(KeyCode::Esc);
+        assert!(app.filter.is_empty());
+        assert!(!app.quit);
+    }
+
+    #[test]
+    fn copy_keys_return_vpc_id_or_arns() {
+        let vpcs = vpcs();
+        let mut app = App::new(&vpcs);
+        assert_eq!(app.handle_key(KeyCode::Char('y')), Some("vpc-aaa".to_string()));
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Char('j'));
+        assert_eq!(app.handle_key(KeyCode::Char('y')), Some("subnet-1".to_string()));
+        assert_eq!(app.handle_key(KeyCode::Char('Y')), Some("i-1\nsubnet-1".to_string()));
+        app.handle_key(KeyCode::Char('j'));
+        assert_eq!(app.resource, 1);
+    }
+}
@@ -2,6 +2,9 @@
 //! ---------------------------------------------------------------------------
 //! Helper utilities that don’t fit anywhere else.
 
+use crate::cli::OutputFormat;
+use comfy_table::{Row, Table};
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{env, fs, path::PathBuf};
 use terminal_size::{terminal_size, Width};
 
@@ -32,6 +35,79 @@ pub fn terminal_width() -> usize {
         .unwrap_or(80)
 }
 
+/// Compact age of a resource created at `created` (Unix seconds), e.g.
+/// `45m`, `6h`, `12d`, `3y`; `-` when the creation time is unknown.
+pub fn format_age(created: Option<i64>, now: i64) -> String {
+    let Some(created) = created else {
+        return "-".to_owned();
+    };
+    let secs = (now - created).max(0);
+    match secs {
+        s if s >= 365 * 86_400 => format!("{}y", s / (365 * 86_400)),
+        s if s >= 86_400 => format!("{}d", s / 86_400),
+        s if s >= 3600 => format!("{}h", s / 3600),
+        s => format!("{}m", s / 60),
+    }
+}
+
+/// Seconds since the Unix epoch, for [`format_age`].
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// `--limit` / `--page` window over an already-sorted result set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    /// Items per page (`None` = everything)
+    pub limit: Option<usize>,
+    /// 1-based page number
+    pub page: usize,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Page { limit: None, page: 1 }
+    }
+}
+
+impl Page {
+    /// The items of this page, in order.
+    pub fn window<I: Iterator>(&self, items: I) -> std::iter::Take<std::iter::Skip<I>> {
+        let limit = self.limit.unwrap_or(usize::MAX);
+        items.skip(limit.saturating_mul(self.page.saturating_sub(1))).take(limit)
+    }
+
+    /// Whether the page drops anything from `total` items.
+    pub fn trims(&self, total: usize) -> bool {
+        self.limit.is_some_and(|limit| self.page > 1 || total > limit)
+    }
+}
+
+/// Render a report table in the chosen format: as drawn for `table`, or as
+/// tab-separated lines (header first) for `tsv` so it pipes cleanly into
+/// `awk`/`cut`.  Tabs and line breaks inside cells become spaces.
+pub fn render_table(table: &Table, format: OutputFormat) -> String {
+    if format != OutputFormat::Tsv {
+        return table.to_string();
+    }
+    let line = |row: &Row| {
+        row.cell_iter()
+            .map(|c| c.content().replace(['\t', '\n'], " "))
+            .collect::<Vec<_>>()
+            .join("\t")
+    };
+    table
+        .header()
+        .into_iter()
+        .chain(table.row_iter())
+        .map(line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Wrap a long AWS identifier (ARN, ENI-id …) so that every rendered line
 /// (after the two-space indent on continuations) is **≤ `max_width`**.
 ///
@@ -90,6 +166,39 @@ pub fn wrap_identifier(ident: &str, max_width: usize) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn page_windows_items() {
+        let all = || 1..=7;
+        let page = |limit, page| Page { limit, page };
+        assert_eq!(Page::default().window(all()).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(page(Some(3), 1).window(all()).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(page(Some(3), 3).window(all()).collect::<Vec<_>>(), vec![7]);
+        assert!(page(Some(3), 4).window(all()).next().is_none());
+        assert!(page(Some(3), 1).trims(7));
+        assert!(!page(Some(10), 1).trims(7));
+        assert!(!Page::default().trims(7));
+    }
+
+    #[test]
+    fn format_age_picks_largest_unit() {
+        let now = 10 * 365 * 86_400;
+        assert_eq!(format_age(None, now), "-");
+        assert_eq!(format_age(Some(now - 90), now), "1m");
+        assert_eq!(format_age(Some(now - 7200), now), "2h");
+        assert_eq!(format_age(Some(now - 3 * 86_400), now), "3d");
+        assert_eq!(format_age(Some(now - 2 * 365 * 86_400), now), "2y");
+        assert_eq!(format_age(Some(now + 60), now), "0m");
+    }
+
+    #[test]
+    fn render_table_tsv_has_no_box_drawing() {
+        let mut table = Table::new();
+        table.set_header(vec!["REGION", "VPC-ID"]);
+        table.add_row(vec!["us-east-1", "vpc-1\nvpc-2"]);
+        assert_eq!(render_table(&table, OutputFormat::Tsv), "REGION\tVPC-ID\nus-east-1\tvpc-1 vpc-2");
+        assert!(render_table(&table, OutputFormat::Table).contains("REGION"));
+    }
+
     #[test]
     fn wrap_identifier_short_string_unchanged() {
         let ident = "vpc-12345678";